@@ -0,0 +1,86 @@
+//! GET throughput micro-benchmark.
+//!
+//! Starts an in-process mini-redis server, seeds a handful of keys and then
+//! runs several clients issuing `GET` concurrently for a fixed duration. The
+//! number of completed requests per second is printed at the end, which makes
+//! it easy to compare read scalability before and after changes to `Db`.
+//!
+//! Every `writes`-th request of a client is a `SET` instead of a `GET` (`0`
+//! disables writes), so the benchmark also shows how well reads keep going
+//! while other clients hold the lock to write.
+//!
+//! You can run it with:
+//!
+//!     cargo run --release --example get_throughput -- [clients] [seconds] [writes]
+
+#![warn(rust_2018_idioms)]
+
+use mini_redis::{clients::Client, server, Result};
+
+use std::time::{Duration, Instant};
+use tokio::net::TcpListener;
+use tokio::sync::oneshot;
+
+const KEYS: usize = 1024;
+
+#[tokio::main]
+pub async fn main() -> Result<()> {
+    let mut args = std::env::args().skip(1);
+    let clients: usize = args.next().map(|s| s.parse()).transpose()?.unwrap_or(16);
+    let seconds: u64 = args.next().map(|s| s.parse()).transpose()?.unwrap_or(5);
+    let writes: u64 = args.next().map(|s| s.parse()).transpose()?.unwrap_or(10);
+
+    // Run the server on a random local port.
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    let (stop_tx, stop_rx) = oneshot::channel::<()>();
+    let server = tokio::spawn(server::run(listener, stop_rx));
+
+    // Seed the keys every client reads from.
+    let mut client = Client::connect(addr).await?;
+    for i in 0..KEYS {
+        client.set(&format!("key:{}", i), "value".into()).await?;
+    }
+
+    let deadline = Instant::now() + Duration::from_secs(seconds);
+    let mut tasks = Vec::with_capacity(clients);
+    for n in 0..clients {
+        tasks.push(tokio::spawn(async move {
+            let mut client = Client::connect(addr).await?;
+            let (mut gets, mut sets) = (0u64, 0u64);
+            let mut i = n;
+            while Instant::now() < deadline {
+                let key = format!("key:{}", i % KEYS);
+                if writes > 0 && (gets + sets + 1) % writes == 0 {
+                    client.set(&key, "value".into()).await?;
+                    sets += 1;
+                } else {
+                    client.get(&key).await?;
+                    gets += 1;
+                }
+                i += clients;
+            }
+            Ok::<_, mini_redis::Error>((gets, sets))
+        }));
+    }
+
+    let (mut gets, mut sets) = (0, 0);
+    for task in tasks {
+        let (g, s) = task.await??;
+        gets += g;
+        sets += s;
+    }
+
+    println!(
+        "{} clients, {}s: {} GETs, {} SETs, {:.0} ops/sec",
+        clients,
+        seconds,
+        gets,
+        sets,
+        (gets + sets) as f64 / seconds as f64
+    );
+
+    drop(stop_tx);
+    server.await?;
+    Ok(())
+}
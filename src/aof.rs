@@ -0,0 +1,198 @@
+use crate::cmd::Select;
+use crate::server::AppendFsync;
+use crate::{Command, Connection, Db, Frame, Shutdown};
+
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+use tokio::fs::{File, OpenOptions};
+use tokio::sync::{broadcast, mpsc, oneshot, Mutex, OwnedMutexGuard};
+use tokio::task::JoinHandle;
+use tokio::time::{self, Duration, MissedTickBehavior};
+use tracing::error;
+
+/// 追加写入日志（AOF）的写入端。
+///
+/// 每个修改键空间的命令执行之后，处理程序把命令的原始帧交给 `AofWriter`，后台任务按与客户端连接相同的编码
+/// 把帧追加到日志文件。服务器启动时由 [`replay`] 重新执行日志中的命令，恢复键空间。
+///
+/// 帧先被发送到后台任务，因此记录命令不会等待磁盘。后台任务把已经排队的帧合并成一次写入，写入之后刷新到
+/// 操作系统，进程崩溃不会丢失已经写入的命令；何时调用 `fsync` 由 [`AppendFsync`] 决定。
+///
+/// 执行命令和把它追加到日志期间持有顺序许可（[`AofWriter::order`]），因此不同连接并发修改同一个键时，日志中的
+/// 顺序与执行的顺序相同。`SET key value EX 10` 这样的相对过期时间在记录之前换算成 Unix 时间，重放时键在原来的
+/// 时间过期。
+///
+/// 每个命令附带执行它的数据库的编号。数据库与上一个记录的命令不同时，先写入一个 `SELECT`，与 Redis 的 AOF 相同；
+/// 服务器启动后记录的第一个命令之前总是写入 `SELECT`，因为日志末尾选择的数据库是未知的。
+#[derive(Debug, Clone)]
+pub(crate) struct AofWriter {
+    tx: mpsc::UnboundedSender<Request>,
+    /// 记录命令的顺序许可，所有写入端共享。
+    order: Arc<Mutex<()>>,
+}
+
+impl AofWriter {
+    /// 以追加方式打开 `path`，文件不存在时创建它。
+    ///
+    /// 返回写入端和写入日志的后台任务。所有写入端都被丢弃之后，后台任务写完剩余的帧并退出。
+    pub(crate) async fn open(path: &Path, fsync: AppendFsync) -> io::Result<(Self, JoinHandle<()>)> {
+        let file = OpenOptions::new().create(true).append(true).open(path).await?;
+        let (tx, rx) = mpsc::unbounded_channel();
+        let task = tokio::spawn(write_log(Connection::new(file), rx, fsync));
+
+        let order = Arc::new(Mutex::new(()));
+
+        Ok((Self { tx, order }, task))
+    }
+
+    /// 等待记录命令的顺序许可。
+    ///
+    /// 从执行一个要记录的命令之前到把它追加到日志之后持有许可，其他连接记录的命令不会插到两者之间。
+    pub(crate) async fn order(&self) -> OwnedMutexGuard<()> {
+        self.order.clone().lock_owned().await
+    }
+
+    /// 把一个在编号为 `db` 的数据库上执行的命令追加到日志。
+    pub(crate) fn append(&self, db: usize, frame: Frame) {
+        // 只有后台任务退出之后发送才会失败，写入错误已经由后台任务记录。
+        let _ = self.tx.send(Request::Append(db, frame));
+    }
+
+    /// 等待之前追加的所有命令被同步到磁盘，不论 [`AppendFsync`] 是什么策略。
+    ///
+    /// 同步成功返回 `true`；写入或同步失败，或者后台任务已经退出时返回 `false`。
+    pub(crate) async fn sync(&self) -> bool {
+        let (tx, rx) = oneshot::channel();
+        if self.tx.send(Request::Sync(tx)).is_err() {
+            return false;
+        }
+        rx.await.unwrap_or(false)
+    }
+}
+
+/// 发送给后台任务的请求。
+#[derive(Debug)]
+enum Request {
+    /// 追加一个在编号为给定值的数据库上执行的命令。
+    Append(usize, Frame),
+    /// 之前的命令都同步到磁盘之后，发送同步是否成功。
+    Sync(oneshot::Sender<bool>),
+}
+
+/// 后台任务：把收到的帧写入日志，直到所有写入端都被丢弃。
+///
+/// `fsync` 为 [`AppendFsync::Always`] 时每批帧写入之后同步一次；为 [`AppendFsync::EverySec`] 时由每秒一次的
+/// 定时器同步上一次同步之后写入的帧。退出之前同步剩余的帧，[`AppendFsync::No`] 除外。一批请求中有
+/// [`Request::Sync`] 时，无论哪种策略都在这批帧写入之后同步。
+async fn write_log(mut log: Connection<File>, mut rx: mpsc::UnboundedReceiver<Request>, fsync: AppendFsync) {
+    // 日志中最后一个 `SELECT` 选择的数据库。
+    let mut selected = None;
+    // 上一次同步之后是否写入过帧。
+    let mut dirty = false;
+    let mut interval = time::interval(Duration::from_secs(1));
+    interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    loop {
+        let first = tokio::select! {
+            first = rx.recv() => match first {
+                Some(first) => first,
+                None => break,
+            },
+            _ = interval.tick(), if fsync == AppendFsync::EverySec && dirty => {
+                sync(&log).await;
+                dirty = false;
+                continue;
+            }
+        };
+
+        // 等待这批帧同步到磁盘的请求。
+        let mut waiters = Vec::new();
+        let result = async {
+            // 已经排队的帧一起写入，最后刷新一次。
+            log.defer_flush();
+            let mut next = Some(first);
+            while let Some(request) = next {
+                match request {
+                    Request::Append(db, frame) => {
+                        if selected != Some(db) {
+                            log.write_frame(&Frame::from(Select::new(db))).await?;
+                            selected = Some(db);
+                        }
+                        log.write_frame(&frame).await?;
+                        dirty = true;
+                    }
+                    Request::Sync(waiter) => waiters.push(waiter),
+                }
+                next = rx.try_recv().ok();
+            }
+            log.flush().await
+        }
+        .await;
+
+        let mut synced = result.is_ok();
+        if let Err(err) = result {
+            error!(cause = %err, "写入 AOF 失败");
+        }
+
+        if dirty && (fsync == AppendFsync::Always || !waiters.is_empty()) {
+            synced &= sync(&log).await;
+            dirty = false;
+        }
+        for waiter in waiters {
+            // 等待的连接可能已经超时返回。
+            let _ = waiter.send(synced);
+        }
+    }
+
+    if dirty && fsync != AppendFsync::No {
+        sync(&log).await;
+    }
+}
+
+/// 把已经刷新到操作系统的日志同步到磁盘，成功时返回 `true`。
+async fn sync(log: &Connection<File>) -> bool {
+    match log.get_ref().sync_data().await {
+        Ok(()) => true,
+        Err(err) => {
+            error!(cause = %err, "同步 AOF 失败");
+            false
+        }
+    }
+}
+
+/// 重新执行 `path` 中记录的所有命令，返回执行的命令数。文件不存在时不执行任何命令。
+///
+/// 命令的响应被丢弃。`BLPOP` 等阻塞命令不会等待：列表为空时立即返回，与它们当初超时返回时的结果相同。
+/// `SELECT` 切换之后的命令所作用的数据库，与它们当初执行时一样。
+///
+/// 日志不完整（例如写入最后一个命令时进程崩溃）或包含无法解析的命令时返回错误，之前的命令已经执行。
+pub(crate) async fn replay(path: &Path, db: &Db) -> crate::Result<usize> {
+    let file = match File::open(path).await {
+        Ok(file) => file,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(0),
+        Err(err) => return Err(err.into()),
+    };
+    let mut log = Connection::new(file);
+    let mut responses = Connection::new(tokio::io::join(tokio::io::empty(), tokio::io::sink()));
+
+    // 发送端已经被丢弃，因此阻塞命令立即收到关闭信号。
+    let (_, notify) = broadcast::channel(1);
+    let mut shutdown = Shutdown::new(notify);
+
+    let mut db = db.clone();
+    let mut replayed = 0;
+    while let Some(frame) = log.read_frame().await? {
+        match Command::try_from(frame)? {
+            Command::Select(cmd) => {
+                db = db.select(cmd.index()).ok_or("AOF selects a database that is out of range")?;
+            }
+            cmd => {
+                cmd.apply(&db, &mut responses, &mut shutdown).await?;
+                replayed += 1;
+            }
+        }
+    }
+
+    Ok(replayed)
+}
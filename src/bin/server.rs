@@ -5,9 +5,13 @@
 //!
 //! 使用 `clap` crate 解析参数。
 
-use mini_redis::{server, DEFAULT_PORT};
+use mini_redis::server::{self, AppendFsync, MaxMemoryPolicy, NoopMetrics, OverloadConfig, ServerConfig};
+use mini_redis::DEFAULT_PORT;
 
 use clap::Parser;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
 use tokio::net::TcpListener;
 use tokio::signal;
 
@@ -34,7 +38,41 @@ pub async fn main() -> mini_redis::Result<()> {
     // 绑定一个 TCP 监听器
     let listener = TcpListener::bind(&format!("127.0.0.1:{port}")).await?;
 
-    server::run(listener, signal::ctrl_c()).await;
+    let config = ServerConfig {
+        max_connections: cli.max_connections.unwrap_or(server::MAX_CONNECTIONS),
+        preallocate: cli.preallocate.unwrap_or(0),
+        notify_expired: cli.notify_expired,
+        notify_lagged: cli.notify_lagged,
+        pubsub_capacity: cli.pubsub_capacity.unwrap_or(server::PUBSUB_CAPACITY),
+        databases: cli.databases.unwrap_or(server::DATABASES),
+        requirepass: cli.requirepass,
+        auth: None,
+        overload: cli.busy_threshold.map(|threshold| OverloadConfig {
+            threshold,
+            idle_timeout: Duration::from_secs(cli.busy_idle_timeout),
+        }),
+        nodelay: !cli.no_nodelay,
+        keepalive: cli.tcp_keepalive.map(Duration::from_secs),
+        keepalive_interval: cli.pubsub_keepalive.map(Duration::from_secs),
+        maxmemory: cli.maxmemory,
+        maxmemory_policy: cli.maxmemory_policy.unwrap_or_default(),
+        command_time_limit: cli.command_time_limit.map(Duration::from_millis),
+        dbfilename: cli.dbfilename,
+        appendfilename: cli.appendfilename,
+        appendfsync: cli.appendfsync.unwrap_or_default(),
+        shutdown_timeout: cli.shutdown_timeout.map(Duration::from_secs),
+        metrics: Arc::new(NoopMetrics),
+        // 负数表示不记录，与 Redis 的 slowlog-log-slower-than 一致。
+        slowlog_threshold: match cli.slowlog_log_slower_than {
+            Some(micros) => u64::try_from(micros).ok().map(Duration::from_micros),
+            None => Some(server::SLOWLOG_THRESHOLD),
+        },
+        slowlog_max_len: cli.slowlog_max_len.unwrap_or(server::SLOWLOG_MAX_LEN),
+        debug_hooks: cli.enable_debug_hooks,
+        sorted_replies: false,
+    };
+
+    server::run_with_config(listener, signal::ctrl_c(), config).await;
 
     Ok(())
 }
@@ -44,6 +82,94 @@ pub async fn main() -> mini_redis::Result<()> {
 struct Cli {
     #[arg(long)]
     port: Option<u16>,
+
+    /// 最大并发连接数
+    #[arg(long)]
+    max_connections: Option<usize>,
+
+    /// 启动时为多少个键预留空间
+    #[arg(long)]
+    preallocate: Option<usize>,
+
+    /// 键过期时发布到 __keyevent__:expired 频道
+    #[arg(long)]
+    notify_expired: bool,
+
+    /// 订阅者太慢而丢弃消息时推送 lagged 消息
+    #[arg(long)]
+    notify_lagged: bool,
+
+    /// 每个频道能缓存的消息数，默认为 1024
+    #[arg(long)]
+    pubsub_capacity: Option<usize>,
+
+    /// 数据库的数量，默认为 16
+    #[arg(long)]
+    databases: Option<usize>,
+
+    /// 连接必须通过 AUTH 验证的密码
+    #[arg(long)]
+    requirepass: Option<String>,
+
+    /// 连接数超过该值时回复 BUSY 并关闭空闲连接
+    #[arg(long)]
+    busy_threshold: Option<usize>,
+
+    /// 过载时关闭空闲超过该秒数的连接
+    #[arg(long, default_value_t = 30)]
+    busy_idle_timeout: u64,
+
+    /// 不对接受的连接设置 TCP_NODELAY
+    #[arg(long)]
+    no_nodelay: bool,
+
+    /// 启用 TCP keepalive，连接空闲该秒数之后开始探测
+    #[arg(long)]
+    tcp_keepalive: Option<u64>,
+
+    /// 订阅空闲该秒数之后推送 ping
+    #[arg(long)]
+    pubsub_keepalive: Option<u64>,
+
+    /// 内存上限（字节），超过时 SET 驱逐最久未使用的键
+    #[arg(long)]
+    maxmemory: Option<usize>,
+
+    /// 超过内存上限时的处理方式：allkeys-lru（默认）或 noeviction
+    #[arg(long)]
+    maxmemory_policy: Option<MaxMemoryPolicy>,
+
+    /// SCAN、SINTER 等遍历大量元素的命令最多执行该毫秒数，超过时回复错误
+    #[arg(long)]
+    command_time_limit: Option<u64>,
+
+    /// 快照文件，启动时从中恢复，SAVE 写入其中
+    #[arg(long)]
+    dbfilename: Option<PathBuf>,
+
+    /// AOF 文件，启动时重放，之后记录每个修改键空间的命令
+    #[arg(long)]
+    appendfilename: Option<PathBuf>,
+
+    /// 何时把 AOF 同步到磁盘：always、everysec（默认）或 no
+    #[arg(long)]
+    appendfsync: Option<AppendFsync>,
+
+    /// 关闭时最多等待该秒数让连接结束
+    #[arg(long)]
+    shutdown_timeout: Option<u64>,
+
+    /// 执行时间达到该微秒数的命令记录到慢日志，负数表示不记录，默认为 10000
+    #[arg(long, allow_negative_numbers = true)]
+    slowlog_log_slower_than: Option<i64>,
+
+    /// 慢日志最多保留的条目数，默认为 128
+    #[arg(long)]
+    slowlog_max_len: Option<usize>,
+
+    /// 允许 DEBUG SLEEP 和 DEBUG SET-ACTIVE-EXPIRE 等只用于测试的命令
+    #[arg(long)]
+    enable_debug_hooks: bool,
 }
 
 #[cfg(not(feature = "otel"))]
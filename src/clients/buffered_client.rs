@@ -10,6 +10,19 @@ use tokio::sync::oneshot;
 enum Command {
     Get(String),
     Set(String, Bytes),
+    Del(Vec<String>),
+    Publish(String, Bytes),
+}
+
+// 连接任务返回给 `BufferedClient` 句柄的响应。不同命令的响应类型不同。
+#[derive(Debug)]
+enum Response {
+    // `GET` 的值
+    Value(Option<Bytes>),
+    // `SET` 和 `DEL` 成功，没有返回值
+    Done,
+    // `PUBLISH` 的订阅者数量
+    Count(u64),
 }
 
 // 通过通道发送到连接任务的消息类型。
@@ -17,7 +30,7 @@ enum Command {
 // `Command` 是要转发到连接的命令。
 //
 // `oneshot::Sender` 是一种发送**单个**值的通道类型。这里用于将从连接接收到的响应发送回原始请求者。
-type Message = (Command, oneshot::Sender<Result<Option<Bytes>>>);
+type Message = (Command, oneshot::Sender<Result<Response>>);
 
 /// 接收通过通道发送的命令并将其转发给客户端。响应通过 `oneshot` 返回给调用者。
 async fn run(mut client: Client, mut rx: Receiver<Message>) {
@@ -25,8 +38,10 @@ async fn run(mut client: Client, mut rx: Receiver<Message>) {
     while let Some((cmd, tx)) = rx.recv().await {
         // 命令被转发到连接
         let response = match cmd {
-            Command::Get(key) => client.get(&key).await,
-            Command::Set(key, value) => client.set(&key, value).await.map(|_| None),
+            Command::Get(key) => client.get(&key).await.map(Response::Value),
+            Command::Set(key, value) => client.set(&key, value).await.map(|_| Response::Done),
+            Command::Del(keys) => client.del(keys).await.map(|_| Response::Done),
+            Command::Publish(channel, message) => client.publish(&channel, message).await.map(Response::Count),
         };
 
         // 将响应发送回调用者。
@@ -65,18 +80,9 @@ impl BufferedClient {
     /// 与 `Client::get` 相同，但请求会被**缓冲**，直到关联的连接能够发送请求。
     pub async fn get(&mut self, key: &str) -> Result<Option<Bytes>> {
         // 初始化一个新的 `Get` 命令，通过通道发送。
-        let get = Command::Get(key.into());
-
-        // 初始化一个新的 oneshot，用于接收来自连接的响应。
-        let (tx, rx) = oneshot::channel();
-
-        // 发送请求
-        self.tx.send((get, tx)).await?;
-
-        // 等待响应
-        match rx.await {
-            Ok(res) => res,
-            Err(err) => Err(err.into()),
+        match self.request(Command::Get(key.into())).await? {
+            Response::Value(value) => Ok(value),
+            response => Err(unexpected(response)),
         }
     }
 
@@ -85,18 +91,49 @@ impl BufferedClient {
     /// 与 `Client::set` 相同，但请求会被**缓冲**，直到关联的连接能够发送请求。
     pub async fn set(&mut self, key: &str, value: Bytes) -> Result<()> {
         // 初始化一个新的 `Set` 命令，通过通道发送。
-        let set = Command::Set(key.into(), value);
+        match self.request(Command::Set(key.into(), value)).await? {
+            Response::Done => Ok(()),
+            response => Err(unexpected(response)),
+        }
+    }
 
+    /// 删除键。
+    ///
+    /// 与 `Client::del` 相同，但请求会被**缓冲**，直到关联的连接能够发送请求。
+    pub async fn del(&mut self, keys: Vec<String>) -> Result<()> {
+        match self.request(Command::Del(keys)).await? {
+            Response::Done => Ok(()),
+            response => Err(unexpected(response)),
+        }
+    }
+
+    /// 将 `message` 发布到给定的 `channel`，返回订阅者数量。
+    ///
+    /// 与 `Client::publish` 相同，但请求会被**缓冲**，直到关联的连接能够发送请求。
+    pub async fn publish(&mut self, channel: &str, message: Bytes) -> Result<u64> {
+        match self.request(Command::Publish(channel.into(), message)).await? {
+            Response::Count(count) => Ok(count),
+            response => Err(unexpected(response)),
+        }
+    }
+
+    /// 将命令发送给连接任务并等待响应。
+    async fn request(&mut self, cmd: Command) -> Result<Response> {
         // 初始化一个新的 oneshot，用于接收来自连接的响应。
         let (tx, rx) = oneshot::channel();
 
         // 发送请求
-        self.tx.send((set, tx)).await?;
+        self.tx.send((cmd, tx)).await?;
 
         // 等待响应
         match rx.await {
-            Ok(res) => res.map(|_| ()),
+            Ok(res) => res,
             Err(err) => Err(err.into()),
         }
     }
 }
+
+/// 连接任务为命令返回了类型不符的响应。`run` 总是按命令返回对应的响应，因此这不应发生。
+fn unexpected(response: Response) -> crate::Error {
+    format!("unexpected response from connection task: {:?}", response).into()
+}
@@ -2,16 +2,26 @@
 //!
 //! 提供异步连接和发出支持的命令的方法。
 
-use crate::cmd::{Del, Get, Ping, Publish, Set, Subscribe, Unsubscribe};
-use crate::{Connection, Frame};
+use crate::cmd::{
+    Append, Auth, BLPop, BRPop, ClientCmd, ConfigCmd, Copy, DbSize, DebugCmd, DecrBy, Del, ExpireAt, Expiry, FlushAll,
+    FlushDb, Get, GetDel, GetEx, GetRange, GetSet, HDel, HGet, HGetAll, HIncrBy, HSet, Hello, IncrBy, IncrByFloat, Info,
+    LLen, LPop, LPush, LRange, Move, ObjectCmd, PExpireAt, PSetEx, PSubscribe, PUnsubscribe, Ping, PubSubCmd, Publish,
+    Quit, RPop, RPush, Rename, Reset, SAdd, SDiff, SInter, SIsMember, SMembers, SRem, SUnion, Save, Scan, Select, Set,
+    SetEx, SetRange, SlowLogCmd, Strlen, Subscribe, SwapDb, Touch, Type, Unlink, Unsubscribe, WaitAof,
+};
+use crate::connection::{configure_socket, DEFAULT_BUFFER_CAPACITY};
+use crate::{Connection, Frame, Protocol};
 
 use async_stream::try_stream;
 use bytes::Bytes;
+use std::borrow::BorrowMut;
+use std::collections::{HashMap, VecDeque};
 use std::io::{Error, ErrorKind};
 use std::time::Duration;
 use tokio::net::{TcpStream, ToSocketAddrs};
+use tokio::time;
 use tokio_stream::Stream;
-use tracing::{debug, instrument};
+use tracing::{debug, instrument, warn};
 
 /// 与 Redis 服务器建立的连接。
 ///
@@ -25,18 +35,87 @@ pub struct Client {
     /// 当 `Listener` 接收到一个入站连接时，`TcpStream` 被传递给 `Connection::new`，
     /// 它初始化相关的缓冲区。`Connection` 允许处理程序在“帧”级别操作，并将字节级别的协议解析细节封装在 `Connection` 中。
     connection: Connection,
+
+    /// 等待每个响应的最长时间。`None` 表示无限等待。
+    timeout: Option<Duration>,
+
+    /// 读写连接时是否发生过错误。发生错误后连接的状态未知，不应再使用。
+    broken: bool,
+
+    /// 等待命令回复时收到的发布/订阅消息推送，由 [`next_message`](Client::next_message) 按顺序返回。
+    ///
+    /// 只有通过 [`subscribe_keep_alive`](Client::subscribe_keep_alive) 订阅之后才会有。
+    pushes: VecDeque<Frame>,
+
+    /// 服务器最近一次在订阅或取消订阅的确认中报告的订阅数，包括频道和模式。
+    ///
+    /// 大于零时连接处于订阅模式，连接池不回收这样的连接。
+    subscriptions: u64,
 }
 
 /// 进入 pub/sub 模式的客户端。
 ///
 /// 一旦客户端订阅了一个频道，它们只能执行与 pub/sub 相关的命令。
 /// `Client` 类型转换为 `Subscriber` 类型，以防止调用非 pub/sub 方法。
-pub struct Subscriber {
-    /// 订阅的客户端。
-    client: Client,
+///
+/// # 生命周期
+///
+/// * [`Client::subscribe`] 消耗 `Client`，返回 `Subscriber<Client>`；[`Client::subscribe_by_ref`] 只借用
+///   `Client`，返回 `Subscriber<&mut Client>`，可以用于从连接池借出的连接。
+/// * [`unsubscribe_all`](Subscriber::unsubscribe_all) 取消所有订阅，[`reset`](Subscriber::reset) 还会把连接的
+///   其他状态恢复为初始状态。两者都交还 `Client`（或者它的借用），之后可以继续执行普通命令。
+/// * 用 [`unsubscribe`](Subscriber::unsubscribe) 和 [`punsubscribe`](Subscriber::punsubscribe) 取消最后一个订阅时，
+///   服务器同样回到普通模式，`Subscriber` 不会再收到消息。
+/// * 在订阅仍然有效时丢弃借用的 `Subscriber`，连接会留在订阅模式，之后的普通命令会失败。
+///   [`Pool`](crate::clients::Pool) 不会回收这样的连接。
+pub struct Subscriber<C = Client> {
+    /// 订阅的客户端，或者它的可变借用。
+    client: C,
 
     /// `Subscriber` 当前订阅的频道集合。
     subscribed_channels: Vec<String>,
+
+    /// `Subscriber` 当前订阅的模式集合。
+    subscribed_patterns: Vec<String>,
+}
+
+/// 一批排队的命令，一次性发送给服务器。
+///
+/// 使用 [`Client::pipeline`] 创建。命令在调用 [`execute`](Pipeline::execute) 之前只是排队；
+/// `execute` 先写入所有请求帧，然后按顺序读取同样数量的响应。在高延迟链路上，
+/// 这样只需要一次往返而不是每个命令一次。
+pub struct Pipeline<'a> {
+    /// 执行命令的客户端。
+    client: &'a mut Client,
+
+    /// 排队的请求帧。
+    frames: Vec<Frame>,
+}
+
+/// 建立连接时使用的选项。
+///
+/// 使用 [`Client::connect_with`] 传入。默认值与 [`Client::connect`] 的行为相同。
+///
+/// # 示例
+///
+/// ```no_run
+/// use mini_redis::clients::{Client, ConnectOptions};
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let options = ConnectOptions::new().read_buffer_size(64 * 1024);
+///     let client = Client::connect_with("localhost:6379", options).await.unwrap();
+/// # drop(client);
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ConnectOptions {
+    /// 连接读取缓冲区的初始容量。
+    read_buffer_size: usize,
+    /// 是否设置 `TCP_NODELAY`。
+    nodelay: bool,
+    /// TCP keepalive 的空闲时间。`None` 表示不启用。
+    keepalive: Option<Duration>,
 }
 
 /// 在订阅频道上收到的消息。
@@ -44,6 +123,25 @@ pub struct Subscriber {
 pub struct Message {
     pub channel: String,
     pub content: Bytes,
+    /// 通过模式订阅收到时，匹配该频道的模式。
+    pub pattern: Option<String>,
+}
+
+/// 慢日志中的一个条目，由 [`Client::slowlog_get`] 返回。
+#[derive(Debug, Clone)]
+pub struct SlowLogEntry {
+    /// 条目的 id，单调递增。
+    pub id: u64,
+    /// 命令开始执行的 Unix 时间戳，单位是秒。
+    pub timestamp: u64,
+    /// 命令的执行时间，精确到微秒。
+    pub duration: Duration,
+    /// 命令名称和参数。服务器会截断太多的参数和太长的参数。
+    pub args: Vec<Bytes>,
+    /// 执行命令的客户端的地址。
+    pub addr: String,
+    /// 执行命令的客户端的名称，没有设置时为空字符串。
+    pub name: String,
 }
 
 impl Client {
@@ -68,14 +166,54 @@ impl Client {
     /// ```
     ///
     pub async fn connect<T: ToSocketAddrs>(addr: T) -> crate::Result<Client> {
+        Self::connect_with(addr, ConnectOptions::default()).await
+    }
+
+    /// 使用给定的 `options` 与位于 `addr` 的 Redis 服务器建立连接。
+    ///
+    /// 处理大值的调用者可以通过 `options` 调整读取缓冲区的大小。
+    pub async fn connect_with<T: ToSocketAddrs>(addr: T, options: ConnectOptions) -> crate::Result<Client> {
         // `addr` 参数直接传递给 `TcpStream::connect`。这会执行任何异步 DNS 查找并尝试建立 TCP 连接。
         // 任一步骤出错都会返回错误，然后该错误会冒泡到 `mini_redis` 连接的调用者。
         let socket = TcpStream::connect(addr).await?;
+        configure_socket(&socket, options.nodelay, options.keepalive)?;
 
         // 初始化连接状态。这会分配读/写缓冲区以执行 redis 协议帧解析。
-        let connection = Connection::new(socket);
+        let connection = Connection::with_capacity(socket, options.read_buffer_size);
+
+        Ok(Client {
+            connection,
+            timeout: None,
+            broken: false,
+            pushes: VecDeque::new(),
+            subscriptions: 0,
+        })
+    }
 
-        Ok(Client { connection })
+    /// 设置等待每个响应的最长时间。
+    ///
+    /// 超时按请求计算：每次读取响应都会重新计时。超时后返回 `ErrorKind::TimedOut` 错误。
+    /// 此时迟到的响应可能仍会到达，连接的状态未知，不应继续使用。
+    ///
+    /// 默认值为 `None`，即无限等待。
+    ///
+    /// # 示例
+    ///
+    /// ```no_run
+    /// use mini_redis::clients::Client;
+    /// use std::time::Duration;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = Client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     client.set_timeout(Some(Duration::from_secs(1)));
+    ///     let val = client.get("foo").await.unwrap();
+    ///     println!("Got = {:?}", val);
+    /// }
+    /// ```
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) {
+        self.timeout = timeout;
     }
 
     /// 向服务器发送 Ping。
@@ -102,7 +240,7 @@ impl Client {
     pub async fn ping(&mut self, msg: Option<Bytes>) -> crate::Result<Bytes> {
         let frame = Frame::from(Ping::new(msg));
         debug!(request = ?frame);
-        self.connection.write_frame(&frame).await?;
+        self.write_frame(&frame).await?;
 
         match self.read_response().await? {
             Frame::Simple(value) => Ok(value.into()),
@@ -111,6 +249,124 @@ impl Client {
         }
     }
 
+    /// 协商连接使用的协议版本，返回服务器的基本信息，例如 `server`、`version` 和 `proto`。
+    ///
+    /// `protover` 为 `None` 时只返回信息，不切换协议。服务器不支持该版本时返回 `NOPROTO` 错误。
+    #[instrument(skip(self))]
+    pub async fn hello(&mut self, protover: Option<i64>) -> crate::Result<HashMap<String, Frame>> {
+        let frame = Frame::from(Hello::new(protover));
+
+        debug!(request = ?frame);
+
+        self.write_frame(&frame).await?;
+
+        let info: HashMap<String, Frame> = match self.read_response().await? {
+            Frame::Array(frames) => frames
+                .chunks(2)
+                .map(|pair| match pair {
+                    [Frame::Bulk(field), value] => Ok((String::from_utf8(field.to_vec())?, value.clone())),
+                    _ => Err("protocol error; invalid `HELLO` response".into()),
+                })
+                .collect::<crate::Result<_>>()?,
+            Frame::Map(pairs) => pairs
+                .into_iter()
+                .map(|pair| match pair {
+                    (Frame::Bulk(field), value) => Ok((String::from_utf8(field.to_vec())?, value)),
+                    _ => Err("protocol error; invalid `HELLO` response".into()),
+                })
+                .collect::<crate::Result<_>>()?,
+            frame => return Err(frame.to_error()),
+        };
+
+        // 记录协商的协议，`subscribe_keep_alive` 据此判断服务器是否会以推送发送消息。
+        match info.get("proto") {
+            Some(Frame::Integer(3)) => self.connection.set_protocol(Protocol::Resp3),
+            Some(_) => self.connection.set_protocol(Protocol::Resp2),
+            None => {}
+        }
+
+        Ok(info)
+    }
+
+    /// 为连接设置名称，便于在服务器上区分连接。`name` 为空时清除名称。
+    ///
+    /// 名称不能包含空格、换行或其他特殊字符，否则服务器返回错误。
+    #[instrument(skip(self))]
+    pub async fn set_name(&mut self, name: &str) -> crate::Result<()> {
+        let frame = Frame::from(ClientCmd::set_name(name));
+
+        debug!(request = ?frame);
+
+        self.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Simple(response) if response == "OK" => Ok(()),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    /// 返回连接的名称。未设置名称时返回空字符串。
+    #[instrument(skip(self))]
+    pub async fn get_name(&mut self) -> crate::Result<String> {
+        let frame = Frame::from(ClientCmd::get_name());
+
+        debug!(request = ?frame);
+
+        self.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Bulk(name) => Ok(String::from_utf8(name.to_vec())?),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    /// 返回服务器为该连接分配的 id。
+    #[instrument(skip(self))]
+    pub async fn client_id(&mut self) -> crate::Result<u64> {
+        let frame = Frame::from(ClientCmd::id());
+
+        debug!(request = ?frame);
+
+        self.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Integer(id) => Ok(id.try_into()?),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    /// 返回服务器上所有活动连接的描述，每行一个连接，例如 `id=1 addr=127.0.0.1:50000 name=worker`。
+    #[instrument(skip(self))]
+    pub async fn client_list(&mut self) -> crate::Result<String> {
+        let frame = Frame::from(ClientCmd::list());
+
+        debug!(request = ?frame);
+
+        self.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Bulk(list) => Ok(String::from_utf8(list.to_vec())?),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    /// 请求服务器关闭连接。
+    ///
+    /// 服务器回复 `OK` 之后关闭连接，因此该方法消费客户端。
+    #[instrument(skip(self))]
+    pub async fn quit(mut self) -> crate::Result<()> {
+        let frame = Frame::from(Quit::new());
+
+        debug!(request = ?frame);
+
+        self.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Simple(response) if response == "OK" => Ok(()),
+            frame => Err(frame.to_error()),
+        }
+    }
+
     /// 获取键的值。
     ///
     /// 如果键不存在，则返回特殊值 `None`。
@@ -138,7 +394,7 @@ impl Client {
         debug!(request = ?frame);
 
         // 将帧写入套接字。这会将完整的帧写入套接字，必要时等待。
-        self.connection.write_frame(&frame).await?;
+        self.write_frame(&frame).await?;
 
         // 等待服务器的响应
         //
@@ -151,16 +407,12 @@ impl Client {
         }
     }
 
-    /// 设置 `key` 以保存给定的 `value`。
-    ///
-    /// `value` 与 `key` 关联，直到被下一次调用 `set` 覆盖或被删除。
+    /// 获取键的值并删除该键。
     ///
-    /// 如果键已经保存了一个值，则会被覆盖。任何与键关联的先前生存时间在成功的 SET 操作中都会被丢弃。
+    /// 读取和删除在服务器上原子地完成。如果键不存在，则返回 `None`。
     ///
     /// # 示例
     ///
-    /// 展示基本用法。
-    ///
     /// ```no_run
     /// use mini_redis::clients::Client;
     ///
@@ -168,192 +420,1729 @@ impl Client {
     /// async fn main() {
     ///     let mut client = Client::connect("localhost:6379").await.unwrap();
     ///
-    /// client.set("foo", "bar".into()).await.unwrap();
-    ///
-    ///     // 立即获取值
-    ///     let val = client.get("foo").await.unwrap().unwrap();
-    ///     assert_eq!(val, "bar");
+    ///     let token = client.get_del("token").await.unwrap();
+    ///     println!("Got = {:?}", token);
     /// }
     /// ```
     #[instrument(skip(self))]
-    pub async fn set(&mut self, key: &str, value: Bytes) -> crate::Result<()> {
-        // 创建一个 `Set` 命令并将其传递给 `set_cmd`。一个单独的方法用于设置带有过期时间的值。
-        // 两个函数的共同部分由 `set_cmd` 实现。
-        self.set_cmd(Set::new(key, value, None)).await
+    pub async fn get_del(&mut self, key: &str) -> crate::Result<Option<Bytes>> {
+        let frame = Frame::from(GetDel::new(key));
+
+        debug!(request = ?frame);
+
+        self.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Bulk(value) => Ok(Some(value)),
+            Frame::Null => Ok(None),
+            frame => Err(frame.to_error()),
+        }
     }
 
+    /// 设置键的值并返回它先前的值。
+    ///
+    /// 读取和写入在服务器上原子地完成，键原有的过期时间被清除。如果键不存在，则返回 `None`。
     #[instrument(skip(self))]
-    pub async fn del(&mut self, keys: Vec<String>) -> crate::Result<()> {
-        // 为 `keys 创建一个 `Del` 命令并将其转换为帧。
-        let frame = Frame::from(Del::new(keys));
+    pub async fn getset(&mut self, key: &str, value: Bytes) -> crate::Result<Option<Bytes>> {
+        let frame = Frame::from(GetSet::new(key, value));
 
         debug!(request = ?frame);
 
-        // 将帧写入套接字。这会将完整的帧写入套接字，必要时等待。
-        self.connection.write_frame(&frame).await?;
+        self.write_frame(&frame).await?;
 
-        // 等待服务器的响应
-        //
-        // 接受 `Simple` 和 `Bulk` 帧。`Null` 表示键不存在，返回 `None`。
         match self.read_response().await? {
-            Frame::Simple(response) if response == "OK" => Ok(()),
+            Frame::Bulk(value) => Ok(Some(value)),
+            Frame::Null => Ok(None),
             frame => Err(frame.to_error()),
         }
     }
 
-    /// 设置 `key` 以保存给定的 `value`。该值在 `expiration` 后过期。
-    ///
-    /// `value` 与 `key` 关联，直到以下情况之一发生：
-    /// - 它过期。
-    /// - 它被下一次调用 `set` 覆盖。
-    /// - 它被删除。
+    /// 获取键的值，并按 `expiry` 修改它的过期时间。
     ///
-    /// 如果键已经保存了一个值，则会被覆盖。任何与键关联的先前生存时间在成功的 SET 操作中都会被丢弃。
+    /// `expiry` 为 `None` 时与 [`get`](Client::get) 相同。读取和修改在服务器上原子地完成。
+    /// 如果键不存在，则返回 `None`，不做任何修改。
     ///
     /// # 示例
     ///
-    /// 展示基本用法。此示例不**保证**始终有效，因为它依赖于基于时间的逻辑，并假设客户端和服务器在时间上保持相对同步。现实世界往往不会如此有利。
-    ///
     /// ```no_run
     /// use mini_redis::clients::Client;
-    /// use tokio::time;
+    /// use mini_redis::cmd::Expiry;
     /// use std::time::Duration;
     ///
     /// #[tokio::main]
     /// async fn main() {
-    ///     let ttl = Duration::from_millis(500);
     ///     let mut client = Client::connect("localhost:6379").await.unwrap();
     ///
-    ///     client.set_expires("foo", "bar".into(), ttl).await.unwrap();
-    ///
-    ///     // 立即获取值
-    ///     let val = client.get("foo").await.unwrap().unwrap();
-    ///     assert_eq!(val, "bar");
-    ///
-    ///     // 等待 TTL 过期
-    ///     time::sleep(ttl).await;
-    ///
-    ///     let val = client.get("foo").await.unwrap();
-    ///     assert!(val.is_some());
+    ///     // 读取会话，并把它的有效期延长到 30 分钟
+    ///     let session = client.getex("session", Some(Expiry::In(Duration::from_secs(1800)))).await.unwrap();
+    ///     println!("Got = {:?}", session);
     /// }
     /// ```
     #[instrument(skip(self))]
-    pub async fn set_expires(&mut self, key: &str, value: Bytes, expiration: Duration) -> crate::Result<()> {
-        // 创建一个 `Set` 命令并将其传递给 `set_cmd`。一个单独的方法用于设置带有过期时间的值。
-        // 两个函数的共同部分由 `set_cmd` 实现。
-        self.set_cmd(Set::new(key, value, Some(expiration))).await
-    }
-
-    /// 核心 `SET` 逻辑，由 `set` 和 `set_expires` 使用。
-    async fn set_cmd(&mut self, cmd: Set) -> crate::Result<()> {
-        // 将 `Set` 命令转换为帧
-        let frame = Frame::from(cmd);
+    pub async fn getex(&mut self, key: &str, expiry: Option<Expiry>) -> crate::Result<Option<Bytes>> {
+        let frame = Frame::from(GetEx::new(key, expiry));
 
         debug!(request = ?frame);
 
-        // 将帧写入套接字。这会将完整的帧写入套接字，必要时等待。
-        self.connection.write_frame(&frame).await?;
+        self.write_frame(&frame).await?;
 
-        // 等待服务器的响应。成功时，服务器简单地响应 `OK`。任何其他响应都表示错误。
         match self.read_response().await? {
-            Frame::Simple(response) if response == "OK" => Ok(()),
+            Frame::Bulk(value) => Ok(Some(value)),
+            Frame::Null => Ok(None),
             frame => Err(frame.to_error()),
         }
     }
 
-    /// 将 `message` 发布到给定的 `channel`。
-    ///
-    /// 返回当前在频道上监听的订阅者数量。不能保证这些订阅者会收到消息，因为他们可能随时断开连接。
-    ///
-    /// # 示例
-    ///
-    /// 展示基本用法。
-    ///
-    /// ```no_run
-    /// use mini_redis::clients::Client;
-    ///
-    /// #[tokio::main]
-    /// async fn main() {
-    ///     let mut client = Client::connect("localhost:6379").await.unwrap();
+    /// 将 `value` 追加到 `key` 的值末尾，键不存在时创建它。
     ///
-    ///     let val = client.publish("foo", "bar".into()).await.unwrap();
-    ///     println!("Got = {:?}", val);
-    /// }
-    /// ```
+    /// 返回追加后值的长度。
     #[instrument(skip(self))]
-    pub async fn publish(&mut self, channel: &str, message: Bytes) -> crate::Result<u64> {
-        // 将 `Publish` 命令转换为帧
-        let frame = Frame::from(Publish::new(channel, message));
+    pub async fn append(&mut self, key: &str, value: Bytes) -> crate::Result<u64> {
+        let frame = Frame::from(Append::new(key, value));
 
         debug!(request = ?frame);
 
-        // 将帧写入套接字
-        self.connection.write_frame(&frame).await?;
+        self.write_frame(&frame).await?;
 
-        // 读取响应
         match self.read_response().await? {
-            Frame::Integer(response) => Ok(response),
+            Frame::Integer(len) => Ok(len.try_into()?),
             frame => Err(frame.to_error()),
         }
     }
 
-    /// 订阅客户端到指定的频道。
-    ///
-    /// 一旦客户端发出订阅命令，它就不能再发出任何非 pub/sub 命令。该函数消耗 `self` 并返回一个 `Subscriber`。
+    /// 将 `key` 的值当作整数加上 `delta`，键不存在时从 `0` 开始。
     ///
-    /// `Subscriber` 值用于接收消息以及管理客户端订阅的频道列表。
+    /// 返回相加后的值。值不是整数或者结果溢出时返回错误。
     #[instrument(skip(self))]
-    pub async fn subscribe(mut self, channels: Vec<String>) -> crate::Result<Subscriber> {
-        // 向服务器发出订阅命令并等待确认。
-        // 然后客户端将被转换为“订阅者”状态，从那时起只能发出 pub/sub 命令。
-        self.subscribe_cmd(&channels).await?;
+    pub async fn incr_by(&mut self, key: &str, delta: i64) -> crate::Result<i64> {
+        let frame = Frame::from(IncrBy::new(key, delta));
 
-        // 返回 `Subscriber` 类型
-        Ok(Subscriber {
-            client: self,
-            subscribed_channels: channels,
-        })
+        debug!(request = ?frame);
+
+        self.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Integer(value) => Ok(value),
+            frame => Err(frame.to_error()),
+        }
     }
 
-    /// 核心 `SUBSCRIBE` 逻辑，由各种订阅函数使用
-    async fn subscribe_cmd(&mut self, channels: &[String]) -> crate::Result<()> {
-        // 将 `Subscribe` 命令转换为帧
-        let frame = Frame::from(Subscribe::new(channels.to_vec()));
+    /// 将 `key` 的值当作整数减去 `delta`，键不存在时从 `0` 开始。
+    ///
+    /// 返回相减后的值。值不是整数或者结果溢出时返回错误。
+    #[instrument(skip(self))]
+    pub async fn decr_by(&mut self, key: &str, delta: i64) -> crate::Result<i64> {
+        let frame = Frame::from(DecrBy::new(key, delta));
 
         debug!(request = ?frame);
 
-        // 将帧写入套接字
-        self.connection.write_frame(&frame).await?;
-        // 对于每个被订阅的频道，服务器都会响应一个确认订阅该频道的消息。
-        for channel in channels {
-            // 读取响应
-            let response = self.read_response().await?;
-            // 验证它是订阅确认。
-            match response {
-                Frame::Array(ref frame) => match frame.as_slice() {
-                    // 服务器以数组帧的形式响应：
-                    //
-                    // ```
-                    // [ "subscribe", channel, num-subscribed ]
-                    // ```
-                    //
-                    // 其中 channel 是频道的名称，
-                    // num-subscribed 是客户端当前订阅的频道数量。
-                    [subscribe, schannel, ..] if *subscribe == "subscribe" && *schannel == channel => {}
-                    _ => return Err(response.to_error()),
-                },
-                frame => return Err(frame.to_error()),
-            };
-        }
+        self.write_frame(&frame).await?;
 
-        Ok(())
+        match self.read_response().await? {
+            Frame::Integer(value) => Ok(value),
+            frame => Err(frame.to_error()),
+        }
     }
 
-    /// 从套接字读取响应帧。
+    /// 将 `key` 的值当作浮点数加上 `delta`，键不存在时从 `0` 开始。
     ///
-    /// 如果收到 `Error` 帧，则将其转换为 `Err`。
-    async fn read_response(&mut self) -> crate::Result<Frame> {
-        let response = self.connection.read_frame().await?;
+    /// 返回相加后的值。值不是有效的浮点数或者结果是 NaN 或无穷大时返回错误。
+    #[instrument(skip(self))]
+    pub async fn incr_by_float(&mut self, key: &str, delta: f64) -> crate::Result<f64> {
+        let frame = Frame::from(IncrByFloat::new(key, delta));
+
+        debug!(request = ?frame);
+
+        self.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Bulk(value) => Ok(std::str::from_utf8(&value)?.parse()?),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    /// 返回 `key` 的值的字节长度，键不存在时返回 `0`。
+    #[instrument(skip(self))]
+    pub async fn strlen(&mut self, key: &str) -> crate::Result<u64> {
+        let frame = Frame::from(Strlen::new(key));
+
+        debug!(request = ?frame);
+
+        self.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Integer(len) => Ok(len.try_into()?),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    /// 返回 `key` 的值中 `start` 到 `end`（包含两端）的字节。
+    ///
+    /// 负数索引从末尾开始计算，`-1` 是最后一个字节。范围为空或键不存在时返回空值。
+    #[instrument(skip(self))]
+    pub async fn get_range(&mut self, key: &str, start: i64, end: i64) -> crate::Result<Bytes> {
+        let frame = Frame::from(GetRange::new(key, start, end));
+
+        debug!(request = ?frame);
+
+        self.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Bulk(value) => Ok(value),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    /// 从 `offset` 开始用 `value` 覆盖 `key` 的值。
+    ///
+    /// `offset` 超过值的当前长度时中间用零字节填充。返回修改后值的长度。
+    #[instrument(skip(self))]
+    pub async fn set_range(&mut self, key: &str, offset: i64, value: Bytes) -> crate::Result<u64> {
+        let frame = Frame::from(SetRange::new(key, offset, value));
+
+        debug!(request = ?frame);
+
+        self.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Integer(len) => Ok(len.try_into()?),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    /// 设置 `key` 以保存给定的 `value`。
+    ///
+    /// `value` 与 `key` 关联，直到被下一次调用 `set` 覆盖或被删除。
+    ///
+    /// 如果键已经保存了一个值，则会被覆盖。任何与键关联的先前生存时间在成功的 SET 操作中都会被丢弃。
+    ///
+    /// # 示例
+    ///
+    /// 展示基本用法。
+    ///
+    /// ```no_run
+    /// use mini_redis::clients::Client;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = Client::connect("localhost:6379").await.unwrap();
+    ///
+    /// client.set("foo", "bar".into()).await.unwrap();
+    ///
+    ///     // 立即获取值
+    ///     let val = client.get("foo").await.unwrap().unwrap();
+    ///     assert_eq!(val, "bar");
+    /// }
+    /// ```
+    #[instrument(skip(self))]
+    pub async fn set(&mut self, key: &str, value: Bytes) -> crate::Result<()> {
+        // 创建一个 `Set` 命令并将其传递给 `set_cmd`。一个单独的方法用于设置带有过期时间的值。
+        // 两个函数的共同部分由 `set_cmd` 实现。
+        self.set_cmd(Set::new(key, value, None)).await
+    }
+
+    #[instrument(skip(self))]
+    pub async fn del(&mut self, keys: Vec<String>) -> crate::Result<()> {
+        // 为 `keys 创建一个 `Del` 命令并将其转换为帧。
+        let frame = Frame::from(Del::new(keys));
+
+        debug!(request = ?frame);
+
+        // 将帧写入套接字。这会将完整的帧写入套接字，必要时等待。
+        self.write_frame(&frame).await?;
+
+        // 等待服务器的响应
+        //
+        // 接受 `Simple` 和 `Bulk` 帧。`Null` 表示键不存在，返回 `None`。
+        match self.read_response().await? {
+            Frame::Simple(response) if response == "OK" => Ok(()),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    /// 删除 `keys`，返回被删除的键数。
+    ///
+    /// 与 `del` 相同，但服务器在后台释放大的值。键在返回之前就已经被删除。
+    #[instrument(skip(self))]
+    pub async fn unlink(&mut self, keys: Vec<String>) -> crate::Result<u64> {
+        let frame = Frame::from(Unlink::new(keys));
+
+        debug!(request = ?frame);
+
+        self.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Integer(count) => Ok(count.try_into()?),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    /// 更新 `keys` 的最近访问时间而不读取它们的值，返回其中存在的键数。
+    ///
+    /// 服务器设置了内存上限时，最近访问过的键最后被驱逐。
+    #[instrument(skip(self))]
+    pub async fn touch(&mut self, keys: Vec<String>) -> crate::Result<u64> {
+        let frame = Frame::from(Touch::new(keys));
+
+        debug!(request = ?frame);
+
+        self.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Integer(count) => Ok(count.try_into()?),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    /// 将 `key` 重命名为 `new_key`。
+    ///
+    /// 如果 `new_key` 已经存在，则会被覆盖。生存时间随值一起转移。如果 `key` 不存在，则返回错误。
+    ///
+    /// # 示例
+    ///
+    /// 展示基本用法。
+    ///
+    /// ```no_run
+    /// use mini_redis::clients::Client;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = Client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     client.set("foo", "bar".into()).await.unwrap();
+    ///     client.rename("foo", "baz").await.unwrap();
+    ///
+    ///     let val = client.get("baz").await.unwrap().unwrap();
+    ///     assert_eq!(val, "bar");
+    /// }
+    /// ```
+    #[instrument(skip(self))]
+    pub async fn rename(&mut self, key: &str, new_key: &str) -> crate::Result<()> {
+        let frame = Frame::from(Rename::new(key, new_key));
+
+        debug!(request = ?frame);
+
+        self.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Simple(response) if response == "OK" => Ok(()),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    /// 只在 `new_key` 不存在时将 `key` 重命名为 `new_key`。
+    ///
+    /// 重命名成功返回 `true`；`new_key` 已经存在时不做任何修改并返回 `false`。如果 `key` 不存在，则返回错误。
+    #[instrument(skip(self))]
+    pub async fn renamenx(&mut self, key: &str, new_key: &str) -> crate::Result<bool> {
+        let frame = Frame::from(Rename::nx(key, new_key));
+
+        debug!(request = ?frame);
+
+        self.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Integer(response) => Ok(response == 1),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    /// 将 `source` 的值和剩余的生存时间复制到 `destination`。
+    ///
+    /// 复制成功返回 `true`。`source` 不存在，或 `destination` 已经存在且 `replace` 为 `false` 时，
+    /// 不做任何修改并返回 `false`。`source` 与 `destination` 相同时返回错误。
+    ///
+    /// # 示例
+    ///
+    /// ```no_run
+    /// use mini_redis::clients::Client;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = Client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     client.set("foo", "bar".into()).await.unwrap();
+    ///     assert!(client.copy("foo", "baz", false).await.unwrap());
+    ///
+    ///     let val = client.get("baz").await.unwrap().unwrap();
+    ///     assert_eq!(val, "bar");
+    /// }
+    /// ```
+    #[instrument(skip(self))]
+    pub async fn copy(&mut self, source: &str, destination: &str, replace: bool) -> crate::Result<bool> {
+        let frame = Frame::from(Copy::new(source, destination, replace));
+
+        debug!(request = ?frame);
+
+        self.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Integer(response) => Ok(response == 1),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    /// 与 [`copy`](Client::copy) 相同，但 `destination` 写入编号为 `db` 的数据库（`DB`）。
+    ///
+    /// 数据库编号超出范围时返回错误。
+    #[instrument(skip(self))]
+    pub async fn copy_to_db(
+        &mut self,
+        source: &str,
+        destination: &str,
+        db: usize,
+        replace: bool,
+    ) -> crate::Result<bool> {
+        let frame = Frame::from(Copy::new(source, destination, replace).with_db(db));
+
+        debug!(request = ?frame);
+
+        self.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Integer(response) => Ok(response == 1),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    /// 将 `key` 连同它的生存时间移动到编号为 `db` 的数据库。
+    ///
+    /// 移动成功返回 `true`；如果 `key` 不存在，或者目标数据库中已经存在同名的键，返回 `false`。
+    #[instrument(skip(self))]
+    pub async fn move_key(&mut self, key: &str, db: usize) -> crate::Result<bool> {
+        let frame = Frame::from(Move::new(key, db));
+
+        debug!(request = ?frame);
+
+        self.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Integer(response) => Ok(response == 1),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    /// 设置 `key` 在 Unix 时间 `timestamp`（秒）过期，替换原来的生存时间。
+    ///
+    /// 设置成功返回 `true`，键不存在返回 `false`。如果时间已经过去，键被立即删除，同样返回 `true`。
+    #[instrument(skip(self))]
+    pub async fn expireat(&mut self, key: &str, timestamp: i64) -> crate::Result<bool> {
+        let frame = Frame::from(ExpireAt::new(key, timestamp));
+
+        debug!(request = ?frame);
+
+        self.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Integer(response) => Ok(response == 1),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    /// 设置 `key` 在 Unix 时间 `timestamp`（毫秒）过期，替换原来的生存时间。
+    ///
+    /// 与 [`expireat`](Client::expireat) 相同，只是时间以毫秒为单位。
+    #[instrument(skip(self))]
+    pub async fn pexpireat(&mut self, key: &str, timestamp: i64) -> crate::Result<bool> {
+        let frame = Frame::from(PExpireAt::new(key, timestamp));
+
+        debug!(request = ?frame);
+
+        self.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Integer(response) => Ok(response == 1),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    /// 设置 `key` 以保存给定的 `value`。该值在 `expiration` 后过期。
+    ///
+    /// `value` 与 `key` 关联，直到以下情况之一发生：
+    /// - 它过期。
+    /// - 它被下一次调用 `set` 覆盖。
+    /// - 它被删除。
+    ///
+    /// 如果键已经保存了一个值，则会被覆盖。任何与键关联的先前生存时间在成功的 SET 操作中都会被丢弃。
+    ///
+    /// # 示例
+    ///
+    /// 展示基本用法。此示例不**保证**始终有效，因为它依赖于基于时间的逻辑，并假设客户端和服务器在时间上保持相对同步。现实世界往往不会如此有利。
+    ///
+    /// ```no_run
+    /// use mini_redis::clients::Client;
+    /// use tokio::time;
+    /// use std::time::Duration;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let ttl = Duration::from_millis(500);
+    ///     let mut client = Client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     client.set_expires("foo", "bar".into(), ttl).await.unwrap();
+    ///
+    ///     // 立即获取值
+    ///     let val = client.get("foo").await.unwrap().unwrap();
+    ///     assert_eq!(val, "bar");
+    ///
+    ///     // 等待 TTL 过期
+    ///     time::sleep(ttl).await;
+    ///
+    ///     let val = client.get("foo").await.unwrap();
+    ///     assert!(val.is_some());
+    /// }
+    /// ```
+    #[instrument(skip(self))]
+    pub async fn set_expires(&mut self, key: &str, value: Bytes, expiration: Duration) -> crate::Result<()> {
+        // 创建一个 `Set` 命令并将其传递给 `set_cmd`。一个单独的方法用于设置带有过期时间的值。
+        // 两个函数的共同部分由 `set_cmd` 实现。
+        self.set_cmd(Set::new(key, value, Some(expiration))).await
+    }
+
+    /// 设置 `key` 以保存给定的 `value`，保留键原有的生存时间（`SET KEEPTTL`）。
+    ///
+    /// 键不存在或者没有生存时间时，写入的值不会过期。
+    #[instrument(skip(self))]
+    pub async fn set_keep_ttl(&mut self, key: &str, value: Bytes) -> crate::Result<()> {
+        self.set_cmd(Set::new(key, value, None).with_keep_ttl()).await
+    }
+
+    /// 设置 `key` 以保存给定的 `value`，`seconds` 秒后过期（`SETEX`）。
+    ///
+    /// `seconds` 必须为正数，否则服务器返回错误且不写入。
+    #[instrument(skip(self))]
+    pub async fn set_ex(&mut self, key: &str, value: Bytes, seconds: i64) -> crate::Result<()> {
+        let frame = Frame::from(SetEx::new(key, seconds, value));
+
+        debug!(request = ?frame);
+
+        self.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Simple(response) if response == "OK" => Ok(()),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    /// 设置 `key` 以保存给定的 `value`，`milliseconds` 毫秒后过期（`PSETEX`）。
+    ///
+    /// `milliseconds` 必须为正数，否则服务器返回错误且不写入。
+    #[instrument(skip(self))]
+    pub async fn pset_ex(&mut self, key: &str, value: Bytes, milliseconds: i64) -> crate::Result<()> {
+        let frame = Frame::from(PSetEx::new(key, milliseconds, value));
+
+        debug!(request = ?frame);
+
+        self.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Simple(response) if response == "OK" => Ok(()),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    /// 核心 `SET` 逻辑，由 `set`、`set_expires` 和 `set_keep_ttl` 使用。
+    async fn set_cmd(&mut self, cmd: Set) -> crate::Result<()> {
+        // 将 `Set` 命令转换为帧
+        let frame = Frame::from(cmd);
+
+        debug!(request = ?frame);
+
+        // 将帧写入套接字。这会将完整的帧写入套接字，必要时等待。
+        self.write_frame(&frame).await?;
+
+        // 等待服务器的响应。成功时，服务器简单地响应 `OK`。任何其他响应都表示错误。
+        match self.read_response().await? {
+            Frame::Simple(response) if response == "OK" => Ok(()),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    /// 将 `message` 发布到给定的 `channel`。
+    ///
+    /// 返回当前在频道上监听的订阅者数量。不能保证这些订阅者会收到消息，因为他们可能随时断开连接。
+    ///
+    /// # 示例
+    ///
+    /// 展示基本用法。
+    ///
+    /// ```no_run
+    /// use mini_redis::clients::Client;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = Client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     let val = client.publish("foo", "bar".into()).await.unwrap();
+    ///     println!("Got = {:?}", val);
+    /// }
+    /// ```
+    #[instrument(skip(self))]
+    pub async fn publish(&mut self, channel: &str, message: Bytes) -> crate::Result<u64> {
+        // 将 `Publish` 命令转换为帧
+        let frame = Frame::from(Publish::new(channel, message));
+
+        debug!(request = ?frame);
+
+        // 将帧写入套接字
+        self.write_frame(&frame).await?;
+
+        // 读取响应
+        match self.read_response().await? {
+            Frame::Integer(response) => Ok(response.try_into()?),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    /// 将 `values` 依次推入 `key` 列表的头部，键不存在时创建列表。
+    ///
+    /// 返回推入后列表的长度。
+    ///
+    /// # 示例
+    ///
+    /// ```no_run
+    /// use mini_redis::clients::Client;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = Client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     client.lpush("jobs", vec!["job-1".into()]).await.unwrap();
+    ///     let job = client.rpop("jobs").await.unwrap();
+    ///     println!("Got = {:?}", job);
+    /// }
+    /// ```
+    #[instrument(skip(self))]
+    pub async fn lpush(&mut self, key: &str, values: Vec<Bytes>) -> crate::Result<u64> {
+        self.push_cmd(Frame::from(LPush::new(key, values))).await
+    }
+
+    /// 将 `values` 依次推入 `key` 列表的尾部，键不存在时创建列表。
+    ///
+    /// 返回推入后列表的长度。
+    #[instrument(skip(self))]
+    pub async fn rpush(&mut self, key: &str, values: Vec<Bytes>) -> crate::Result<u64> {
+        self.push_cmd(Frame::from(RPush::new(key, values))).await
+    }
+
+    /// 移除并返回 `key` 列表头部的元素。键不存在时返回 `None`。
+    #[instrument(skip(self))]
+    pub async fn lpop(&mut self, key: &str) -> crate::Result<Option<Bytes>> {
+        self.pop_cmd(Frame::from(LPop::new(key))).await
+    }
+
+    /// 移除并返回 `key` 列表尾部的元素。键不存在时返回 `None`。
+    #[instrument(skip(self))]
+    pub async fn rpop(&mut self, key: &str) -> crate::Result<Option<Bytes>> {
+        self.pop_cmd(Frame::from(RPop::new(key))).await
+    }
+
+    /// 移除并返回 `key` 列表头部的最多 `count` 个元素。列表较短时返回所有元素，键不存在时返回 `None`。
+    #[instrument(skip(self))]
+    pub async fn lpop_count(&mut self, key: &str, count: usize) -> crate::Result<Option<Vec<Bytes>>> {
+        self.pop_count_cmd(Frame::from(LPop::new(key).with_count(count))).await
+    }
+
+    /// 移除并返回 `key` 列表尾部的最多 `count` 个元素。列表较短时返回所有元素，键不存在时返回 `None`。
+    #[instrument(skip(self))]
+    pub async fn rpop_count(&mut self, key: &str, count: usize) -> crate::Result<Option<Vec<Bytes>>> {
+        self.pop_count_cmd(Frame::from(RPop::new(key).with_count(count))).await
+    }
+
+    /// 从 `keys` 中第一个非空列表的头部弹出元素，所有列表都为空时阻塞等待。
+    ///
+    /// 返回弹出元素的列表的键和元素。等待超过 `timeout` 时返回 `None`；`timeout` 为 `None` 时一直等待。
+    /// 如果通过 [`set_timeout`](Client::set_timeout) 设置了请求超时，它需要比 `timeout` 更长。
+    ///
+    /// # 示例
+    ///
+    /// ```no_run
+    /// use mini_redis::clients::Client;
+    /// use std::time::Duration;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = Client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     let job = client.blpop(&["jobs".into()], Some(Duration::from_secs(5))).await.unwrap();
+    ///     println!("Got = {:?}", job);
+    /// }
+    /// ```
+    #[instrument(skip(self))]
+    pub async fn blpop(
+        &mut self,
+        keys: &[String],
+        timeout: Option<Duration>,
+    ) -> crate::Result<Option<(String, Bytes)>> {
+        self.blocking_pop_cmd(Frame::from(BLPop::new(keys.to_vec(), timeout))).await
+    }
+
+    /// 从 `keys` 中第一个非空列表的尾部弹出元素，所有列表都为空时阻塞等待。
+    ///
+    /// 与 [`blpop`](Client::blpop) 相同，只是从尾部弹出。
+    #[instrument(skip(self))]
+    pub async fn brpop(
+        &mut self,
+        keys: &[String],
+        timeout: Option<Duration>,
+    ) -> crate::Result<Option<(String, Bytes)>> {
+        self.blocking_pop_cmd(Frame::from(BRPop::new(keys.to_vec(), timeout))).await
+    }
+
+    /// 返回 `key` 列表的长度。键不存在时返回 `0`。
+    #[instrument(skip(self))]
+    pub async fn llen(&mut self, key: &str) -> crate::Result<u64> {
+        let frame = Frame::from(LLen::new(key));
+
+        debug!(request = ?frame);
+
+        self.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Integer(len) => Ok(len.try_into()?),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    /// 返回 `key` 列表中 `start` 到 `stop`（包含两端）的元素。
+    ///
+    /// 负数索引从末尾开始计算，`-1` 是最后一个元素。范围为空或键不存在时返回空列表。
+    #[instrument(skip(self))]
+    pub async fn lrange(&mut self, key: &str, start: i64, stop: i64) -> crate::Result<Vec<Bytes>> {
+        let frame = Frame::from(LRange::new(key, start, stop));
+
+        debug!(request = ?frame);
+
+        self.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Array(frames) => frames
+                .into_iter()
+                .map(|frame| match frame {
+                    Frame::Bulk(value) => Ok(value),
+                    frame => Err(frame.to_error()),
+                })
+                .collect(),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    /// 设置 `key` 哈希中的字段，返回新增的字段数。
+    ///
+    /// 键不存在时先创建一个空哈希，已经存在的字段被覆盖。
+    #[instrument(skip(self))]
+    pub async fn hset(&mut self, key: &str, fields: Vec<(String, Bytes)>) -> crate::Result<u64> {
+        let frame = Frame::from(HSet::new(key, fields));
+
+        debug!(request = ?frame);
+
+        self.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Integer(added) => Ok(added.try_into()?),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    /// 获取 `key` 哈希中 `field` 的值。字段或键不存在时返回 `None`。
+    #[instrument(skip(self))]
+    pub async fn hget(&mut self, key: &str, field: &str) -> crate::Result<Option<Bytes>> {
+        let frame = Frame::from(HGet::new(key, field));
+
+        debug!(request = ?frame);
+
+        self.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Bulk(value) => Ok(Some(value)),
+            Frame::Null => Ok(None),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    /// 删除 `key` 哈希中的 `fields`，返回实际删除的字段数。
+    #[instrument(skip(self))]
+    pub async fn hdel(&mut self, key: &str, fields: &[String]) -> crate::Result<u64> {
+        let frame = Frame::from(HDel::new(key, fields.to_vec()));
+
+        debug!(request = ?frame);
+
+        self.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Integer(removed) => Ok(removed.try_into()?),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    /// 返回 `key` 哈希中的所有字段和值。键不存在时返回空的 `HashMap`。
+    #[instrument(skip(self))]
+    pub async fn hgetall(&mut self, key: &str) -> crate::Result<HashMap<String, Bytes>> {
+        let frame = Frame::from(HGetAll::new(key));
+
+        debug!(request = ?frame);
+
+        self.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Array(frames) => frames
+                .chunks(2)
+                .map(|pair| match pair {
+                    [Frame::Bulk(field), Frame::Bulk(value)] => Ok((String::from_utf8(field.to_vec())?, value.clone())),
+                    _ => Err("protocol error; invalid `HGETALL` response".into()),
+                })
+                .collect(),
+            // 连接通过 `HELLO 3` 切换到 RESP3 之后，服务器返回 map。
+            Frame::Map(pairs) => pairs
+                .into_iter()
+                .map(|pair| match pair {
+                    (Frame::Bulk(field), Frame::Bulk(value)) => Ok((String::from_utf8(field.to_vec())?, value)),
+                    _ => Err("protocol error; invalid `HGETALL` response".into()),
+                })
+                .collect(),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    /// 将 `key` 哈希中 `field` 的值加上 `delta`，返回相加后的值。键或字段不存在时从 `0` 开始。
+    #[instrument(skip(self))]
+    pub async fn hincrby(&mut self, key: &str, field: &str, delta: i64) -> crate::Result<i64> {
+        let frame = Frame::from(HIncrBy::new(key, field, delta));
+
+        debug!(request = ?frame);
+
+        self.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Integer(value) => Ok(value),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    /// 向 `key` 集合添加 `members`，返回新增的成员数。
+    ///
+    /// 键不存在时先创建一个空集合，已经存在的成员不计入。
+    #[instrument(skip(self))]
+    pub async fn sadd(&mut self, key: &str, members: Vec<Bytes>) -> crate::Result<u64> {
+        let frame = Frame::from(SAdd::new(key, members));
+
+        debug!(request = ?frame);
+
+        self.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Integer(added) => Ok(added.try_into()?),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    /// 从 `key` 集合删除 `members`，返回实际删除的成员数。
+    #[instrument(skip(self))]
+    pub async fn srem(&mut self, key: &str, members: Vec<Bytes>) -> crate::Result<u64> {
+        let frame = Frame::from(SRem::new(key, members));
+
+        debug!(request = ?frame);
+
+        self.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Integer(removed) => Ok(removed.try_into()?),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    /// 返回 `key` 集合中的所有成员，顺序不确定。键不存在时返回空列表。
+    #[instrument(skip(self))]
+    pub async fn smembers(&mut self, key: &str) -> crate::Result<Vec<Bytes>> {
+        let frame = Frame::from(SMembers::new(key));
+
+        debug!(request = ?frame);
+
+        self.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Array(frames) => frames
+                .into_iter()
+                .map(|frame| match frame {
+                    Frame::Bulk(member) => Ok(member),
+                    frame => Err(frame.to_error()),
+                })
+                .collect(),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    /// 判断 `member` 是否在 `key` 集合中。
+    #[instrument(skip(self))]
+    pub async fn sismember(&mut self, key: &str, member: Bytes) -> crate::Result<bool> {
+        let frame = Frame::from(SIsMember::new(key, member));
+
+        debug!(request = ?frame);
+
+        self.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Integer(is_member) => Ok(is_member == 1),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    /// 返回 `keys` 中所有集合的交集，顺序不确定。不存在的键视为空集合。
+    #[instrument(skip(self))]
+    pub async fn sinter(&mut self, keys: &[String]) -> crate::Result<Vec<Bytes>> {
+        self.set_op_cmd(Frame::from(SInter::new(keys.to_vec()))).await
+    }
+
+    /// 返回 `keys` 中所有集合的并集，顺序不确定。不存在的键视为空集合。
+    #[instrument(skip(self))]
+    pub async fn sunion(&mut self, keys: &[String]) -> crate::Result<Vec<Bytes>> {
+        self.set_op_cmd(Frame::from(SUnion::new(keys.to_vec()))).await
+    }
+
+    /// 返回 `keys` 中第一个集合与其余集合的差集，顺序不确定。不存在的键视为空集合。
+    #[instrument(skip(self))]
+    pub async fn sdiff(&mut self, keys: &[String]) -> crate::Result<Vec<Bytes>> {
+        self.set_op_cmd(Frame::from(SDiff::new(keys.to_vec()))).await
+    }
+
+    /// `SINTER`、`SUNION` 和 `SDIFF` 的共同逻辑
+    async fn set_op_cmd(&mut self, frame: Frame) -> crate::Result<Vec<Bytes>> {
+        debug!(request = ?frame);
+
+        self.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Array(frames) => frames
+                .into_iter()
+                .map(|frame| match frame {
+                    Frame::Bulk(member) => Ok(member),
+                    frame => Err(frame.to_error()),
+                })
+                .collect(),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    /// `BLPOP` 和 `BRPOP` 的共同逻辑
+    async fn blocking_pop_cmd(&mut self, frame: Frame) -> crate::Result<Option<(String, Bytes)>> {
+        debug!(request = ?frame);
+
+        self.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Array(frames) => match <[Frame; 2]>::try_from(frames) {
+                Ok([Frame::Bulk(key), Frame::Bulk(value)]) => Ok(Some((String::from_utf8(key.to_vec())?, value))),
+                _ => Err("protocol error; invalid blocking pop response".into()),
+            },
+            Frame::NullArray => Ok(None),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    /// `LPUSH` 和 `RPUSH` 的共同逻辑
+    async fn push_cmd(&mut self, frame: Frame) -> crate::Result<u64> {
+        debug!(request = ?frame);
+
+        self.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Integer(len) => Ok(len.try_into()?),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    /// `LPOP` 和 `RPOP` 的共同逻辑
+    async fn pop_cmd(&mut self, frame: Frame) -> crate::Result<Option<Bytes>> {
+        debug!(request = ?frame);
+
+        self.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Bulk(value) => Ok(Some(value)),
+            Frame::Null => Ok(None),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    /// 带 `count` 的 `LPOP` 和 `RPOP` 的共同逻辑
+    async fn pop_count_cmd(&mut self, frame: Frame) -> crate::Result<Option<Vec<Bytes>>> {
+        debug!(request = ?frame);
+
+        self.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Array(frames) => frames
+                .into_iter()
+                .map(|frame| match frame {
+                    Frame::Bulk(value) => Ok(value),
+                    frame => Err(frame.to_error()),
+                })
+                .collect::<crate::Result<_>>()
+                .map(Some),
+            Frame::NullArray => Ok(None),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    /// 返回 `key` 的值的类型名称，例如 `"string"`。键不存在时返回 `"none"`。
+    ///
+    /// 因为 `type` 是关键字，所以命名为 `type_of`。
+    #[instrument(skip(self))]
+    pub async fn type_of(&mut self, key: &str) -> crate::Result<String> {
+        let frame = Frame::from(Type::new(key));
+
+        debug!(request = ?frame);
+
+        self.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Simple(name) => Ok(name),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    /// 返回 `key` 的值的编码名称，例如短字符串的 `embstr`。键不存在时返回错误。
+    #[instrument(skip(self))]
+    pub async fn object_encoding(&mut self, key: &str) -> crate::Result<String> {
+        let frame = Frame::from(ObjectCmd::encoding(key));
+
+        debug!(request = ?frame);
+
+        self.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Bulk(encoding) => Ok(String::from_utf8(encoding.to_vec())?),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    /// 返回 `DEBUG OBJECT` 报告的 `key` 的值的序列化长度，即值的字节数。容器类型是所有元素的字节数之和。
+    /// 键不存在时返回错误。
+    #[instrument(skip(self))]
+    pub async fn debug_serialized_length(&mut self, key: &str) -> crate::Result<u64> {
+        let frame = Frame::from(DebugCmd::object(key));
+
+        debug!(request = ?frame);
+
+        self.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            // 响应由空格分隔的 `field:value` 组成。
+            Frame::Simple(info) => match info.split(' ').find_map(|field| field.strip_prefix("serializedlength:")) {
+                Some(len) => Ok(len.parse()?),
+                None => Err("protocol error; missing `serializedlength` in `DEBUG OBJECT` response".into()),
+            },
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    /// 从 `cursor` 开始增量迭代键空间，返回下一次调用使用的游标和本批次的键。
+    ///
+    /// 第一次调用传入 `0`，返回的游标为 `0` 时迭代结束。`pattern` 只返回匹配的键；`count` 是每次检查的键数，默认为
+    /// `10`。
+    ///
+    /// # 示例
+    ///
+    /// ```no_run
+    /// use mini_redis::clients::Client;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = Client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     let mut cursor = 0;
+    ///     loop {
+    ///         let (next, keys) = client.scan(cursor, Some("user:*"), None).await.unwrap();
+    ///         println!("{:?}", keys);
+    ///         if next == 0 {
+    ///             break;
+    ///         }
+    ///         cursor = next;
+    ///     }
+    /// }
+    /// ```
+    #[instrument(skip(self))]
+    pub async fn scan(
+        &mut self,
+        cursor: u64,
+        pattern: Option<&str>,
+        count: Option<u64>,
+    ) -> crate::Result<(u64, Vec<String>)> {
+        let frame = Frame::from(Scan::new(cursor, pattern.map(str::to_string), count));
+
+        debug!(request = ?frame);
+
+        self.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Array(frames) => match <[Frame; 2]>::try_from(frames) {
+                Ok([Frame::Bulk(cursor), Frame::Array(keys)]) => {
+                    let cursor = std::str::from_utf8(&cursor)?.parse()?;
+                    let keys = keys
+                        .into_iter()
+                        .map(|key| match key {
+                            Frame::Bulk(key) => Ok(String::from_utf8(key.to_vec())?),
+                            frame => Err(frame.to_error()),
+                        })
+                        .collect::<crate::Result<_>>()?;
+                    Ok((cursor, keys))
+                }
+                _ => Err("protocol error; invalid `SCAN` response".into()),
+            },
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    /// 返回当前选择的数据库中未过期的键数。
+    #[instrument(skip(self))]
+    pub async fn dbsize(&mut self) -> crate::Result<u64> {
+        let frame = Frame::from(DbSize::new());
+
+        debug!(request = ?frame);
+
+        self.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Integer(len) => Ok(len.try_into()?),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    /// 选择编号为 `index` 的数据库。之后这个连接上与键有关的命令都作用于该数据库。
+    ///
+    /// 新连接使用 0 号数据库。编号超出服务器的数据库数量时返回错误。
+    #[instrument(skip(self))]
+    pub async fn select(&mut self, index: usize) -> crate::Result<()> {
+        let frame = Frame::from(Select::new(index));
+
+        debug!(request = ?frame);
+
+        self.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Simple(response) if response == "OK" => Ok(()),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    /// 交换编号为 `first` 和 `second` 的两个数据库的内容。
+    ///
+    /// 已经选择了这两个数据库的连接（包括这个连接）直接看到交换之后的内容。编号超出范围时返回错误。
+    #[instrument(skip(self))]
+    pub async fn swapdb(&mut self, first: usize, second: usize) -> crate::Result<()> {
+        let frame = Frame::from(SwapDb::new(first, second));
+
+        debug!(request = ?frame);
+
+        self.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Simple(response) if response == "OK" => Ok(()),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    /// 删除当前选择的数据库中的所有键。其他数据库、频道和订阅不受影响。
+    #[instrument(skip(self))]
+    pub async fn flushdb(&mut self) -> crate::Result<()> {
+        let frame = Frame::from(FlushDb::new());
+
+        debug!(request = ?frame);
+
+        self.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Simple(response) if response == "OK" => Ok(()),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    /// 删除所有数据库中的所有键。频道和订阅不受影响。
+    #[instrument(skip(self))]
+    pub async fn flushall(&mut self) -> crate::Result<()> {
+        let frame = Frame::from(FlushAll::new());
+
+        debug!(request = ?frame);
+
+        self.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Simple(response) if response == "OK" => Ok(()),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    /// 把键空间保存到服务器配置的快照文件。服务器没有配置快照文件时返回错误。
+    #[instrument(skip(self))]
+    pub async fn save(&mut self) -> crate::Result<()> {
+        let frame = Frame::from(Save::new());
+
+        debug!(request = ?frame);
+
+        self.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Simple(response) if response == "OK" => Ok(()),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    /// 等待之前写入的命令被同步到服务器的 AOF 所在的磁盘。
+    ///
+    /// 返回完成同步的本地 AOF 数量和确认的副本数量。服务器开启 AOF 时，第一个值在同步完成之后为 1，
+    /// 在 `timeout` 之内没有完成时为 0；没有开启 AOF 时为 0。服务器没有副本，第二个值总是 0。
+    /// `timeout` 为 `None` 时一直等待。
+    #[instrument(skip(self))]
+    pub async fn waitaof(
+        &mut self,
+        numlocal: u64,
+        numreplicas: u64,
+        timeout: Option<Duration>,
+    ) -> crate::Result<(u64, u64)> {
+        let frame = Frame::from(WaitAof::new(numlocal, numreplicas, timeout));
+
+        debug!(request = ?frame);
+
+        self.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Array(response) => match &response[..] {
+                [Frame::Integer(local), Frame::Integer(replicas)] => {
+                    Ok(((*local).try_into()?, (*replicas).try_into()?))
+                }
+                _ => Err("protocol error; invalid `WAITAOF` response".into()),
+            },
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    /// 返回服务器的运行信息和统计数据。
+    ///
+    /// 返回值与 Redis 的格式相同：每个部分以 `# Section` 行开头，后面是若干 `field:value` 行。
+    ///
+    /// # 示例
+    ///
+    /// ```no_run
+    /// use mini_redis::clients::Client;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = Client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     let info = client.info().await.unwrap();
+    ///     for line in info.lines().filter(|line| line.starts_with("connected_clients:")) {
+    ///         println!("{}", line);
+    ///     }
+    /// }
+    /// ```
+    #[instrument(skip(self))]
+    pub async fn info(&mut self) -> crate::Result<String> {
+        let frame = Frame::from(Info::new(None));
+
+        debug!(request = ?frame);
+
+        self.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Bulk(info) => Ok(String::from_utf8(info.to_vec())?),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    /// 返回慢日志中最新的 `count` 个条目，最新的在前面。`None` 返回所有条目。
+    ///
+    /// # 示例
+    ///
+    /// ```no_run
+    /// use mini_redis::clients::Client;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = Client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     for entry in client.slowlog_get(Some(10)).await.unwrap() {
+    ///         println!("{:?} took {:?}", entry.args, entry.duration);
+    ///     }
+    /// }
+    /// ```
+    #[instrument(skip(self))]
+    pub async fn slowlog_get(&mut self, count: Option<usize>) -> crate::Result<Vec<SlowLogEntry>> {
+        let frame = Frame::from(SlowLogCmd::get(count));
+
+        debug!(request = ?frame);
+
+        self.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Array(entries) => entries.into_iter().map(SlowLogEntry::from_frame).collect(),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    /// 返回慢日志中的条目数。
+    #[instrument(skip(self))]
+    pub async fn slowlog_len(&mut self) -> crate::Result<u64> {
+        let frame = Frame::from(SlowLogCmd::len());
+
+        debug!(request = ?frame);
+
+        self.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Integer(len) => Ok(len.try_into()?),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    /// 清空慢日志。
+    #[instrument(skip(self))]
+    pub async fn slowlog_reset(&mut self) -> crate::Result<()> {
+        let frame = Frame::from(SlowLogCmd::reset());
+
+        debug!(request = ?frame);
+
+        self.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Simple(response) if response == "OK" => Ok(()),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    /// 返回名称与 `pattern` 匹配的配置参数及其值。没有匹配的参数时返回空列表。
+    ///
+    /// # 示例
+    ///
+    /// ```no_run
+    /// use mini_redis::clients::Client;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = Client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     for (parameter, value) in client.config_get("maxmemory*").await.unwrap() {
+    ///         println!("{} = {}", parameter, value);
+    ///     }
+    /// }
+    /// ```
+    #[instrument(skip(self))]
+    pub async fn config_get(&mut self, pattern: &str) -> crate::Result<Vec<(String, String)>> {
+        let frame = Frame::from(ConfigCmd::get(pattern));
+
+        debug!(request = ?frame);
+
+        self.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            // 响应是参数名称和值交替组成的数组。
+            Frame::Array(frames) => frames
+                .chunks(2)
+                .map(|pair| match pair {
+                    [Frame::Bulk(parameter), Frame::Bulk(value)] => {
+                        Ok((String::from_utf8(parameter.to_vec())?, String::from_utf8(value.to_vec())?))
+                    }
+                    _ => Err("protocol error; invalid `CONFIG GET` response".into()),
+                })
+                .collect(),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    /// 把配置参数 `parameter` 设为 `value`，立即生效。参数未知或值无效时返回错误。
+    #[instrument(skip(self))]
+    pub async fn config_set(&mut self, parameter: &str, value: &str) -> crate::Result<()> {
+        let frame = Frame::from(ConfigCmd::set(parameter, value));
+
+        debug!(request = ?frame);
+
+        self.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Simple(response) if response == "OK" => Ok(()),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    /// 返回当前至少有一个订阅者的频道，给定 `pattern` 时只返回与其匹配的频道。
+    ///
+    /// # 示例
+    ///
+    /// ```no_run
+    /// use mini_redis::clients::Client;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = Client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     let channels = client.pubsub_channels(Some("news.*")).await.unwrap();
+    ///     println!("Got = {:?}", channels);
+    /// }
+    /// ```
+    #[instrument(skip(self))]
+    pub async fn pubsub_channels(&mut self, pattern: Option<&str>) -> crate::Result<Vec<String>> {
+        let frame = Frame::from(PubSubCmd::channels(pattern.map(str::to_string)));
+
+        debug!(request = ?frame);
+
+        self.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Array(frames) => frames
+                .into_iter()
+                .map(|frame| match frame {
+                    Frame::Bulk(channel) => Ok(String::from_utf8(channel.to_vec())?),
+                    frame => Err(frame.to_error()),
+                })
+                .collect(),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    /// 返回每个给定频道的订阅者数量，顺序与 `channels` 相同。
+    #[instrument(skip(self))]
+    pub async fn pubsub_numsub(&mut self, channels: &[String]) -> crate::Result<Vec<(String, u64)>> {
+        let frame = Frame::from(PubSubCmd::numsub(channels.to_vec()));
+
+        debug!(request = ?frame);
+
+        self.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            // 响应是频道名称和订阅者数量交替组成的数组。
+            Frame::Array(frames) => frames
+                .chunks(2)
+                .map(|pair| match pair {
+                    [Frame::Bulk(channel), Frame::Integer(count)] => {
+                        Ok((String::from_utf8(channel.to_vec())?, (*count).try_into()?))
+                    }
+                    _ => Err("protocol error; invalid `PUBSUB NUMSUB` response".into()),
+                })
+                .collect(),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    /// 订阅客户端到指定的频道。
+    ///
+    /// 一旦客户端发出订阅命令，它就不能再发出任何非 pub/sub 命令。该函数消耗 `self` 并返回一个 `Subscriber`。
+    ///
+    /// `Subscriber` 值用于接收消息以及管理客户端订阅的频道列表。
+    #[instrument(skip(self))]
+    pub async fn subscribe(mut self, channels: Vec<String>) -> crate::Result<Subscriber> {
+        // 向服务器发出订阅命令并等待确认。
+        // 然后客户端将被转换为“订阅者”状态，从那时起只能发出 pub/sub 命令。
+        self.subscribe_cmd(&channels).await?;
+
+        // 返回 `Subscriber` 类型
+        Ok(Subscriber {
+            client: self,
+            subscribed_channels: channels,
+            subscribed_patterns: vec![],
+        })
+    }
+
+    /// 订阅指定的频道，只借用 `self`。
+    ///
+    /// 与 [`subscribe`](Client::subscribe) 相同，但返回的 `Subscriber` 借用客户端。订阅结束时应调用
+    /// [`Subscriber::unsubscribe_all`] 或 [`Subscriber::reset`] 让连接回到普通模式，之后 `self` 可以继续使用。
+    ///
+    /// # 示例
+    ///
+    /// ```no_run
+    /// use mini_redis::clients::Client;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = Client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     let mut subscriber = client.subscribe_by_ref(vec!["news".into()]).await.unwrap();
+    ///     let message = subscriber.next_message().await.unwrap();
+    ///     println!("Got = {:?}", message);
+    ///     subscriber.unsubscribe_all().await.unwrap();
+    ///
+    ///     client.set("foo", "bar".into()).await.unwrap();
+    /// }
+    /// ```
+    #[instrument(skip(self))]
+    pub async fn subscribe_by_ref(&mut self, channels: Vec<String>) -> crate::Result<Subscriber<&mut Client>> {
+        self.subscribe_cmd(&channels).await?;
+
+        Ok(Subscriber {
+            client: self,
+            subscribed_channels: channels,
+            subscribed_patterns: vec![],
+        })
+    }
+
+    /// 订阅与指定 glob 模式匹配的所有频道，只借用 `self`。
+    ///
+    /// 与 [`psubscribe`](Client::psubscribe) 相同，但返回的 `Subscriber` 借用客户端，参见
+    /// [`subscribe_by_ref`](Client::subscribe_by_ref)。
+    #[instrument(skip(self))]
+    pub async fn psubscribe_by_ref(&mut self, patterns: Vec<String>) -> crate::Result<Subscriber<&mut Client>> {
+        self.psubscribe_cmd(&patterns).await?;
+
+        Ok(Subscriber {
+            client: self,
+            subscribed_channels: vec![],
+            subscribed_patterns: patterns,
+        })
+    }
+
+    /// 订阅客户端到与指定 glob 模式匹配的所有频道。
+    ///
+    /// 与 [`subscribe`](Client::subscribe) 相同，该函数消耗 `self` 并返回一个 `Subscriber`。
+    /// 通过模式收到的消息的 [`Message::pattern`] 为匹配的模式。
+    #[instrument(skip(self))]
+    pub async fn psubscribe(mut self, patterns: Vec<String>) -> crate::Result<Subscriber> {
+        self.psubscribe_cmd(&patterns).await?;
+
+        Ok(Subscriber {
+            client: self,
+            subscribed_channels: vec![],
+            subscribed_patterns: patterns,
+        })
+    }
+
+    /// 订阅指定的频道，同时保留执行其他命令的能力。
+    ///
+    /// 与 [`subscribe`](Client::subscribe) 不同，该函数不消耗 `self`：订阅之后仍然可以调用 `get`、`set`
+    /// 等方法，收到的消息通过 [`next_message`](Client::next_message) 读取。
+    ///
+    /// 这依赖 RESP3 的推送帧，服务器据此区分消息和命令的回复，因此必须先调用 `hello(Some(3))` 切换协议，
+    /// 否则返回错误。使用时需要注意：
+    ///
+    /// * 等待命令回复时到达的消息会被缓存在客户端中，直到调用 `next_message`。长时间不读取消息会让缓存一直增长。
+    /// * 订阅一直持续到连接关闭，这样的客户端不应该放回连接池。
+    #[instrument(skip(self))]
+    pub async fn subscribe_keep_alive(&mut self, channels: &[String]) -> crate::Result<()> {
+        if self.connection.protocol() != Protocol::Resp3 {
+            return Err("`subscribe_keep_alive` requires RESP3; call `hello(Some(3))` first".into());
+        }
+
+        self.subscribe_cmd(channels).await
+    }
+
+    /// 返回通过 [`subscribe_keep_alive`](Client::subscribe_keep_alive) 订阅的频道上的下一条消息，必要时等待。
+    ///
+    /// 先返回等待命令回复时已经收到的消息。`None` 表示服务器关闭了连接。
+    #[instrument(skip(self))]
+    pub async fn next_message(&mut self) -> crate::Result<Option<Message>> {
+        loop {
+            let frame = match self.pushes.pop_front() {
+                Some(frame) => frame,
+                None => match self.read_frame().await? {
+                    Some(frame) => frame,
+                    None => return Ok(None),
+                },
+            };
+            debug!(?frame);
+
+            // 服务器在订阅空闲时推送的保活 `ping` 不是消息。
+            if !is_keepalive(&frame) {
+                return Message::from_frame(frame).map(Some);
+            }
+        }
+    }
+
+    /// 核心 `SUBSCRIBE` 逻辑，由各种订阅函数使用
+    async fn subscribe_cmd(&mut self, channels: &[String]) -> crate::Result<()> {
+        // 将 `Subscribe` 命令转换为帧
+        let frame = Frame::from(Subscribe::new(channels.to_vec()));
+        self.subscribe_with(frame, "subscribe", channels, true).await?;
+        Ok(())
+    }
+
+    /// 核心 `PSUBSCRIBE` 逻辑，由各种模式订阅函数使用
+    async fn psubscribe_cmd(&mut self, patterns: &[String]) -> crate::Result<()> {
+        let frame = Frame::from(PSubscribe::new(patterns.to_vec()));
+        self.subscribe_with(frame, "psubscribe", patterns, true).await?;
+        Ok(())
+    }
+
+    /// 发送订阅请求 `frame`，并为 `channels` 中的每一项读取一个类型为 `kind` 的确认。返回最后一个确认中的订阅数。
+    ///
+    /// `ordered` 为 `true` 时每个确认必须与 `channels` 中对应位置的频道相同；为 `false` 时先读取所有确认，
+    /// 再检查确认的频道与 `channels` 是否相同，不要求顺序一致。
+    async fn subscribe_with(
+        &mut self,
+        frame: Frame,
+        kind: &str,
+        channels: &[String],
+        ordered: bool,
+    ) -> crate::Result<usize> {
+        debug!(request = ?frame);
+
+        // 将帧写入套接字
+        self.write_frame(&frame).await?;
+        // 对于每个被订阅的频道，服务器都会响应一个确认订阅该频道的消息。
+        let mut acked = Vec::with_capacity(channels.len());
+        let mut count = 0;
+        for channel in channels {
+            // 读取响应
+            let response = self.read_subscription_response().await?;
+            // 验证它是订阅确认。
+            match response {
+                Frame::Array(ref frame) | Frame::Push(ref frame) => match frame.as_slice() {
+                    // 服务器以数组帧（RESP3 连接上为推送帧）的形式响应：
+                    //
+                    // ```
+                    // [ "subscribe", channel, num-subscribed ]
+                    // ```
+                    //
+                    // 其中 channel 是频道的名称，
+                    // num-subscribed 是客户端当前订阅的频道数量。
+                    [subscribe, schannel, Frame::Integer(num)]
+                        if *subscribe == kind && (!ordered || *schannel == channel.as_str()) =>
+                    {
+                        acked.push(schannel.to_string());
+                        count = *num;
+                    }
+                    _ => return Err(response.to_error()),
+                },
+                frame => return Err(frame.to_error()),
+            };
+        }
+
+        if !ordered {
+            let mut expected = channels.to_vec();
+            expected.sort();
+            acked.sort();
+            if acked != expected {
+                let err = format!("protocol error; `{}` acknowledged {:?}, expected {:?}", kind, acked, expected);
+                return Err(err.into());
+            }
+        }
+
+        Ok(count.try_into()?)
+    }
+
+    /// 使用 `password` 对连接进行身份验证。
+    ///
+    /// 服务器配置了密码时，连接在通过验证之前只能执行 `AUTH` 和 `PING`。
+    /// 密码错误时返回错误，连接仍然可用。
+    ///
+    /// # 示例
+    ///
+    /// ```no_run
+    /// use mini_redis::clients::Client;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = Client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     client.auth("secret").await.unwrap();
+    /// }
+    /// ```
+    #[instrument(skip(self, password))]
+    pub async fn auth(&mut self, password: &str) -> crate::Result<()> {
+        let frame = Frame::from(Auth::new(password));
+
+        // 不记录请求，以免密码出现在日志中。
+        self.write_frame(&frame).await?;
+
+        match self.read_response().await? {
+            Frame::Simple(response) if response == "OK" => Ok(()),
+            frame => Err(frame.to_error()),
+        }
+    }
+
+    /// 发送由 `args` 组成的任意命令，返回服务器的原始响应帧。
+    ///
+    /// 每个参数作为一个批量字符串发送，第一个参数是命令名称。用于尝试客户端还没有对应方法的命令。
+    /// 与其他方法一样，服务器回复的错误帧被转换为 `Err`。
+    ///
+    /// # 示例
+    ///
+    /// ```no_run
+    /// use mini_redis::clients::Client;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = Client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     let response = client.raw_command(&[b"ECHO", b"hello"]).await.unwrap();
+    ///     println!("{}", response);
+    /// }
+    /// ```
+    #[instrument(skip(self))]
+    pub async fn raw_command(&mut self, args: &[&[u8]]) -> crate::Result<Frame> {
+        let mut frame = Frame::array();
+        for arg in args {
+            frame.push_bulk(Bytes::copy_from_slice(arg));
+        }
+
+        debug!(request = ?frame);
+
+        self.write_frame(&frame).await?;
+
+        self.read_response().await
+    }
+
+    /// 创建一个新的 [`Pipeline`]，用于批量发送命令。
+    ///
+    /// # 示例
+    ///
+    /// ```no_run
+    /// use mini_redis::clients::Client;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = Client::connect("localhost:6379").await.unwrap();
+    ///
+    ///     let mut pipeline = client.pipeline();
+    ///     pipeline.set("foo", "bar".into()).get("foo");
+    ///     let responses = pipeline.execute().await.unwrap();
+    ///     assert_eq!(2, responses.len());
+    /// }
+    /// ```
+    pub fn pipeline(&mut self) -> Pipeline<'_> {
+        Pipeline {
+            client: self,
+            frames: vec![],
+        }
+    }
+
+    /// 读取订阅命令的确认，跳过服务器在订阅空闲时推送的保活 `ping`。
+    ///
+    /// RESP2 连接上保活推送是普通的数组，`read_response` 无法把它与其他命令的回复区分开，
+    /// 但订阅命令的回复不会是这样的数组。
+    ///
+    /// 同时记录确认中的订阅数。
+    async fn read_subscription_response(&mut self) -> crate::Result<Frame> {
+        loop {
+            match self.read_response().await? {
+                frame if is_keepalive(&frame) => debug!("skipping keepalive"),
+                frame => {
+                    if let Frame::Array(fields) | Frame::Push(fields) = &frame {
+                        if let [kind, _, Frame::Integer(count)] = fields.as_slice() {
+                            if ["subscribe", "psubscribe", "unsubscribe", "punsubscribe"].iter().any(|k| kind == k) {
+                                self.subscriptions = (*count).try_into()?;
+                            }
+                        }
+                    }
+                    return Ok(frame);
+                }
+            }
+        }
+    }
+
+    /// 从套接字读取响应帧。
+    ///
+    /// 如果收到 `Error` 帧，则将其转换为 `Err`。
+    async fn read_response(&mut self) -> crate::Result<Frame> {
+        let response = loop {
+            match self.read_frame().await? {
+                // RESP3 连接上，消息推送可能在任何回复之前到达。把它们留给 `next_message`，继续等待回复。
+                Some(Frame::Push(push)) if push.first().is_some_and(is_message_kind) => {
+                    debug!(?push, "buffering pushed message");
+                    self.pushes.push_back(Frame::Push(push));
+                }
+                // 保活推送不需要处理。
+                Some(frame @ Frame::Push(_)) if is_keepalive(&frame) => debug!("skipping keepalive"),
+                response => break response,
+            }
+        };
 
         debug!(?response);
 
@@ -370,35 +2159,257 @@ impl Client {
             }
         }
     }
+
+    /// 如果读写连接时发生过错误，则返回 `true`。
+    ///
+    /// 服务器回复的错误帧不算在内，它们不影响连接本身。连接池用它来丢弃损坏的连接。
+    pub(crate) fn is_broken(&self) -> bool {
+        self.broken
+    }
+
+    /// 如果连接还处于订阅模式，则返回 `true`。例如借用的 [`Subscriber`] 在取消订阅之前就被丢弃。
+    pub(crate) fn is_subscribed(&self) -> bool {
+        self.subscriptions > 0
+    }
+
+    /// 将帧写入套接字。失败时将连接标记为损坏。
+    async fn write_frame(&mut self, frame: &Frame) -> crate::Result<()> {
+        let res = self.connection.write_frame(frame).await;
+        self.broken |= res.is_err();
+        Ok(res?)
+    }
+
+    /// 刷新写缓冲区。失败时将连接标记为损坏。
+    async fn flush(&mut self) -> crate::Result<()> {
+        let res = self.connection.flush().await;
+        self.broken |= res.is_err();
+        Ok(res?)
+    }
+
+    /// 从套接字读取一帧，最多等待 `timeout`。失败、超时或服务器关闭连接时将连接标记为损坏。
+    async fn read_frame(&mut self) -> crate::Result<Option<Frame>> {
+        let res = match self.timeout {
+            Some(timeout) => match time::timeout(timeout, self.connection.read_frame()).await {
+                Ok(res) => res,
+                Err(_) => Err(Error::new(ErrorKind::TimedOut, "timed out waiting for server response").into()),
+            },
+            None => self.connection.read_frame().await,
+        };
+        self.broken |= !matches!(res, Ok(Some(_)));
+        res
+    }
+}
+
+impl Message {
+    /// 从服务器推送的 `message`、`pmessage` 或 `lagged` 帧解析出 `Message`。
+    ///
+    /// `lagged` 帧说明服务器丢弃了消息，返回一个说明丢弃了多少条消息的错误。
+    fn from_frame(mframe: Frame) -> crate::Result<Self> {
+        match mframe {
+            Frame::Array(ref frame) | Frame::Push(ref frame) => match frame.as_slice() {
+                [message, channel, content] if *message == "message" => Ok(Message {
+                    channel: channel.to_string(),
+                    content: Bytes::from(content.to_string()),
+                    pattern: None,
+                }),
+                [message, pattern, channel, content] if *message == "pmessage" => Ok(Message {
+                    channel: channel.to_string(),
+                    content: Bytes::from(content.to_string()),
+                    pattern: Some(pattern.to_string()),
+                }),
+                [message, name, Frame::Integer(dropped)] if *message == "lagged" => Err(format!(
+                    "subscriber lagged behind on {}; {} messages were dropped",
+                    name, dropped
+                )
+                .into()),
+                _ => Err(mframe.to_error()),
+            },
+            frame => Err(frame.to_error()),
+        }
+    }
+}
+
+/// 如果 `kind` 是消息推送的类型，即推送的第一个元素，则返回 `true`。订阅确认等其他推送是命令的回复。
+fn is_message_kind(kind: &Frame) -> bool {
+    *kind == "message" || *kind == "pmessage" || *kind == "lagged"
+}
+
+/// 如果 `frame` 是服务器在订阅空闲时推送的保活 `ping`，则返回 `true`。
+fn is_keepalive(frame: &Frame) -> bool {
+    match frame {
+        Frame::Array(frame) | Frame::Push(frame) => matches!(frame.as_slice(), [kind] if *kind == "ping"),
+        _ => false,
+    }
+}
+
+impl SlowLogEntry {
+    /// 从 `SLOWLOG GET` 响应中的一个条目解析出 `SlowLogEntry`。
+    fn from_frame(frame: Frame) -> crate::Result<Self> {
+        let fields = match frame {
+            Frame::Array(fields) => fields,
+            frame => return Err(frame.to_error()),
+        };
+        match &fields[..] {
+            [
+                Frame::Integer(id),
+                Frame::Integer(timestamp),
+                Frame::Integer(micros),
+                Frame::Array(args),
+                Frame::Bulk(addr),
+                Frame::Bulk(name),
+            ] => Ok(Self {
+                id: (*id).try_into()?,
+                timestamp: (*timestamp).try_into()?,
+                duration: Duration::from_micros((*micros).try_into()?),
+                args: args
+                    .iter()
+                    .map(|arg| match arg {
+                        Frame::Bulk(arg) => Ok(arg.clone()),
+                        frame => Err(frame.to_error()),
+                    })
+                    .collect::<crate::Result<_>>()?,
+                addr: String::from_utf8(addr.to_vec())?,
+                name: String::from_utf8(name.to_vec())?,
+            }),
+            _ => Err("protocol error; invalid `SLOWLOG GET` entry".into()),
+        }
+    }
+}
+
+impl Pipeline<'_> {
+    /// 排队一个 `GET` 命令。
+    pub fn get(&mut self, key: &str) -> &mut Self {
+        self.frames.push(Frame::from(Get::new(key)));
+        self
+    }
+
+    /// 排队一个 `SET` 命令。
+    pub fn set(&mut self, key: &str, value: Bytes) -> &mut Self {
+        self.frames.push(Frame::from(Set::new(key, value, None)));
+        self
+    }
+
+    /// 排队一个 `DEL` 命令。
+    pub fn del(&mut self, keys: Vec<String>) -> &mut Self {
+        self.frames.push(Frame::from(Del::new(keys)));
+        self
+    }
+
+    /// 返回已排队的命令数量。
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// 如果没有排队的命令，则返回 `true`。
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// 发送所有排队的命令并按顺序收集它们的响应。
+    ///
+    /// 返回的向量中每个元素对应一个排队的命令。服务器对某个命令回复的错误帧表示为该位置上的 `Err`，
+    /// 不会影响其他命令的结果。
+    ///
+    /// 如果写入请求或读取响应失败（例如连接断开或协议错误），则返回 `Err`，
+    /// 错误信息指出是第几个命令的响应无法读取。此时连接的状态未知，不应继续使用。
+    #[instrument(skip(self))]
+    pub async fn execute(self) -> crate::Result<Vec<crate::Result<Frame>>> {
+        // 先写入所有请求帧，最后只刷新一次。
+        self.client.connection.defer_flush();
+        for frame in &self.frames {
+            debug!(request = ?frame);
+            self.client.write_frame(frame).await?;
+        }
+        self.client.flush().await?;
+
+        // 读取与排队命令数量相同的响应。
+        let mut responses = Vec::with_capacity(self.frames.len());
+        for i in 0..self.frames.len() {
+            let response = match self.client.read_frame().await {
+                Ok(Some(frame)) => frame,
+                Ok(None) => return Err(format!("pipeline command {}: connection reset by server", i).into()),
+                Err(err) => return Err(format!("pipeline command {}: {}", i, err).into()),
+            };
+
+            debug!(?response);
+
+            responses.push(match response {
+                Frame::Error(msg) => Err(msg.into()),
+                frame => Ok(frame),
+            });
+        }
+
+        Ok(responses)
+    }
+}
+
+impl ConnectOptions {
+    /// 使用默认值创建选项。
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 设置读取缓冲区的初始容量，以字节为单位。
+    pub fn read_buffer_size(mut self, size: usize) -> Self {
+        self.read_buffer_size = size;
+        self
+    }
+
+    /// 设置是否禁用 Nagle 算法（`TCP_NODELAY`）。默认为 `true`，请求不会为了等待合并而延迟发送。
+    pub fn nodelay(mut self, nodelay: bool) -> Self {
+        self.nodelay = nodelay;
+        self
+    }
+
+    /// 启用 TCP keepalive，连接空闲 `time` 之后开始探测服务器。默认不启用。
+    pub fn keepalive(mut self, time: Duration) -> Self {
+        self.keepalive = Some(time);
+        self
+    }
+}
+
+impl Default for ConnectOptions {
+    fn default() -> Self {
+        Self {
+            read_buffer_size: DEFAULT_BUFFER_CAPACITY,
+            nodelay: true,
+            keepalive: None,
+        }
+    }
 }
 
-impl Subscriber {
+impl<C: BorrowMut<Client>> Subscriber<C> {
+    /// 返回订阅的客户端。
+    fn client(&mut self) -> &mut Client {
+        self.client.borrow_mut()
+    }
+
     /// 返回当前订阅的频道集合。
     pub fn get_subscribed(&self) -> &[String] {
         &self.subscribed_channels
     }
 
+    /// 返回当前订阅的模式集合。
+    pub fn get_subscribed_patterns(&self) -> &[String] {
+        &self.subscribed_patterns
+    }
+
+    /// 返回服务器在最近一次订阅或取消订阅的确认中报告的订阅数，包括频道和模式订阅。
+    ///
+    /// 与 [`get_subscribed`](Subscriber::get_subscribed) 不同，这是服务器看到的状态，可以用来核对客户端的记录。
+    pub fn subscription_count(&self) -> u64 {
+        self.client.borrow().subscriptions
+    }
+
     /// 接收在订阅频道上发布的下一条消息，必要时等待。
     ///
     /// `None` 表示订阅已终止。
+    ///
+    /// 服务器开启了滞后通知时，如果订阅者读取得太慢、服务器丢弃了消息，则返回一个说明丢弃了多少条消息的错误。
+    /// 订阅不受影响，可以继续接收之后的消息。
     pub async fn next_message(&mut self) -> crate::Result<Option<Message>> {
-        match self.client.connection.read_frame().await? {
-            Some(mframe) => {
-                debug!(?mframe);
-
-                match mframe {
-                    Frame::Array(ref frame) => match frame.as_slice() {
-                        [message, channel, content] if *message == "message" => Ok(Some(Message {
-                            channel: channel.to_string(),
-                            content: Bytes::from(content.to_string()),
-                        })),
-                        _ => Err(mframe.to_error()),
-                    },
-                    frame => Err(frame.to_error()),
-                }
-            }
-            None => Ok(None),
-        }
+        // RESP3 连接上取消订阅时到达的消息被缓存在客户端中，`Client::next_message` 会先返回它们。
+        self.client().next_message().await
     }
 
     /// 将订阅者转换为一个 `Stream`，生成在订阅频道上发布的新消息。
@@ -421,7 +2432,7 @@ impl Subscriber {
     #[instrument(skip(self))]
     pub async fn subscribe(&mut self, channels: &[String]) -> crate::Result<()> {
         // 发出订阅命令
-        self.client.subscribe_cmd(channels).await?;
+        self.client().subscribe_cmd(channels).await?;
 
         // 更新订阅频道的集合。
         self.subscribed_channels.extend(channels.iter().map(Clone::clone));
@@ -429,6 +2440,20 @@ impl Subscriber {
         Ok(())
     }
 
+    /// 订阅一组新频道，不要求服务器按请求的顺序确认。返回订阅之后的订阅总数，包括模式订阅。
+    ///
+    /// 与 [`subscribe`](Subscriber::subscribe) 逐个按位置检查确认不同，这里先读取所有确认，再作为一个集合与
+    /// `channels` 比较。一次订阅大量频道时更宽松。
+    #[instrument(skip(self))]
+    pub async fn subscribe_unordered(&mut self, channels: &[String]) -> crate::Result<usize> {
+        let frame = Frame::from(Subscribe::new(channels.to_vec()));
+        let count = self.client().subscribe_with(frame, "subscribe", channels, false).await?;
+
+        self.subscribed_channels.extend(channels.iter().map(Clone::clone));
+
+        Ok(count)
+    }
+
     /// 取消订阅一组新频道
     #[instrument(skip(self))]
     pub async fn unsubscribe(&mut self, channels: &[String]) -> crate::Result<()> {
@@ -437,7 +2462,7 @@ impl Subscriber {
         debug!(request = ?frame);
 
         // 将帧写入套接字
-        self.client.connection.write_frame(&frame).await?;
+        self.client().write_frame(&frame).await?;
 
         // 如果输入频道列表为空，服务器确认取消订阅所有订阅的频道，
         // 因此我们断言接收到的取消订阅列表与客户端订阅的列表匹配
@@ -449,24 +2474,83 @@ impl Subscriber {
 
         // 读取响应
         for _ in 0..num {
-            let response = self.client.read_response().await?;
+            let response = self.client().read_subscription_response().await?;
 
             match response {
-                Frame::Array(ref frame) => match frame.as_slice() {
+                Frame::Array(ref frame) | Frame::Push(ref frame) => match frame.as_slice() {
                     [unsubscribe, channel, ..] if *unsubscribe == "unsubscribe" => {
                         let len = self.subscribed_channels.len();
 
-                        if len == 0 {
-                            // 必须至少有一个频道
-                            return Err(response.to_error());
+                        // 以服务器的确认为准，从订阅列表中移除该频道。
+                        self.subscribed_channels.retain(|c| *channel != &c[..]);
+
+                        // 服务器可能确认一个客户端认为并未订阅的频道（例如重复取消订阅）。
+                        // 这是无害的不一致，记录警告而不是终止订阅者。
+                        if self.subscribed_channels.len() == len {
+                            warn!(%channel, "收到未订阅频道的取消订阅确认");
                         }
+                    }
+                    _ => return Err(response.to_error()),
+                },
+                frame => return Err(frame.to_error()),
+            };
+        }
 
-                        // 取消订阅的频道此时应该存在于订阅列表中
-                        self.subscribed_channels.retain(|c| *channel != &c[..]);
+        Ok(())
+    }
+
+    /// 订阅一组新模式
+    #[instrument(skip(self))]
+    pub async fn psubscribe(&mut self, patterns: &[String]) -> crate::Result<()> {
+        self.client().psubscribe_cmd(patterns).await?;
+
+        self.subscribed_patterns.extend(patterns.iter().map(Clone::clone));
+
+        Ok(())
+    }
+
+    /// 订阅一组新模式，不要求服务器按请求的顺序确认。返回订阅之后的订阅总数，包括频道订阅。
+    ///
+    /// 参见 [`subscribe_unordered`](Subscriber::subscribe_unordered)。
+    #[instrument(skip(self))]
+    pub async fn psubscribe_unordered(&mut self, patterns: &[String]) -> crate::Result<usize> {
+        let frame = Frame::from(PSubscribe::new(patterns.to_vec()));
+        let count = self.client().subscribe_with(frame, "psubscribe", patterns, false).await?;
+
+        self.subscribed_patterns.extend(patterns.iter().map(Clone::clone));
+
+        Ok(count)
+    }
+
+    /// 取消订阅一组模式
+    ///
+    /// 与 [`unsubscribe`](Subscriber::unsubscribe) 相同，空列表表示取消订阅所有模式。
+    #[instrument(skip(self))]
+    pub async fn punsubscribe(&mut self, patterns: &[String]) -> crate::Result<()> {
+        let frame = Frame::from(PUnsubscribe::new(patterns));
+
+        debug!(request = ?frame);
 
-                        // 订阅频道列表中只应删除一个频道。
-                        if self.subscribed_channels.len() != len - 1 {
-                            return Err(response.to_error());
+        self.client().write_frame(&frame).await?;
+
+        let num = if patterns.is_empty() {
+            self.subscribed_patterns.len()
+        } else {
+            patterns.len()
+        };
+
+        for _ in 0..num {
+            let response = self.client().read_subscription_response().await?;
+
+            match response {
+                Frame::Array(ref frame) | Frame::Push(ref frame) => match frame.as_slice() {
+                    [punsubscribe, pattern, ..] if *punsubscribe == "punsubscribe" => {
+                        let len = self.subscribed_patterns.len();
+
+                        self.subscribed_patterns.retain(|p| *pattern != &p[..]);
+
+                        if self.subscribed_patterns.len() == len {
+                            warn!(%pattern, "收到未订阅模式的取消订阅确认");
                         }
                     }
                     _ => return Err(response.to_error()),
@@ -477,4 +2561,62 @@ impl Subscriber {
 
         Ok(())
     }
+
+    /// 取消所有频道和模式订阅，返回可以继续执行其他命令的 `Client`（或者它的借用）。
+    ///
+    /// 与 [`reset`](Subscriber::reset) 不同，连接选择的数据库、名称和协议等状态保持不变。
+    /// 取消订阅之前已经发布的消息会被丢弃。
+    #[instrument(skip(self))]
+    pub async fn unsubscribe_all(mut self) -> crate::Result<C> {
+        // 不带参数的取消订阅在普通模式中没有回复，因此即使第一个命令已经让服务器回到普通模式，第二个也是无害的。
+        if self.client().is_subscribed() {
+            for frame in [Frame::from(Unsubscribe::new(&[])), Frame::from(PUnsubscribe::new(&[]))] {
+                debug!(request = ?frame);
+                self.client().write_frame(&frame).await?;
+            }
+        }
+
+        // 读取确认，直到服务器报告的订阅数降为零。
+        while self.client().is_subscribed() {
+            match self.client().read_subscription_response().await? {
+                Frame::Array(frame) | Frame::Push(frame) if frame.first().is_some_and(is_message_kind) => {}
+                Frame::Array(frame) | Frame::Push(frame)
+                    if frame.first().is_some_and(|kind| *kind == "unsubscribe" || *kind == "punsubscribe") => {}
+                frame => return Err(frame.to_error()),
+            }
+        }
+
+        let client = self.client();
+        client.pushes.clear();
+        client.subscriptions = 0;
+        Ok(self.client)
+    }
+
+    /// 取消所有订阅并回到普通模式，返回可以继续执行其他命令的 `Client`（或者它的借用）。
+    ///
+    /// 发送 `RESET` 之前已经发布的消息会被丢弃。
+    #[instrument(skip(self))]
+    pub async fn reset(mut self) -> crate::Result<C> {
+        let frame = Frame::from(Reset::new());
+
+        debug!(request = ?frame);
+
+        self.client().write_frame(&frame).await?;
+
+        // 服务器处理 `RESET` 之前可能还在推送消息，跳过它们直到收到确认。
+        loop {
+            match self.client().read_response().await? {
+                Frame::Simple(response) if response == "RESET" => {
+                    // RESP3 连接上的消息已经被 `read_response` 缓存，同样丢弃。
+                    let client = self.client();
+                    client.pushes.clear();
+                    client.subscriptions = 0;
+                    return Ok(self.client);
+                }
+                Frame::Array(frame) if frame.first().is_some_and(is_message_kind) => {}
+                frame if is_keepalive(&frame) => {}
+                frame => return Err(frame.to_error()),
+            }
+        }
+    }
 }
@@ -1,8 +1,14 @@
 mod client;
-pub use client::{Client, Message, Subscriber};
+pub use client::{Client, ConnectOptions, Message, Pipeline, SlowLogEntry, Subscriber};
 
 mod blocking_client;
 pub use blocking_client::BlockingClient;
 
 mod buffered_client;
 pub use buffered_client::BufferedClient;
+
+mod pool;
+pub use pool::{Pool, PooledClient};
+
+mod reconnecting_client;
+pub use reconnecting_client::ReconnectingClient;
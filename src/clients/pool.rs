@@ -0,0 +1,131 @@
+use crate::clients::{Client, ConnectOptions};
+use crate::Result;
+
+use std::ops::{Deref, DerefMut};
+use std::sync::{Arc, Mutex};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// 一组到同一服务器的 `Client` 连接。
+///
+/// `Client` 一次只能处理一个请求，并且需要 `&mut self`，因此不能在多个任务之间共享。
+/// `Pool` 预先打开 `size` 个连接，[`Pool::get`] 借出其中一个，返回的 [`PooledClient`]
+/// 可以像 `Client` 一样使用，被丢弃时连接自动归还。借出的连接数达到 `size` 时，`get` 等待有连接归还。
+///
+/// 读写时出错的连接和还处于订阅模式的连接在归还时被丢弃，下一次 `get` 会重新拨号补上。
+///
+/// `Pool` 可以廉价地克隆，克隆共享同一组连接。
+///
+/// # 示例
+///
+/// ```no_run
+/// use mini_redis::clients::Pool;
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let pool = Pool::connect("localhost:6379", 4).await.unwrap();
+///
+///     let mut client = pool.get().await.unwrap();
+///     client.set("foo", "bar".into()).await.unwrap();
+/// }
+/// ```
+#[derive(Clone)]
+pub struct Pool {
+    shared: Arc<Shared>,
+}
+
+/// 从 `Pool` 借出的连接。解引用为 [`Client`]，被丢弃时归还给连接池。
+pub struct PooledClient {
+    /// 借出的连接。只在 `drop` 中被取出。
+    client: Option<Client>,
+
+    /// 所属的连接池。
+    shared: Arc<Shared>,
+
+    /// 借出连接的许可。连接归还后才释放，确保同时借出的连接不超过池的大小。
+    _permit: OwnedSemaphorePermit,
+}
+
+struct Shared {
+    /// 服务器地址，补充连接时使用。
+    addr: String,
+
+    /// 建立连接时使用的选项。
+    options: ConnectOptions,
+
+    /// 空闲的连接。
+    idle: Mutex<Vec<Client>>,
+
+    /// 限制同时借出的连接数。
+    permits: Arc<Semaphore>,
+}
+
+impl Pool {
+    /// 打开 `size` 个到 `addr` 的连接。任何一个连接失败都会返回错误。
+    pub async fn connect(addr: impl ToString, size: usize) -> Result<Pool> {
+        Self::connect_with(addr, size, ConnectOptions::default()).await
+    }
+
+    /// 使用给定的 `options` 打开 `size` 个到 `addr` 的连接。补充连接时也使用这些选项。
+    pub async fn connect_with(addr: impl ToString, size: usize, options: ConnectOptions) -> Result<Pool> {
+        let addr = addr.to_string();
+
+        let mut idle = Vec::with_capacity(size);
+        for _ in 0..size {
+            idle.push(Client::connect_with(addr.as_str(), options.clone()).await?);
+        }
+
+        Ok(Pool {
+            shared: Arc::new(Shared {
+                addr,
+                options,
+                idle: Mutex::new(idle),
+                permits: Arc::new(Semaphore::new(size)),
+            }),
+        })
+    }
+
+    /// 借出一个连接，必要时等待其他任务归还。
+    ///
+    /// 如果之前有损坏的连接被丢弃，这里会重新拨号补上；拨号失败时返回错误。
+    pub async fn get(&self) -> Result<PooledClient> {
+        // 信号量从不关闭，所以 `unwrap()` 是安全的。
+        let permit = self.shared.permits.clone().acquire_owned().await.unwrap();
+
+        let idle = self.shared.idle.lock().unwrap().pop();
+        let client = match idle {
+            Some(client) => client,
+            None => Client::connect_with(self.shared.addr.as_str(), self.shared.options.clone()).await?,
+        };
+
+        Ok(PooledClient {
+            client: Some(client),
+            shared: self.shared.clone(),
+            _permit: permit,
+        })
+    }
+}
+
+impl Deref for PooledClient {
+    type Target = Client;
+
+    fn deref(&self) -> &Client {
+        self.client.as_ref().unwrap()
+    }
+}
+
+impl DerefMut for PooledClient {
+    fn deref_mut(&mut self) -> &mut Client {
+        self.client.as_mut().unwrap()
+    }
+}
+
+impl Drop for PooledClient {
+    fn drop(&mut self) {
+        if let Some(client) = self.client.take() {
+            // 损坏的连接和还处于订阅模式的连接直接丢弃，下一次 `get` 会补上新连接。
+            if !client.is_broken() && !client.is_subscribed() {
+                self.shared.idle.lock().unwrap().push(client);
+            }
+        }
+    }
+}
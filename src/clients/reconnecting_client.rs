@@ -0,0 +1,222 @@
+use crate::clients::{Client, ConnectOptions};
+use crate::Result;
+
+use bytes::Bytes;
+use std::io::{Error, ErrorKind};
+use std::time::Duration;
+use tokio::time;
+
+/// 连接断开时自动重连的客户端。
+///
+/// 包装一个 [`Client`] 并记住服务器地址。当请求因连接断开而失败时，丢弃旧连接，
+/// 以指数退避重新拨号，然后重试该请求。
+///
+/// 只有幂等的命令会被自动重试：`PING`、`GET`、`SET`（包括带过期时间的）和 `DEL`。
+/// 重复执行它们不会改变最终结果。`PUBLISH` 不会被重试，因为重复发送会让订阅者收到重复的消息；
+/// 它在连接断开时返回错误，但下一次调用会重新拨号。
+///
+/// 重试次数由 [`set_max_retries`](ReconnectingClient::set_max_retries) 控制，默认为 3 次。
+pub struct ReconnectingClient {
+    /// 服务器地址，重新拨号时使用。
+    addr: String,
+
+    /// 建立连接时使用的选项。
+    options: ConnectOptions,
+
+    /// 当前连接。连接断开后为 `None`，直到下一次请求重新拨号。
+    client: Option<Client>,
+
+    /// 一次请求最多重试的次数，也是一次拨号最多重试的次数。
+    max_retries: u32,
+
+    /// 第一次重试前等待的时间。之后每次重试等待时间加倍。
+    initial_backoff: Duration,
+}
+
+impl ReconnectingClient {
+    /// 与位于 `addr` 的 Redis 服务器建立连接。
+    ///
+    /// 第一次连接失败会直接返回错误，不会重试。
+    ///
+    /// # 示例
+    ///
+    /// ```no_run
+    /// use mini_redis::clients::ReconnectingClient;
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let mut client = ReconnectingClient::connect("localhost:6379").await.unwrap();
+    ///
+    ///     client.set("foo", "bar".into()).await.unwrap();
+    /// }
+    /// ```
+    pub async fn connect(addr: impl ToString) -> Result<ReconnectingClient> {
+        Self::connect_with(addr, ConnectOptions::default()).await
+    }
+
+    /// 使用给定的 `options` 与位于 `addr` 的 Redis 服务器建立连接。重新拨号时也使用这些选项。
+    pub async fn connect_with(addr: impl ToString, options: ConnectOptions) -> Result<ReconnectingClient> {
+        let addr = addr.to_string();
+        let client = Client::connect_with(addr.as_str(), options.clone()).await?;
+
+        Ok(ReconnectingClient {
+            addr,
+            options,
+            client: Some(client),
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(100),
+        })
+    }
+
+    /// 设置一次请求最多重试的次数。`0` 表示从不重试，但下一次请求仍会重新拨号。
+    pub fn set_max_retries(&mut self, max_retries: u32) {
+        self.max_retries = max_retries;
+    }
+
+    /// 设置第一次重试前等待的时间。
+    pub fn set_initial_backoff(&mut self, backoff: Duration) {
+        self.initial_backoff = backoff;
+    }
+
+    /// 向服务器发送 Ping。连接断开时重试。
+    ///
+    /// 与 [`Client::ping`] 相同。
+    pub async fn ping(&mut self, msg: Option<Bytes>) -> Result<Bytes> {
+        let mut attempt = 0;
+        loop {
+            match self.client().await?.ping(msg.clone()).await {
+                Err(err) if self.should_retry(&err, &mut attempt) => continue,
+                res => return res,
+            }
+        }
+    }
+
+    /// 获取键的值。连接断开时重试。
+    ///
+    /// 与 [`Client::get`] 相同。
+    pub async fn get(&mut self, key: &str) -> Result<Option<Bytes>> {
+        let mut attempt = 0;
+        loop {
+            match self.client().await?.get(key).await {
+                Err(err) if self.should_retry(&err, &mut attempt) => continue,
+                res => return res,
+            }
+        }
+    }
+
+    /// 设置键的值。连接断开时重试。
+    ///
+    /// 与 [`Client::set`] 相同。
+    pub async fn set(&mut self, key: &str, value: Bytes) -> Result<()> {
+        let mut attempt = 0;
+        loop {
+            match self.client().await?.set(key, value.clone()).await {
+                Err(err) if self.should_retry(&err, &mut attempt) => continue,
+                res => return res,
+            }
+        }
+    }
+
+    /// 设置键的值和过期时间。连接断开时重试。
+    ///
+    /// 与 [`Client::set_expires`] 相同。重试时过期时间从重试的请求开始重新计算。
+    pub async fn set_expires(&mut self, key: &str, value: Bytes, expiration: Duration) -> Result<()> {
+        let mut attempt = 0;
+        loop {
+            match self.client().await?.set_expires(key, value.clone(), expiration).await {
+                Err(err) if self.should_retry(&err, &mut attempt) => continue,
+                res => return res,
+            }
+        }
+    }
+
+    /// 删除键。连接断开时重试。
+    ///
+    /// 与 [`Client::del`] 相同。
+    pub async fn del(&mut self, keys: Vec<String>) -> Result<()> {
+        let mut attempt = 0;
+        loop {
+            match self.client().await?.del(keys.clone()).await {
+                Err(err) if self.should_retry(&err, &mut attempt) => continue,
+                res => return res,
+            }
+        }
+    }
+
+    /// 向频道发布消息。**不会**重试。
+    ///
+    /// 与 [`Client::publish`] 相同。连接断开时返回错误，因为无法知道消息是否已经发布；
+    /// 下一次请求会重新拨号。
+    pub async fn publish(&mut self, channel: &str, message: Bytes) -> Result<u64> {
+        let res = self.client().await?.publish(channel, message).await;
+        if let Err(err) = &res {
+            if is_disconnect(err) {
+                self.client = None;
+            }
+        }
+        res
+    }
+
+    /// 返回当前连接，如果连接已断开则重新拨号。
+    async fn client(&mut self) -> Result<&mut Client> {
+        if self.client.is_none() {
+            let client = self.dial().await?;
+            self.client = Some(client);
+        }
+
+        Ok(self.client.as_mut().unwrap())
+    }
+
+    /// 以指数退避重新拨号。
+    ///
+    /// 与 `Server::accept` 使用相同的策略：每次失败后等待时间加倍，超过重试次数后返回最后一个错误。
+    async fn dial(&self) -> Result<Client> {
+        let mut backoff = self.initial_backoff;
+        let mut attempt = 0;
+        loop {
+            match Client::connect_with(self.addr.as_str(), self.options.clone()).await {
+                Ok(client) => return Ok(client),
+                Err(err) => {
+                    if attempt >= self.max_retries {
+                        return Err(err);
+                    }
+                }
+            }
+            // 暂停执行直到退避期结束。
+            time::sleep(backoff).await;
+            // 加倍退避时间
+            backoff *= 2;
+            attempt += 1;
+        }
+    }
+
+    /// 如果 `err` 表示连接断开，丢弃当前连接，并在重试次数未用完时返回 `true`。
+    fn should_retry(&mut self, err: &crate::Error, attempt: &mut u32) -> bool {
+        if !is_disconnect(err) {
+            return false;
+        }
+
+        self.client = None;
+
+        if *attempt >= self.max_retries {
+            return false;
+        }
+        *attempt += 1;
+        true
+    }
+}
+
+/// 如果 `err` 表示与服务器的连接已经断开，则返回 `true`。
+fn is_disconnect(err: &crate::Error) -> bool {
+    match err.downcast_ref::<Error>() {
+        Some(err) => matches!(
+            err.kind(),
+            ErrorKind::ConnectionReset
+                | ErrorKind::ConnectionAborted
+                | ErrorKind::BrokenPipe
+                | ErrorKind::NotConnected
+                | ErrorKind::UnexpectedEof
+        ),
+        None => false,
+    }
+}
@@ -0,0 +1,80 @@
+use crate::{AsyncStream, Connection, Db, Frame, Parser};
+
+use bytes::Bytes;
+use tracing::{debug, instrument};
+
+/// 将 `value` 追加到键的值末尾。
+///
+/// 如果键不存在，则先创建为空字符串，因此此时 `APPEND` 与 `SET` 相同。键原有的生存时间保持不变。
+/// 响应是追加后值的总长度。
+#[derive(Debug)]
+pub struct Append {
+    /// 要追加到的键的名称
+    key: String,
+    /// 要追加的值
+    value: Bytes,
+}
+
+impl Append {
+    /// 创建一个新的 `Append` 命令，将 `value` 追加到 `key`。
+    pub fn new(key: impl ToString, value: Bytes) -> Self {
+        Self {
+            key: key.to_string(),
+            value,
+        }
+    }
+
+    /// 获取键
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// 将 `Append` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 响应写入 `dst`。这是由服务器调用以执行接收到的命令。
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection<impl AsyncStream>) -> crate::Result<()> {
+        let response = match db.append(self.key, self.value) {
+            Ok(len) => Frame::Integer(len as i64),
+            Err(err) => Frame::Error(err.to_string()),
+        };
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+}
+
+/// 从接收到的帧中解析出一个 `Append` 实例。
+///
+/// `APPEND` 字符串已经被消费。
+///
+/// # 格式
+///
+/// ```text
+/// APPEND key value
+/// ```
+impl TryFrom<&mut Parser> for Append {
+    type Error = crate::Error;
+
+    fn try_from(parser: &mut Parser) -> crate::Result<Self> {
+        let key = parser.next_string()?;
+        let value = parser.next_bytes()?;
+
+        Ok(Self { key, value })
+    }
+}
+
+/// 将命令转换为等效的 `Frame`。
+impl From<Append> for Frame {
+    fn from(append: Append) -> Self {
+        let mut frame = Self::array();
+        frame.push_bulk(Bytes::from("append".as_bytes()));
+        frame.push_bulk(Bytes::from(append.key.into_bytes()));
+        frame.push_bulk(append.value);
+
+        frame
+    }
+}
@@ -0,0 +1,85 @@
+use crate::server::{AuthProvider, Identity};
+use crate::{Connection, Frame, Parser};
+
+use bytes::Bytes;
+use std::fmt;
+use tracing::{debug, instrument};
+
+/// 使用密码对连接进行身份验证。
+///
+/// 服务器配置了 `requirepass` 或验证器时，连接在通过验证之前只能执行 `AUTH` 和 `PING`。
+/// 密码错误时返回错误，但不关闭连接。
+pub struct Auth {
+    /// 客户端提供的密码
+    password: String,
+}
+
+impl Auth {
+    /// 创建一个新的 `Auth` 命令，使用 `password` 进行验证。
+    pub fn new(password: impl ToString) -> Self {
+        Self {
+            password: password.to_string(),
+        }
+    }
+
+    /// 将 `Auth` 命令应用于连接，`auth` 为服务器配置的验证器。
+    ///
+    /// 响应写入 `dst`。验证通过时返回连接的身份，由连接处理程序记录连接已通过验证以及它的权限。
+    #[instrument(skip(self, auth, dst))]
+    pub(crate) async fn apply(
+        self,
+        auth: Option<&dyn AuthProvider>,
+        dst: &mut Connection,
+    ) -> crate::Result<Option<Identity>> {
+        let (identity, response) = match auth {
+            None => (None, Frame::Error("ERR Client sent AUTH, but no password is set".to_string())),
+            Some(auth) => match auth.authenticate(&self.password) {
+                Some(identity) => (Some(identity), Frame::Simple("OK".to_string())),
+                None => (None, Frame::Error("WRONGPASS invalid password".to_string())),
+            },
+        };
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(identity)
+    }
+}
+
+/// 不输出密码，以免它出现在日志中。
+impl fmt::Debug for Auth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Auth").field("password", &"<redacted>").finish()
+    }
+}
+
+/// 从接收到的帧中解析出一个 `Auth` 实例。
+///
+/// `AUTH` 字符串已经被消费。
+///
+/// # 格式
+///
+/// ```text
+/// AUTH password
+/// ```
+impl TryFrom<&mut Parser> for Auth {
+    type Error = crate::Error;
+
+    fn try_from(parser: &mut Parser) -> crate::Result<Self> {
+        let password = parser.next_string()?;
+
+        Ok(Self { password })
+    }
+}
+
+/// 将命令转换为等效的 `Frame`。
+impl From<Auth> for Frame {
+    fn from(auth: Auth) -> Self {
+        let mut frame = Self::array();
+        frame.push_bulk(Bytes::from("auth".as_bytes()));
+        frame.push_bulk(Bytes::from(auth.password.into_bytes()));
+
+        frame
+    }
+}
@@ -0,0 +1,253 @@
+use crate::aof::AofWriter;
+use crate::cmd::{InvalidArgument, LPop, Parser, ParserError, RPop};
+use crate::db::ListEnd;
+use crate::{AsyncStream, Connection, Db, Frame, Shutdown};
+
+use bytes::Bytes;
+use tokio::time::{self, Duration, Instant};
+use tracing::{debug, instrument};
+
+/// 移除并返回第一个非空列表头部的元素，所有列表都为空时阻塞等待。
+///
+/// 按顺序检查 `keys`，从第一个非空列表弹出元素，回复 `[key, element]`。所有列表都为空时阻塞，
+/// 直到有客户端推入其中一个列表，或者超时。超时回复空数组。`timeout` 为 `0` 表示一直等待。
+#[derive(Debug)]
+pub struct BLPop {
+    /// 按顺序检查的列表的键
+    keys: Vec<String>,
+    /// 最长的等待时间。`None` 表示一直等待。
+    timeout: Option<Duration>,
+}
+
+impl BLPop {
+    /// 创建一个新的 `BLPop` 命令，从 `keys` 中第一个非空列表的头部弹出元素。
+    ///
+    /// `timeout` 为 `None` 时一直等待。
+    pub fn new(keys: Vec<String>, timeout: Option<Duration>) -> Self {
+        Self { keys, timeout }
+    }
+
+    /// 获取键
+    pub fn keys(&self) -> &[String] {
+        &self.keys
+    }
+
+    /// 将 `BLPop` 命令应用于指定的 `Db` 实例。弹出的元素记录到 `aof`，不需要记录时为 `None`。
+    ///
+    /// 在等待期间会监听关闭信号，收到时不写入响应直接返回。
+    #[instrument(skip(self, db, aof, dst, shutdown))]
+    pub(crate) async fn apply(
+        self,
+        db: &Db,
+        aof: Option<&AofWriter>,
+        dst: &mut Connection<impl AsyncStream>,
+        shutdown: &mut Shutdown,
+    ) -> crate::Result<()> {
+        blocking_pop(self.keys, self.timeout, ListEnd::Left, db, aof, dst, shutdown).await
+    }
+}
+
+/// 从接收到的帧中解析出一个 `BLPop` 实例。
+///
+/// `BLPOP` 字符串已经被消费。
+///
+/// # 格式
+///
+/// ```text
+/// BLPOP key [key ...] timeout
+/// ```
+///
+/// `timeout` 是以秒为单位的浮点数。
+impl TryFrom<&mut Parser> for BLPop {
+    type Error = crate::Error;
+
+    fn try_from(parser: &mut Parser) -> crate::Result<Self> {
+        let (keys, timeout) = parse_args(parser)?;
+
+        Ok(Self { keys, timeout })
+    }
+}
+
+/// 将命令转换为等效的 `Frame`。
+impl From<BLPop> for Frame {
+    fn from(cmd: BLPop) -> Self {
+        let mut frame = Self::array();
+        frame.push_bulk(Bytes::from("blpop".as_bytes()));
+        push_args(&mut frame, cmd.keys, cmd.timeout);
+
+        frame
+    }
+}
+
+/// 移除并返回第一个非空列表尾部的元素，所有列表都为空时阻塞等待。
+///
+/// 按顺序检查 `keys`，从第一个非空列表弹出元素，回复 `[key, element]`。所有列表都为空时阻塞，
+/// 直到有客户端推入其中一个列表，或者超时。超时回复空数组。`timeout` 为 `0` 表示一直等待。
+#[derive(Debug)]
+pub struct BRPop {
+    /// 按顺序检查的列表的键
+    keys: Vec<String>,
+    /// 最长的等待时间。`None` 表示一直等待。
+    timeout: Option<Duration>,
+}
+
+impl BRPop {
+    /// 创建一个新的 `BRPop` 命令，从 `keys` 中第一个非空列表的尾部弹出元素。
+    ///
+    /// `timeout` 为 `None` 时一直等待。
+    pub fn new(keys: Vec<String>, timeout: Option<Duration>) -> Self {
+        Self { keys, timeout }
+    }
+
+    /// 获取键
+    pub fn keys(&self) -> &[String] {
+        &self.keys
+    }
+
+    /// 将 `BRPop` 命令应用于指定的 `Db` 实例。弹出的元素记录到 `aof`，不需要记录时为 `None`。
+    ///
+    /// 在等待期间会监听关闭信号，收到时不写入响应直接返回。
+    #[instrument(skip(self, db, aof, dst, shutdown))]
+    pub(crate) async fn apply(
+        self,
+        db: &Db,
+        aof: Option<&AofWriter>,
+        dst: &mut Connection<impl AsyncStream>,
+        shutdown: &mut Shutdown,
+    ) -> crate::Result<()> {
+        blocking_pop(self.keys, self.timeout, ListEnd::Right, db, aof, dst, shutdown).await
+    }
+}
+
+/// 从接收到的帧中解析出一个 `BRPop` 实例。
+///
+/// `BRPOP` 字符串已经被消费。
+///
+/// # 格式
+///
+/// ```text
+/// BRPOP key [key ...] timeout
+/// ```
+///
+/// `timeout` 是以秒为单位的浮点数。
+impl TryFrom<&mut Parser> for BRPop {
+    type Error = crate::Error;
+
+    fn try_from(parser: &mut Parser) -> crate::Result<Self> {
+        let (keys, timeout) = parse_args(parser)?;
+
+        Ok(Self { keys, timeout })
+    }
+}
+
+/// 将命令转换为等效的 `Frame`。
+impl From<BRPop> for Frame {
+    fn from(cmd: BRPop) -> Self {
+        let mut frame = Self::array();
+        frame.push_bulk(Bytes::from("brpop".as_bytes()));
+        push_args(&mut frame, cmd.keys, cmd.timeout);
+
+        frame
+    }
+}
+
+/// `BLPOP` 和 `BRPOP` 的共同逻辑。
+///
+/// 每次被唤醒后都在锁内重新检查列表：同一次推入会唤醒所有等待者，但只有一个能弹出元素，其他的继续等待。
+///
+/// 开启 AOF 时每次检查都持有顺序许可，弹出之后在同一个许可下记录等效的 `LPOP key` 或 `RPOP key`，`key` 是
+/// 实际弹出的列表。其他连接的推入因此不会在日志中插到弹出和它的记录之间，重放时也不需要阻塞。等待期间不持有许可。
+async fn blocking_pop(
+    keys: Vec<String>,
+    timeout: Option<Duration>,
+    end: ListEnd,
+    db: &Db,
+    aof: Option<&AofWriter>,
+    dst: &mut Connection<impl AsyncStream>,
+    shutdown: &mut Shutdown,
+) -> crate::Result<()> {
+    let deadline = timeout.map(|timeout| Instant::now() + timeout);
+    let waiter = db.list_waiter(&keys);
+
+    let response = loop {
+        // 先开始监听再检查列表，避免错过两者之间的推入。
+        let pushed = waiter.pushed();
+
+        let order = match aof {
+            Some(aof) => Some(aof.order().await),
+            None => None,
+        };
+        match db.pop_first(&keys, end) {
+            Ok(Some((key, value))) => {
+                if let (Some(aof), Some(_order)) = (aof, order) {
+                    let logged = match end {
+                        ListEnd::Left => Frame::from(LPop::new(&key)),
+                        ListEnd::Right => Frame::from(RPop::new(&key)),
+                    };
+                    aof.append(db.index(), logged);
+                }
+                let mut response = Frame::array();
+                response.push_bulk(Bytes::from(key));
+                response.push_bulk(value);
+                break response;
+            }
+            Ok(None) => drop(order),
+            Err(err) => break Frame::Error(err.to_string()),
+        }
+
+        tokio::select! {
+            _ = pushed => {}
+            _ = sleep_until(deadline) => break Frame::NullArray,
+            _ = shutdown.recv() => return Ok(()),
+        }
+    };
+
+    debug!(?response);
+
+    dst.write_frame(&response).await?;
+
+    Ok(())
+}
+
+/// 等待直到 `deadline`。`None` 表示永远不会完成。
+async fn sleep_until(deadline: Option<Instant>) {
+    match deadline {
+        Some(deadline) => time::sleep_until(deadline).await,
+        None => std::future::pending().await,
+    }
+}
+
+/// 解析至少一个键和最后的超时时间。超时时间为 `0` 时返回 `None`。
+fn parse_args(parser: &mut Parser) -> crate::Result<(Vec<String>, Option<Duration>)> {
+    use ParserError::EndOfStream;
+
+    // 最后一个参数是超时时间，但在读完所有参数之前不知道哪个是最后一个。
+    let mut args = vec![parser.next_string()?, parser.next_string()?];
+    loop {
+        match parser.next_string() {
+            Ok(arg) => args.push(arg),
+            Err(EndOfStream) => break,
+            Err(err) => return Err(err.into()),
+        }
+    }
+
+    let timeout = args.pop().unwrap();
+    let timeout = timeout
+        .parse::<f64>()
+        .map_err(|_| InvalidArgument::new("timeout is not a float or out of range"))?;
+    if timeout < 0.0 {
+        return Err(InvalidArgument::new("timeout is negative").into());
+    }
+    let timeout = Duration::try_from_secs_f64(timeout).map_err(|_| InvalidArgument::new("timeout is out of range"))?;
+
+    Ok((args, Some(timeout).filter(|timeout| !timeout.is_zero())))
+}
+
+/// 将键和超时时间写入命令帧。
+fn push_args(frame: &mut Frame, keys: Vec<String>, timeout: Option<Duration>) {
+    for key in keys {
+        frame.push_bulk(Bytes::from(key.into_bytes()));
+    }
+    let secs = timeout.map_or(0.0, |timeout| timeout.as_secs_f64());
+    frame.push_bulk(Bytes::from(secs.to_string()));
+}
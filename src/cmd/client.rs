@@ -0,0 +1,138 @@
+use crate::cmd::InvalidArgument;
+use crate::server::ClientRegistration;
+use crate::{Connection, Frame, Parser};
+
+use bytes::Bytes;
+use tracing::{debug, instrument};
+
+/// 查看和修改当前连接的状态。
+///
+/// 命名为 `ClientCmd` 是为了避免与 [`Client`](crate::clients::Client) 冲突。
+///
+/// # 子命令
+///
+/// * SETNAME `name` -- 为连接设置名称，便于调试时区分连接。名称为空时清除名称。
+/// * GETNAME -- 返回连接的名称，未设置时返回空字符串。
+/// * ID -- 返回连接的 id。服务器为每个接受的连接分配一个唯一且单调递增的 id。
+/// * LIST -- 返回所有活动连接的描述，每行一个连接，包括 id、对等方地址和名称。
+#[derive(Debug)]
+pub struct ClientCmd {
+    /// 要执行的子命令
+    sub: ClientSubcommand,
+}
+
+#[derive(Debug)]
+enum ClientSubcommand {
+    SetName(String),
+    GetName,
+    Id,
+    List,
+}
+
+impl ClientCmd {
+    /// 创建一个新的 `CLIENT SETNAME` 命令，将连接的名称设置为 `name`。
+    pub fn set_name(name: impl ToString) -> Self {
+        Self {
+            sub: ClientSubcommand::SetName(name.to_string()),
+        }
+    }
+
+    /// 创建一个新的 `CLIENT GETNAME` 命令。
+    pub fn get_name() -> Self {
+        Self {
+            sub: ClientSubcommand::GetName,
+        }
+    }
+
+    /// 创建一个新的 `CLIENT ID` 命令。
+    pub fn id() -> Self {
+        Self {
+            sub: ClientSubcommand::Id,
+        }
+    }
+
+    /// 创建一个新的 `CLIENT LIST` 命令。
+    pub fn list() -> Self {
+        Self {
+            sub: ClientSubcommand::List,
+        }
+    }
+
+    /// 将 `CLIENT` 命令应用于连接，`client` 为连接在服务器连接登记表中的登记项。
+    ///
+    /// 响应写入 `dst`。连接的状态属于连接处理程序，因此由它调用。
+    #[instrument(skip(self, client, dst))]
+    pub(crate) async fn apply(self, client: &ClientRegistration, dst: &mut Connection) -> crate::Result<()> {
+        let response = match self.sub {
+            // 名称会出现在每行描述一个连接的输出中，因此不能包含空格、换行或其他特殊字符，与 Redis 一致。
+            ClientSubcommand::SetName(new_name) if new_name.bytes().any(|b| !(b'!'..=b'~').contains(&b)) => {
+                Frame::Error("ERR Client names cannot contain spaces, newlines or special characters.".to_string())
+            }
+            ClientSubcommand::SetName(new_name) => {
+                client.set_name(Some(new_name).filter(|name| !name.is_empty()));
+                Frame::Simple("OK".to_string())
+            }
+            ClientSubcommand::GetName => Frame::Bulk(Bytes::from(client.name().unwrap_or_default())),
+            ClientSubcommand::Id => Frame::Integer(client.id() as i64),
+            ClientSubcommand::List => Frame::Bulk(Bytes::from(client.list())),
+        };
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+}
+
+/// 从接收到的帧中解析出一个 `ClientCmd` 实例。
+///
+/// `CLIENT` 字符串已经被消费。
+///
+/// # 格式
+///
+/// ```text
+/// CLIENT SETNAME name
+/// CLIENT GETNAME
+/// CLIENT ID
+/// CLIENT LIST
+/// ```
+impl TryFrom<&mut Parser> for ClientCmd {
+    type Error = crate::Error;
+
+    fn try_from(parser: &mut Parser) -> crate::Result<Self> {
+        let sub = parser.next_string()?.to_uppercase();
+        match &sub[..] {
+            "SETNAME" => Ok(Self::set_name(parser.next_string()?)),
+            "GETNAME" => Ok(Self::get_name()),
+            "ID" => Ok(Self::id()),
+            "LIST" => Ok(Self::list()),
+            _ => Err(InvalidArgument::unknown_subcommand("client", &sub).into()),
+        }
+    }
+}
+
+/// 将命令转换为等效的 `Frame`。
+impl From<ClientCmd> for Frame {
+    fn from(cmd: ClientCmd) -> Self {
+        let mut frame = Self::array();
+        frame.push_bulk(Bytes::from("client".as_bytes()));
+        match cmd.sub {
+            ClientSubcommand::SetName(name) => {
+                frame.push_bulk(Bytes::from("setname".as_bytes()));
+                frame.push_bulk(Bytes::from(name.into_bytes()));
+            }
+            ClientSubcommand::GetName => {
+                frame.push_bulk(Bytes::from("getname".as_bytes()));
+            }
+            ClientSubcommand::Id => {
+                frame.push_bulk(Bytes::from("id".as_bytes()));
+            }
+            ClientSubcommand::List => {
+                frame.push_bulk(Bytes::from("list".as_bytes()));
+            }
+        }
+
+        frame
+    }
+}
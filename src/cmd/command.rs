@@ -0,0 +1,168 @@
+use crate::cmd::{InvalidArgument, Parser, ParserError};
+use crate::{AsyncStream, Command, Connection, Frame};
+
+use bytes::Bytes;
+use std::collections::HashSet;
+use tracing::{debug, instrument};
+
+/// 命令的类别。
+///
+/// 类别是 ACL 的基础：权限按类别授予，而不是逐个命令授予。名称与 Redis 的 `@read`、`@write` 等一致。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Category {
+    /// 只读取键空间的命令，例如 `GET`。
+    Read,
+    /// 修改键空间的命令，例如 `SET`。
+    Write,
+    /// 管理和诊断命令，例如 `COMMAND`。
+    Admin,
+    /// 发布/订阅命令。
+    PubSub,
+    /// 与具体值类型无关、作用于键本身的命令，例如 `DEL`。
+    Keyspace,
+    /// 脚本命令，例如 `EVAL`。
+    Scripting,
+    /// 事务命令，例如 `MULTI`。
+    Transaction,
+}
+
+impl Category {
+    /// 返回 Redis 风格的类别名称，例如 `@read`。
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Read => "@read",
+            Self::Write => "@write",
+            Self::Admin => "@admin",
+            Self::PubSub => "@pubsub",
+            Self::Keyspace => "@keyspace",
+            Self::Scripting => "@scripting",
+            Self::Transaction => "@transaction",
+        }
+    }
+}
+
+/// 连接可以执行的命令类别。默认允许所有类别。
+///
+/// 连接处理程序在执行命令之前检查它的所有类别，其中任何一个不被允许时回复 `NOPERM` 错误，不执行命令。
+/// `AUTH`、`PING`、`QUIT` 以及只改变连接自身状态的 `SELECT`、`HELLO`、`RESET` 和 `CLIENT` 总是被允许。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Permissions {
+    /// 允许的类别。`None` 表示允许所有类别。
+    categories: Option<HashSet<Category>>,
+}
+
+impl Permissions {
+    /// 允许所有类别。
+    pub fn all() -> Self {
+        Self::default()
+    }
+
+    /// 只允许 `categories` 中的类别。
+    pub fn only(categories: impl IntoIterator<Item = Category>) -> Self {
+        Self {
+            categories: Some(categories.into_iter().collect()),
+        }
+    }
+
+    /// 如果 `categories` 中的所有类别都被允许，则返回 `true`。
+    pub fn allows(&self, categories: &[Category]) -> bool {
+        self.categories.as_ref().is_none_or(|allowed| categories.iter().all(|category| allowed.contains(category)))
+    }
+}
+
+/// 查询命令元数据。
+///
+/// 目前只支持 `GETKEYS` 子命令：给定一条完整的命令，返回其中哪些参数是键。代理和集群客户端依赖它来路由请求。
+#[derive(Debug)]
+pub struct CommandCmd {
+    /// 要分析的命令，包括命令名称。
+    args: Vec<Bytes>,
+}
+
+impl CommandCmd {
+    /// 创建一个新的 `COMMAND GETKEYS` 命令，分析由 `args` 组成的命令。
+    pub fn getkeys(args: Vec<Bytes>) -> Self {
+        Self { args }
+    }
+
+    /// 应用 `COMMAND GETKEYS` 命令。
+    ///
+    /// 响应写入 `dst`。这是由服务器调用以执行接收到的命令。
+    #[instrument(skip(self, dst))]
+    pub(crate) async fn apply(self, dst: &mut Connection<impl AsyncStream>) -> crate::Result<()> {
+        // 像服务器接收到的请求一样解析要分析的命令，然后询问它的键。
+        let mut frame = Frame::array();
+        for arg in self.args {
+            frame.push_bulk(arg);
+        }
+
+        let response = match Command::try_from(frame) {
+            Ok(Command::Unknown(_)) | Err(_) => Frame::Error("ERR Invalid command specified".to_string()),
+            Ok(cmd) => {
+                let keys = cmd.keys();
+                if keys.is_empty() {
+                    Frame::Error("ERR The command has no key arguments".to_string())
+                } else {
+                    let mut frame = Frame::array();
+                    for key in keys {
+                        frame.push_bulk(Bytes::from(key.to_string()));
+                    }
+                    frame
+                }
+            }
+        };
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+        Ok(())
+    }
+}
+
+/// 从接收到的帧中解析出一个 `CommandCmd` 实例。
+///
+/// `COMMAND` 字符串已经被消费。
+///
+/// # 格式
+///
+/// ```text
+/// COMMAND GETKEYS command [arg ...]
+/// ```
+impl TryFrom<&mut Parser> for CommandCmd {
+    type Error = crate::Error;
+
+    fn try_from(parser: &mut Parser) -> crate::Result<Self> {
+        use ParserError::EndOfStream;
+
+        let sub = parser.next_string()?.to_uppercase();
+        if sub != "GETKEYS" {
+            return Err(InvalidArgument::unknown_subcommand("command", &sub).into());
+        }
+
+        // 剩余的所有参数组成要分析的命令，至少需要命令名称。
+        let mut args = vec![parser.next_bytes()?];
+        loop {
+            match parser.next_bytes() {
+                Ok(arg) => args.push(arg),
+                Err(EndOfStream) => break,
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        Ok(Self::getkeys(args))
+    }
+}
+
+/// 将命令转换为等效的 `Frame`。
+impl From<CommandCmd> for Frame {
+    fn from(cmd: CommandCmd) -> Self {
+        let mut frame = Self::array();
+        frame.push_bulk(Bytes::from("command".as_bytes()));
+        frame.push_bulk(Bytes::from("getkeys".as_bytes()));
+        for arg in cmd.args {
+            frame.push_bulk(arg);
+        }
+
+        frame
+    }
+}
@@ -0,0 +1,154 @@
+use crate::cmd::{InvalidArgument, Parser};
+use crate::server::{MaxMemoryPolicy, Stats};
+use crate::{glob, Connection, Db, Frame};
+
+use bytes::Bytes;
+use tracing::{debug, instrument};
+
+/// 在运行时查看和修改服务器的配置参数。
+///
+/// # 子命令
+///
+/// * GET `pattern` -- 返回名称与 `pattern` 匹配的参数。响应是参数名称和值交替组成的数组，没有匹配的参数时为空数组。
+/// * SET `parameter` `value` -- 修改参数，立即生效。参数未知或值无效时回复错误。
+///
+/// # 参数
+///
+/// * `maxmemory` -- 内存上限（字节），`0` 表示不限制。
+/// * `maxmemory-policy` -- 超过内存上限时的处理方式，`allkeys-lru` 或 `noeviction`。
+/// * `maxclients` -- 最大并发连接数。调小时已经建立的连接不会被关闭，连接数降到新的上限以下之前不接受新连接。
+///
+/// 参数名称不区分大小写。修改只保存在内存中，服务器重启后恢复为 [`ServerConfig`](crate::server::ServerConfig) 的值。
+#[derive(Debug)]
+pub struct ConfigCmd {
+    /// 要执行的子命令
+    sub: ConfigSubcommand,
+}
+
+#[derive(Debug)]
+enum ConfigSubcommand {
+    Get(String),
+    Set(String, String),
+}
+
+/// 支持的参数，`CONFIG GET` 按这个顺序返回。
+const PARAMETERS: [&str; 3] = ["maxmemory", "maxmemory-policy", "maxclients"];
+
+impl ConfigCmd {
+    /// 创建一个新的 `CONFIG GET` 命令，查询名称与 `pattern` 匹配的参数。
+    pub fn get(pattern: impl ToString) -> Self {
+        Self {
+            sub: ConfigSubcommand::Get(pattern.to_string()),
+        }
+    }
+
+    /// 创建一个新的 `CONFIG SET` 命令，把参数 `parameter` 设为 `value`。
+    pub fn set(parameter: impl ToString, value: impl ToString) -> Self {
+        Self {
+            sub: ConfigSubcommand::Set(parameter.to_string(), value.to_string()),
+        }
+    }
+
+    /// 将 `CONFIG` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 与 `INFO` 一样，最大连接数属于服务器，因此由连接处理程序传入 `stats` 并调用。
+    #[instrument(skip(self, db, stats, dst))]
+    pub(crate) async fn apply(self, db: &Db, stats: &Stats, dst: &mut Connection) -> crate::Result<()> {
+        let response = match self.sub {
+            ConfigSubcommand::Get(pattern) => {
+                let pattern = pattern.to_lowercase();
+                let mut response = Frame::array();
+                for parameter in PARAMETERS {
+                    if glob::matches(pattern.as_bytes(), parameter.as_bytes()) {
+                        response.push_bulk(Bytes::from(parameter));
+                        response.push_bulk(Bytes::from(get(parameter, db, stats)));
+                    }
+                }
+                response
+            }
+            ConfigSubcommand::Set(parameter, value) => match set(&parameter, &value, db, stats) {
+                Ok(()) => Frame::Simple("OK".to_string()),
+                Err(msg) => Frame::Error(msg),
+            },
+        };
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+}
+
+/// 返回参数 `parameter` 的当前值。`parameter` 是 [`PARAMETERS`] 中的一个。
+fn get(parameter: &str, db: &Db, stats: &Stats) -> String {
+    match parameter {
+        "maxmemory" => db.maxmemory().unwrap_or(0).to_string(),
+        "maxmemory-policy" => db.maxmemory_policy().as_str().to_string(),
+        _ => stats.max_connections().to_string(),
+    }
+}
+
+/// 把参数 `parameter` 设为 `value`。参数未知或值无效时返回错误消息，不做任何修改。
+fn set(parameter: &str, value: &str, db: &Db, stats: &Stats) -> Result<(), String> {
+    let invalid = || format!("ERR Invalid argument '{}' for CONFIG SET '{}'", value, parameter);
+
+    match &parameter.to_lowercase()[..] {
+        "maxmemory" => {
+            let maxmemory: usize = value.parse().map_err(|_| invalid())?;
+            db.set_maxmemory(Some(maxmemory).filter(|&maxmemory| maxmemory > 0));
+        }
+        "maxmemory-policy" => db.set_maxmemory_policy(value.parse::<MaxMemoryPolicy>().map_err(|_| invalid())?),
+        "maxclients" => match value.parse() {
+            Ok(max_connections) if max_connections > 0 => stats.set_max_connections(max_connections),
+            _ => return Err(invalid()),
+        },
+        _ => return Err(format!("ERR Unknown option or number of arguments for CONFIG SET - '{}'", parameter)),
+    }
+
+    Ok(())
+}
+
+/// 从接收到的帧中解析出一个 `ConfigCmd` 实例。
+///
+/// `CONFIG` 字符串已经被消费。
+///
+/// # 格式
+///
+/// ```text
+/// CONFIG GET pattern
+/// CONFIG SET parameter value
+/// ```
+impl TryFrom<&mut Parser> for ConfigCmd {
+    type Error = crate::Error;
+
+    fn try_from(parser: &mut Parser) -> crate::Result<Self> {
+        let sub = parser.next_string()?.to_uppercase();
+        match &sub[..] {
+            "GET" => Ok(Self::get(parser.next_string()?)),
+            "SET" => Ok(Self::set(parser.next_string()?, parser.next_string()?)),
+            _ => Err(InvalidArgument::unknown_subcommand("config", &sub).into()),
+        }
+    }
+}
+
+/// 将命令转换为等效的 `Frame`。
+impl From<ConfigCmd> for Frame {
+    fn from(cmd: ConfigCmd) -> Self {
+        let mut frame = Self::array();
+        frame.push_bulk(Bytes::from("config".as_bytes()));
+        match cmd.sub {
+            ConfigSubcommand::Get(pattern) => {
+                frame.push_bulk(Bytes::from("get".as_bytes()));
+                frame.push_bulk(Bytes::from(pattern.into_bytes()));
+            }
+            ConfigSubcommand::Set(parameter, value) => {
+                frame.push_bulk(Bytes::from("set".as_bytes()));
+                frame.push_bulk(Bytes::from(parameter.into_bytes()));
+                frame.push_bulk(Bytes::from(value.into_bytes()));
+            }
+        }
+
+        frame
+    }
+}
@@ -0,0 +1,152 @@
+use crate::cmd::{InvalidArgument, Parser, ParserError, SyntaxError};
+use crate::{AsyncStream, Connection, Db, Frame};
+
+use bytes::Bytes;
+use tracing::{debug, instrument};
+
+/// 将 `source` 的值复制到 `destination`。
+///
+/// 键的剩余生存时间随值一起复制；没有生存时间的键复制出的目标键也没有生存时间。
+/// 如果 `destination` 已经存在且没有给出 `REPLACE`，则不做任何修改并返回 0，复制成功返回 1。
+/// `source` 不存在时同样返回 0。给出 `DB` 时 `destination` 写入编号为 `db` 的数据库，而不是当前数据库。
+#[derive(Debug)]
+pub struct Copy {
+    /// 要复制的键
+    source: String,
+
+    /// 目标键
+    destination: String,
+
+    /// 是否覆盖已经存在的目标键
+    replace: bool,
+
+    /// 目标数据库的编号，`None` 表示当前数据库
+    db: Option<usize>,
+}
+
+impl Copy {
+    /// 创建一个新的 `COPY` 命令，将 `source` 复制到 `destination`。
+    ///
+    /// `replace` 为 `true` 时覆盖已经存在的 `destination`。
+    pub fn new(source: impl ToString, destination: impl ToString, replace: bool) -> Self {
+        Self {
+            source: source.to_string(),
+            destination: destination.to_string(),
+            replace,
+            db: None,
+        }
+    }
+
+    /// 将 `destination` 写入编号为 `db` 的数据库（`DB`）。
+    pub fn with_db(mut self, db: usize) -> Self {
+        self.db = Some(db);
+        self
+    }
+
+    /// 获取要复制的键
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// 获取目标键
+    pub fn destination(&self) -> &str {
+        &self.destination
+    }
+
+    /// 如果会覆盖已经存在的目标键，则返回 `true`。
+    pub fn is_replace(&self) -> bool {
+        self.replace
+    }
+
+    /// 获取目标数据库的编号，没有给出 `DB` 时返回 `None`
+    pub fn db(&self) -> Option<usize> {
+        self.db
+    }
+
+    /// 将 `Copy` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 响应写入 `dst`。这是由服务器调用以执行接收到的命令。
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection<impl AsyncStream>) -> crate::Result<()> {
+        let target = match self.db {
+            Some(index) => db.select(index),
+            None => Some(db.clone()),
+        };
+
+        // 与 Redis 一致，复制到自身是错误，而不是什么都不做
+        let response = match target {
+            Some(target) if target.index() == db.index() && self.source == self.destination => {
+                Frame::Error("ERR source and destination objects are the same".to_string())
+            }
+            Some(target) => {
+                let copied = db.copy(&self.source, &target, self.destination, self.replace);
+                Frame::Integer(copied as i64)
+            }
+            None => Frame::Error("ERR DB index is out of range".to_string()),
+        };
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+}
+
+/// 从接收到的帧中解析出一个 `Copy` 实例。
+///
+/// `COPY` 字符串已经被消费。
+///
+/// # 格式
+///
+/// ```text
+/// COPY source destination [DB destination-db] [REPLACE]
+/// ```
+///
+/// 选项的顺序无关紧要。
+impl TryFrom<&mut Parser> for Copy {
+    type Error = crate::Error;
+
+    fn try_from(parser: &mut Parser) -> crate::Result<Self> {
+        use ParserError::EndOfStream;
+
+        let source = parser.next_string()?;
+        let destination = parser.next_string()?;
+        let mut copy = Self::new(source, destination, false);
+        loop {
+            match parser.next_string() {
+                Ok(s) if s.to_uppercase() == "REPLACE" && !copy.replace => copy.replace = true,
+                Ok(s) if s.to_uppercase() == "DB" && copy.db.is_none() => {
+                    let db = parser.next_int()?;
+                    let db = usize::try_from(db).map_err(|_| InvalidArgument::new("DB index is out of range"))?;
+                    copy.db = Some(db);
+                }
+                // 未知或重复的选项只回复 `ERR syntax error`，连接继续正常运行
+                Ok(_) => return Err(SyntaxError.into()),
+                Err(EndOfStream) => break,
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        Ok(copy)
+    }
+}
+
+/// 将命令转换为等效的 `Frame`。
+impl From<Copy> for Frame {
+    fn from(copy: Copy) -> Self {
+        let mut frame = Self::array();
+        frame.push_bulk(Bytes::from("copy".as_bytes()));
+        frame.push_bulk(Bytes::from(copy.source.into_bytes()));
+        frame.push_bulk(Bytes::from(copy.destination.into_bytes()));
+        if let Some(db) = copy.db {
+            frame.push_bulk(Bytes::from("db".as_bytes()));
+            frame.push_bulk(Bytes::from(db.to_string()));
+        }
+        if copy.replace {
+            frame.push_bulk(Bytes::from("replace".as_bytes()));
+        }
+
+        frame
+    }
+}
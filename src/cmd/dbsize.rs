@@ -0,0 +1,57 @@
+use crate::cmd::Parser;
+use crate::{AsyncStream, Connection, Db, Frame};
+
+use bytes::Bytes;
+use tracing::{debug, instrument};
+
+/// 返回当前选择的数据库中未过期的键数。
+#[derive(Debug, Default)]
+pub struct DbSize;
+
+impl DbSize {
+    /// 创建一个新的 `DbSize` 命令。
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// 将 `DbSize` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 响应写入 `dst`。这是由服务器调用以执行接收到的命令。
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection<impl AsyncStream>) -> crate::Result<()> {
+        let response = Frame::Integer(db.len() as i64);
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+}
+
+/// 从接收到的帧中解析出一个 `DbSize` 实例。
+///
+/// `DBSIZE` 字符串已经被消费。
+///
+/// # 格式
+///
+/// ```text
+/// DBSIZE
+/// ```
+impl TryFrom<&mut Parser> for DbSize {
+    type Error = crate::Error;
+
+    fn try_from(_parser: &mut Parser) -> crate::Result<Self> {
+        Ok(Self)
+    }
+}
+
+/// 将命令转换为等效的 `Frame`。
+impl From<DbSize> for Frame {
+    fn from(_: DbSize) -> Self {
+        let mut frame = Self::array();
+        frame.push_bulk(Bytes::from("dbsize".as_bytes()));
+
+        frame
+    }
+}
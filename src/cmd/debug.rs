@@ -0,0 +1,187 @@
+use crate::cmd::{InvalidArgument, Parser};
+use crate::{AsyncStream, Connection, Db, Frame};
+
+use bytes::Bytes;
+use std::time::Duration;
+use tokio::time;
+use tracing::{debug, instrument};
+
+/// 用于测试和性能调优的 `DEBUG` 命令。
+///
+/// # 子命令
+///
+/// * RESERVE `n` -- 为至少 `n` 个新键预留空间，避免批量加载时反复扩容。
+/// * CAPACITY -- 返回键空间在不扩容的情况下能容纳的键数。
+/// * SLEEP `ms` -- 让连接暂停 `ms` 毫秒之后再回复，用于测试超时和慢命令。
+/// * SET-ACTIVE-EXPIRE `0|1` -- 禁用或启用后台对过期键的主动清理，用于测试访问时的过期处理。
+/// * OBJECT `key` -- 返回键的值的内部信息，例如 `refcount:1 encoding:embstr serializedlength:5`。
+///   格式与 Redis 相同，由空格分隔的 `field:value` 组成，只是没有 Redis 的内存地址和 LRU 字段。
+///   `serializedlength` 是值的字节数，容器类型是所有元素的字节数之和。键不存在时回复错误。
+///
+/// SLEEP 和 SET-ACTIVE-EXPIRE 只用于测试，只有在服务器配置了
+/// [`ServerConfig::debug_hooks`](crate::server::ServerConfig::debug_hooks) 时才能执行。
+#[derive(Debug)]
+pub struct DebugCmd {
+    /// 要执行的子命令
+    sub: DebugSubcommand,
+}
+
+#[derive(Debug)]
+enum DebugSubcommand {
+    Reserve(usize),
+    Capacity,
+    Sleep(Duration),
+    SetActiveExpire(bool),
+    Object(String),
+}
+
+impl DebugCmd {
+    /// 创建一个新的 `DEBUG RESERVE` 命令，为至少 `additional` 个新键预留空间。
+    pub fn reserve(additional: usize) -> Self {
+        Self {
+            sub: DebugSubcommand::Reserve(additional),
+        }
+    }
+
+    /// 创建一个新的 `DEBUG CAPACITY` 命令。
+    pub fn capacity() -> Self {
+        Self {
+            sub: DebugSubcommand::Capacity,
+        }
+    }
+
+    /// 创建一个新的 `DEBUG SLEEP` 命令，让连接暂停 `duration` 之后再回复。
+    pub fn sleep(duration: Duration) -> Self {
+        Self {
+            sub: DebugSubcommand::Sleep(duration),
+        }
+    }
+
+    /// 创建一个新的 `DEBUG SET-ACTIVE-EXPIRE` 命令，`enabled` 为 `false` 时禁用后台对过期键的主动清理。
+    pub fn set_active_expire(enabled: bool) -> Self {
+        Self {
+            sub: DebugSubcommand::SetActiveExpire(enabled),
+        }
+    }
+
+    /// 创建一个新的 `DEBUG OBJECT` 命令，查看 `key` 的值的内部信息。
+    pub fn object(key: impl ToString) -> Self {
+        Self {
+            sub: DebugSubcommand::Object(key.to_string()),
+        }
+    }
+
+    /// 获取 `DEBUG OBJECT` 的键。其他子命令没有键。
+    pub fn key(&self) -> Option<&str> {
+        match &self.sub {
+            DebugSubcommand::Object(key) => Some(key),
+            _ => None,
+        }
+    }
+
+    /// 将 `DEBUG` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 响应写入 `dst`。这是由服务器调用以执行接收到的命令。
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection<impl AsyncStream>) -> crate::Result<()> {
+        let response = match self.sub {
+            DebugSubcommand::Reserve(additional) => {
+                db.reserve(additional);
+                Frame::Simple("OK".to_string())
+            }
+            DebugSubcommand::Capacity => Frame::Integer(db.capacity() as i64),
+            DebugSubcommand::Sleep(_) | DebugSubcommand::SetActiveExpire(_) if !db.debug_hooks() => {
+                Frame::Error("ERR DEBUG test hooks are disabled; enable `debug_hooks` in the server config".to_string())
+            }
+            DebugSubcommand::Sleep(duration) => {
+                time::sleep(duration).await;
+                Frame::Simple("OK".to_string())
+            }
+            DebugSubcommand::SetActiveExpire(enabled) => {
+                db.set_active_expire(enabled);
+                Frame::Simple("OK".to_string())
+            }
+            DebugSubcommand::Object(key) => match db.debug_object(&key) {
+                Some((encoding, len)) => {
+                    Frame::Simple(format!("refcount:1 encoding:{} serializedlength:{}", encoding, len))
+                }
+                None => Frame::Error("ERR no such key".to_string()),
+            },
+        };
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+}
+
+/// 从接收到的帧中解析出一个 `DebugCmd` 实例。
+///
+/// `DEBUG` 字符串已经被消费。
+///
+/// # 格式
+///
+/// ```text
+/// DEBUG RESERVE n
+/// DEBUG CAPACITY
+/// DEBUG SLEEP ms
+/// DEBUG SET-ACTIVE-EXPIRE 0|1
+/// DEBUG OBJECT key
+/// ```
+impl TryFrom<&mut Parser> for DebugCmd {
+    type Error = crate::Error;
+
+    fn try_from(parser: &mut Parser) -> crate::Result<Self> {
+        let sub = parser.next_string()?.to_uppercase();
+        match &sub[..] {
+            "RESERVE" => {
+                let additional = parser.next_int()?;
+                let additional = usize::try_from(additional).map_err(|_| InvalidArgument::new("invalid count"))?;
+                Ok(Self::reserve(additional))
+            }
+            "CAPACITY" => Ok(Self::capacity()),
+            "SLEEP" => {
+                let ms = u64::try_from(parser.next_int()?).map_err(|_| InvalidArgument::new("invalid time"))?;
+                Ok(Self::sleep(Duration::from_millis(ms)))
+            }
+            "SET-ACTIVE-EXPIRE" => match parser.next_int()? {
+                0 => Ok(Self::set_active_expire(false)),
+                1 => Ok(Self::set_active_expire(true)),
+                _ => Err(InvalidArgument::new("DEBUG SET-ACTIVE-EXPIRE expects 0 or 1").into()),
+            },
+            "OBJECT" => Ok(Self::object(parser.next_string()?)),
+            _ => Err(InvalidArgument::unknown_subcommand("debug", &sub).into()),
+        }
+    }
+}
+
+/// 将命令转换为等效的 `Frame`。
+impl From<DebugCmd> for Frame {
+    fn from(cmd: DebugCmd) -> Self {
+        let mut frame = Self::array();
+        frame.push_bulk(Bytes::from("debug".as_bytes()));
+        match cmd.sub {
+            DebugSubcommand::Reserve(additional) => {
+                frame.push_bulk(Bytes::from("reserve".as_bytes()));
+                frame.push_int(additional as i64);
+            }
+            DebugSubcommand::Capacity => frame.push_bulk(Bytes::from("capacity".as_bytes())),
+            DebugSubcommand::Sleep(duration) => {
+                frame.push_bulk(Bytes::from("sleep".as_bytes()));
+                frame.push_int(duration.as_millis() as i64);
+            }
+            DebugSubcommand::SetActiveExpire(enabled) => {
+                frame.push_bulk(Bytes::from("set-active-expire".as_bytes()));
+                frame.push_int(enabled as i64);
+            }
+            DebugSubcommand::Object(key) => {
+                frame.push_bulk(Bytes::from("object".as_bytes()));
+                frame.push_bulk(Bytes::from(key.into_bytes()));
+            }
+        }
+
+        frame
+    }
+}
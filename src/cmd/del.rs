@@ -1,5 +1,5 @@
 use crate::cmd::{Parser, ParserError};
-use crate::{Connection, Db, Frame};
+use crate::{AsyncStream, Connection, Db, Frame};
 
 use bytes::Bytes;
 use tracing::{debug, instrument};
@@ -29,11 +29,16 @@ impl Del {
         Self { keys: key }
     }
 
+    /// 获取要删除的键
+    pub fn keys(&self) -> &[String] {
+        &self.keys
+    }
+
     /// 将 `Set` 命令应用于指定的 `Db` 实例。
     ///
     /// 响应写入 `dst`。这是由服务器调用以执行接收到的命令。
     #[instrument(skip(self, db, dst))]
-    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<()> {
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection<impl AsyncStream>) -> crate::Result<()> {
         // 在共享数据库状态中设置值。
         db.del(self.keys);
 
@@ -0,0 +1,183 @@
+use crate::cmd::Parser;
+use crate::{AsyncStream, Connection, Db, Frame};
+
+use bytes::Bytes;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::time::{Duration, Instant};
+use tracing::{debug, instrument};
+
+/// 设置键在给定的 Unix 时间（秒）过期。
+///
+/// 键原来的生存时间被替换。如果时间已经过去，键被立即删除。
+/// 设置成功（包括立即删除）返回 1，键不存在返回 0。
+#[derive(Debug)]
+pub struct ExpireAt {
+    /// 要设置过期时间的键
+    key: String,
+    /// 过期的 Unix 时间，以秒为单位
+    timestamp: i64,
+}
+
+impl ExpireAt {
+    /// 创建一个新的 `ExpireAt` 命令，`key` 在 Unix 时间 `timestamp`（秒）过期。
+    pub fn new(key: impl ToString, timestamp: i64) -> Self {
+        Self {
+            key: key.to_string(),
+            timestamp,
+        }
+    }
+
+    /// 获取键
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// 将 `ExpireAt` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 响应写入 `dst`。这是由服务器调用以执行接收到的命令。
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection<impl AsyncStream>) -> crate::Result<()> {
+        let when = u64::try_from(self.timestamp)
+            .unwrap_or(0)
+            .checked_mul(1000)
+            .and_then(|millis| instant_at(Duration::from_millis(millis)));
+        let response = expire_at(db, &self.key, when, "expireat");
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+}
+
+/// 从接收到的帧中解析出一个 `ExpireAt` 实例。
+///
+/// `EXPIREAT` 字符串已经被消费。
+///
+/// # 格式
+///
+/// ```text
+/// EXPIREAT key unix-time-seconds
+/// ```
+impl TryFrom<&mut Parser> for ExpireAt {
+    type Error = crate::Error;
+
+    fn try_from(parser: &mut Parser) -> crate::Result<Self> {
+        let key = parser.next_string()?;
+        let timestamp = parser.next_int()?;
+
+        Ok(Self { key, timestamp })
+    }
+}
+
+/// 将命令转换为等效的 `Frame`。
+impl From<ExpireAt> for Frame {
+    fn from(cmd: ExpireAt) -> Self {
+        let mut frame = Self::array();
+        frame.push_bulk(Bytes::from("expireat".as_bytes()));
+        frame.push_bulk(Bytes::from(cmd.key.into_bytes()));
+        frame.push_bulk(Bytes::from(cmd.timestamp.to_string()));
+
+        frame
+    }
+}
+
+/// 设置键在给定的 Unix 时间（毫秒）过期。
+///
+/// 与 [`ExpireAt`] 相同，只是时间以毫秒为单位。
+#[derive(Debug)]
+pub struct PExpireAt {
+    /// 要设置过期时间的键
+    key: String,
+    /// 过期的 Unix 时间，以毫秒为单位
+    timestamp: i64,
+}
+
+impl PExpireAt {
+    /// 创建一个新的 `PExpireAt` 命令，`key` 在 Unix 时间 `timestamp`（毫秒）过期。
+    pub fn new(key: impl ToString, timestamp: i64) -> Self {
+        Self {
+            key: key.to_string(),
+            timestamp,
+        }
+    }
+
+    /// 获取键
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// 将 `PExpireAt` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 响应写入 `dst`。这是由服务器调用以执行接收到的命令。
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection<impl AsyncStream>) -> crate::Result<()> {
+        let millis = u64::try_from(self.timestamp).unwrap_or(0);
+        let when = instant_at(Duration::from_millis(millis));
+        let response = expire_at(db, &self.key, when, "pexpireat");
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+}
+
+/// 从接收到的帧中解析出一个 `PExpireAt` 实例。
+///
+/// `PEXPIREAT` 字符串已经被消费。
+///
+/// # 格式
+///
+/// ```text
+/// PEXPIREAT key unix-time-milliseconds
+/// ```
+impl TryFrom<&mut Parser> for PExpireAt {
+    type Error = crate::Error;
+
+    fn try_from(parser: &mut Parser) -> crate::Result<Self> {
+        let key = parser.next_string()?;
+        let timestamp = parser.next_int()?;
+
+        Ok(Self { key, timestamp })
+    }
+}
+
+/// 将命令转换为等效的 `Frame`。
+impl From<PExpireAt> for Frame {
+    fn from(cmd: PExpireAt) -> Self {
+        let mut frame = Self::array();
+        frame.push_bulk(Bytes::from("pexpireat".as_bytes()));
+        frame.push_bulk(Bytes::from(cmd.key.into_bytes()));
+        frame.push_bulk(Bytes::from(cmd.timestamp.to_string()));
+
+        frame
+    }
+}
+
+/// 将距 Unix 纪元 `since_epoch` 的时间转换为 `Instant`。
+///
+/// `Db` 使用单调时钟，因此按当前的系统时间换算。已经过去的时间转换为当前时间；时间太大无法表示时返回 `None`。
+pub(super) fn instant_at(since_epoch: Duration) -> Option<Instant> {
+    let now = Instant::now();
+    match UNIX_EPOCH.checked_add(since_epoch)?.duration_since(SystemTime::now()) {
+        Ok(remaining) => now.checked_add(remaining),
+        Err(_) => Some(now),
+    }
+}
+
+/// 返回从现在起经过 `duration` 之后的 Unix 时间，单位是毫秒。时间太大无法表示时返回 `None`。
+pub(super) fn unix_millis_after(duration: Duration) -> Option<u64> {
+    let since_epoch = SystemTime::now().checked_add(duration)?.duration_since(UNIX_EPOCH).ok()?;
+    u64::try_from(since_epoch.as_millis()).ok()
+}
+
+/// 将 `key` 的过期时间设置为 `when`，返回响应帧。`when` 为 `None` 表示时间戳无效。
+fn expire_at(db: &Db, key: &str, when: Option<Instant>, name: &str) -> Frame {
+    match when {
+        Some(when) => Frame::Integer(db.expire_at(key, when) as i64),
+        None => Frame::Error(format!("ERR invalid expire time in '{}' command", name)),
+    }
+}
@@ -0,0 +1,117 @@
+use crate::cmd::Parser;
+use crate::{AsyncStream, Connection, Db, Frame};
+
+use bytes::Bytes;
+use tracing::{debug, instrument};
+
+/// 删除当前选择的数据库中的所有键，其他数据库不受影响。
+///
+/// 频道和订阅不受影响。
+#[derive(Debug, Default)]
+pub struct FlushDb;
+
+/// 删除所有数据库中的所有键。
+///
+/// 频道和订阅不受影响。
+#[derive(Debug, Default)]
+pub struct FlushAll;
+
+impl FlushDb {
+    /// 创建一个新的 `FlushDb` 命令。
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// 将 `FlushDb` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 响应写入 `dst`。这是由服务器调用以执行接收到的命令。
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection<impl AsyncStream>) -> crate::Result<()> {
+        db.flush();
+
+        let response = Frame::Simple("OK".to_string());
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+}
+
+/// 从接收到的帧中解析出一个 `FlushDb` 实例。
+///
+/// `FLUSHDB` 字符串已经被消费。
+///
+/// # 格式
+///
+/// ```text
+/// FLUSHDB
+/// ```
+impl TryFrom<&mut Parser> for FlushDb {
+    type Error = crate::Error;
+
+    fn try_from(_parser: &mut Parser) -> crate::Result<Self> {
+        Ok(Self)
+    }
+}
+
+/// 将命令转换为等效的 `Frame`。
+impl From<FlushDb> for Frame {
+    fn from(_: FlushDb) -> Self {
+        let mut frame = Self::array();
+        frame.push_bulk(Bytes::from("flushdb".as_bytes()));
+
+        frame
+    }
+}
+
+impl FlushAll {
+    /// 创建一个新的 `FlushAll` 命令。
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// 将 `FlushAll` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 响应写入 `dst`。这是由服务器调用以执行接收到的命令。
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection<impl AsyncStream>) -> crate::Result<()> {
+        db.flush_all();
+
+        let response = Frame::Simple("OK".to_string());
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+}
+
+/// 从接收到的帧中解析出一个 `FlushAll` 实例。
+///
+/// `FLUSHALL` 字符串已经被消费。
+///
+/// # 格式
+///
+/// ```text
+/// FLUSHALL
+/// ```
+impl TryFrom<&mut Parser> for FlushAll {
+    type Error = crate::Error;
+
+    fn try_from(_parser: &mut Parser) -> crate::Result<Self> {
+        Ok(Self)
+    }
+}
+
+/// 将命令转换为等效的 `Frame`。
+impl From<FlushAll> for Frame {
+    fn from(_: FlushAll) -> Self {
+        let mut frame = Self::array();
+        frame.push_bulk(Bytes::from("flushall".as_bytes()));
+
+        frame
+    }
+}
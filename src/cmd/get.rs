@@ -1,4 +1,4 @@
-use crate::{Connection, Db, Frame, Parser};
+use crate::{AsyncStream, Connection, Db, Frame, Parser};
 
 use bytes::Bytes;
 use tracing::{debug, instrument};
@@ -18,18 +18,24 @@ impl Get {
         Self { key: key.to_string() }
     }
 
+    /// 获取键
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
     /// 将 `Get` 命令应用于指定的 `Db` 实例。
     ///
     /// 响应写入 `dst`。这是由服务器调用以执行接收到的命令。
     #[instrument(skip(self, db, dst))]
-    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<()> {
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection<impl AsyncStream>) -> crate::Result<()> {
         // 从共享数据库状态中获取值
-        let response = if let Some(value) = db.get(&self.key) {
+        let response = match db.get(&self.key) {
             // 如果存在值，则以“bulk”格式写入客户端。
-            Frame::Bulk(value)
-        } else {
+            Ok(Some(value)) => Frame::Bulk(value),
             // 如果没有值，则写入 `Null`。
-            Frame::Null
+            Ok(None) => Frame::Null,
+            // 键保存的不是字符串。
+            Err(err) => Frame::Error(err.to_string()),
         };
 
         debug!(?response);
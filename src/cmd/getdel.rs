@@ -0,0 +1,74 @@
+use crate::{AsyncStream, Connection, Db, Frame, Parser};
+
+use bytes::Bytes;
+use tracing::{debug, instrument};
+
+/// 获取键的值并删除该键。
+///
+/// 读取和删除在同一个锁内完成，因此同一个值只会被一个客户端取走。适用于一次性令牌和简单的工作队列。
+/// 如果键不存在，则返回特殊值 nil。
+#[derive(Debug)]
+pub struct GetDel {
+    /// 要获取并删除的键的名称
+    key: String,
+}
+
+impl GetDel {
+    /// 创建一个新的 `GetDel` 命令以获取并删除 `key`。
+    pub fn new(key: impl ToString) -> Self {
+        Self { key: key.to_string() }
+    }
+
+    /// 获取键
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// 将 `GetDel` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 响应写入 `dst`。这是由服务器调用以执行接收到的命令。
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection<impl AsyncStream>) -> crate::Result<()> {
+        let response = match db.get_del(&self.key) {
+            Ok(Some(value)) => Frame::Bulk(value),
+            Ok(None) => Frame::Null,
+            Err(err) => Frame::Error(err.to_string()),
+        };
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+}
+
+/// 从接收到的帧中解析出一个 `GetDel` 实例。
+///
+/// `GETDEL` 字符串已经被消费。
+///
+/// # 格式
+///
+/// ```text
+/// GETDEL key
+/// ```
+impl TryFrom<&mut Parser> for GetDel {
+    type Error = crate::Error;
+
+    fn try_from(parser: &mut Parser) -> crate::Result<Self> {
+        let key = parser.next_string()?;
+
+        Ok(Self { key })
+    }
+}
+
+/// 将命令转换为等效的 `Frame`。
+impl From<GetDel> for Frame {
+    fn from(get_del: GetDel) -> Self {
+        let mut frame = Self::array();
+        frame.push_bulk(Bytes::from("getdel".as_bytes()));
+        frame.push_bulk(Bytes::from(get_del.key.into_bytes()));
+
+        frame
+    }
+}
@@ -0,0 +1,176 @@
+use crate::cmd::expireat::{instant_at, unix_millis_after};
+use crate::cmd::{InvalidArgument, Parser, ParserError, SyntaxError};
+use crate::{AsyncStream, Connection, Db, Frame};
+
+use bytes::Bytes;
+use tokio::time::{Duration, Instant};
+use tracing::{debug, instrument};
+
+/// 获取键的值，并可选地修改它的过期时间。
+///
+/// 读取值和修改过期时间在同一个锁内完成。没有给出选项时与 `GET` 相同，不修改过期时间。
+/// 如果键不存在，则返回特殊值 nil，不做任何修改。
+///
+/// # 选项
+///
+/// * EX `seconds` -- 从现在起经过给定的秒数之后过期。
+/// * PX `milliseconds` -- 从现在起经过给定的毫秒数之后过期。
+/// * EXAT `unix-time-seconds` -- 在给定的 Unix 时间过期。时间已经过去时删除键，但仍然返回它的值。
+/// * PERSIST -- 清除过期时间。键没有过期时间时什么都不做。
+#[derive(Debug)]
+pub struct GetEx {
+    /// 要获取的键的名称
+    key: String,
+    /// 对过期时间的修改。`None` 表示不修改。
+    expiry: Option<Expiry>,
+}
+
+/// `GETEX` 对键的过期时间做的修改。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Expiry {
+    /// `EX` 或 `PX`：从现在起经过给定的时间之后过期。
+    In(Duration),
+    /// `EXAT`：在给定的 Unix 时间（秒）过期。
+    At(u64),
+    /// `PXAT`：在给定的 Unix 时间（毫秒）过期。
+    AtMillis(u64),
+    /// `PERSIST`：清除过期时间，键不再过期。
+    Persist,
+}
+
+impl GetEx {
+    /// 创建一个新的 `GetEx` 命令，获取 `key` 的值并按 `expiry` 修改它的过期时间。
+    pub fn new(key: impl ToString, expiry: Option<Expiry>) -> Self {
+        Self {
+            key: key.to_string(),
+            expiry,
+        }
+    }
+
+    /// 获取键
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// 把相对的过期时间（`EX`/`PX`）换算成 Unix 时间，返回等效的 `PXAT` 命令。没有相对的过期时间时返回 `None`。
+    pub(crate) fn absolute_expiry(&self) -> Option<Self> {
+        match self.expiry {
+            Some(Expiry::In(duration)) => {
+                let millis = unix_millis_after(duration)?;
+                Some(Self::new(&self.key, Some(Expiry::AtMillis(millis))))
+            }
+            _ => None,
+        }
+    }
+
+    /// 将 `GetEx` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 响应写入 `dst`。这是由服务器调用以执行接收到的命令。
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection<impl AsyncStream>) -> crate::Result<()> {
+        // 换算成 `Db` 使用的单调时钟。
+        let when = match self.expiry {
+            Some(Expiry::In(duration)) => Instant::now().checked_add(duration),
+            Some(Expiry::At(timestamp)) => {
+                timestamp.checked_mul(1000).and_then(|millis| instant_at(Duration::from_millis(millis)))
+            }
+            Some(Expiry::AtMillis(millis)) => instant_at(Duration::from_millis(millis)),
+            _ => None,
+        };
+
+        let response = match self.expiry {
+            // 时间太大，无法表示
+            Some(Expiry::In(_) | Expiry::At(_) | Expiry::AtMillis(_)) if when.is_none() => {
+                Frame::Error("ERR invalid expire time in 'getex' command".to_string())
+            }
+            expiry => {
+                // 外层的 `None` 表示不修改过期时间，内层的 `None` 表示清除过期时间。
+                match db.get_ex(&self.key, expiry.map(|_| when)) {
+                    Ok(Some(value)) => Frame::Bulk(value),
+                    Ok(None) => Frame::Null,
+                    Err(err) => Frame::Error(err.to_string()),
+                }
+            }
+        };
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+}
+
+/// 从接收到的帧中解析出一个 `GetEx` 实例。
+///
+/// `GETEX` 字符串已经被消费。
+///
+/// # 格式
+///
+/// ```text
+/// GETEX key [EX seconds|PX milliseconds|EXAT unix-time-seconds|PXAT unix-time-milliseconds|PERSIST]
+/// ```
+impl TryFrom<&mut Parser> for GetEx {
+    type Error = crate::Error;
+
+    fn try_from(parser: &mut Parser) -> crate::Result<Self> {
+        use ParserError::EndOfStream;
+
+        let key = parser.next_string()?;
+        // 选项最多只有一个。
+        let expiry = match parser.next_string() {
+            Ok(s) => {
+                let option = s.to_uppercase();
+                if option == "PERSIST" {
+                    Expiry::Persist
+                } else if matches!(&option[..], "EX" | "PX" | "EXAT" | "PXAT") {
+                    // 过期时间必须是正数，与 Redis 一致。
+                    let value = u64::try_from(parser.next_int()?)
+                        .ok()
+                        .filter(|value| *value > 0)
+                        .ok_or_else(|| InvalidArgument::new("invalid expire time in 'getex' command"))?;
+                    match &option[..] {
+                        "EX" => Expiry::In(Duration::from_secs(value)),
+                        "PX" => Expiry::In(Duration::from_millis(value)),
+                        "EXAT" => Expiry::At(value),
+                        _ => Expiry::AtMillis(value),
+                    }
+                } else {
+                    return Err(SyntaxError.into());
+                }
+            }
+            Err(EndOfStream) => return Ok(Self::new(key, None)),
+            Err(err) => return Err(err.into()),
+        };
+
+        Ok(Self::new(key, Some(expiry)))
+    }
+}
+
+/// 将命令转换为等效的 `Frame`。
+impl From<GetEx> for Frame {
+    fn from(cmd: GetEx) -> Self {
+        let mut frame = Self::array();
+        frame.push_bulk(Bytes::from("getex".as_bytes()));
+        frame.push_bulk(Bytes::from(cmd.key.into_bytes()));
+        match cmd.expiry {
+            // 与 `SET` 相同，使用精度更高的毫秒
+            Some(Expiry::In(duration)) => {
+                frame.push_bulk(Bytes::from("px".as_bytes()));
+                frame.push_int(duration.as_millis() as i64);
+            }
+            Some(Expiry::At(timestamp)) => {
+                frame.push_bulk(Bytes::from("exat".as_bytes()));
+                frame.push_int(timestamp as i64);
+            }
+            Some(Expiry::AtMillis(millis)) => {
+                frame.push_bulk(Bytes::from("pxat".as_bytes()));
+                frame.push_int(millis as i64);
+            }
+            Some(Expiry::Persist) => frame.push_bulk(Bytes::from("persist".as_bytes())),
+            None => {}
+        }
+
+        frame
+    }
+}
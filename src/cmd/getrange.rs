@@ -0,0 +1,85 @@
+use crate::{AsyncStream, Connection, Db, Frame, Parser};
+
+use bytes::Bytes;
+use tracing::{debug, instrument};
+
+/// 获取键的值中 `start` 到 `end`（包含两端）的字节。
+///
+/// 与 Redis 一致，负数索引从值的末尾开始计算，`-1` 是最后一个字节。超出范围的索引会被截断到值的范围内，
+/// 范围为空或键不存在时返回空字符串。
+#[derive(Debug)]
+pub struct GetRange {
+    /// 要读取的键的名称
+    key: String,
+    /// 起始索引，包含
+    start: i64,
+    /// 结束索引，包含
+    end: i64,
+}
+
+impl GetRange {
+    /// 创建一个新的 `GetRange` 命令，读取 `key` 的值中 `start` 到 `end` 的字节。
+    pub fn new(key: impl ToString, start: i64, end: i64) -> Self {
+        Self {
+            key: key.to_string(),
+            start,
+            end,
+        }
+    }
+
+    /// 获取键
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// 将 `GetRange` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 响应写入 `dst`。这是由服务器调用以执行接收到的命令。
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection<impl AsyncStream>) -> crate::Result<()> {
+        let response = match db.get_range(&self.key, self.start, self.end) {
+            Ok(value) => Frame::Bulk(value),
+            Err(err) => Frame::Error(err.to_string()),
+        };
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+}
+
+/// 从接收到的帧中解析出一个 `GetRange` 实例。
+///
+/// `GETRANGE` 字符串已经被消费。
+///
+/// # 格式
+///
+/// ```text
+/// GETRANGE key start end
+/// ```
+impl TryFrom<&mut Parser> for GetRange {
+    type Error = crate::Error;
+
+    fn try_from(parser: &mut Parser) -> crate::Result<Self> {
+        let key = parser.next_string()?;
+        let start = parser.next_int()?;
+        let end = parser.next_int()?;
+
+        Ok(Self { key, start, end })
+    }
+}
+
+/// 将命令转换为等效的 `Frame`。
+impl From<GetRange> for Frame {
+    fn from(get_range: GetRange) -> Self {
+        let mut frame = Self::array();
+        frame.push_bulk(Bytes::from("getrange".as_bytes()));
+        frame.push_bulk(Bytes::from(get_range.key.into_bytes()));
+        frame.push_bulk(Bytes::from(get_range.start.to_string()));
+        frame.push_bulk(Bytes::from(get_range.end.to_string()));
+
+        frame
+    }
+}
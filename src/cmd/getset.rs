@@ -0,0 +1,83 @@
+use crate::{AsyncStream, Connection, Db, Frame, Parser};
+
+use bytes::Bytes;
+use tracing::{debug, instrument};
+
+/// 设置键的值并返回它先前的值。
+///
+/// 读取和写入在同一个锁内完成。与 `SET` 一样清除键原有的过期时间，并覆盖任何类型的值。
+/// 如果键不存在或者先前的值不是字符串，则返回特殊值 nil。
+///
+/// 这个命令已经被 `SET key value GET` 取代，但仍有许多旧客户端在使用。
+#[derive(Debug)]
+pub struct GetSet {
+    /// 要设置的键的名称
+    key: String,
+    /// 新的值
+    value: Bytes,
+}
+
+impl GetSet {
+    /// 创建一个新的 `GetSet` 命令，将 `key` 设为 `value` 并返回先前的值。
+    pub fn new(key: impl ToString, value: Bytes) -> Self {
+        Self {
+            key: key.to_string(),
+            value,
+        }
+    }
+
+    /// 获取键
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// 将 `GetSet` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 响应写入 `dst`。这是由服务器调用以执行接收到的命令。
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection<impl AsyncStream>) -> crate::Result<()> {
+        let response = match db.get_set(self.key, self.value) {
+            Ok(Some(value)) => Frame::Bulk(value),
+            Ok(None) => Frame::Null,
+            Err(err) => Frame::Error(err.to_string()),
+        };
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+}
+
+/// 从接收到的帧中解析出一个 `GetSet` 实例。
+///
+/// `GETSET` 字符串已经被消费。
+///
+/// # 格式
+///
+/// ```text
+/// GETSET key value
+/// ```
+impl TryFrom<&mut Parser> for GetSet {
+    type Error = crate::Error;
+
+    fn try_from(parser: &mut Parser) -> crate::Result<Self> {
+        let key = parser.next_string()?;
+        let value = parser.next_bytes()?;
+
+        Ok(Self { key, value })
+    }
+}
+
+/// 将命令转换为等效的 `Frame`。
+impl From<GetSet> for Frame {
+    fn from(cmd: GetSet) -> Self {
+        let mut frame = Self::array();
+        frame.push_bulk(Bytes::from("getset".as_bytes()));
+        frame.push_bulk(Bytes::from(cmd.key.into_bytes()));
+        frame.push_bulk(cmd.value);
+
+        frame
+    }
+}
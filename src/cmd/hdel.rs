@@ -0,0 +1,92 @@
+use crate::cmd::{Parser, ParserError};
+use crate::{AsyncStream, Connection, Db, Frame};
+
+use bytes::Bytes;
+use tracing::{debug, instrument};
+
+/// 删除哈希中的一个或多个字段。
+///
+/// 不存在的字段被忽略。响应是实际删除的字段数。哈希被删空后键会被删除。
+#[derive(Debug)]
+pub struct HDel {
+    /// 哈希的键
+    key: String,
+    /// 要删除的字段
+    fields: Vec<String>,
+}
+
+impl HDel {
+    /// 创建一个新的 `HDel` 命令，删除 `key` 哈希中的 `fields`。
+    pub fn new(key: impl ToString, fields: Vec<String>) -> Self {
+        Self {
+            key: key.to_string(),
+            fields,
+        }
+    }
+
+    /// 获取键
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// 将 `HDel` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 响应写入 `dst`。这是由服务器调用以执行接收到的命令。
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection<impl AsyncStream>) -> crate::Result<()> {
+        let response = match db.hdel(&self.key, &self.fields) {
+            Ok(removed) => Frame::Integer(removed as i64),
+            Err(err) => Frame::Error(err.to_string()),
+        };
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+}
+
+/// 从接收到的帧中解析出一个 `HDel` 实例。
+///
+/// `HDEL` 字符串已经被消费。
+///
+/// # 格式
+///
+/// ```text
+/// HDEL key field [field ...]
+/// ```
+impl TryFrom<&mut Parser> for HDel {
+    type Error = crate::Error;
+
+    fn try_from(parser: &mut Parser) -> crate::Result<Self> {
+        use ParserError::EndOfStream;
+
+        let key = parser.next_string()?;
+
+        let mut fields = vec![parser.next_string()?];
+        loop {
+            match parser.next_string() {
+                Ok(field) => fields.push(field),
+                Err(EndOfStream) => break,
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        Ok(Self { key, fields })
+    }
+}
+
+/// 将命令转换为等效的 `Frame`。
+impl From<HDel> for Frame {
+    fn from(cmd: HDel) -> Self {
+        let mut frame = Self::array();
+        frame.push_bulk(Bytes::from("hdel".as_bytes()));
+        frame.push_bulk(Bytes::from(cmd.key.into_bytes()));
+        for field in cmd.fields {
+            frame.push_bulk(Bytes::from(field.into_bytes()));
+        }
+
+        frame
+    }
+}
@@ -0,0 +1,93 @@
+use crate::connection::Protocol;
+use crate::{AsyncStream, Connection, Frame, Parser, ParserError};
+
+use bytes::Bytes;
+use tracing::{debug, instrument};
+
+/// 协商连接使用的 RESP 协议版本，并返回服务器的基本信息。
+///
+/// 指定版本 `3` 时连接切换到 RESP3，之后的响应可以使用 RESP3 独有的类型；指定 `2` 时切换回 RESP2。不指定版本时
+/// 保持当前协议。响应是包含 `server`、`version`、`proto` 和 `role` 字段的 map，RESP2 连接上是字段名称和值交替
+/// 组成的数组。
+#[derive(Debug, Default)]
+pub struct Hello {
+    /// 要切换到的协议版本
+    protover: Option<i64>,
+}
+
+impl Hello {
+    /// 创建一个新的 `Hello` 命令，可选地切换到协议版本 `protover`。
+    pub fn new(protover: Option<i64>) -> Self {
+        Self { protover }
+    }
+
+    /// 应用 `Hello` 命令，记录协商的协议版本。
+    ///
+    /// 响应写入 `dst`。这是由服务器调用以执行接收到的命令。
+    #[instrument(skip(self, dst))]
+    pub(crate) async fn apply(self, dst: &mut Connection<impl AsyncStream>) -> crate::Result<()> {
+        let protocol = match self.protover {
+            None => Some(dst.protocol()),
+            Some(2) => Some(Protocol::Resp2),
+            Some(3) => Some(Protocol::Resp3),
+            Some(_) => None,
+        };
+
+        let response = match protocol {
+            Some(protocol) => {
+                dst.set_protocol(protocol);
+
+                let field = |name: &'static str| Frame::Bulk(Bytes::from(name.as_bytes()));
+                Frame::Map(vec![
+                    (field("server"), field("mini-redis")),
+                    (field("version"), field(env!("CARGO_PKG_VERSION"))),
+                    (field("proto"), Frame::Integer(protocol.version())),
+                    (field("role"), field("master")),
+                ])
+            }
+            None => Frame::Error("NOPROTO unsupported protocol version".to_string()),
+        };
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+}
+
+/// 从接收到的帧中解析出一个 `Hello` 实例。
+///
+/// `HELLO` 字符串已经被消费。
+///
+/// # 格式
+///
+/// ```text
+/// HELLO [protover]
+/// ```
+impl TryFrom<&mut Parser> for Hello {
+    type Error = crate::Error;
+
+    fn try_from(parser: &mut Parser) -> crate::Result<Self> {
+        use ParserError::EndOfStream;
+
+        match parser.next_int() {
+            Ok(protover) => Ok(Self::new(Some(protover))),
+            Err(EndOfStream) => Ok(Self::default()),
+            Err(err) => Err(err.into()),
+        }
+    }
+}
+
+/// 将命令转换为等效的 `Frame`。
+impl From<Hello> for Frame {
+    fn from(cmd: Hello) -> Self {
+        let mut frame = Self::array();
+        frame.push_bulk(Bytes::from("hello".as_bytes()));
+        if let Some(protover) = cmd.protover {
+            frame.push_bulk(Bytes::from(protover.to_string()));
+        }
+
+        frame
+    }
+}
@@ -0,0 +1,80 @@
+use crate::{AsyncStream, Connection, Db, Frame, Parser};
+
+use bytes::Bytes;
+use tracing::{debug, instrument};
+
+/// 获取哈希中字段的值。
+///
+/// 字段或键不存在时返回 nil。
+#[derive(Debug)]
+pub struct HGet {
+    /// 哈希的键
+    key: String,
+    /// 要获取的字段
+    field: String,
+}
+
+impl HGet {
+    /// 创建一个新的 `HGet` 命令，获取 `key` 哈希中 `field` 的值。
+    pub fn new(key: impl ToString, field: impl ToString) -> Self {
+        Self {
+            key: key.to_string(),
+            field: field.to_string(),
+        }
+    }
+
+    /// 获取键
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// 将 `HGet` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 响应写入 `dst`。这是由服务器调用以执行接收到的命令。
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection<impl AsyncStream>) -> crate::Result<()> {
+        let response = match db.hget(&self.key, &self.field) {
+            Ok(Some(value)) => Frame::Bulk(value),
+            Ok(None) => Frame::Null,
+            Err(err) => Frame::Error(err.to_string()),
+        };
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+}
+
+/// 从接收到的帧中解析出一个 `HGet` 实例。
+///
+/// `HGET` 字符串已经被消费。
+///
+/// # 格式
+///
+/// ```text
+/// HGET key field
+/// ```
+impl TryFrom<&mut Parser> for HGet {
+    type Error = crate::Error;
+
+    fn try_from(parser: &mut Parser) -> crate::Result<Self> {
+        let key = parser.next_string()?;
+        let field = parser.next_string()?;
+
+        Ok(Self { key, field })
+    }
+}
+
+/// 将命令转换为等效的 `Frame`。
+impl From<HGet> for Frame {
+    fn from(cmd: HGet) -> Self {
+        let mut frame = Self::array();
+        frame.push_bulk(Bytes::from("hget".as_bytes()));
+        frame.push_bulk(Bytes::from(cmd.key.into_bytes()));
+        frame.push_bulk(Bytes::from(cmd.field.into_bytes()));
+
+        frame
+    }
+}
@@ -0,0 +1,78 @@
+use crate::{AsyncStream, Connection, Db, Frame, Parser};
+
+use bytes::Bytes;
+use tracing::{debug, instrument};
+
+/// 返回哈希中的所有字段和值。
+///
+/// 响应是字段到值的 map，顺序不确定；RESP2 连接上是字段和值交替组成的扁平数组：`[field, value, ...]`。
+/// 键不存在时返回空的 map。
+#[derive(Debug)]
+pub struct HGetAll {
+    /// 哈希的键
+    key: String,
+}
+
+impl HGetAll {
+    /// 创建一个新的 `HGetAll` 命令，返回 `key` 哈希中的所有字段。
+    pub fn new(key: impl ToString) -> Self {
+        Self { key: key.to_string() }
+    }
+
+    /// 获取键
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// 将 `HGetAll` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 响应写入 `dst`。这是由服务器调用以执行接收到的命令。
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection<impl AsyncStream>) -> crate::Result<()> {
+        let response = match db.hgetall(&self.key) {
+            Ok(fields) => Frame::Map(
+                fields
+                    .into_iter()
+                    .map(|(field, value)| (Frame::Bulk(Bytes::from(field)), Frame::Bulk(value)))
+                    .collect(),
+            ),
+            Err(err) => Frame::Error(err.to_string()),
+        };
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+}
+
+/// 从接收到的帧中解析出一个 `HGetAll` 实例。
+///
+/// `HGETALL` 字符串已经被消费。
+///
+/// # 格式
+///
+/// ```text
+/// HGETALL key
+/// ```
+impl TryFrom<&mut Parser> for HGetAll {
+    type Error = crate::Error;
+
+    fn try_from(parser: &mut Parser) -> crate::Result<Self> {
+        let key = parser.next_string()?;
+
+        Ok(Self { key })
+    }
+}
+
+/// 将命令转换为等效的 `Frame`。
+impl From<HGetAll> for Frame {
+    fn from(cmd: HGetAll) -> Self {
+        let mut frame = Self::array();
+        frame.push_bulk(Bytes::from("hgetall".as_bytes()));
+        frame.push_bulk(Bytes::from(cmd.key.into_bytes()));
+
+        frame
+    }
+}
@@ -0,0 +1,93 @@
+use crate::db::IncrError;
+use crate::{AsyncStream, Connection, Db, Frame, Parser};
+
+use bytes::Bytes;
+use tracing::{debug, instrument};
+
+/// 将哈希中字段的值当作整数加上给定的增量。
+///
+/// 键或字段不存在时先创建它们，字段从 `0` 开始。读取、相加和写回在同一个锁内完成，
+/// 并发的 `HINCRBY` 不会丢失更新。响应是相加后的值。
+///
+/// 字段的值或增量不是 64 位有符号整数，或者结果溢出时，回复错误而不修改哈希。
+#[derive(Debug)]
+pub struct HIncrBy {
+    /// 哈希的键
+    key: String,
+    /// 要修改的字段
+    field: String,
+    /// 增量，与 [`IncrBy`](crate::cmd::IncrBy) 一样在应用命令时才解析。
+    delta: String,
+}
+
+impl HIncrBy {
+    /// 创建一个新的 `HIncrBy` 命令，将 `key` 哈希中 `field` 的值加上 `delta`。
+    pub fn new(key: impl ToString, field: impl ToString, delta: i64) -> Self {
+        Self {
+            key: key.to_string(),
+            field: field.to_string(),
+            delta: delta.to_string(),
+        }
+    }
+
+    /// 获取键
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// 将 `HIncrBy` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 响应写入 `dst`。这是由服务器调用以执行接收到的命令。
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection<impl AsyncStream>) -> crate::Result<()> {
+        let result = match self.delta.parse() {
+            Ok(delta) => db.hincr_by(self.key, self.field, delta),
+            Err(_) => Err(IncrError::NotInteger),
+        };
+
+        let response = match result {
+            Ok(value) => Frame::Integer(value),
+            Err(err) => Frame::Error(err.to_string()),
+        };
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+}
+
+/// 从接收到的帧中解析出一个 `HIncrBy` 实例。
+///
+/// `HINCRBY` 字符串已经被消费。
+///
+/// # 格式
+///
+/// ```text
+/// HINCRBY key field increment
+/// ```
+impl TryFrom<&mut Parser> for HIncrBy {
+    type Error = crate::Error;
+
+    fn try_from(parser: &mut Parser) -> crate::Result<Self> {
+        let key = parser.next_string()?;
+        let field = parser.next_string()?;
+        let delta = parser.next_string()?;
+
+        Ok(Self { key, field, delta })
+    }
+}
+
+/// 将命令转换为等效的 `Frame`。
+impl From<HIncrBy> for Frame {
+    fn from(cmd: HIncrBy) -> Self {
+        let mut frame = Self::array();
+        frame.push_bulk(Bytes::from("hincrby".as_bytes()));
+        frame.push_bulk(Bytes::from(cmd.key.into_bytes()));
+        frame.push_bulk(Bytes::from(cmd.field.into_bytes()));
+        frame.push_bulk(Bytes::from(cmd.delta.into_bytes()));
+
+        frame
+    }
+}
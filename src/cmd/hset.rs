@@ -0,0 +1,95 @@
+use crate::cmd::{Parser, ParserError};
+use crate::{AsyncStream, Connection, Db, Frame};
+
+use bytes::Bytes;
+use tracing::{debug, instrument};
+
+/// 设置哈希中一个或多个字段的值。
+///
+/// 键不存在时先创建一个空哈希。已经存在的字段被覆盖。响应是新增的字段数，不包括被覆盖的字段。
+#[derive(Debug)]
+pub struct HSet {
+    /// 哈希的键
+    key: String,
+    /// 要设置的字段和值
+    fields: Vec<(String, Bytes)>,
+}
+
+impl HSet {
+    /// 创建一个新的 `HSet` 命令，在 `key` 哈希中设置 `fields`。
+    pub fn new(key: impl ToString, fields: Vec<(String, Bytes)>) -> Self {
+        Self {
+            key: key.to_string(),
+            fields,
+        }
+    }
+
+    /// 获取键
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// 将 `HSet` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 响应写入 `dst`。这是由服务器调用以执行接收到的命令。
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection<impl AsyncStream>) -> crate::Result<()> {
+        let response = match db.hset(self.key, self.fields) {
+            Ok(added) => Frame::Integer(added as i64),
+            Err(err) => Frame::Error(err.to_string()),
+        };
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+}
+
+/// 从接收到的帧中解析出一个 `HSet` 实例。
+///
+/// `HSET` 字符串已经被消费。
+///
+/// # 格式
+///
+/// ```text
+/// HSET key field value [field value ...]
+/// ```
+impl TryFrom<&mut Parser> for HSet {
+    type Error = crate::Error;
+
+    fn try_from(parser: &mut Parser) -> crate::Result<Self> {
+        use ParserError::EndOfStream;
+
+        let key = parser.next_string()?;
+
+        let mut fields = vec![(parser.next_string()?, parser.next_bytes()?)];
+        loop {
+            let field = match parser.next_string() {
+                Ok(field) => field,
+                Err(EndOfStream) => break,
+                Err(err) => return Err(err.into()),
+            };
+            // 字段和值必须成对出现。
+            fields.push((field, parser.next_bytes()?));
+        }
+
+        Ok(Self { key, fields })
+    }
+}
+
+/// 将命令转换为等效的 `Frame`。
+impl From<HSet> for Frame {
+    fn from(cmd: HSet) -> Self {
+        let mut frame = Self::array();
+        frame.push_bulk(Bytes::from("hset".as_bytes()));
+        frame.push_bulk(Bytes::from(cmd.key.into_bytes()));
+        for (field, value) in cmd.fields {
+            frame.push_bulk(Bytes::from(field.into_bytes()));
+            frame.push_bulk(value);
+        }
+
+        frame
+    }
+}
@@ -0,0 +1,254 @@
+use crate::db::IncrError;
+use crate::{AsyncStream, Connection, Db, Frame, Parser};
+
+use bytes::Bytes;
+use tracing::{debug, instrument};
+
+/// 将键的值当作整数加上给定的增量。
+///
+/// 如果键不存在，则先将它设为 `0`。读取、相加和写回在同一个锁内完成，并发的 `INCRBY` 不会丢失更新。
+/// 键原有的生存时间保持不变。响应是相加后的值。
+///
+/// 值或增量不是 64 位有符号整数，或者结果溢出时，回复错误而不修改键。
+#[derive(Debug)]
+pub struct IncrBy {
+    /// 要修改的键的名称
+    key: String,
+    /// 增量。在应用命令时才解析，这样无效的增量只会得到错误回复，而不会关闭连接。
+    delta: String,
+}
+
+/// 将键的值当作整数减去给定的减量。
+///
+/// 除了符号相反之外与 [`IncrBy`] 相同。
+#[derive(Debug)]
+pub struct DecrBy {
+    /// 要修改的键的名称
+    key: String,
+    /// 减量，与 [`IncrBy`] 一样在应用命令时才解析。
+    delta: String,
+}
+
+/// 将键的值当作浮点数加上给定的增量。
+///
+/// 如果键不存在，则先将它设为 `0`。响应是相加后的值，以批量字符串表示。
+///
+/// 值或增量不是有效的浮点数，或者结果是 NaN 或无穷大时，回复错误而不修改键。
+#[derive(Debug)]
+pub struct IncrByFloat {
+    /// 要修改的键的名称
+    key: String,
+    /// 增量，与 [`IncrBy`] 一样在应用命令时才解析。
+    delta: String,
+}
+
+impl IncrBy {
+    /// 创建一个新的 `IncrBy` 命令，将 `key` 的值加上 `delta`。
+    pub fn new(key: impl ToString, delta: i64) -> Self {
+        Self {
+            key: key.to_string(),
+            delta: delta.to_string(),
+        }
+    }
+
+    /// 获取键
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// 将 `IncrBy` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 响应写入 `dst`。这是由服务器调用以执行接收到的命令。
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection<impl AsyncStream>) -> crate::Result<()> {
+        let result = match self.delta.parse() {
+            Ok(delta) => db.incr_by(self.key, delta),
+            Err(_) => Err(IncrError::NotInteger),
+        };
+
+        let response = match result {
+            Ok(value) => Frame::Integer(value),
+            Err(err) => Frame::Error(err.to_string()),
+        };
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+}
+
+impl DecrBy {
+    /// 创建一个新的 `DecrBy` 命令，将 `key` 的值减去 `delta`。
+    pub fn new(key: impl ToString, delta: i64) -> Self {
+        Self {
+            key: key.to_string(),
+            delta: delta.to_string(),
+        }
+    }
+
+    /// 获取键
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// 将 `DecrBy` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 响应写入 `dst`。这是由服务器调用以执行接收到的命令。
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection<impl AsyncStream>) -> crate::Result<()> {
+        let result = match self.delta.parse::<i64>() {
+            // `i64::MIN` 没有对应的正数，取反会溢出。
+            Ok(delta) => match delta.checked_neg() {
+                Some(delta) => db.incr_by(self.key, delta),
+                None => Err(IncrError::Overflow),
+            },
+            Err(_) => Err(IncrError::NotInteger),
+        };
+
+        let response = match result {
+            Ok(value) => Frame::Integer(value),
+            Err(err) => Frame::Error(err.to_string()),
+        };
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+}
+
+impl IncrByFloat {
+    /// 创建一个新的 `IncrByFloat` 命令，将 `key` 的值加上 `delta`。
+    pub fn new(key: impl ToString, delta: f64) -> Self {
+        Self {
+            key: key.to_string(),
+            delta: delta.to_string(),
+        }
+    }
+
+    /// 获取键
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// 将 `IncrByFloat` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 响应写入 `dst`。这是由服务器调用以执行接收到的命令。
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection<impl AsyncStream>) -> crate::Result<()> {
+        let result = match self.delta.parse::<f64>() {
+            Ok(delta) if delta.is_finite() => db.incr_by_float(self.key, delta),
+            _ => Err(IncrError::NotFloat),
+        };
+
+        let response = match result {
+            Ok(value) => Frame::Bulk(Bytes::from(value.to_string())),
+            Err(err) => Frame::Error(err.to_string()),
+        };
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+}
+
+/// 从接收到的帧中解析出一个 `IncrBy` 实例。
+///
+/// `INCRBY` 字符串已经被消费。
+///
+/// # 格式
+///
+/// ```text
+/// INCRBY key increment
+/// ```
+impl TryFrom<&mut Parser> for IncrBy {
+    type Error = crate::Error;
+
+    fn try_from(parser: &mut Parser) -> crate::Result<Self> {
+        let key = parser.next_string()?;
+        let delta = parser.next_string()?;
+
+        Ok(Self { key, delta })
+    }
+}
+
+/// 从接收到的帧中解析出一个 `DecrBy` 实例。
+///
+/// `DECRBY` 字符串已经被消费。
+///
+/// # 格式
+///
+/// ```text
+/// DECRBY key decrement
+/// ```
+impl TryFrom<&mut Parser> for DecrBy {
+    type Error = crate::Error;
+
+    fn try_from(parser: &mut Parser) -> crate::Result<Self> {
+        let key = parser.next_string()?;
+        let delta = parser.next_string()?;
+
+        Ok(Self { key, delta })
+    }
+}
+
+/// 从接收到的帧中解析出一个 `IncrByFloat` 实例。
+///
+/// `INCRBYFLOAT` 字符串已经被消费。
+///
+/// # 格式
+///
+/// ```text
+/// INCRBYFLOAT key increment
+/// ```
+impl TryFrom<&mut Parser> for IncrByFloat {
+    type Error = crate::Error;
+
+    fn try_from(parser: &mut Parser) -> crate::Result<Self> {
+        let key = parser.next_string()?;
+        let delta = parser.next_string()?;
+
+        Ok(Self { key, delta })
+    }
+}
+
+/// 将命令转换为等效的 `Frame`。
+impl From<IncrBy> for Frame {
+    fn from(cmd: IncrBy) -> Self {
+        let mut frame = Self::array();
+        frame.push_bulk(Bytes::from("incrby".as_bytes()));
+        frame.push_bulk(Bytes::from(cmd.key.into_bytes()));
+        frame.push_bulk(Bytes::from(cmd.delta.into_bytes()));
+
+        frame
+    }
+}
+
+/// 将命令转换为等效的 `Frame`。
+impl From<DecrBy> for Frame {
+    fn from(cmd: DecrBy) -> Self {
+        let mut frame = Self::array();
+        frame.push_bulk(Bytes::from("decrby".as_bytes()));
+        frame.push_bulk(Bytes::from(cmd.key.into_bytes()));
+        frame.push_bulk(Bytes::from(cmd.delta.into_bytes()));
+
+        frame
+    }
+}
+
+/// 将命令转换为等效的 `Frame`。
+impl From<IncrByFloat> for Frame {
+    fn from(cmd: IncrByFloat) -> Self {
+        let mut frame = Self::array();
+        frame.push_bulk(Bytes::from("incrbyfloat".as_bytes()));
+        frame.push_bulk(Bytes::from(cmd.key.into_bytes()));
+        frame.push_bulk(Bytes::from(cmd.delta.into_bytes()));
+
+        frame
+    }
+}
@@ -0,0 +1,114 @@
+use crate::cmd::{Parser, ParserError};
+use crate::server::Stats;
+use crate::{Connection, Db, Frame};
+
+use bytes::Bytes;
+use std::fmt::Write;
+use tracing::{debug, instrument};
+
+/// 返回服务器的运行信息和统计数据。
+///
+/// 响应是一个 bulk 字符串，格式与 Redis 相同：每个部分以 `# Section` 行开头，后面是若干 `field:value` 行，
+/// 部分之间以空行分隔，行以 `\r\n` 结尾。可以逐行解析，未知的字段应该忽略，以后会增加新的字段。
+///
+/// 可以给出部分的名称（不区分大小写），只返回该部分。
+#[derive(Debug, Default)]
+pub struct Info {
+    /// 只返回这个部分。`None` 表示返回所有部分。
+    section: Option<String>,
+}
+
+impl Info {
+    /// 创建一个新的 `Info` 命令。`section` 为 `None` 时返回所有部分。
+    pub fn new(section: Option<String>) -> Self {
+        Self { section }
+    }
+
+    /// 将 `Info` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 与其他命令不同，`INFO` 需要读取服务器的状态，因此由连接处理程序传入 `stats` 并调用。
+    #[instrument(skip(self, db, stats, dst))]
+    pub(crate) async fn apply(self, db: &Db, stats: &Stats, dst: &mut Connection) -> crate::Result<()> {
+        let sections = [
+            ("Server", vec![("uptime_in_seconds", stats.uptime().as_secs() as usize)]),
+            (
+                "Clients",
+                vec![
+                    ("connected_clients", stats.connected_clients()),
+                    ("maxclients", stats.max_connections()),
+                ],
+            ),
+            (
+                "Stats",
+                vec![
+                    ("pubsub_channels", db.channels(None).len()),
+                    ("total_net_input_bytes", stats.net_input_bytes() as usize),
+                    ("total_net_output_bytes", stats.net_output_bytes() as usize),
+                ],
+            ),
+            (
+                "Keyspace",
+                vec![("db_keys", db.len()), ("allocated_databases", db.allocated_databases())],
+            ),
+        ];
+
+        let mut info = String::new();
+        for (name, fields) in sections {
+            if let Some(section) = &self.section {
+                if !section.eq_ignore_ascii_case(name) {
+                    continue;
+                }
+            }
+
+            if !info.is_empty() {
+                info.push_str("\r\n");
+            }
+            write!(info, "# {}\r\n", name)?;
+            for (field, value) in fields {
+                write!(info, "{}:{}\r\n", field, value)?;
+            }
+        }
+
+        let response = Frame::Bulk(Bytes::from(info));
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+}
+
+/// 从接收到的帧中解析出一个 `Info` 实例。
+///
+/// `INFO` 字符串已经被消费。
+///
+/// # 格式
+///
+/// ```text
+/// INFO [section]
+/// ```
+impl TryFrom<&mut Parser> for Info {
+    type Error = crate::Error;
+
+    fn try_from(parser: &mut Parser) -> crate::Result<Self> {
+        match parser.next_string() {
+            Ok(section) => Ok(Self::new(Some(section))),
+            Err(ParserError::EndOfStream) => Ok(Self::default()),
+            Err(err) => Err(err.into()),
+        }
+    }
+}
+
+/// 将命令转换为等效的 `Frame`。
+impl From<Info> for Frame {
+    fn from(info: Info) -> Self {
+        let mut frame = Self::array();
+        frame.push_bulk(Bytes::from("info".as_bytes()));
+        if let Some(section) = info.section {
+            frame.push_bulk(Bytes::from(section.into_bytes()));
+        }
+
+        frame
+    }
+}
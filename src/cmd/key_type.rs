@@ -0,0 +1,69 @@
+use crate::{AsyncStream, Connection, Db, Frame, Parser};
+
+use bytes::Bytes;
+use tracing::{debug, instrument};
+
+/// 返回键的值的类型。
+///
+/// 回复是类型的名称，例如 `string`；键不存在时回复 `none`。
+#[derive(Debug)]
+pub struct Type {
+    /// 要查询的键的名称
+    key: String,
+}
+
+impl Type {
+    /// 创建一个新的 `Type` 命令以查询 `key` 的类型。
+    pub fn new(key: impl ToString) -> Self {
+        Self { key: key.to_string() }
+    }
+
+    /// 获取键
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// 将 `Type` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 响应写入 `dst`。这是由服务器调用以执行接收到的命令。
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection<impl AsyncStream>) -> crate::Result<()> {
+        let response = Frame::Simple(db.type_of(&self.key).to_string());
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+}
+
+/// 从接收到的帧中解析出一个 `Type` 实例。
+///
+/// `TYPE` 字符串已经被消费。
+///
+/// # 格式
+///
+/// ```text
+/// TYPE key
+/// ```
+impl TryFrom<&mut Parser> for Type {
+    type Error = crate::Error;
+
+    fn try_from(parser: &mut Parser) -> crate::Result<Self> {
+        let key = parser.next_string()?;
+
+        Ok(Self { key })
+    }
+}
+
+/// 将命令转换为等效的 `Frame`。
+impl From<Type> for Frame {
+    fn from(cmd: Type) -> Self {
+        let mut frame = Self::array();
+        frame.push_bulk(Bytes::from("type".as_bytes()));
+        frame.push_bulk(Bytes::from(cmd.key.into_bytes()));
+
+        frame
+    }
+}
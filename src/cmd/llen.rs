@@ -0,0 +1,72 @@
+use crate::{AsyncStream, Connection, Db, Frame, Parser};
+
+use bytes::Bytes;
+use tracing::{debug, instrument};
+
+/// 返回列表的长度。
+///
+/// 键不存在时返回 `0`。
+#[derive(Debug)]
+pub struct LLen {
+    /// 列表的键
+    key: String,
+}
+
+impl LLen {
+    /// 创建一个新的 `LLen` 命令以查询 `key` 的列表长度。
+    pub fn new(key: impl ToString) -> Self {
+        Self { key: key.to_string() }
+    }
+
+    /// 获取键
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// 将 `LLen` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 响应写入 `dst`。这是由服务器调用以执行接收到的命令。
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection<impl AsyncStream>) -> crate::Result<()> {
+        let response = match db.llen(&self.key) {
+            Ok(len) => Frame::Integer(len as i64),
+            Err(err) => Frame::Error(err.to_string()),
+        };
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+}
+
+/// 从接收到的帧中解析出一个 `LLen` 实例。
+///
+/// `LLEN` 字符串已经被消费。
+///
+/// # 格式
+///
+/// ```text
+/// LLEN key
+/// ```
+impl TryFrom<&mut Parser> for LLen {
+    type Error = crate::Error;
+
+    fn try_from(parser: &mut Parser) -> crate::Result<Self> {
+        let key = parser.next_string()?;
+
+        Ok(Self { key })
+    }
+}
+
+/// 将命令转换为等效的 `Frame`。
+impl From<LLen> for Frame {
+    fn from(cmd: LLen) -> Self {
+        let mut frame = Self::array();
+        frame.push_bulk(Bytes::from("llen".as_bytes()));
+        frame.push_bulk(Bytes::from(cmd.key.into_bytes()));
+
+        frame
+    }
+}
@@ -0,0 +1,91 @@
+use crate::{AsyncStream, Connection, Db, Frame, Parser};
+
+use bytes::Bytes;
+use tracing::{debug, instrument};
+
+/// 返回列表中 `start` 到 `stop`（包含两端）的元素。
+///
+/// 与 Redis 一致，负数索引从列表的末尾开始计算，`-1` 是最后一个元素。超出范围的索引会被截断到列表的范围内，
+/// 范围为空或键不存在时返回空数组。
+#[derive(Debug)]
+pub struct LRange {
+    /// 列表的键
+    key: String,
+    /// 起始索引，包含
+    start: i64,
+    /// 结束索引，包含
+    stop: i64,
+}
+
+impl LRange {
+    /// 创建一个新的 `LRange` 命令，读取 `key` 列表中 `start` 到 `stop` 的元素。
+    pub fn new(key: impl ToString, start: i64, stop: i64) -> Self {
+        Self {
+            key: key.to_string(),
+            start,
+            stop,
+        }
+    }
+
+    /// 获取键
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// 将 `LRange` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 响应写入 `dst`。这是由服务器调用以执行接收到的命令。
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection<impl AsyncStream>) -> crate::Result<()> {
+        let response = match db.lrange(&self.key, self.start, self.stop) {
+            Ok(values) => {
+                let mut response = Frame::array();
+                for value in values {
+                    response.push_bulk(value);
+                }
+                response
+            }
+            Err(err) => Frame::Error(err.to_string()),
+        };
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+}
+
+/// 从接收到的帧中解析出一个 `LRange` 实例。
+///
+/// `LRANGE` 字符串已经被消费。
+///
+/// # 格式
+///
+/// ```text
+/// LRANGE key start stop
+/// ```
+impl TryFrom<&mut Parser> for LRange {
+    type Error = crate::Error;
+
+    fn try_from(parser: &mut Parser) -> crate::Result<Self> {
+        let key = parser.next_string()?;
+        let start = parser.next_int()?;
+        let stop = parser.next_int()?;
+
+        Ok(Self { key, start, stop })
+    }
+}
+
+/// 将命令转换为等效的 `Frame`。
+impl From<LRange> for Frame {
+    fn from(cmd: LRange) -> Self {
+        let mut frame = Self::array();
+        frame.push_bulk(Bytes::from("lrange".as_bytes()));
+        frame.push_bulk(Bytes::from(cmd.key.into_bytes()));
+        frame.push_bulk(Bytes::from(cmd.start.to_string()));
+        frame.push_bulk(Bytes::from(cmd.stop.to_string()));
+
+        frame
+    }
+}
@@ -2,24 +2,182 @@ mod get;
 pub use get::Get;
 
 mod set;
-pub use set::Set;
+pub use set::{Set, SetCondition};
+
+mod setex;
+pub use setex::{PSetEx, SetEx};
 
 mod del;
 pub use del::Del;
 
+mod touch;
+pub use touch::Touch;
+
+mod unlink;
+pub use unlink::Unlink;
+
+mod getdel;
+pub use getdel::GetDel;
+
+mod getset;
+pub use getset::GetSet;
+
+mod getex;
+pub use getex::{Expiry, GetEx};
+
+mod append;
+pub use append::Append;
+
+mod incrby;
+pub use incrby::{DecrBy, IncrBy, IncrByFloat};
+
+mod strlen;
+pub use strlen::Strlen;
+
+mod getrange;
+pub use getrange::GetRange;
+
+mod setrange;
+pub use setrange::SetRange;
+
+mod rename;
+pub use rename::Rename;
+
+mod copy;
+pub use copy::Copy;
+
+mod r#move;
+pub use r#move::Move;
+
+mod expireat;
+pub use expireat::{ExpireAt, PExpireAt};
+
+mod push;
+pub use push::{LPush, RPush};
+
+mod pop;
+pub use pop::{LPop, RPop};
+
+mod bpop;
+pub use bpop::{BLPop, BRPop};
+
+mod llen;
+pub use llen::LLen;
+
+mod lrange;
+pub use lrange::LRange;
+
+mod hset;
+pub use hset::HSet;
+
+mod hget;
+pub use hget::HGet;
+
+mod hdel;
+pub use hdel::HDel;
+
+mod hgetall;
+pub use hgetall::HGetAll;
+
+mod hincrby;
+pub use hincrby::HIncrBy;
+
+mod sadd;
+pub use sadd::SAdd;
+
+mod srem;
+pub use srem::SRem;
+
+mod smembers;
+pub use smembers::SMembers;
+
+mod sismember;
+pub use sismember::SIsMember;
+
+mod setop;
+pub use setop::{SDiff, SInter, SUnion};
+
+mod key_type;
+pub use key_type::Type;
+
+mod object;
+pub use object::ObjectCmd;
+
+mod dbsize;
+pub use dbsize::DbSize;
+
+mod scan;
+pub use scan::Scan;
+
+mod flushdb;
+pub use flushdb::{FlushAll, FlushDb};
+
+mod swapdb;
+pub use swapdb::SwapDb;
+
+mod save;
+pub use save::Save;
+
+mod multi;
+pub use multi::{Discard, Exec, Multi};
+
+mod client;
+pub use client::ClientCmd;
+
+mod hello;
+pub use hello::Hello;
+
+mod quit;
+pub use quit::Quit;
+
+mod reset;
+pub use reset::Reset;
+
+mod select;
+pub use select::Select;
+
 mod publish;
 pub use publish::Publish;
 
 mod subscribe;
-pub use subscribe::{Subscribe, Unsubscribe};
+pub use subscribe::{PSubscribe, PUnsubscribe, Subscribe, Unsubscribe};
+pub(crate) use subscribe::SubscribeExit;
+
+mod pubsub;
+pub use pubsub::PubSubCmd;
 
 mod ping;
 pub use ping::Ping;
 
+mod auth;
+pub use auth::Auth;
+
+mod command;
+pub use command::{Category, CommandCmd, Permissions};
+
+mod debug;
+pub use debug::DebugCmd;
+
+mod info;
+pub use info::Info;
+
+mod config;
+pub use config::ConfigCmd;
+
+mod slowlog;
+pub use slowlog::SlowLogCmd;
+
+mod waitaof;
+pub use waitaof::WaitAof;
+
+mod script;
+pub use script::Script;
+
 mod unknown;
 pub use unknown::Unknown;
+pub(crate) use unknown::{InvalidArgument, SyntaxError, WrongArity};
 
-use crate::{Connection, Db, Frame, Parser, ParserError, Shutdown};
+use crate::{AsyncStream, Connection, Db, Frame, Parser, ParserError, Shutdown};
 
 /// 支持的 Redis 命令的枚举。
 ///
@@ -28,11 +186,77 @@ use crate::{Connection, Db, Frame, Parser, ParserError, Shutdown};
 pub enum Command {
     Get(Get),
     Set(Set),
+    SetEx(SetEx),
+    PSetEx(PSetEx),
     Del(Del),
+    Touch(Touch),
+    Unlink(Unlink),
+    GetDel(GetDel),
+    GetSet(GetSet),
+    GetEx(GetEx),
+    Append(Append),
+    IncrBy(IncrBy),
+    DecrBy(DecrBy),
+    IncrByFloat(IncrByFloat),
+    Strlen(Strlen),
+    GetRange(GetRange),
+    SetRange(SetRange),
+    Rename(Rename),
+    Copy(Copy),
+    Move(Move),
+    ExpireAt(ExpireAt),
+    PExpireAt(PExpireAt),
+    LPush(LPush),
+    RPush(RPush),
+    LPop(LPop),
+    RPop(RPop),
+    BLPop(BLPop),
+    BRPop(BRPop),
+    LLen(LLen),
+    LRange(LRange),
+    HSet(HSet),
+    HGet(HGet),
+    HDel(HDel),
+    HGetAll(HGetAll),
+    HIncrBy(HIncrBy),
+    SAdd(SAdd),
+    SRem(SRem),
+    SMembers(SMembers),
+    SIsMember(SIsMember),
+    SInter(SInter),
+    SUnion(SUnion),
+    SDiff(SDiff),
+    Type(Type),
+    Object(ObjectCmd),
+    DbSize(DbSize),
+    Scan(Scan),
+    FlushDb(FlushDb),
+    FlushAll(FlushAll),
+    SwapDb(SwapDb),
+    Save(Save),
     Publish(Publish),
     Subscribe(Subscribe),
     Unsubscribe(Unsubscribe),
+    PSubscribe(PSubscribe),
+    PUnsubscribe(PUnsubscribe),
+    PubSub(PubSubCmd),
     Ping(Ping),
+    Auth(Auth),
+    Command(CommandCmd),
+    Debug(DebugCmd),
+    Info(Info),
+    Config(ConfigCmd),
+    SlowLog(SlowLogCmd),
+    WaitAof(WaitAof),
+    Multi(Multi),
+    Exec(Exec),
+    Discard(Discard),
+    Reset(Reset),
+    Select(Select),
+    Quit(Quit),
+    Hello(Hello),
+    Client(ClientCmd),
+    Script(Script),
     Unknown(Unknown),
 }
 
@@ -40,17 +264,93 @@ impl Command {
     /// 将命令应用于指定的 `Db` 实例。
     ///
     /// 响应写入 `dst`。这是由服务器调用以执行接收到的命令。
-    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection, shutdown: &mut Shutdown) -> crate::Result<()> {
+    pub(crate) async fn apply(
+        self,
+        db: &Db,
+        dst: &mut Connection<impl AsyncStream>,
+        shutdown: &mut Shutdown,
+    ) -> crate::Result<()> {
         match self {
             Self::Get(cmd) => cmd.apply(db, dst).await,
             Self::Set(cmd) => cmd.apply(db, dst).await,
+            Self::SetEx(cmd) => cmd.apply(db, dst).await,
+            Self::PSetEx(cmd) => cmd.apply(db, dst).await,
             Self::Del(cmd) => cmd.apply(db, dst).await,
+            Self::Touch(cmd) => cmd.apply(db, dst).await,
+            Self::Unlink(cmd) => cmd.apply(db, dst).await,
+            Self::GetDel(cmd) => cmd.apply(db, dst).await,
+            Self::GetSet(cmd) => cmd.apply(db, dst).await,
+            Self::GetEx(cmd) => cmd.apply(db, dst).await,
+            Self::Append(cmd) => cmd.apply(db, dst).await,
+            Self::IncrBy(cmd) => cmd.apply(db, dst).await,
+            Self::DecrBy(cmd) => cmd.apply(db, dst).await,
+            Self::IncrByFloat(cmd) => cmd.apply(db, dst).await,
+            Self::Strlen(cmd) => cmd.apply(db, dst).await,
+            Self::GetRange(cmd) => cmd.apply(db, dst).await,
+            Self::SetRange(cmd) => cmd.apply(db, dst).await,
+            Self::Rename(cmd) => cmd.apply(db, dst).await,
+            Self::Copy(cmd) => cmd.apply(db, dst).await,
+            Self::Move(cmd) => cmd.apply(db, dst).await,
+            Self::ExpireAt(cmd) => cmd.apply(db, dst).await,
+            Self::PExpireAt(cmd) => cmd.apply(db, dst).await,
+            Self::LPush(cmd) => cmd.apply(db, dst).await,
+            Self::RPush(cmd) => cmd.apply(db, dst).await,
+            Self::LPop(cmd) => cmd.apply(db, dst).await,
+            Self::RPop(cmd) => cmd.apply(db, dst).await,
+            Self::BLPop(cmd) => cmd.apply(db, None, dst, shutdown).await,
+            Self::BRPop(cmd) => cmd.apply(db, None, dst, shutdown).await,
+            Self::LLen(cmd) => cmd.apply(db, dst).await,
+            Self::LRange(cmd) => cmd.apply(db, dst).await,
+            Self::HSet(cmd) => cmd.apply(db, dst).await,
+            Self::HGet(cmd) => cmd.apply(db, dst).await,
+            Self::HDel(cmd) => cmd.apply(db, dst).await,
+            Self::HGetAll(cmd) => cmd.apply(db, dst).await,
+            Self::HIncrBy(cmd) => cmd.apply(db, dst).await,
+            Self::SAdd(cmd) => cmd.apply(db, dst).await,
+            Self::SRem(cmd) => cmd.apply(db, dst).await,
+            Self::SMembers(cmd) => cmd.apply(db, dst).await,
+            Self::SIsMember(cmd) => cmd.apply(db, dst).await,
+            Self::SInter(cmd) => cmd.apply(db, dst).await,
+            Self::SUnion(cmd) => cmd.apply(db, dst).await,
+            Self::SDiff(cmd) => cmd.apply(db, dst).await,
+            Self::Type(cmd) => cmd.apply(db, dst).await,
+            Self::Object(cmd) => cmd.apply(db, dst).await,
+            Self::DbSize(cmd) => cmd.apply(db, dst).await,
+            Self::Scan(cmd) => cmd.apply(db, dst).await,
+            Self::FlushDb(cmd) => cmd.apply(db, dst).await,
+            Self::FlushAll(cmd) => cmd.apply(db, dst).await,
+            Self::SwapDb(cmd) => cmd.apply(db, dst).await,
+            Self::Save(cmd) => cmd.apply(db, dst).await,
             Self::Publish(cmd) => cmd.apply(db, dst).await,
-            Self::Subscribe(cmd) => cmd.apply(db, dst, shutdown).await,
             Self::Ping(cmd) => cmd.apply(dst).await,
+            Self::Hello(cmd) => cmd.apply(dst).await,
+            Self::Command(cmd) => cmd.apply(dst).await,
+            Self::Debug(cmd) => cmd.apply(db, dst).await,
+            Self::PubSub(cmd) => cmd.apply(db, dst).await,
+            Self::Script(cmd) => cmd.apply(dst).await,
             Self::Unknown(cmd) => cmd.apply(dst).await,
             // `Unsubscribe` 不能被应用。它只能在 `Subscribe` 命令的上下文中接收。
             Self::Unsubscribe(_) => Err("`Unsubscribe` is unsupported in this context".into()),
+            Self::PUnsubscribe(_) => Err("`PUnsubscribe` is unsupported in this context".into()),
+            // `Auth` 修改的是连接的状态，由连接处理程序执行。
+            Self::Auth(_) => Err("`Auth` is applied by the connection handler".into()),
+            // `Info` 需要读取服务器的状态，同样由连接处理程序执行。
+            Self::Info(_) => Err("`Info` is applied by the connection handler".into()),
+            Self::Config(_) => Err("`Config` is applied by the connection handler".into()),
+            Self::SlowLog(_) => Err("`SlowLog` is applied by the connection handler".into()),
+            Self::WaitAof(_) => Err("`WaitAof` is applied by the connection handler".into()),
+            // 事务的状态属于连接，由连接处理程序执行。
+            Self::Multi(_) => Err("`Multi` is applied by the connection handler".into()),
+            Self::Exec(_) => Err("`Exec` is applied by the connection handler".into()),
+            Self::Discard(_) => Err("`Discard` is applied by the connection handler".into()),
+            // 订阅会接管连接，结束时可能需要恢复连接的状态，同样由连接处理程序执行。
+            Self::Subscribe(_) => Err("`Subscribe` is applied by the connection handler".into()),
+            Self::PSubscribe(_) => Err("`PSubscribe` is applied by the connection handler".into()),
+            Self::Reset(_) => Err("`Reset` is applied by the connection handler".into()),
+            // 选择的数据库属于连接。
+            Self::Select(_) => Err("`Select` is applied by the connection handler".into()),
+            Self::Quit(_) => Err("`Quit` is applied by the connection handler".into()),
+            Self::Client(_) => Err("`Client` is applied by the connection handler".into()),
         }
     }
 
@@ -59,41 +359,345 @@ impl Command {
         match self {
             Self::Get(_) => "get",
             Self::Set(_) => "set",
+            Self::SetEx(_) => "setex",
+            Self::PSetEx(_) => "psetex",
             Self::Del(_) => "del",
+            Self::Touch(_) => "touch",
+            Self::Unlink(_) => "unlink",
+            Self::GetDel(_) => "getdel",
+            Self::GetSet(_) => "getset",
+            Self::GetEx(_) => "getex",
+            Self::Append(_) => "append",
+            Self::IncrBy(_) => "incrby",
+            Self::DecrBy(_) => "decrby",
+            Self::IncrByFloat(_) => "incrbyfloat",
+            Self::Strlen(_) => "strlen",
+            Self::GetRange(_) => "getrange",
+            Self::SetRange(_) => "setrange",
+            Self::Rename(cmd) if cmd.is_nx() => "renamenx",
+            Self::Rename(_) => "rename",
+            Self::Copy(_) => "copy",
+            Self::Move(_) => "move",
+            Self::ExpireAt(_) => "expireat",
+            Self::PExpireAt(_) => "pexpireat",
+            Self::LPush(_) => "lpush",
+            Self::RPush(_) => "rpush",
+            Self::LPop(_) => "lpop",
+            Self::RPop(_) => "rpop",
+            Self::BLPop(_) => "blpop",
+            Self::BRPop(_) => "brpop",
+            Self::LLen(_) => "llen",
+            Self::LRange(_) => "lrange",
+            Self::HSet(_) => "hset",
+            Self::HGet(_) => "hget",
+            Self::HDel(_) => "hdel",
+            Self::HGetAll(_) => "hgetall",
+            Self::HIncrBy(_) => "hincrby",
+            Self::SAdd(_) => "sadd",
+            Self::SRem(_) => "srem",
+            Self::SMembers(_) => "smembers",
+            Self::SIsMember(_) => "sismember",
+            Self::SInter(_) => "sinter",
+            Self::SUnion(_) => "sunion",
+            Self::SDiff(_) => "sdiff",
+            Self::Type(_) => "type",
+            Self::Object(_) => "object",
+            Self::DbSize(_) => "dbsize",
+            Self::Scan(_) => "scan",
+            Self::FlushDb(_) => "flushdb",
+            Self::FlushAll(_) => "flushall",
+            Self::SwapDb(_) => "swapdb",
+            Self::Save(_) => "save",
             Self::Publish(_) => "pub",
             Self::Subscribe(_) => "subscribe",
             Self::Unsubscribe(_) => "unsubscribe",
+            Self::PSubscribe(_) => "psubscribe",
+            Self::PUnsubscribe(_) => "punsubscribe",
+            Self::PubSub(_) => "pubsub",
             Self::Ping(_) => "ping",
+            Self::Auth(_) => "auth",
+            Self::Command(_) => "command",
+            Self::Debug(_) => "debug",
+            Self::Info(_) => "info",
+            Self::Config(_) => "config",
+            Self::SlowLog(_) => "slowlog",
+            Self::WaitAof(_) => "waitaof",
+            Self::Multi(_) => "multi",
+            Self::Exec(_) => "exec",
+            Self::Discard(_) => "discard",
+            Self::Reset(_) => "reset",
+            Self::Select(_) => "select",
+            Self::Quit(_) => "quit",
+            Self::Hello(_) => "hello",
+            Self::Client(_) => "client",
+            Self::Script(cmd) => cmd.get_name(),
             Self::Unknown(cmd) => cmd.get_name(),
         }
     }
-}
 
-/// 从接收到的帧中解析命令。
-///
-/// `Frame` 必须表示 `mini-redis` 支持的 Redis 命令，并且是数组变体。
-///
-/// # 返回值
-///
-/// 成功时返回命令值，否则返回 `Err`。
-impl TryFrom<Frame> for Command {
-    type Error = crate::Error;
-    fn try_from(frame: Frame) -> crate::Result<Self> {
-        // 帧值用 `Parse` 装饰。`Parse` 提供了一个类似“游标”的 API，使解析命令更容易。
-        //
-        // 帧值必须是数组变体。任何其他帧变体都会导致返回错误。
-        let mut parser = Parser::new(frame)?;
-        // 所有 Redis 命令都以命令名称作为字符串开头。读取名称并转换为小写以进行区分大小写的匹配。
-        let cmd_name = parser.next_string()?.to_lowercase();
+    /// 返回命令所属的主要类别。
+    ///
+    /// 未知命令被归为 `@admin`。
+    pub fn category(&self) -> Category {
+        match self {
+            Self::Get(_)
+            | Self::Strlen(_)
+            | Self::GetRange(_)
+            | Self::LLen(_)
+            | Self::LRange(_)
+            | Self::HGet(_)
+            | Self::HGetAll(_)
+            | Self::SMembers(_)
+            | Self::SIsMember(_)
+            | Self::SInter(_)
+            | Self::SUnion(_)
+            | Self::SDiff(_) => Category::Read,
+            Self::Set(_)
+            | Self::SetEx(_)
+            | Self::PSetEx(_)
+            | Self::GetDel(_)
+            | Self::GetSet(_)
+            | Self::GetEx(_)
+            | Self::Append(_)
+            | Self::IncrBy(_)
+            | Self::DecrBy(_)
+            | Self::IncrByFloat(_)
+            | Self::SetRange(_)
+            | Self::LPush(_)
+            | Self::RPush(_)
+            | Self::LPop(_)
+            | Self::RPop(_)
+            | Self::BLPop(_)
+            | Self::BRPop(_)
+            | Self::HSet(_)
+            | Self::HDel(_)
+            | Self::HIncrBy(_)
+            | Self::SAdd(_)
+            | Self::SRem(_) => Category::Write,
+            Self::Del(_)
+            | Self::Touch(_)
+            | Self::Unlink(_)
+            | Self::Rename(_)
+            | Self::Copy(_)
+            | Self::Move(_)
+            | Self::ExpireAt(_)
+            | Self::PExpireAt(_)
+            | Self::Type(_)
+            | Self::Object(_)
+            | Self::DbSize(_)
+            | Self::Scan(_)
+            | Self::FlushDb(_)
+            | Self::FlushAll(_)
+            | Self::SwapDb(_) => Category::Keyspace,
+            Self::Publish(_)
+            | Self::Subscribe(_)
+            | Self::Unsubscribe(_)
+            | Self::PSubscribe(_)
+            | Self::PUnsubscribe(_)
+            | Self::PubSub(_) => Category::PubSub,
+            Self::Script(_) => Category::Scripting,
+            Self::Multi(_) | Self::Exec(_) | Self::Discard(_) => Category::Transaction,
+            Self::Ping(_)
+            | Self::Auth(_)
+            | Self::Command(_)
+            | Self::Debug(_)
+            | Self::Save(_)
+            | Self::Info(_)
+            | Self::Config(_)
+            | Self::SlowLog(_)
+            | Self::WaitAof(_)
+            | Self::Reset(_)
+            | Self::Select(_)
+            | Self::Quit(_)
+            | Self::Hello(_)
+            | Self::Client(_)
+            | Self::Unknown(_) => Category::Admin,
+        }
+    }
+
+    /// 返回命令所属的所有类别：主要类别，以及修改键空间的 `@keyspace` 命令（例如 `DEL` 和 `FLUSHALL`）额外的
+    /// `@write`。
+    ///
+    /// 检查权限时使用，只允许 `@read` 和 `@keyspace` 的连接因此不能删除键或者清空数据库。
+    pub fn categories(&self) -> Vec<Category> {
+        let category = self.category();
+        if category != Category::Write && self.is_write() {
+            vec![category, Category::Write]
+        } else {
+            vec![category]
+        }
+    }
+
+    /// 如果命令可能修改键空间，则返回 `true`。开启 AOF 时只记录这些命令。
+    pub fn is_write(&self) -> bool {
+        self.category() == Category::Write
+            || matches!(
+                self,
+                Self::Del(_)
+                    | Self::Unlink(_)
+                    | Self::Rename(_)
+                    | Self::Copy(_)
+                    | Self::Move(_)
+                    | Self::ExpireAt(_)
+                    | Self::PExpireAt(_)
+                    | Self::FlushDb(_)
+                    | Self::FlushAll(_)
+                    | Self::SwapDb(_)
+            )
+    }
+
+    /// 把相对的过期时间换算成 Unix 时间，返回等效命令的帧。命令没有相对的过期时间时返回 `None`。
+    ///
+    /// 开启 AOF 时记录换算之后的帧，重放时键在原来的时间过期，而不是从重放时重新开始计时。
+    pub(crate) fn absolute_expiry(&self) -> Option<Frame> {
+        match self {
+            Self::Set(cmd) => cmd.absolute_expiry().map(Frame::from),
+            Self::SetEx(cmd) => cmd.absolute_expiry().map(Frame::from),
+            Self::PSetEx(cmd) => cmd.absolute_expiry().map(Frame::from),
+            Self::GetEx(cmd) => cmd.absolute_expiry().map(Frame::from),
+            _ => None,
+        }
+    }
+
+    /// 如果服务器能够执行该命令，则返回 `true`。
+    ///
+    /// 未知命令以及被识别但不支持的命令（例如脚本命令）返回 `false`，它们只会回复一个错误。
+    pub fn is_supported(&self) -> bool {
+        !matches!(self, Self::Script(_) | Self::Unknown(_))
+    }
+
+    /// 返回命令参数中作为键的那些参数。
+    ///
+    /// 频道名称不是键，因此 pub/sub 命令返回空列表。
+    pub fn keys(&self) -> Vec<&str> {
+        match self {
+            Self::Get(cmd) => vec![cmd.key()],
+            Self::Set(cmd) => vec![cmd.key()],
+            Self::SetEx(cmd) => vec![cmd.key()],
+            Self::PSetEx(cmd) => vec![cmd.key()],
+            Self::GetDel(cmd) => vec![cmd.key()],
+            Self::GetSet(cmd) => vec![cmd.key()],
+            Self::GetEx(cmd) => vec![cmd.key()],
+            Self::Append(cmd) => vec![cmd.key()],
+            Self::IncrBy(cmd) => vec![cmd.key()],
+            Self::DecrBy(cmd) => vec![cmd.key()],
+            Self::IncrByFloat(cmd) => vec![cmd.key()],
+            Self::Strlen(cmd) => vec![cmd.key()],
+            Self::GetRange(cmd) => vec![cmd.key()],
+            Self::SetRange(cmd) => vec![cmd.key()],
+            Self::Del(cmd) => cmd.keys().iter().map(String::as_str).collect(),
+            Self::Touch(cmd) => cmd.keys().iter().map(String::as_str).collect(),
+            Self::Unlink(cmd) => cmd.keys().iter().map(String::as_str).collect(),
+            Self::Rename(cmd) => vec![cmd.key(), cmd.new_key()],
+            Self::Copy(cmd) => vec![cmd.source(), cmd.destination()],
+            Self::Move(cmd) => vec![cmd.key()],
+            Self::ExpireAt(cmd) => vec![cmd.key()],
+            Self::PExpireAt(cmd) => vec![cmd.key()],
+            Self::LPush(cmd) => vec![cmd.key()],
+            Self::RPush(cmd) => vec![cmd.key()],
+            Self::LPop(cmd) => vec![cmd.key()],
+            Self::RPop(cmd) => vec![cmd.key()],
+            Self::BLPop(cmd) => cmd.keys().iter().map(String::as_str).collect(),
+            Self::BRPop(cmd) => cmd.keys().iter().map(String::as_str).collect(),
+            Self::LLen(cmd) => vec![cmd.key()],
+            Self::LRange(cmd) => vec![cmd.key()],
+            Self::HSet(cmd) => vec![cmd.key()],
+            Self::HGet(cmd) => vec![cmd.key()],
+            Self::HDel(cmd) => vec![cmd.key()],
+            Self::HIncrBy(cmd) => vec![cmd.key()],
+            Self::HGetAll(cmd) => vec![cmd.key()],
+            Self::SAdd(cmd) => vec![cmd.key()],
+            Self::SRem(cmd) => vec![cmd.key()],
+            Self::SMembers(cmd) => vec![cmd.key()],
+            Self::SIsMember(cmd) => vec![cmd.key()],
+            Self::SInter(cmd) => cmd.keys().iter().map(String::as_str).collect(),
+            Self::SUnion(cmd) => cmd.keys().iter().map(String::as_str).collect(),
+            Self::SDiff(cmd) => cmd.keys().iter().map(String::as_str).collect(),
+            Self::Type(cmd) => vec![cmd.key()],
+            Self::Object(cmd) => vec![cmd.key()],
+            Self::Debug(cmd) => cmd.key().into_iter().collect(),
+            _ => vec![],
+        }
+    }
+
+    /// 解析名称为 `cmd_name` 的命令的参数。命令名称已经被消费。
+    fn parse_args(cmd_name: &str, mut parser: Parser) -> crate::Result<Self> {
         // 匹配命令名称，将其余的解析委托给特定命令。
-        let cmd = match &cmd_name[..] {
+        let cmd = match cmd_name {
             "get" => Self::Get(Get::try_from(&mut parser)?),
             "set" => Self::Set(Set::try_from(&mut parser)?),
+            "setex" => Self::SetEx(SetEx::try_from(&mut parser)?),
+            "psetex" => Self::PSetEx(PSetEx::try_from(&mut parser)?),
             "del" => Self::Del(Del::try_from(&mut parser)?),
+            "touch" => Self::Touch(Touch::try_from(&mut parser)?),
+            "unlink" => Self::Unlink(Unlink::try_from(&mut parser)?),
+            "getdel" => Self::GetDel(GetDel::try_from(&mut parser)?),
+            "getset" => Self::GetSet(GetSet::try_from(&mut parser)?),
+            "getex" => Self::GetEx(GetEx::try_from(&mut parser)?),
+            "append" => Self::Append(Append::try_from(&mut parser)?),
+            "incrby" => Self::IncrBy(IncrBy::try_from(&mut parser)?),
+            "decrby" => Self::DecrBy(DecrBy::try_from(&mut parser)?),
+            "incrbyfloat" => Self::IncrByFloat(IncrByFloat::try_from(&mut parser)?),
+            "strlen" => Self::Strlen(Strlen::try_from(&mut parser)?),
+            "getrange" => Self::GetRange(GetRange::try_from(&mut parser)?),
+            "setrange" => Self::SetRange(SetRange::try_from(&mut parser)?),
+            "rename" => Self::Rename(Rename::parse(&mut parser, false)?),
+            "renamenx" => Self::Rename(Rename::parse(&mut parser, true)?),
+            "copy" => Self::Copy(Copy::try_from(&mut parser)?),
+            "move" => Self::Move(Move::try_from(&mut parser)?),
+            "expireat" => Self::ExpireAt(ExpireAt::try_from(&mut parser)?),
+            "pexpireat" => Self::PExpireAt(PExpireAt::try_from(&mut parser)?),
+            "lpush" => Self::LPush(LPush::try_from(&mut parser)?),
+            "rpush" => Self::RPush(RPush::try_from(&mut parser)?),
+            "lpop" => Self::LPop(LPop::try_from(&mut parser)?),
+            "rpop" => Self::RPop(RPop::try_from(&mut parser)?),
+            "blpop" => Self::BLPop(BLPop::try_from(&mut parser)?),
+            "brpop" => Self::BRPop(BRPop::try_from(&mut parser)?),
+            "llen" => Self::LLen(LLen::try_from(&mut parser)?),
+            "lrange" => Self::LRange(LRange::try_from(&mut parser)?),
+            "hset" => Self::HSet(HSet::try_from(&mut parser)?),
+            "hget" => Self::HGet(HGet::try_from(&mut parser)?),
+            "hdel" => Self::HDel(HDel::try_from(&mut parser)?),
+            "hgetall" => Self::HGetAll(HGetAll::try_from(&mut parser)?),
+            "hincrby" => Self::HIncrBy(HIncrBy::try_from(&mut parser)?),
+            "sadd" => Self::SAdd(SAdd::try_from(&mut parser)?),
+            "srem" => Self::SRem(SRem::try_from(&mut parser)?),
+            "smembers" => Self::SMembers(SMembers::try_from(&mut parser)?),
+            "sismember" => Self::SIsMember(SIsMember::try_from(&mut parser)?),
+            "sinter" => Self::SInter(SInter::try_from(&mut parser)?),
+            "sunion" => Self::SUnion(SUnion::try_from(&mut parser)?),
+            "sdiff" => Self::SDiff(SDiff::try_from(&mut parser)?),
+            "type" => Self::Type(Type::try_from(&mut parser)?),
+            "object" => Self::Object(ObjectCmd::try_from(&mut parser)?),
+            "dbsize" => Self::DbSize(DbSize::try_from(&mut parser)?),
+            "scan" => Self::Scan(Scan::try_from(&mut parser)?),
+            "flushdb" => Self::FlushDb(FlushDb::try_from(&mut parser)?),
+            "flushall" => Self::FlushAll(FlushAll::try_from(&mut parser)?),
+            "swapdb" => Self::SwapDb(SwapDb::try_from(&mut parser)?),
+            "save" => Self::Save(Save::try_from(&mut parser)?),
             "publish" => Self::Publish(Publish::try_from(&mut parser)?),
             "subscribe" => Self::Subscribe(Subscribe::try_from(&mut parser)?),
             "unsubscribe" => Self::Unsubscribe(Unsubscribe::try_from(&mut parser)?),
+            "psubscribe" => Self::PSubscribe(PSubscribe::try_from(&mut parser)?),
+            "punsubscribe" => Self::PUnsubscribe(PUnsubscribe::try_from(&mut parser)?),
+            "pubsub" => Self::PubSub(PubSubCmd::try_from(&mut parser)?),
             "ping" => Self::Ping(Ping::try_from(&mut parser)?),
+            "auth" => Self::Auth(Auth::try_from(&mut parser)?),
+            "command" => Self::Command(CommandCmd::try_from(&mut parser)?),
+            "debug" => Self::Debug(DebugCmd::try_from(&mut parser)?),
+            "info" => Self::Info(Info::try_from(&mut parser)?),
+            "config" => Self::Config(ConfigCmd::try_from(&mut parser)?),
+            "slowlog" => Self::SlowLog(SlowLogCmd::try_from(&mut parser)?),
+            "waitaof" => Self::WaitAof(WaitAof::try_from(&mut parser)?),
+            "multi" => Self::Multi(Multi::try_from(&mut parser)?),
+            "exec" => Self::Exec(Exec::try_from(&mut parser)?),
+            "discard" => Self::Discard(Discard::try_from(&mut parser)?),
+            "reset" => Self::Reset(Reset::try_from(&mut parser)?),
+            "select" => Self::Select(Select::try_from(&mut parser)?),
+            "quit" => Self::Quit(Quit::try_from(&mut parser)?),
+            "hello" => Self::Hello(Hello::try_from(&mut parser)?),
+            "client" => Self::Client(ClientCmd::try_from(&mut parser)?),
+            "script" | "eval" | "evalsha" => Self::Script(Script::parse(cmd_name, &mut parser)?),
             _ => {
                 // 命令未被识别，返回 Unknown 命令。
                 //
@@ -101,10 +705,39 @@ impl TryFrom<Frame> for Command {
                 return Ok(Self::Unknown(Unknown::new(cmd_name)));
             }
         };
-        // 检查 `Parse` 值中是否有任何未消费的字段。如果有剩余字段，这表示帧格式意外，返回错误。
-        parser.finish()?;
+        // 检查 `Parse` 值中是否有任何未消费的字段。如果有剩余字段，说明参数太多。
+        parser.finish().map_err(|_| WrongArity::new(cmd_name))?;
 
         // 命令已成功解析
         Ok(cmd)
     }
 }
+
+/// 从接收到的帧中解析命令。
+///
+/// `Frame` 必须表示 `mini-redis` 支持的 Redis 命令，并且是数组变体。
+///
+/// # 返回值
+///
+/// 成功时返回命令值，否则返回 `Err`。
+impl TryFrom<Frame> for Command {
+    type Error = crate::Error;
+    fn try_from(frame: Frame) -> crate::Result<Self> {
+        // 帧值用 `Parse` 装饰。`Parse` 提供了一个类似“游标”的 API，使解析命令更容易。
+        //
+        // 帧值必须是数组变体。任何其他帧变体都会导致返回错误。
+        let mut parser = Parser::new(frame)?;
+        // 所有 Redis 命令都以命令名称作为字符串开头。读取名称并转换为小写以进行区分大小写的匹配。
+        let cmd_name = parser.next_string()?.to_lowercase();
+        // 参数不够时命令的解析以 `EndOfStream` 失败。此时帧本身是完整的，协议没有被破坏，
+        // 因此转换为 `WrongArity`，连接处理程序只回复错误而不关闭连接，与 Redis 一致。
+        // 其他解析错误取出包装的错误，连接处理程序据此区分无效的参数和被破坏的协议。
+        Self::parse_args(&cmd_name, parser).map_err(|err| match err.downcast::<ParserError>() {
+            Ok(err) => match *err {
+                ParserError::EndOfStream => WrongArity::new(cmd_name).into(),
+                ParserError::Other(err) => err,
+            },
+            Err(err) => err,
+        })
+    }
+}
@@ -0,0 +1,92 @@
+use crate::cmd::{InvalidArgument, Parser};
+use crate::{AsyncStream, Connection, Db, Frame};
+
+use bytes::Bytes;
+use tracing::{debug, instrument};
+
+/// 将 `key` 从当前数据库移动到编号为 `db` 的数据库。
+///
+/// 键的剩余生存时间随值一起移动。如果 `key` 不存在，或者目标数据库中已经存在同名的键，则不做任何修改并返回 0，
+/// 移动成功返回 1。
+#[derive(Debug)]
+pub struct Move {
+    /// 要移动的键
+    key: String,
+
+    /// 目标数据库的编号
+    db: usize,
+}
+
+impl Move {
+    /// 创建一个新的 `MOVE` 命令，将 `key` 移动到编号为 `db` 的数据库。
+    pub fn new(key: impl ToString, db: usize) -> Self {
+        Self {
+            key: key.to_string(),
+            db,
+        }
+    }
+
+    /// 获取要移动的键
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// 获取目标数据库的编号
+    pub fn db(&self) -> usize {
+        self.db
+    }
+
+    /// 将 `Move` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 响应写入 `dst`。这是由服务器调用以执行接收到的命令。
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection<impl AsyncStream>) -> crate::Result<()> {
+        // 与 Redis 一致，移动到当前数据库是错误，而不是什么都不做
+        let response = if self.db == db.index() {
+            Frame::Error("ERR source and destination objects are the same".to_string())
+        } else {
+            match db.select(self.db) {
+                Some(target) => Frame::Integer(db.move_key(&self.key, &target) as i64),
+                None => Frame::Error("ERR DB index is out of range".to_string()),
+            }
+        };
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+}
+
+/// 从接收到的帧中解析出一个 `Move` 实例。
+///
+/// `MOVE` 字符串已经被消费。
+///
+/// # 格式
+///
+/// ```text
+/// MOVE key db
+/// ```
+impl TryFrom<&mut Parser> for Move {
+    type Error = crate::Error;
+
+    fn try_from(parser: &mut Parser) -> crate::Result<Self> {
+        let key = parser.next_string()?;
+        let db = usize::try_from(parser.next_int()?).map_err(|_| InvalidArgument::new("DB index is out of range"))?;
+
+        Ok(Self::new(key, db))
+    }
+}
+
+/// 将命令转换为等效的 `Frame`。
+impl From<Move> for Frame {
+    fn from(cmd: Move) -> Self {
+        let mut frame = Self::array();
+        frame.push_bulk(Bytes::from("move".as_bytes()));
+        frame.push_bulk(Bytes::from(cmd.key.into_bytes()));
+        frame.push_bulk(Bytes::from(cmd.db.to_string()));
+
+        frame
+    }
+}
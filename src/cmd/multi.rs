@@ -0,0 +1,123 @@
+use crate::{Frame, Parser};
+
+use bytes::Bytes;
+
+/// 开启一个事务。
+///
+/// 之后连接上的命令不会立即执行，而是排队并回复 `QUEUED`，直到 `EXEC` 按顺序执行所有命令，或者 `DISCARD`
+/// 丢弃它们。事务的状态属于连接，因此这三个命令都由连接处理程序执行。
+#[derive(Debug, Default)]
+pub struct Multi;
+
+impl Multi {
+    /// 创建一个新的 `Multi` 命令。
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+/// 执行事务中排队的所有命令。
+///
+/// 事务执行期间其他连接的命令不会穿插执行。响应是每个命令的响应组成的数组。如果排队时出现了错误，
+/// 整个事务被放弃，返回 `EXECABORT` 错误。
+#[derive(Debug, Default)]
+pub struct Exec;
+
+impl Exec {
+    /// 创建一个新的 `Exec` 命令。
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+/// 丢弃事务中排队的所有命令并退出事务。
+#[derive(Debug, Default)]
+pub struct Discard;
+
+impl Discard {
+    /// 创建一个新的 `Discard` 命令。
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+/// 从接收到的帧中解析出一个 `Multi` 实例。
+///
+/// `MULTI` 字符串已经被消费。
+///
+/// # 格式
+///
+/// ```text
+/// MULTI
+/// ```
+impl TryFrom<&mut Parser> for Multi {
+    type Error = crate::Error;
+
+    fn try_from(_parser: &mut Parser) -> crate::Result<Self> {
+        Ok(Self)
+    }
+}
+
+/// 从接收到的帧中解析出一个 `Exec` 实例。
+///
+/// `EXEC` 字符串已经被消费。
+///
+/// # 格式
+///
+/// ```text
+/// EXEC
+/// ```
+impl TryFrom<&mut Parser> for Exec {
+    type Error = crate::Error;
+
+    fn try_from(_parser: &mut Parser) -> crate::Result<Self> {
+        Ok(Self)
+    }
+}
+
+/// 从接收到的帧中解析出一个 `Discard` 实例。
+///
+/// `DISCARD` 字符串已经被消费。
+///
+/// # 格式
+///
+/// ```text
+/// DISCARD
+/// ```
+impl TryFrom<&mut Parser> for Discard {
+    type Error = crate::Error;
+
+    fn try_from(_parser: &mut Parser) -> crate::Result<Self> {
+        Ok(Self)
+    }
+}
+
+/// 将命令转换为等效的 `Frame`。
+impl From<Multi> for Frame {
+    fn from(_: Multi) -> Self {
+        let mut frame = Self::array();
+        frame.push_bulk(Bytes::from("multi".as_bytes()));
+
+        frame
+    }
+}
+
+/// 将命令转换为等效的 `Frame`。
+impl From<Exec> for Frame {
+    fn from(_: Exec) -> Self {
+        let mut frame = Self::array();
+        frame.push_bulk(Bytes::from("exec".as_bytes()));
+
+        frame
+    }
+}
+
+/// 将命令转换为等效的 `Frame`。
+impl From<Discard> for Frame {
+    fn from(_: Discard) -> Self {
+        let mut frame = Self::array();
+        frame.push_bulk(Bytes::from("discard".as_bytes()));
+
+        frame
+    }
+}
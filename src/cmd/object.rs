@@ -0,0 +1,107 @@
+use crate::cmd::{InvalidArgument, Parser};
+use crate::{AsyncStream, Connection, Db, Frame};
+
+use bytes::Bytes;
+use tracing::{debug, instrument};
+
+/// 查看键的值的内部信息。
+///
+/// # 子命令
+///
+/// * ENCODING `key` -- 返回值的编码名称，例如短字符串的 `embstr`。mini-redis 对每种类型只有一种表示，
+///   回复的是 Redis 对同样大小的值会使用的编码。
+/// * REFCOUNT `key` -- 返回值的引用计数。值不在键之间共享，因此总是 1。
+///
+/// 键不存在时回复错误。
+#[derive(Debug)]
+pub struct ObjectCmd {
+    /// 要执行的子命令
+    sub: ObjectSubcommand,
+    /// 要查看的键的名称
+    key: String,
+}
+
+#[derive(Debug)]
+enum ObjectSubcommand {
+    Encoding,
+    RefCount,
+}
+
+impl ObjectCmd {
+    /// 创建一个新的 `OBJECT ENCODING` 命令，查询 `key` 的值的编码。
+    pub fn encoding(key: impl ToString) -> Self {
+        Self {
+            sub: ObjectSubcommand::Encoding,
+            key: key.to_string(),
+        }
+    }
+
+    /// 创建一个新的 `OBJECT REFCOUNT` 命令，查询 `key` 的值的引用计数。
+    pub fn refcount(key: impl ToString) -> Self {
+        Self {
+            sub: ObjectSubcommand::RefCount,
+            key: key.to_string(),
+        }
+    }
+
+    /// 获取键
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// 将 `OBJECT` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 响应写入 `dst`。这是由服务器调用以执行接收到的命令。
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection<impl AsyncStream>) -> crate::Result<()> {
+        let response = match (self.sub, db.encoding_of(&self.key)) {
+            (_, None) => Frame::Error("ERR no such key".to_string()),
+            (ObjectSubcommand::Encoding, Some(encoding)) => Frame::Bulk(Bytes::from(encoding)),
+            (ObjectSubcommand::RefCount, Some(_)) => Frame::Integer(1),
+        };
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+}
+
+/// 从接收到的帧中解析出一个 `ObjectCmd` 实例。
+///
+/// `OBJECT` 字符串已经被消费。
+///
+/// # 格式
+///
+/// ```text
+/// OBJECT ENCODING key
+/// OBJECT REFCOUNT key
+/// ```
+impl TryFrom<&mut Parser> for ObjectCmd {
+    type Error = crate::Error;
+
+    fn try_from(parser: &mut Parser) -> crate::Result<Self> {
+        let sub = parser.next_string()?.to_uppercase();
+        match &sub[..] {
+            "ENCODING" => Ok(Self::encoding(parser.next_string()?)),
+            "REFCOUNT" => Ok(Self::refcount(parser.next_string()?)),
+            _ => Err(InvalidArgument::unknown_subcommand("object", &sub).into()),
+        }
+    }
+}
+
+/// 将命令转换为等效的 `Frame`。
+impl From<ObjectCmd> for Frame {
+    fn from(cmd: ObjectCmd) -> Self {
+        let mut frame = Self::array();
+        frame.push_bulk(Bytes::from("object".as_bytes()));
+        match cmd.sub {
+            ObjectSubcommand::Encoding => frame.push_bulk(Bytes::from("encoding".as_bytes())),
+            ObjectSubcommand::RefCount => frame.push_bulk(Bytes::from("refcount".as_bytes())),
+        }
+        frame.push_bulk(Bytes::from(cmd.key.into_bytes()));
+
+        frame
+    }
+}
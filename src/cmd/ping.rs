@@ -1,4 +1,4 @@
-use crate::{Connection, Frame, Parser, ParserError};
+use crate::{AsyncStream, Connection, Frame, Parser, ParserError, Protocol};
 use bytes::Bytes;
 use tracing::{debug, instrument};
 
@@ -21,7 +21,7 @@ impl Ping {
     ///
     /// 响应写入 `dst`。这是由服务器调用以执行接收到的命令。
     #[instrument(skip(self, dst))]
-    pub(crate) async fn apply(self, dst: &mut Connection) -> crate::Result<()> {
+    pub(crate) async fn apply(self, dst: &mut Connection<impl AsyncStream>) -> crate::Result<()> {
         let response = match self.msg {
             Some(msg) => Frame::Bulk(msg),
             None => Frame::Simple("PONG".to_string()),
@@ -33,6 +33,26 @@ impl Ping {
 
         Ok(())
     }
+
+    /// 在订阅模式下应用 `Ping` 命令。
+    ///
+    /// RESP2 连接在订阅模式下只能接收消息格式的数组，因此与 Redis 一样回复 `["pong", message]`，
+    /// 没有消息时为空字符串。RESP3 连接可以区分推送和回复，回复与普通模式相同。
+    pub(crate) async fn apply_subscribed(self, dst: &mut Connection<impl AsyncStream>) -> crate::Result<()> {
+        if dst.protocol() == Protocol::Resp3 {
+            return self.apply(dst).await;
+        }
+
+        let mut response = Frame::array();
+        response.push_bulk(Bytes::from_static(b"pong"));
+        response.push_bulk(self.msg.unwrap_or_default());
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
 }
 
 /// 从接收到的帧中解析出一个 `Ping` 实例。
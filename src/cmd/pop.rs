@@ -0,0 +1,206 @@
+use crate::cmd::{InvalidArgument, Parser, ParserError};
+use crate::db::ListEnd;
+use crate::{AsyncStream, Connection, Db, Frame};
+
+use bytes::Bytes;
+use tracing::{debug, instrument};
+
+/// 移除并返回列表头部的元素。
+///
+/// 键不存在时返回 nil。列表被弹空后键会被删除。
+///
+/// 给出 `count` 时最多弹出这么多元素，以数组返回；列表较短时返回所有元素，键不存在时返回 nil 数组。
+#[derive(Debug)]
+pub struct LPop {
+    /// 列表的键
+    key: String,
+    /// 要弹出的元素个数。`None` 表示只弹出一个，并且不以数组返回。
+    count: Option<usize>,
+}
+
+impl LPop {
+    /// 创建一个新的 `LPop` 命令，弹出 `key` 的头部元素。
+    pub fn new(key: impl ToString) -> Self {
+        Self {
+            key: key.to_string(),
+            count: None,
+        }
+    }
+
+    /// 最多弹出 `count` 个元素，并以数组返回。
+    pub fn with_count(mut self, count: usize) -> Self {
+        self.count = Some(count);
+        self
+    }
+
+    /// 获取键
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// 将 `LPop` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 响应写入 `dst`。这是由服务器调用以执行接收到的命令。
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection<impl AsyncStream>) -> crate::Result<()> {
+        apply_pop(&self.key, self.count, ListEnd::Left, db, dst).await
+    }
+}
+
+/// 从接收到的帧中解析出一个 `LPop` 实例。
+///
+/// `LPOP` 字符串已经被消费。
+///
+/// # 格式
+///
+/// ```text
+/// LPOP key [count]
+/// ```
+impl TryFrom<&mut Parser> for LPop {
+    type Error = crate::Error;
+
+    fn try_from(parser: &mut Parser) -> crate::Result<Self> {
+        let key = parser.next_string()?;
+        let count = parse_count(parser)?;
+
+        Ok(Self { key, count })
+    }
+}
+
+/// 将命令转换为等效的 `Frame`。
+impl From<LPop> for Frame {
+    fn from(cmd: LPop) -> Self {
+        let mut frame = Self::array();
+        frame.push_bulk(Bytes::from("lpop".as_bytes()));
+        frame.push_bulk(Bytes::from(cmd.key.into_bytes()));
+        if let Some(count) = cmd.count {
+            frame.push_int(count as i64);
+        }
+
+        frame
+    }
+}
+
+/// 移除并返回列表尾部的元素。
+///
+/// 键不存在时返回 nil。列表被弹空后键会被删除。
+///
+/// 给出 `count` 时最多弹出这么多元素，以数组返回；列表较短时返回所有元素，键不存在时返回 nil 数组。
+#[derive(Debug)]
+pub struct RPop {
+    /// 列表的键
+    key: String,
+    /// 要弹出的元素个数。`None` 表示只弹出一个，并且不以数组返回。
+    count: Option<usize>,
+}
+
+impl RPop {
+    /// 创建一个新的 `RPop` 命令，弹出 `key` 的尾部元素。
+    pub fn new(key: impl ToString) -> Self {
+        Self {
+            key: key.to_string(),
+            count: None,
+        }
+    }
+
+    /// 最多弹出 `count` 个元素，并以数组返回。
+    pub fn with_count(mut self, count: usize) -> Self {
+        self.count = Some(count);
+        self
+    }
+
+    /// 获取键
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// 将 `RPop` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 响应写入 `dst`。这是由服务器调用以执行接收到的命令。
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection<impl AsyncStream>) -> crate::Result<()> {
+        apply_pop(&self.key, self.count, ListEnd::Right, db, dst).await
+    }
+}
+
+/// 从接收到的帧中解析出一个 `RPop` 实例。
+///
+/// `RPOP` 字符串已经被消费。
+///
+/// # 格式
+///
+/// ```text
+/// RPOP key [count]
+/// ```
+impl TryFrom<&mut Parser> for RPop {
+    type Error = crate::Error;
+
+    fn try_from(parser: &mut Parser) -> crate::Result<Self> {
+        let key = parser.next_string()?;
+        let count = parse_count(parser)?;
+
+        Ok(Self { key, count })
+    }
+}
+
+/// 将命令转换为等效的 `Frame`。
+impl From<RPop> for Frame {
+    fn from(cmd: RPop) -> Self {
+        let mut frame = Self::array();
+        frame.push_bulk(Bytes::from("rpop".as_bytes()));
+        frame.push_bulk(Bytes::from(cmd.key.into_bytes()));
+        if let Some(count) = cmd.count {
+            frame.push_int(count as i64);
+        }
+
+        frame
+    }
+}
+
+/// `LPOP` 和 `RPOP` 的共同逻辑：从 `end` 端弹出元素并回复。
+///
+/// 没有给出 `count` 时回复单个元素或 nil，否则回复数组或 nil 数组。
+async fn apply_pop(
+    key: &str,
+    count: Option<usize>,
+    end: ListEnd,
+    db: &Db,
+    dst: &mut Connection<impl AsyncStream>,
+) -> crate::Result<()> {
+    let response = match count {
+        None => match db.pop(key, end) {
+            Ok(Some(value)) => Frame::Bulk(value),
+            Ok(None) => Frame::Null,
+            Err(err) => Frame::Error(err.to_string()),
+        },
+        Some(count) => match db.pop_count(key, end, count) {
+            Ok(Some(values)) => {
+                let mut response = Frame::array();
+                for value in values {
+                    response.push_bulk(value);
+                }
+                response
+            }
+            Ok(None) => Frame::NullArray,
+            Err(err) => Frame::Error(err.to_string()),
+        },
+    };
+
+    debug!(?response);
+
+    dst.write_frame(&response).await?;
+
+    Ok(())
+}
+
+/// 解析可选的 `count` 参数。
+fn parse_count(parser: &mut Parser) -> crate::Result<Option<usize>> {
+    match parser.next_int() {
+        Ok(count) => match usize::try_from(count) {
+            Ok(count) => Ok(Some(count)),
+            Err(_) => Err(InvalidArgument::new("value is out of range, must be positive").into()),
+        },
+        Err(ParserError::EndOfStream) => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{Connection, Db, Frame, Parser};
+use crate::{AsyncStream, Connection, Db, Frame, Parser};
 
 use bytes::Bytes;
 
@@ -29,7 +29,7 @@ impl Publish {
     /// 将 `Publish` 命令应用到指定的 `Db` 实例。
     ///
     /// 响应写入 `dst`。这是由服务器调用以执行接收到的命令。
-    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<()> {
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection<impl AsyncStream>) -> crate::Result<()> {
         // 共享状态包含所有活动频道的 `tokio::sync::broadcast::Sender`。
         // 调用 `db.publish` 将消息分发到相应的频道。
         //
@@ -38,7 +38,7 @@ impl Publish {
         let num_subscribers = db.publish(&self.channel, self.message);
 
         // 订阅者数量作为发布请求的响应返回。
-        let response = Frame::Integer(num_subscribers as u64);
+        let response = Frame::Integer(num_subscribers as i64);
 
         // 将帧写入客户端。
         dst.write_frame(&response).await?;
@@ -0,0 +1,132 @@
+use crate::cmd::{InvalidArgument, Parser, ParserError};
+use crate::{AsyncStream, Connection, Db, Frame};
+
+use bytes::Bytes;
+use tracing::{debug, instrument};
+
+/// 查看发布/订阅状态的 `PUBSUB` 命令。
+///
+/// # 子命令
+///
+/// * CHANNELS `[pattern]` -- 返回当前至少有一个订阅者的频道，可选地只返回与 `pattern` 匹配的频道。
+/// * NUMSUB `[channel ...]` -- 返回每个给定频道的订阅者数量。
+///
+/// 只统计频道订阅，模式订阅不计入，与 Redis 一致。
+#[derive(Debug)]
+pub struct PubSubCmd {
+    /// 要执行的子命令
+    sub: PubSubSubcommand,
+}
+
+#[derive(Debug)]
+enum PubSubSubcommand {
+    Channels(Option<String>),
+    NumSub(Vec<String>),
+}
+
+impl PubSubCmd {
+    /// 创建一个新的 `PUBSUB CHANNELS` 命令，可选地只列出与 `pattern` 匹配的频道。
+    pub fn channels(pattern: Option<String>) -> Self {
+        Self {
+            sub: PubSubSubcommand::Channels(pattern),
+        }
+    }
+
+    /// 创建一个新的 `PUBSUB NUMSUB` 命令，查询 `channels` 中每个频道的订阅者数量。
+    pub fn numsub(channels: Vec<String>) -> Self {
+        Self {
+            sub: PubSubSubcommand::NumSub(channels),
+        }
+    }
+
+    /// 将 `PUBSUB` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 响应写入 `dst`。这是由服务器调用以执行接收到的命令。
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection<impl AsyncStream>) -> crate::Result<()> {
+        let mut response = Frame::array();
+        match self.sub {
+            PubSubSubcommand::Channels(pattern) => {
+                for channel in db.channels(pattern.as_deref()) {
+                    response.push_bulk(Bytes::from(channel));
+                }
+            }
+            PubSubSubcommand::NumSub(channels) => {
+                // 响应是频道名称和订阅者数量交替组成的数组。
+                for (channel, count) in db.num_sub(channels) {
+                    response.push_bulk(Bytes::from(channel));
+                    response.push_int(count as i64);
+                }
+            }
+        }
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+}
+
+/// 从接收到的帧中解析出一个 `PubSubCmd` 实例。
+///
+/// `PUBSUB` 字符串已经被消费。
+///
+/// # 格式
+///
+/// ```text
+/// PUBSUB CHANNELS [pattern]
+/// PUBSUB NUMSUB [channel [channel ...]]
+/// ```
+impl TryFrom<&mut Parser> for PubSubCmd {
+    type Error = crate::Error;
+
+    fn try_from(parser: &mut Parser) -> crate::Result<Self> {
+        use ParserError::EndOfStream;
+
+        let sub = parser.next_string()?.to_uppercase();
+        match &sub[..] {
+            "CHANNELS" => match parser.next_string() {
+                Ok(pattern) => Ok(Self::channels(Some(pattern))),
+                Err(EndOfStream) => Ok(Self::channels(None)),
+                Err(err) => Err(err.into()),
+            },
+            "NUMSUB" => {
+                let mut channels = vec![];
+                loop {
+                    match parser.next_string() {
+                        Ok(channel) => channels.push(channel),
+                        Err(EndOfStream) => break,
+                        Err(err) => return Err(err.into()),
+                    }
+                }
+                Ok(Self::numsub(channels))
+            }
+            _ => Err(InvalidArgument::unknown_subcommand("pubsub", &sub).into()),
+        }
+    }
+}
+
+/// 将命令转换为等效的 `Frame`。
+impl From<PubSubCmd> for Frame {
+    fn from(cmd: PubSubCmd) -> Self {
+        let mut frame = Self::array();
+        frame.push_bulk(Bytes::from("pubsub".as_bytes()));
+        match cmd.sub {
+            PubSubSubcommand::Channels(pattern) => {
+                frame.push_bulk(Bytes::from("channels".as_bytes()));
+                if let Some(pattern) = pattern {
+                    frame.push_bulk(Bytes::from(pattern.into_bytes()));
+                }
+            }
+            PubSubSubcommand::NumSub(channels) => {
+                frame.push_bulk(Bytes::from("numsub".as_bytes()));
+                for channel in channels {
+                    frame.push_bulk(Bytes::from(channel.into_bytes()));
+                }
+            }
+        }
+
+        frame
+    }
+}
@@ -0,0 +1,178 @@
+use crate::cmd::{Parser, ParserError};
+use crate::db::ListEnd;
+use crate::{AsyncStream, Connection, Db, Frame};
+
+use bytes::Bytes;
+use tracing::{debug, instrument};
+
+/// 将一个或多个值推入列表的头部。
+///
+/// 键不存在时先创建一个空列表。响应是推入后列表的长度。
+///
+/// 每个值都成为新的第一个元素，因此 `LPUSH key a b c` 之后列表为 `c b a`。
+#[derive(Debug)]
+pub struct LPush {
+    /// 列表的键
+    key: String,
+    /// 要推入的值，按顺序推入
+    values: Vec<Bytes>,
+}
+
+impl LPush {
+    /// 创建一个新的 `LPush` 命令，将 `values` 推入 `key` 的头部。
+    pub fn new(key: impl ToString, values: Vec<Bytes>) -> Self {
+        Self {
+            key: key.to_string(),
+            values,
+        }
+    }
+
+    /// 获取键
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// 将 `LPush` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 响应写入 `dst`。这是由服务器调用以执行接收到的命令。
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection<impl AsyncStream>) -> crate::Result<()> {
+        let response = match db.push(self.key, self.values, ListEnd::Left) {
+            Ok(len) => Frame::Integer(len as i64),
+            Err(err) => Frame::Error(err.to_string()),
+        };
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+}
+
+/// 从接收到的帧中解析出一个 `LPush` 实例。
+///
+/// `LPUSH` 字符串已经被消费。
+///
+/// # 格式
+///
+/// ```text
+/// LPUSH key element [element ...]
+/// ```
+impl TryFrom<&mut Parser> for LPush {
+    type Error = crate::Error;
+
+    fn try_from(parser: &mut Parser) -> crate::Result<Self> {
+        let key = parser.next_string()?;
+        let values = parse_values(parser)?;
+
+        Ok(Self { key, values })
+    }
+}
+
+/// 将命令转换为等效的 `Frame`。
+impl From<LPush> for Frame {
+    fn from(cmd: LPush) -> Self {
+        let mut frame = Self::array();
+        frame.push_bulk(Bytes::from("lpush".as_bytes()));
+        frame.push_bulk(Bytes::from(cmd.key.into_bytes()));
+        for value in cmd.values {
+            frame.push_bulk(value);
+        }
+
+        frame
+    }
+}
+
+/// 将一个或多个值推入列表的尾部。
+///
+/// 键不存在时先创建一个空列表。响应是推入后列表的长度。
+#[derive(Debug)]
+pub struct RPush {
+    /// 列表的键
+    key: String,
+    /// 要推入的值，按顺序推入
+    values: Vec<Bytes>,
+}
+
+impl RPush {
+    /// 创建一个新的 `RPush` 命令，将 `values` 推入 `key` 的尾部。
+    pub fn new(key: impl ToString, values: Vec<Bytes>) -> Self {
+        Self {
+            key: key.to_string(),
+            values,
+        }
+    }
+
+    /// 获取键
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// 将 `RPush` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 响应写入 `dst`。这是由服务器调用以执行接收到的命令。
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection<impl AsyncStream>) -> crate::Result<()> {
+        let response = match db.push(self.key, self.values, ListEnd::Right) {
+            Ok(len) => Frame::Integer(len as i64),
+            Err(err) => Frame::Error(err.to_string()),
+        };
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+}
+
+/// 从接收到的帧中解析出一个 `RPush` 实例。
+///
+/// `RPUSH` 字符串已经被消费。
+///
+/// # 格式
+///
+/// ```text
+/// RPUSH key element [element ...]
+/// ```
+impl TryFrom<&mut Parser> for RPush {
+    type Error = crate::Error;
+
+    fn try_from(parser: &mut Parser) -> crate::Result<Self> {
+        let key = parser.next_string()?;
+        let values = parse_values(parser)?;
+
+        Ok(Self { key, values })
+    }
+}
+
+/// 将命令转换为等效的 `Frame`。
+impl From<RPush> for Frame {
+    fn from(cmd: RPush) -> Self {
+        let mut frame = Self::array();
+        frame.push_bulk(Bytes::from("rpush".as_bytes()));
+        frame.push_bulk(Bytes::from(cmd.key.into_bytes()));
+        for value in cmd.values {
+            frame.push_bulk(value);
+        }
+
+        frame
+    }
+}
+
+/// 解析至少一个要推入的值。
+fn parse_values(parser: &mut Parser) -> crate::Result<Vec<Bytes>> {
+    use ParserError::EndOfStream;
+
+    let mut values = vec![parser.next_bytes()?];
+    loop {
+        match parser.next_bytes() {
+            Ok(value) => values.push(value),
+            Err(EndOfStream) => break,
+            Err(err) => return Err(err.into()),
+        }
+    }
+
+    Ok(values)
+}
@@ -0,0 +1,44 @@
+use crate::{Frame, Parser};
+
+use bytes::Bytes;
+
+/// 要求服务器关闭连接。
+///
+/// 服务器回复 `OK`，然后关闭连接，同一批流水线请求中 `QUIT` 之后的命令不会被执行。关闭连接属于连接处理程序的
+/// 工作，因此由它执行。
+#[derive(Debug, Default)]
+pub struct Quit;
+
+impl Quit {
+    /// 创建一个新的 `Quit` 命令。
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+/// 从接收到的帧中解析出一个 `Quit` 实例。
+///
+/// `QUIT` 字符串已经被消费。
+///
+/// # 格式
+///
+/// ```text
+/// QUIT
+/// ```
+impl TryFrom<&mut Parser> for Quit {
+    type Error = crate::Error;
+
+    fn try_from(_parser: &mut Parser) -> crate::Result<Self> {
+        Ok(Self)
+    }
+}
+
+/// 将命令转换为等效的 `Frame`。
+impl From<Quit> for Frame {
+    fn from(_: Quit) -> Self {
+        let mut frame = Self::array();
+        frame.push_bulk(Bytes::from("quit".as_bytes()));
+
+        frame
+    }
+}
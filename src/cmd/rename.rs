@@ -0,0 +1,104 @@
+use crate::cmd::Parser;
+use crate::{AsyncStream, Connection, Db, Frame};
+
+use bytes::Bytes;
+use tracing::{debug, instrument};
+
+/// 将 `key` 重命名为 `newkey`。
+///
+/// 如果 `newkey` 已经存在，`RENAME` 会覆盖它，而 `RENAMENX` 不做任何修改并返回 0。
+/// 键的生存时间随值一起转移到 `newkey`。如果 `key` 不存在，则返回错误。
+#[derive(Debug)]
+pub struct Rename {
+    /// 要重命名的键
+    key: String,
+
+    /// 新的键名
+    new_key: String,
+
+    /// 是否只在 `new_key` 不存在时才重命名（`RENAMENX`）
+    nx: bool,
+}
+
+impl Rename {
+    /// 创建一个新的 `RENAME` 命令，将 `key` 重命名为 `new_key`。
+    pub fn new(key: impl ToString, new_key: impl ToString) -> Self {
+        Self {
+            key: key.to_string(),
+            new_key: new_key.to_string(),
+            nx: false,
+        }
+    }
+
+    /// 创建一个新的 `RENAMENX` 命令，只在 `new_key` 不存在时将 `key` 重命名为 `new_key`。
+    pub fn nx(key: impl ToString, new_key: impl ToString) -> Self {
+        Self {
+            nx: true,
+            ..Self::new(key, new_key)
+        }
+    }
+
+    /// 获取要重命名的键
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// 获取新的键名
+    pub fn new_key(&self) -> &str {
+        &self.new_key
+    }
+
+    /// 如果这是 `RENAMENX` 命令，则返回 `true`。
+    pub fn is_nx(&self) -> bool {
+        self.nx
+    }
+
+    /// 将 `Rename` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 响应写入 `dst`。这是由服务器调用以执行接收到的命令。
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection<impl AsyncStream>) -> crate::Result<()> {
+        let response = match db.rename(&self.key, self.new_key, self.nx) {
+            None => Frame::Error("ERR no such key".to_string()),
+            Some(renamed) if self.nx => Frame::Integer(renamed as i64),
+            Some(_) => Frame::Simple("OK".to_string()),
+        };
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+
+    /// 从接收到的帧中解析出一个 `Rename` 实例。
+    ///
+    /// `RENAME` 或 `RENAMENX` 字符串已经被消费。
+    ///
+    /// # 格式
+    ///
+    /// ```text
+    /// RENAME key newkey
+    /// RENAMENX key newkey
+    /// ```
+    pub(crate) fn parse(parser: &mut Parser, nx: bool) -> crate::Result<Self> {
+        let key = parser.next_string()?;
+        let new_key = parser.next_string()?;
+
+        Ok(Self { key, new_key, nx })
+    }
+}
+
+/// 将命令转换为等效的 `Frame`。
+impl From<Rename> for Frame {
+    fn from(rename: Rename) -> Self {
+        let name = if rename.nx { "renamenx" } else { "rename" };
+
+        let mut frame = Self::array();
+        frame.push_bulk(Bytes::from(name.as_bytes()));
+        frame.push_bulk(Bytes::from(rename.key.into_bytes()));
+        frame.push_bulk(Bytes::from(rename.new_key.into_bytes()));
+
+        frame
+    }
+}
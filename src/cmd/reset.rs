@@ -0,0 +1,44 @@
+use crate::{Frame, Parser};
+
+use bytes::Bytes;
+
+/// 将连接恢复到初始状态。
+///
+/// 退出订阅模式并取消所有订阅，丢弃正在进行的事务，然后回复 `RESET`。之后连接可以像新连接一样执行任何命令。
+/// 连接的状态属于连接处理程序，因此由它执行。
+#[derive(Debug, Default)]
+pub struct Reset;
+
+impl Reset {
+    /// 创建一个新的 `Reset` 命令。
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+/// 从接收到的帧中解析出一个 `Reset` 实例。
+///
+/// `RESET` 字符串已经被消费。
+///
+/// # 格式
+///
+/// ```text
+/// RESET
+/// ```
+impl TryFrom<&mut Parser> for Reset {
+    type Error = crate::Error;
+
+    fn try_from(_parser: &mut Parser) -> crate::Result<Self> {
+        Ok(Self)
+    }
+}
+
+/// 将命令转换为等效的 `Frame`。
+impl From<Reset> for Frame {
+    fn from(_: Reset) -> Self {
+        let mut frame = Self::array();
+        frame.push_bulk(Bytes::from("reset".as_bytes()));
+
+        frame
+    }
+}
@@ -0,0 +1,92 @@
+use crate::cmd::{Parser, ParserError};
+use crate::{AsyncStream, Connection, Db, Frame};
+
+use bytes::Bytes;
+use tracing::{debug, instrument};
+
+/// 向集合中添加一个或多个成员。
+///
+/// 键不存在时先创建一个空集合。响应是新增的成员数，已经存在的成员不计入。
+#[derive(Debug)]
+pub struct SAdd {
+    /// 集合的键
+    key: String,
+    /// 要添加的成员
+    members: Vec<Bytes>,
+}
+
+impl SAdd {
+    /// 创建一个新的 `SAdd` 命令，向 `key` 集合添加 `members`。
+    pub fn new(key: impl ToString, members: Vec<Bytes>) -> Self {
+        Self {
+            key: key.to_string(),
+            members,
+        }
+    }
+
+    /// 获取键
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// 将 `SAdd` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 响应写入 `dst`。这是由服务器调用以执行接收到的命令。
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection<impl AsyncStream>) -> crate::Result<()> {
+        let response = match db.sadd(self.key, self.members) {
+            Ok(count) => Frame::Integer(count as i64),
+            Err(err) => Frame::Error(err.to_string()),
+        };
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+}
+
+/// 从接收到的帧中解析出一个 `SAdd` 实例。
+///
+/// `SADD` 字符串已经被消费。
+///
+/// # 格式
+///
+/// ```text
+/// SADD key member [member ...]
+/// ```
+impl TryFrom<&mut Parser> for SAdd {
+    type Error = crate::Error;
+
+    fn try_from(parser: &mut Parser) -> crate::Result<Self> {
+        use ParserError::EndOfStream;
+
+        let key = parser.next_string()?;
+
+        let mut members = vec![parser.next_bytes()?];
+        loop {
+            match parser.next_bytes() {
+                Ok(member) => members.push(member),
+                Err(EndOfStream) => break,
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        Ok(Self { key, members })
+    }
+}
+
+/// 将命令转换为等效的 `Frame`。
+impl From<SAdd> for Frame {
+    fn from(cmd: SAdd) -> Self {
+        let mut frame = Self::array();
+        frame.push_bulk(Bytes::from("sadd".as_bytes()));
+        frame.push_bulk(Bytes::from(cmd.key.into_bytes()));
+        for member in cmd.members {
+            frame.push_bulk(member);
+        }
+
+        frame
+    }
+}
@@ -0,0 +1,65 @@
+use crate::cmd::Parser;
+use crate::{AsyncStream, Connection, Db, Frame};
+
+use bytes::Bytes;
+use tracing::{debug, instrument};
+
+/// 把键空间保存到快照文件。
+///
+/// 快照写入服务器配置的 `dbfilename`，服务器下次启动时从中恢复。没有配置文件时回复错误。
+#[derive(Debug, Default)]
+pub struct Save;
+
+impl Save {
+    /// 创建一个新的 `Save` 命令。
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// 将 `Save` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 响应写入 `dst`。这是由服务器调用以执行接收到的命令。
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection<impl AsyncStream>) -> crate::Result<()> {
+        let response = match db.snapshot_path() {
+            Some(path) => match db.snapshot_to(&path).await {
+                Ok(()) => Frame::Simple("OK".to_string()),
+                Err(err) => Frame::Error(format!("ERR failed to save snapshot: {}", err)),
+            },
+            None => Frame::Error("ERR no snapshot file configured".to_string()),
+        };
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+}
+
+/// 从接收到的帧中解析出一个 `Save` 实例。
+///
+/// `SAVE` 字符串已经被消费。
+///
+/// # 格式
+///
+/// ```text
+/// SAVE
+/// ```
+impl TryFrom<&mut Parser> for Save {
+    type Error = crate::Error;
+
+    fn try_from(_parser: &mut Parser) -> crate::Result<Self> {
+        Ok(Self)
+    }
+}
+
+/// 将命令转换为等效的 `Frame`。
+impl From<Save> for Frame {
+    fn from(_: Save) -> Self {
+        let mut frame = Self::array();
+        frame.push_bulk(Bytes::from("save".as_bytes()));
+
+        frame
+    }
+}
@@ -0,0 +1,120 @@
+use crate::cmd::{InvalidArgument, Parser, ParserError, SyntaxError};
+use crate::{AsyncStream, Connection, Db, Frame};
+
+use bytes::Bytes;
+use tracing::{debug, instrument};
+
+/// 未指定 `COUNT` 时每次迭代检查的键数，与 Redis 相同。
+const DEFAULT_COUNT: u64 = 10;
+
+/// 基于游标增量迭代键空间。
+///
+/// 与一次返回所有键不同，每次调用只检查一小批键，并返回下一次调用使用的游标。游标从 `0` 开始，返回 `0` 时迭代结束。
+///
+/// `MATCH` 在选出一批键之后才过滤，因此一次调用可能返回很少甚至零个键，但游标不为 `0`。`COUNT` 是每次检查的键数，
+/// 而不是返回的键数。
+///
+/// 游标是排序后的键列表中的偏移量。迭代期间键空间保持不变时，每个键恰好返回一次；但与 Redis 不同，迭代期间
+/// 添加或删除键会移动偏移量，可能导致某些键被跳过或重复返回。
+#[derive(Debug)]
+pub struct Scan {
+    /// 开始迭代的游标
+    cursor: u64,
+    /// 只返回与该模式匹配的键
+    pattern: Option<String>,
+    /// 每次检查的键数
+    count: u64,
+}
+
+impl Scan {
+    /// 创建一个新的 `Scan` 命令，从 `cursor` 继续迭代。
+    pub fn new(cursor: u64, pattern: Option<String>, count: Option<u64>) -> Self {
+        Self {
+            cursor,
+            pattern,
+            count: count.unwrap_or(DEFAULT_COUNT),
+        }
+    }
+
+    /// 将 `Scan` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 响应写入 `dst`。这是由服务器调用以执行接收到的命令。
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection<impl AsyncStream>) -> crate::Result<()> {
+        let response = match db.scan(self.cursor, self.pattern.as_deref(), self.count) {
+            Ok((cursor, keys)) => {
+                // 响应是 `[cursor, [key, ...]]`，游标以字符串形式返回，与 Redis 一致。
+                let mut batch = Frame::array();
+                for key in keys {
+                    batch.push_bulk(Bytes::from(key));
+                }
+                Frame::Array(vec![Frame::Bulk(Bytes::from(cursor.to_string())), batch])
+            }
+            Err(err) => Frame::Error(err.to_string()),
+        };
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+}
+
+/// 从接收到的帧中解析出一个 `Scan` 实例。
+///
+/// `SCAN` 字符串已经被消费。
+///
+/// # 格式
+///
+/// ```text
+/// SCAN cursor [MATCH pattern] [COUNT count]
+/// ```
+impl TryFrom<&mut Parser> for Scan {
+    type Error = crate::Error;
+
+    fn try_from(parser: &mut Parser) -> crate::Result<Self> {
+        use ParserError::EndOfStream;
+
+        let cursor = match parser.next_int() {
+            Ok(cursor) => u64::try_from(cursor).ok(),
+            Err(EndOfStream) => return Err(EndOfStream.into()),
+            Err(_) => None,
+        };
+        let cursor = cursor.ok_or_else(|| InvalidArgument::new("invalid cursor"))?;
+        let mut scan = Self::new(cursor, None, None);
+        loop {
+            match parser.next_string() {
+                Ok(s) if s.to_uppercase() == "MATCH" => scan.pattern = Some(parser.next_string()?),
+                Ok(s) if s.to_uppercase() == "COUNT" => {
+                    scan.count = match u64::try_from(parser.next_int()?) {
+                        Ok(count) if count > 0 => count,
+                        _ => return Err(SyntaxError.into()),
+                    };
+                }
+                Ok(_) => return Err(SyntaxError.into()),
+                Err(EndOfStream) => break,
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        Ok(scan)
+    }
+}
+
+/// 将命令转换为等效的 `Frame`。
+impl From<Scan> for Frame {
+    fn from(cmd: Scan) -> Self {
+        let mut frame = Self::array();
+        frame.push_bulk(Bytes::from("scan".as_bytes()));
+        frame.push_bulk(Bytes::from(cmd.cursor.to_string()));
+        if let Some(pattern) = cmd.pattern {
+            frame.push_bulk(Bytes::from("match".as_bytes()));
+            frame.push_bulk(Bytes::from(pattern.into_bytes()));
+        }
+        frame.push_bulk(Bytes::from("count".as_bytes()));
+        frame.push_bulk(Bytes::from(cmd.count.to_string()));
+
+        frame
+    }
+}
@@ -0,0 +1,54 @@
+use crate::cmd::{Parser, ParserError};
+use crate::{AsyncStream, Connection, Frame};
+
+use tracing::{debug, instrument};
+
+/// 脚本命令：`SCRIPT`、`EVAL` 和 `EVALSHA`。
+///
+/// mini-redis 不支持 Lua 脚本。这些命令会被识别并回复一个明确的错误，而不是走未知命令的路径，
+/// 这样探测脚本能力的客户端库可以检测到不支持并回退到普通命令。
+#[derive(Debug)]
+pub struct Script {
+    /// 客户端发出的命令名称。
+    cmd_name: String,
+}
+
+impl Script {
+    /// 创建一个新的 `Script` 命令，`cmd_name` 为客户端发出的命令名称。
+    pub(crate) fn new(cmd_name: impl ToString) -> Self {
+        Self {
+            cmd_name: cmd_name.to_string(),
+        }
+    }
+
+    /// 返回命令名称
+    pub(crate) fn get_name(&self) -> &str {
+        &self.cmd_name
+    }
+
+    /// 响应客户端，指示不支持脚本。
+    #[instrument(skip(self, dst))]
+    pub(crate) async fn apply(self, dst: &mut Connection<impl AsyncStream>) -> crate::Result<()> {
+        let response = Frame::Error("ERR scripting is not supported by mini-redis".to_string());
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+        Ok(())
+    }
+
+    /// 从接收到的帧中解析出一个 `Script` 实例。
+    ///
+    /// 命令名称已经被消费。参数不会被解释，只是被丢弃。
+    pub(crate) fn parse(cmd_name: impl ToString, parser: &mut Parser) -> crate::Result<Self> {
+        loop {
+            match parser.next_bytes() {
+                Ok(_) => {}
+                Err(ParserError::EndOfStream) => break,
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        Ok(Self::new(cmd_name))
+    }
+}
@@ -0,0 +1,75 @@
+use crate::cmd::InvalidArgument;
+use crate::{AsyncStream, Connection, Db, Frame, Parser};
+
+use bytes::Bytes;
+use tracing::{debug, instrument};
+
+/// 选择连接使用的数据库。
+///
+/// 服务器有多个编号的数据库，每个都是独立的键空间，新连接使用 0 号数据库。选择之后，连接上所有与键有关的命令
+/// 都作用于选择的数据库；pub/sub 与数据库无关。编号超出范围时回复错误，连接继续使用原来的数据库。
+#[derive(Debug)]
+pub struct Select {
+    /// 要选择的数据库的编号
+    index: usize,
+}
+
+impl Select {
+    /// 创建一个新的 `Select` 命令，选择编号为 `index` 的数据库。
+    pub fn new(index: usize) -> Self {
+        Self { index }
+    }
+
+    /// 获取要选择的数据库的编号
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// 将 `Select` 命令应用于 `db` 所在的服务器。
+    ///
+    /// 响应写入 `dst`。选择成功时返回指向新数据库的句柄，由连接处理程序替换连接使用的 `Db`。
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection<impl AsyncStream>) -> crate::Result<Option<Db>> {
+        let selected = db.select(self.index);
+        let response = match selected {
+            Some(_) => Frame::Simple("OK".to_string()),
+            None => Frame::Error("ERR DB index is out of range".to_string()),
+        };
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(selected)
+    }
+}
+
+/// 从接收到的帧中解析出一个 `Select` 实例。
+///
+/// `SELECT` 字符串已经被消费。
+///
+/// # 格式
+///
+/// ```text
+/// SELECT index
+/// ```
+impl TryFrom<&mut Parser> for Select {
+    type Error = crate::Error;
+
+    fn try_from(parser: &mut Parser) -> crate::Result<Self> {
+        let index = usize::try_from(parser.next_int()?).map_err(|_| InvalidArgument::new("DB index is out of range"))?;
+
+        Ok(Self { index })
+    }
+}
+
+/// 将命令转换为等效的 `Frame`。
+impl From<Select> for Frame {
+    fn from(cmd: Select) -> Self {
+        let mut frame = Self::array();
+        frame.push_bulk(Bytes::from("select".as_bytes()));
+        frame.push_bulk(Bytes::from(cmd.index.to_string()));
+
+        frame
+    }
+}
@@ -1,14 +1,15 @@
-use crate::cmd::{Parser, ParserError};
-use crate::{Connection, Db, Frame};
+use crate::cmd::expireat::{instant_at, unix_millis_after};
+use crate::cmd::{InvalidArgument, Parser, ParserError, SyntaxError};
+use crate::{AsyncStream, Connection, Db, Frame};
 
 use bytes::Bytes;
-use std::time::Duration;
+use tokio::time::{Duration, Instant};
 use tracing::{debug, instrument};
 
 /// 将 `key` 设置为保存字符串 `value`。
 ///
 /// 如果 `key` 已经保存了一个值，则无论其类型如何，都会被覆盖。
-/// 任何与键关联的先前生存时间在成功的 SET 操作中都会被丢弃。
+/// 除非给出 `KEEPTTL`，任何与键关联的先前生存时间在成功的 SET 操作中都会被丢弃。
 ///
 /// # 选项
 ///
@@ -16,6 +17,12 @@ use tracing::{debug, instrument};
 ///
 /// * EX `seconds` -- 设置指定的过期时间，以秒为单位。
 /// * PX `milliseconds` -- 设置指定的过期时间，以毫秒为单位。
+/// * EXAT `timestamp` -- 在指定的 Unix 时间过期，以秒为单位。
+/// * PXAT `timestamp` -- 在指定的 Unix 时间过期，以毫秒为单位。
+/// * KEEPTTL -- 保留键原有的生存时间。不能与其他过期时间选项同时使用。
+/// * NX -- 仅当键不存在时才设置。
+/// * XX -- 仅当键已存在时才设置。
+/// * GET -- 返回键先前的值，而不是 `OK`。
 #[derive(Debug)]
 pub struct Set {
     /// 查找键
@@ -24,6 +31,23 @@ pub struct Set {
     value: Bytes,
     /// 键的过期时间
     expire: Option<Duration>,
+    /// 键过期的 Unix 时间，以毫秒为单位（`EXAT` 或 `PXAT`）
+    expire_at: Option<u64>,
+    /// 是否保留键原有的过期时间（`KEEPTTL`）
+    keep_ttl: bool,
+    /// 写入必须满足的条件（`NX` 或 `XX`）
+    condition: Option<SetCondition>,
+    /// 是否返回先前的值（`GET`）
+    get: bool,
+}
+
+/// `SET` 写入的前提条件。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SetCondition {
+    /// `NX`：仅当键不存在时写入。
+    NotExists,
+    /// `XX`：仅当键已存在时写入。
+    Exists,
 }
 
 impl Set {
@@ -35,24 +59,91 @@ impl Set {
             key: key.to_string(),
             value,
             expire,
+            expire_at: None,
+            keep_ttl: false,
+            condition: None,
+            get: false,
         }
     }
 
+    /// 在 Unix 时间 `unix_millis`（毫秒）过期（`PXAT`），替换 `expire`。
+    pub fn with_expire_at(mut self, unix_millis: u64) -> Self {
+        self.expire = None;
+        self.expire_at = Some(unix_millis);
+        self
+    }
+
+    /// 保留键原有的过期时间（`KEEPTTL`），而不是清除它。`expire` 被忽略。
+    pub fn with_keep_ttl(mut self) -> Self {
+        self.keep_ttl = true;
+        self
+    }
+
+    /// 设置写入的前提条件（`NX` 或 `XX`）。
+    pub fn with_condition(mut self, condition: SetCondition) -> Self {
+        self.condition = Some(condition);
+        self
+    }
+
+    /// 请求服务器返回键先前的值（`GET`）。
+    pub fn with_get(mut self) -> Self {
+        self.get = true;
+        self
+    }
+
+    /// 获取键
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// 把相对的过期时间（`EX`/`PX`）换算成 Unix 时间，返回等效的 `PXAT` 命令。没有相对的过期时间时返回 `None`。
+    pub(crate) fn absolute_expiry(&self) -> Option<Self> {
+        let expire = self.expire.filter(|_| !self.keep_ttl)?;
+        Some(Self {
+            key: self.key.clone(),
+            value: self.value.clone(),
+            expire: None,
+            expire_at: Some(unix_millis_after(expire)?),
+            keep_ttl: false,
+            condition: self.condition,
+            get: self.get,
+        })
+    }
+
     /// 将 `Set` 命令应用于指定的 `Db` 实例。
     ///
     /// 响应写入 `dst`。这是由服务器调用以执行接收到的命令。
     #[instrument(skip(self, db, dst))]
-    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection) -> crate::Result<()> {
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection<impl AsyncStream>) -> crate::Result<()> {
         // 在共享数据库状态中设置值。
-        db.set(self.key, self.value, self.expire);
-
-        // 创建一个成功响应并将其写入 `dst`。
-        let response = Frame::Simple("OK".to_string());
+        //
+        // 带有 `GET` 时返回先前的值；否则条件不满足时返回 `Null`，成功时返回 `OK`。
+        let response = match self.expire() {
+            Ok(expire) => match db.set(self.key, self.value, expire, self.keep_ttl, self.condition) {
+                Ok((_, previous)) if self.get => previous.map_or(Frame::Null, Frame::Bulk),
+                Ok((true, _)) => Frame::Simple("OK".to_string()),
+                Ok((false, _)) => Frame::Null,
+                Err(err) => Frame::Error(err.to_string()),
+            },
+            Err(response) => response,
+        };
         debug!(?response);
         dst.write_frame(&response).await?;
 
         Ok(())
     }
+
+    /// 返回从现在起的过期时间。`EXAT`/`PXAT` 给出的时间已经过去时返回零，键立即过期；时间太大无法表示时
+    /// 返回错误响应。
+    fn expire(&self) -> Result<Option<Duration>, Frame> {
+        match self.expire_at {
+            Some(millis) => match instant_at(Duration::from_millis(millis)) {
+                Some(when) => Ok(Some(when.saturating_duration_since(Instant::now()))),
+                None => Err(Frame::Error("ERR invalid expire time in 'set' command".to_string())),
+            },
+            None => Ok(self.expire),
+        }
+    }
 }
 
 /// 从接收到的帧中解析出一个 `Set` 实例。
@@ -70,8 +161,10 @@ impl Set {
 /// 期望一个包含至少 3 个条目的数组帧。
 ///
 /// ```text
-/// SET key value [EX seconds|PX milliseconds]
+/// SET key value [EX seconds|PX milliseconds|EXAT timestamp|PXAT milliseconds-timestamp|KEEPTTL] [NX|XX] [GET]
 /// ```
+///
+/// 选项的顺序无关紧要。
 impl TryFrom<&mut Parser> for Set {
     type Error = crate::Error;
 
@@ -82,34 +175,67 @@ impl TryFrom<&mut Parser> for Set {
         let key = parser.next_string()?;
         // 读取要设置的值。这是一个必填字段。
         let value = parser.next_bytes()?;
-        // 过期时间是可选的。如果没有其他内容，则为 `None`。
-        let mut expire = None;
-        // 尝试解析另一个字符串。
-        match parser.next_string() {
-            Ok(s) if s.to_uppercase() == "EX" => {
-                // 过期时间以秒为单位指定。下一个值是一个整数。
-                let secs = parser.next_int()?;
-                expire = Some(Duration::from_secs(secs));
-            }
-            Ok(s) if s.to_uppercase() == "PX" => {
-                // 过期时间以毫秒为单位指定。下一个值是一个整数。
-                let ms = parser.next_int()?;
-                expire = Some(Duration::from_millis(ms));
+        let mut set = Self::new(key, value, None);
+        // `EX`、`PX`、`EXAT`、`PXAT` 和 `KEEPTTL` 互相冲突，因此只能出现其中一个。
+        let no_ttl = |set: &Self| set.expire.is_none() && set.expire_at.is_none() && !set.keep_ttl;
+        // 其余的都是选项。逐个读取，直到没有更多数据。
+        loop {
+            match parser.next_string() {
+                Ok(s) if s.to_uppercase() == "EX" && no_ttl(&set) => {
+                    // 过期时间以秒为单位指定。下一个值是一个整数。
+                    set.expire = Some(Duration::from_secs(next_expire_time(parser)?));
+                }
+                Ok(s) if s.to_uppercase() == "PX" && no_ttl(&set) => {
+                    // 过期时间以毫秒为单位指定。下一个值是一个整数。
+                    set.expire = Some(Duration::from_millis(next_expire_time(parser)?));
+                }
+                Ok(s) if s.to_uppercase() == "EXAT" && no_ttl(&set) => {
+                    // 以秒为单位的 Unix 时间，换算成毫秒。
+                    let ms = next_expire_time(parser)?.checked_mul(1000);
+                    set.expire_at = Some(ms.ok_or_else(invalid_expire_time)?);
+                }
+                Ok(s) if s.to_uppercase() == "PXAT" && no_ttl(&set) => {
+                    set.expire_at = Some(next_expire_time(parser)?);
+                }
+                Ok(s) if s.to_uppercase() == "KEEPTTL" && no_ttl(&set) => {
+                    set.keep_ttl = true;
+                }
+                // `NX` 和 `XX` 互相冲突，因此只能出现其中一个。
+                Ok(s) if s.to_uppercase() == "NX" && set.condition.is_none() => {
+                    set.condition = Some(SetCondition::NotExists);
+                }
+                Ok(s) if s.to_uppercase() == "XX" && set.condition.is_none() => {
+                    set.condition = Some(SetCondition::Exists);
+                }
+                Ok(s) if s.to_uppercase() == "GET" && !set.get => set.get = true,
+                // 目前，mini-redis 不支持任何其他 SET 选项，也不允许重复或冲突的选项。
+                // 帧本身是完整的，因此只回复 `ERR syntax error`，连接继续正常运行。
+                Ok(_) => return Err(SyntaxError.into()),
+                // `EndOfStream` 错误表示没有更多数据可解析。在这种情况下，这是正常的运行时情况，
+                // 表示没有更多的 `SET` 选项。
+                Err(EndOfStream) => break,
+                // 所有其他错误都会冒泡，导致连接被终止。
+                Err(err) => return Err(err.into()),
             }
-            // 目前，mini-redis 不支持任何其他 SET 选项。此处的错误会导致连接被终止。
-            // 其他连接将继续正常运行。
-            Ok(_) => return Err("currently `SET` only supports the expiration option".into()),
-            // `EndOfStream` 错误表示没有更多数据可解析。在这种情况下，这是正常的运行时情况，
-            // 表示没有指定的 `SET` 选项。
-            Err(EndOfStream) => {}
-            // 所有其他错误都会冒泡，导致连接被终止。
-            Err(err) => return Err(err.into()),
         }
 
-        Ok(Self { key, value, expire })
+        Ok(set)
     }
 }
 
+/// 读取 `EX`、`PX`、`EXAT` 或 `PXAT` 之后的过期时间。与 Redis 一致，过期时间必须是正整数，
+/// 零和负数回复错误，不关闭连接。
+fn next_expire_time(parser: &mut Parser) -> crate::Result<u64> {
+    let value = parser.next_int()?;
+    let value = u64::try_from(value).ok().filter(|value| *value > 0);
+    Ok(value.ok_or_else(invalid_expire_time)?)
+}
+
+/// 过期时间无效时的错误。
+fn invalid_expire_time() -> InvalidArgument {
+    InvalidArgument::new("invalid expire time in 'set' command")
+}
+
 /// 将命令转换为等效的 `Frame`。
 ///
 /// 这是由客户端在编码 `Set` 命令以发送到服务器时调用的。
@@ -119,7 +245,8 @@ impl From<Set> for Frame {
         frame.push_bulk(Bytes::from("set".as_bytes()));
         frame.push_bulk(Bytes::from(set.key.into_bytes()));
         frame.push_bulk(set.value);
-        if let Some(ms) = set.expire {
+        // 保留过期时间时忽略 `expire`。
+        if let Some(ms) = set.expire.filter(|_| !set.keep_ttl) {
             // Redis 协议中的过期时间可以通过两种方式指定
             // 1. SET key value EX seconds
             // 2. SET key value PX milliseconds
@@ -127,7 +254,22 @@ impl From<Set> for Frame {
             // src/bin/cli.rs 将过期参数解析为毫秒
             // 在 duration_from_ms_str() 中
             frame.push_bulk(Bytes::from("px".as_bytes()));
-            frame.push_int(ms.as_millis() as u64);
+            frame.push_int(ms.as_millis() as i64);
+        }
+        if let Some(ms) = set.expire_at.filter(|_| !set.keep_ttl) {
+            frame.push_bulk(Bytes::from("pxat".as_bytes()));
+            frame.push_int(ms as i64);
+        }
+        if set.keep_ttl {
+            frame.push_bulk(Bytes::from("keepttl".as_bytes()));
+        }
+        match set.condition {
+            Some(SetCondition::NotExists) => frame.push_bulk(Bytes::from("nx".as_bytes())),
+            Some(SetCondition::Exists) => frame.push_bulk(Bytes::from("xx".as_bytes())),
+            None => {}
+        }
+        if set.get {
+            frame.push_bulk(Bytes::from("get".as_bytes()));
         }
 
         frame
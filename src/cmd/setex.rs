@@ -0,0 +1,197 @@
+use crate::cmd::expireat::unix_millis_after;
+use crate::cmd::{Parser, Set};
+use crate::{AsyncStream, Connection, Db, Frame};
+
+use bytes::Bytes;
+use std::time::Duration;
+use tracing::{debug, instrument};
+
+/// 将 `key` 设置为保存字符串 `value`，并在 `seconds` 秒后过期。
+///
+/// 等价于 `SET key value EX seconds`。过期时间必须为正数，否则返回错误且不写入。
+#[derive(Debug)]
+pub struct SetEx {
+    /// 查找键
+    key: String,
+    /// 过期时间，以秒为单位
+    seconds: i64,
+    /// 要存储的值
+    value: Bytes,
+}
+
+impl SetEx {
+    /// 创建一个新的 `SetEx` 命令，将 `key` 设置为 `value`，`seconds` 秒后过期。
+    pub fn new(key: impl ToString, seconds: i64, value: Bytes) -> Self {
+        Self {
+            key: key.to_string(),
+            seconds,
+            value,
+        }
+    }
+
+    /// 获取键
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// 把过期时间换算成 Unix 时间，返回等效的 `SET key value PXAT` 命令。过期时间无效时返回 `None`，
+    /// 命令按原样记录，重放时同样回复错误。
+    pub(crate) fn absolute_expiry(&self) -> Option<Set> {
+        let seconds = u64::try_from(self.seconds).ok().filter(|&seconds| seconds > 0)?;
+        let millis = unix_millis_after(Duration::from_secs(seconds))?;
+        Some(Set::new(&self.key, self.value.clone(), None).with_expire_at(millis))
+    }
+
+    /// 将 `SetEx` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 响应写入 `dst`。这是由服务器调用以执行接收到的命令。
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection<impl AsyncStream>) -> crate::Result<()> {
+        let response = match u64::try_from(self.seconds) {
+            Ok(seconds) if seconds > 0 => {
+                match db.set(self.key, self.value, Some(Duration::from_secs(seconds)), false, None) {
+                    Ok(_) => Frame::Simple("OK".to_string()),
+                    Err(err) => Frame::Error(err.to_string()),
+                }
+            }
+            _ => Frame::Error("ERR invalid expire time in 'setex' command".to_string()),
+        };
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+}
+
+/// 从接收到的帧中解析出一个 `SetEx` 实例。
+///
+/// `SETEX` 字符串已经被消费。
+///
+/// # 格式
+///
+/// ```text
+/// SETEX key seconds value
+/// ```
+impl TryFrom<&mut Parser> for SetEx {
+    type Error = crate::Error;
+
+    fn try_from(parser: &mut Parser) -> crate::Result<Self> {
+        let key = parser.next_string()?;
+        let seconds = parser.next_int()?;
+        let value = parser.next_bytes()?;
+
+        Ok(Self { key, seconds, value })
+    }
+}
+
+/// 将命令转换为等效的 `Frame`。
+impl From<SetEx> for Frame {
+    fn from(cmd: SetEx) -> Self {
+        let mut frame = Self::array();
+        frame.push_bulk(Bytes::from("setex".as_bytes()));
+        frame.push_bulk(Bytes::from(cmd.key.into_bytes()));
+        frame.push_bulk(Bytes::from(cmd.seconds.to_string()));
+        frame.push_bulk(cmd.value);
+
+        frame
+    }
+}
+
+/// 将 `key` 设置为保存字符串 `value`，并在 `milliseconds` 毫秒后过期。
+///
+/// 等价于 `SET key value PX milliseconds`。过期时间必须为正数，否则返回错误且不写入。
+#[derive(Debug)]
+pub struct PSetEx {
+    /// 查找键
+    key: String,
+    /// 过期时间，以毫秒为单位
+    milliseconds: i64,
+    /// 要存储的值
+    value: Bytes,
+}
+
+impl PSetEx {
+    /// 创建一个新的 `PSetEx` 命令，将 `key` 设置为 `value`，`milliseconds` 毫秒后过期。
+    pub fn new(key: impl ToString, milliseconds: i64, value: Bytes) -> Self {
+        Self {
+            key: key.to_string(),
+            milliseconds,
+            value,
+        }
+    }
+
+    /// 获取键
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// 把过期时间换算成 Unix 时间，返回等效的 `SET key value PXAT` 命令。过期时间无效时返回 `None`，
+    /// 命令按原样记录，重放时同样回复错误。
+    pub(crate) fn absolute_expiry(&self) -> Option<Set> {
+        let milliseconds = u64::try_from(self.milliseconds).ok().filter(|&milliseconds| milliseconds > 0)?;
+        let millis = unix_millis_after(Duration::from_millis(milliseconds))?;
+        Some(Set::new(&self.key, self.value.clone(), None).with_expire_at(millis))
+    }
+
+    /// 将 `PSetEx` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 响应写入 `dst`。这是由服务器调用以执行接收到的命令。
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection<impl AsyncStream>) -> crate::Result<()> {
+        let response = match u64::try_from(self.milliseconds) {
+            Ok(milliseconds) if milliseconds > 0 => {
+                match db.set(self.key, self.value, Some(Duration::from_millis(milliseconds)), false, None) {
+                    Ok(_) => Frame::Simple("OK".to_string()),
+                    Err(err) => Frame::Error(err.to_string()),
+                }
+            }
+            _ => Frame::Error("ERR invalid expire time in 'psetex' command".to_string()),
+        };
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+}
+
+/// 从接收到的帧中解析出一个 `PSetEx` 实例。
+///
+/// `PSETEX` 字符串已经被消费。
+///
+/// # 格式
+///
+/// ```text
+/// PSETEX key milliseconds value
+/// ```
+impl TryFrom<&mut Parser> for PSetEx {
+    type Error = crate::Error;
+
+    fn try_from(parser: &mut Parser) -> crate::Result<Self> {
+        let key = parser.next_string()?;
+        let milliseconds = parser.next_int()?;
+        let value = parser.next_bytes()?;
+
+        Ok(Self {
+            key,
+            milliseconds,
+            value,
+        })
+    }
+}
+
+/// 将命令转换为等效的 `Frame`。
+impl From<PSetEx> for Frame {
+    fn from(cmd: PSetEx) -> Self {
+        let mut frame = Self::array();
+        frame.push_bulk(Bytes::from("psetex".as_bytes()));
+        frame.push_bulk(Bytes::from(cmd.key.into_bytes()));
+        frame.push_bulk(Bytes::from(cmd.milliseconds.to_string()));
+        frame.push_bulk(cmd.value);
+
+        frame
+    }
+}
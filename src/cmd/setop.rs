@@ -0,0 +1,213 @@
+use crate::cmd::{Parser, ParserError};
+use crate::db::SetOp;
+use crate::{AsyncStream, Connection, Db, Frame};
+
+use bytes::Bytes;
+use tracing::{debug, instrument};
+
+/// 返回所有给定集合的交集。
+///
+/// 不存在的键视为空集合，因此只要有一个键不存在，结果就是空数组。成员的顺序不确定。
+/// 任何一个键保存的不是集合时回复 `WRONGTYPE` 错误。
+#[derive(Debug)]
+pub struct SInter {
+    /// 集合的键
+    keys: Vec<String>,
+}
+
+/// 返回所有给定集合的并集。
+///
+/// 不存在的键视为空集合。成员的顺序不确定。任何一个键保存的不是集合时回复 `WRONGTYPE` 错误。
+#[derive(Debug)]
+pub struct SUnion {
+    /// 集合的键
+    keys: Vec<String>,
+}
+
+/// 返回第一个集合与其余所有集合的差集，即只在第一个集合中出现的成员。
+///
+/// 不存在的键视为空集合。成员的顺序不确定。任何一个键保存的不是集合时回复 `WRONGTYPE` 错误。
+#[derive(Debug)]
+pub struct SDiff {
+    /// 集合的键，第一个是被减去的集合
+    keys: Vec<String>,
+}
+
+impl SInter {
+    /// 创建一个新的 `SInter` 命令，返回 `keys` 中所有集合的交集。
+    pub fn new(keys: Vec<String>) -> Self {
+        Self { keys }
+    }
+
+    /// 获取键
+    pub fn keys(&self) -> &[String] {
+        &self.keys
+    }
+
+    /// 将 `SInter` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 响应写入 `dst`。这是由服务器调用以执行接收到的命令。
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection<impl AsyncStream>) -> crate::Result<()> {
+        apply_op(&self.keys, SetOp::Inter, db, dst).await
+    }
+}
+
+impl SUnion {
+    /// 创建一个新的 `SUnion` 命令，返回 `keys` 中所有集合的并集。
+    pub fn new(keys: Vec<String>) -> Self {
+        Self { keys }
+    }
+
+    /// 获取键
+    pub fn keys(&self) -> &[String] {
+        &self.keys
+    }
+
+    /// 将 `SUnion` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 响应写入 `dst`。这是由服务器调用以执行接收到的命令。
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection<impl AsyncStream>) -> crate::Result<()> {
+        apply_op(&self.keys, SetOp::Union, db, dst).await
+    }
+}
+
+impl SDiff {
+    /// 创建一个新的 `SDiff` 命令，返回 `keys` 中第一个集合与其余集合的差集。
+    pub fn new(keys: Vec<String>) -> Self {
+        Self { keys }
+    }
+
+    /// 获取键
+    pub fn keys(&self) -> &[String] {
+        &self.keys
+    }
+
+    /// 将 `SDiff` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 响应写入 `dst`。这是由服务器调用以执行接收到的命令。
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection<impl AsyncStream>) -> crate::Result<()> {
+        apply_op(&self.keys, SetOp::Diff, db, dst).await
+    }
+}
+
+/// `SINTER`、`SUNION` 和 `SDIFF` 的共同逻辑：执行 `op` 并把结果作为数组回复。
+async fn apply_op(keys: &[String], op: SetOp, db: &Db, dst: &mut Connection<impl AsyncStream>) -> crate::Result<()> {
+    let response = match db.set_op(keys, op) {
+        Ok(members) => {
+            let mut response = Frame::array();
+            for member in members {
+                response.push_bulk(member);
+            }
+            response
+        }
+        Err(err) => Frame::Error(err.to_string()),
+    };
+
+    debug!(?response);
+
+    dst.write_frame(&response).await?;
+
+    Ok(())
+}
+
+/// 从接收到的帧中解析出一个 `SInter` 实例。
+///
+/// `SINTER` 字符串已经被消费。
+///
+/// # 格式
+///
+/// ```text
+/// SINTER key [key ...]
+/// ```
+impl TryFrom<&mut Parser> for SInter {
+    type Error = crate::Error;
+
+    fn try_from(parser: &mut Parser) -> crate::Result<Self> {
+        Ok(Self::new(parse_keys(parser)?))
+    }
+}
+
+/// 从接收到的帧中解析出一个 `SUnion` 实例。
+///
+/// `SUNION` 字符串已经被消费。
+///
+/// # 格式
+///
+/// ```text
+/// SUNION key [key ...]
+/// ```
+impl TryFrom<&mut Parser> for SUnion {
+    type Error = crate::Error;
+
+    fn try_from(parser: &mut Parser) -> crate::Result<Self> {
+        Ok(Self::new(parse_keys(parser)?))
+    }
+}
+
+/// 从接收到的帧中解析出一个 `SDiff` 实例。
+///
+/// `SDIFF` 字符串已经被消费。
+///
+/// # 格式
+///
+/// ```text
+/// SDIFF key [key ...]
+/// ```
+impl TryFrom<&mut Parser> for SDiff {
+    type Error = crate::Error;
+
+    fn try_from(parser: &mut Parser) -> crate::Result<Self> {
+        Ok(Self::new(parse_keys(parser)?))
+    }
+}
+
+/// 解析一个或多个键。
+fn parse_keys(parser: &mut Parser) -> crate::Result<Vec<String>> {
+    use ParserError::EndOfStream;
+
+    let mut keys = vec![parser.next_string()?];
+    loop {
+        match parser.next_string() {
+            Ok(key) => keys.push(key),
+            Err(EndOfStream) => break,
+            Err(err) => return Err(err.into()),
+        }
+    }
+
+    Ok(keys)
+}
+
+/// 将命令转换为等效的 `Frame`。
+impl From<SInter> for Frame {
+    fn from(cmd: SInter) -> Self {
+        to_frame("sinter", cmd.keys)
+    }
+}
+
+/// 将命令转换为等效的 `Frame`。
+impl From<SUnion> for Frame {
+    fn from(cmd: SUnion) -> Self {
+        to_frame("sunion", cmd.keys)
+    }
+}
+
+/// 将命令转换为等效的 `Frame`。
+impl From<SDiff> for Frame {
+    fn from(cmd: SDiff) -> Self {
+        to_frame("sdiff", cmd.keys)
+    }
+}
+
+/// 编码名称为 `name`、参数为 `keys` 的命令。
+fn to_frame(name: &str, keys: Vec<String>) -> Frame {
+    let mut frame = Frame::array();
+    frame.push_bulk(Bytes::from(name.to_string()));
+    for key in keys {
+        frame.push_bulk(Bytes::from(key.into_bytes()));
+    }
+
+    frame
+}
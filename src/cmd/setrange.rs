@@ -0,0 +1,96 @@
+use crate::{AsyncStream, Connection, Db, Frame, Parser};
+
+use bytes::Bytes;
+use tracing::{debug, instrument};
+
+/// 从 `offset` 开始用 `value` 覆盖键的值的一部分。
+///
+/// 如果 `offset` 超过值的当前长度，中间用零字节填充。键不存在时视为空字符串，但 `value` 为空时不会创建键。
+/// 键原有的生存时间保持不变。响应是修改后值的长度。
+#[derive(Debug)]
+pub struct SetRange {
+    /// 要修改的键的名称
+    key: String,
+    /// 开始覆盖的位置
+    offset: i64,
+    /// 写入的值
+    value: Bytes,
+}
+
+/// 值的最大长度，与 Redis 的默认 `proto-max-bulk-len` 相同。
+///
+/// 防止一个很大的 `offset` 让服务器分配大量内存。
+const MAX_LEN: usize = 512 * 1024 * 1024;
+
+impl SetRange {
+    /// 创建一个新的 `SetRange` 命令，从 `offset` 开始用 `value` 覆盖 `key` 的值。
+    pub fn new(key: impl ToString, offset: i64, value: Bytes) -> Self {
+        Self {
+            key: key.to_string(),
+            offset,
+            value,
+        }
+    }
+
+    /// 获取键
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// 将 `SetRange` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 响应写入 `dst`。这是由服务器调用以执行接收到的命令。
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection<impl AsyncStream>) -> crate::Result<()> {
+        let response = match usize::try_from(self.offset) {
+            Err(_) => Frame::Error("ERR offset is out of range".to_string()),
+            Ok(offset) if offset.saturating_add(self.value.len()) > MAX_LEN => {
+                Frame::Error("ERR string exceeds maximum allowed size (proto-max-bulk-len)".to_string())
+            }
+            Ok(offset) => match db.set_range(self.key, offset, &self.value) {
+                Ok(len) => Frame::Integer(len as i64),
+                Err(err) => Frame::Error(err.to_string()),
+            },
+        };
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+}
+
+/// 从接收到的帧中解析出一个 `SetRange` 实例。
+///
+/// `SETRANGE` 字符串已经被消费。
+///
+/// # 格式
+///
+/// ```text
+/// SETRANGE key offset value
+/// ```
+impl TryFrom<&mut Parser> for SetRange {
+    type Error = crate::Error;
+
+    fn try_from(parser: &mut Parser) -> crate::Result<Self> {
+        let key = parser.next_string()?;
+        let offset = parser.next_int()?;
+        let value = parser.next_bytes()?;
+
+        Ok(Self { key, offset, value })
+    }
+}
+
+/// 将命令转换为等效的 `Frame`。
+impl From<SetRange> for Frame {
+    fn from(set_range: SetRange) -> Self {
+        let mut frame = Self::array();
+        frame.push_bulk(Bytes::from("setrange".as_bytes()));
+        frame.push_bulk(Bytes::from(set_range.key.into_bytes()));
+        frame.push_bulk(Bytes::from(set_range.offset.to_string()));
+        frame.push_bulk(set_range.value);
+
+        frame
+    }
+}
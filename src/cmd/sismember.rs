@@ -0,0 +1,79 @@
+use crate::{AsyncStream, Connection, Db, Frame, Parser};
+
+use bytes::Bytes;
+use tracing::{debug, instrument};
+
+/// 判断 `member` 是否是集合的成员。
+///
+/// 是成员时返回 `1`，否则返回 `0`。键不存在时视为空集合。
+#[derive(Debug)]
+pub struct SIsMember {
+    /// 集合的键
+    key: String,
+    /// 要检查的成员
+    member: Bytes,
+}
+
+impl SIsMember {
+    /// 创建一个新的 `SIsMember` 命令，检查 `member` 是否在 `key` 集合中。
+    pub fn new(key: impl ToString, member: Bytes) -> Self {
+        Self {
+            key: key.to_string(),
+            member,
+        }
+    }
+
+    /// 获取键
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// 将 `SIsMember` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 响应写入 `dst`。这是由服务器调用以执行接收到的命令。
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection<impl AsyncStream>) -> crate::Result<()> {
+        let response = match db.sismember(&self.key, &self.member) {
+            Ok(is_member) => Frame::Integer(is_member as i64),
+            Err(err) => Frame::Error(err.to_string()),
+        };
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+}
+
+/// 从接收到的帧中解析出一个 `SIsMember` 实例。
+///
+/// `SISMEMBER` 字符串已经被消费。
+///
+/// # 格式
+///
+/// ```text
+/// SISMEMBER key member
+/// ```
+impl TryFrom<&mut Parser> for SIsMember {
+    type Error = crate::Error;
+
+    fn try_from(parser: &mut Parser) -> crate::Result<Self> {
+        let key = parser.next_string()?;
+        let member = parser.next_bytes()?;
+
+        Ok(Self { key, member })
+    }
+}
+
+/// 将命令转换为等效的 `Frame`。
+impl From<SIsMember> for Frame {
+    fn from(cmd: SIsMember) -> Self {
+        let mut frame = Self::array();
+        frame.push_bulk(Bytes::from("sismember".as_bytes()));
+        frame.push_bulk(Bytes::from(cmd.key.into_bytes()));
+        frame.push_bulk(cmd.member);
+
+        frame
+    }
+}
@@ -0,0 +1,144 @@
+use crate::cmd::{InvalidArgument, Parser, ParserError};
+use crate::server::SlowLog;
+use crate::{Connection, Frame};
+
+use bytes::Bytes;
+use tracing::{debug, instrument};
+
+/// 查看和清空慢日志。
+///
+/// 服务器为每个命令的执行计时，执行时间达到 [`ServerConfig::slowlog_threshold`](crate::server::ServerConfig)
+/// 的命令被记录到慢日志中。慢日志只保存在内存中，容量有限，写满之后丢弃最旧的条目。
+///
+/// # 子命令
+///
+/// * GET `[count]` -- 返回最新的 `count` 个条目，最新的在前面。默认返回 10 个，`count` 为负数时返回所有条目。
+///   每个条目依次包括 id、命令开始执行的 Unix 时间戳（秒）、执行时间（微秒）、命令名称和参数、
+///   客户端地址以及客户端名称，与 Redis 相同。
+/// * LEN -- 返回慢日志中的条目数。
+/// * RESET -- 清空慢日志。
+#[derive(Debug)]
+pub struct SlowLogCmd {
+    /// 要执行的子命令
+    sub: SlowLogSubcommand,
+}
+
+#[derive(Debug)]
+enum SlowLogSubcommand {
+    /// `None` 表示返回所有条目。
+    Get(Option<usize>),
+    Len,
+    Reset,
+}
+
+/// `SLOWLOG GET` 没有给出数量时返回的条目数，与 Redis 相同。
+const DEFAULT_COUNT: usize = 10;
+
+impl SlowLogCmd {
+    /// 创建一个新的 `SLOWLOG GET` 命令，返回最新的 `count` 个条目。`None` 返回所有条目。
+    pub fn get(count: Option<usize>) -> Self {
+        Self {
+            sub: SlowLogSubcommand::Get(count),
+        }
+    }
+
+    /// 创建一个新的 `SLOWLOG LEN` 命令。
+    pub fn len() -> Self {
+        Self {
+            sub: SlowLogSubcommand::Len,
+        }
+    }
+
+    /// 创建一个新的 `SLOWLOG RESET` 命令。
+    pub fn reset() -> Self {
+        Self {
+            sub: SlowLogSubcommand::Reset,
+        }
+    }
+
+    /// 将 `SLOWLOG` 命令应用于服务器的慢日志 `slowlog`。
+    ///
+    /// 响应写入 `dst`。慢日志属于服务器，因此由连接处理程序调用。
+    #[instrument(skip(self, slowlog, dst))]
+    pub(crate) async fn apply(self, slowlog: &SlowLog, dst: &mut Connection) -> crate::Result<()> {
+        let response = match self.sub {
+            SlowLogSubcommand::Get(count) => Frame::Array(
+                slowlog
+                    .get(count)
+                    .into_iter()
+                    .map(|entry| {
+                        Frame::Array(vec![
+                            Frame::Integer(entry.id as i64),
+                            Frame::Integer(entry.timestamp as i64),
+                            Frame::Integer(entry.duration.as_micros() as i64),
+                            Frame::Array(entry.args.into_iter().map(Frame::Bulk).collect()),
+                            Frame::Bulk(Bytes::from(entry.addr.to_string())),
+                            Frame::Bulk(Bytes::from(entry.name.unwrap_or_default())),
+                        ])
+                    })
+                    .collect(),
+            ),
+            SlowLogSubcommand::Len => Frame::Integer(slowlog.len() as i64),
+            SlowLogSubcommand::Reset => {
+                slowlog.reset();
+                Frame::Simple("OK".to_string())
+            }
+        };
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+}
+
+/// 从接收到的帧中解析出一个 `SlowLogCmd` 实例。
+///
+/// `SLOWLOG` 字符串已经被消费。
+///
+/// # 格式
+///
+/// ```text
+/// SLOWLOG GET [count]
+/// SLOWLOG LEN
+/// SLOWLOG RESET
+/// ```
+impl TryFrom<&mut Parser> for SlowLogCmd {
+    type Error = crate::Error;
+
+    fn try_from(parser: &mut Parser) -> crate::Result<Self> {
+        use ParserError::EndOfStream;
+
+        let sub = parser.next_string()?.to_uppercase();
+        match &sub[..] {
+            "GET" => match parser.next_int() {
+                Ok(count) => Ok(Self::get(usize::try_from(count).ok())),
+                Err(EndOfStream) => Ok(Self::get(Some(DEFAULT_COUNT))),
+                Err(err) => Err(err.into()),
+            },
+            "LEN" => Ok(Self::len()),
+            "RESET" => Ok(Self::reset()),
+            _ => Err(InvalidArgument::unknown_subcommand("slowlog", &sub).into()),
+        }
+    }
+}
+
+/// 将命令转换为等效的 `Frame`。
+impl From<SlowLogCmd> for Frame {
+    fn from(cmd: SlowLogCmd) -> Self {
+        let mut frame = Self::array();
+        frame.push_bulk(Bytes::from("slowlog".as_bytes()));
+        match cmd.sub {
+            SlowLogSubcommand::Get(count) => {
+                frame.push_bulk(Bytes::from("get".as_bytes()));
+                // 负数表示返回所有条目。
+                frame.push_int(count.map_or(-1, |count| count as i64));
+            }
+            SlowLogSubcommand::Len => frame.push_bulk(Bytes::from("len".as_bytes())),
+            SlowLogSubcommand::Reset => frame.push_bulk(Bytes::from("reset".as_bytes())),
+        }
+
+        frame
+    }
+}
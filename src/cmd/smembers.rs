@@ -0,0 +1,78 @@
+use crate::{AsyncStream, Connection, Db, Frame, Parser};
+
+use bytes::Bytes;
+use tracing::{debug, instrument};
+
+/// 返回集合中的所有成员。
+///
+/// 成员的顺序不确定。键不存在时返回空数组。
+#[derive(Debug)]
+pub struct SMembers {
+    /// 集合的键
+    key: String,
+}
+
+impl SMembers {
+    /// 创建一个新的 `SMembers` 命令，返回 `key` 集合中的所有成员。
+    pub fn new(key: impl ToString) -> Self {
+        Self { key: key.to_string() }
+    }
+
+    /// 获取键
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// 将 `SMembers` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 响应写入 `dst`。这是由服务器调用以执行接收到的命令。
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection<impl AsyncStream>) -> crate::Result<()> {
+        let response = match db.smembers(&self.key) {
+            Ok(members) => {
+                let mut response = Frame::array();
+                for member in members {
+                    response.push_bulk(member);
+                }
+                response
+            }
+            Err(err) => Frame::Error(err.to_string()),
+        };
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+}
+
+/// 从接收到的帧中解析出一个 `SMembers` 实例。
+///
+/// `SMEMBERS` 字符串已经被消费。
+///
+/// # 格式
+///
+/// ```text
+/// SMEMBERS key
+/// ```
+impl TryFrom<&mut Parser> for SMembers {
+    type Error = crate::Error;
+
+    fn try_from(parser: &mut Parser) -> crate::Result<Self> {
+        let key = parser.next_string()?;
+
+        Ok(Self { key })
+    }
+}
+
+/// 将命令转换为等效的 `Frame`。
+impl From<SMembers> for Frame {
+    fn from(cmd: SMembers) -> Self {
+        let mut frame = Self::array();
+        frame.push_bulk(Bytes::from("smembers".as_bytes()));
+        frame.push_bulk(Bytes::from(cmd.key.into_bytes()));
+
+        frame
+    }
+}
@@ -0,0 +1,92 @@
+use crate::cmd::{Parser, ParserError};
+use crate::{AsyncStream, Connection, Db, Frame};
+
+use bytes::Bytes;
+use tracing::{debug, instrument};
+
+/// 从集合中删除一个或多个成员。
+///
+/// 不存在的成员被忽略。响应是实际删除的成员数。集合被删空后键会被删除。
+#[derive(Debug)]
+pub struct SRem {
+    /// 集合的键
+    key: String,
+    /// 要删除的成员
+    members: Vec<Bytes>,
+}
+
+impl SRem {
+    /// 创建一个新的 `SRem` 命令，从 `key` 集合删除 `members`。
+    pub fn new(key: impl ToString, members: Vec<Bytes>) -> Self {
+        Self {
+            key: key.to_string(),
+            members,
+        }
+    }
+
+    /// 获取键
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// 将 `SRem` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 响应写入 `dst`。这是由服务器调用以执行接收到的命令。
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection<impl AsyncStream>) -> crate::Result<()> {
+        let response = match db.srem(&self.key, &self.members) {
+            Ok(count) => Frame::Integer(count as i64),
+            Err(err) => Frame::Error(err.to_string()),
+        };
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+}
+
+/// 从接收到的帧中解析出一个 `SRem` 实例。
+///
+/// `SREM` 字符串已经被消费。
+///
+/// # 格式
+///
+/// ```text
+/// SREM key member [member ...]
+/// ```
+impl TryFrom<&mut Parser> for SRem {
+    type Error = crate::Error;
+
+    fn try_from(parser: &mut Parser) -> crate::Result<Self> {
+        use ParserError::EndOfStream;
+
+        let key = parser.next_string()?;
+
+        let mut members = vec![parser.next_bytes()?];
+        loop {
+            match parser.next_bytes() {
+                Ok(member) => members.push(member),
+                Err(EndOfStream) => break,
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        Ok(Self { key, members })
+    }
+}
+
+/// 将命令转换为等效的 `Frame`。
+impl From<SRem> for Frame {
+    fn from(cmd: SRem) -> Self {
+        let mut frame = Self::array();
+        frame.push_bulk(Bytes::from("srem".as_bytes()));
+        frame.push_bulk(Bytes::from(cmd.key.into_bytes()));
+        for member in cmd.members {
+            frame.push_bulk(member);
+        }
+
+        frame
+    }
+}
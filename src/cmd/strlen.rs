@@ -0,0 +1,72 @@
+use crate::{AsyncStream, Connection, Db, Frame, Parser};
+
+use bytes::Bytes;
+use tracing::{debug, instrument};
+
+/// 获取键的值的字节长度。
+///
+/// 如果键不存在，则返回 `0`。
+#[derive(Debug)]
+pub struct Strlen {
+    /// 要查询的键的名称
+    key: String,
+}
+
+impl Strlen {
+    /// 创建一个新的 `Strlen` 命令以查询 `key` 的值的长度。
+    pub fn new(key: impl ToString) -> Self {
+        Self { key: key.to_string() }
+    }
+
+    /// 获取键
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// 将 `Strlen` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 响应写入 `dst`。这是由服务器调用以执行接收到的命令。
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection<impl AsyncStream>) -> crate::Result<()> {
+        let response = match db.strlen(&self.key) {
+            Ok(len) => Frame::Integer(len as i64),
+            Err(err) => Frame::Error(err.to_string()),
+        };
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+}
+
+/// 从接收到的帧中解析出一个 `Strlen` 实例。
+///
+/// `STRLEN` 字符串已经被消费。
+///
+/// # 格式
+///
+/// ```text
+/// STRLEN key
+/// ```
+impl TryFrom<&mut Parser> for Strlen {
+    type Error = crate::Error;
+
+    fn try_from(parser: &mut Parser) -> crate::Result<Self> {
+        let key = parser.next_string()?;
+
+        Ok(Self { key })
+    }
+}
+
+/// 将命令转换为等效的 `Frame`。
+impl From<Strlen> for Frame {
+    fn from(strlen: Strlen) -> Self {
+        let mut frame = Self::array();
+        frame.push_bulk(Bytes::from("strlen".as_bytes()));
+        frame.push_bulk(Bytes::from(strlen.key.into_bytes()));
+
+        frame
+    }
+}
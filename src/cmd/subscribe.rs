@@ -1,18 +1,41 @@
-use crate::cmd::{Parser, ParserError, Unknown};
-use crate::{Command, Connection, Db, Frame, Shutdown};
+use crate::cmd::{InvalidArgument, Parser, ParserError, SyntaxError, Unknown, WrongArity};
+use crate::{Command, Connection, Db, Frame, Protocol, Shutdown};
 
 use bytes::Bytes;
+use std::future;
+use std::ops::ControlFlow;
 use std::pin::Pin;
 use tokio::select;
 use tokio::sync::broadcast;
+use tokio::time::{self, Instant, Interval, MissedTickBehavior};
 use tokio_stream::{Stream, StreamExt, StreamMap};
 
 /// 订阅客户端到一个或多个频道。
 ///
 /// 一旦客户端进入订阅状态，它不应该发出任何其他命令，除了额外的 SUBSCRIBE、PSUBSCRIBE、UNSUBSCRIBE、PUNSUBSCRIBE、PING 和 QUIT 命令。
+/// `RESET` 取消所有订阅并让连接回到普通模式；用 `UNSUBSCRIBE` 和 `PUNSUBSCRIBE` 取消最后一个订阅时同样回到普通模式。
 #[derive(Debug)]
 pub struct Subscribe {
     channels: Vec<String>,
+    /// 要订阅的模式。只有通过 `PSUBSCRIBE` 进入订阅状态时才非空。
+    patterns: Vec<String>,
+}
+
+/// 订阅客户端到一个或多个 glob 模式。
+///
+/// 发布到任何与模式匹配的频道的消息都会以 `pmessage` 帧的形式推送给客户端，其中包含匹配的模式和实际的频道。
+/// 支持的模式语法参见 Redis 文档：`*`、`?`、`[...]` 和 `\` 转义。
+#[derive(Debug)]
+pub struct PSubscribe {
+    patterns: Vec<String>,
+}
+
+/// 取消订阅客户端从一个或多个模式。
+///
+/// 当没有指定模式时，客户端将从所有先前订阅的模式中取消订阅。
+#[derive(Clone, Debug)]
+pub struct PUnsubscribe {
+    patterns: Vec<String>,
 }
 
 /// 取消订阅客户端从一个或多个频道。
@@ -23,14 +46,113 @@ pub struct Unsubscribe {
     channels: Vec<String>,
 }
 
+/// `Subscribe::apply` 结束的原因。
+pub(crate) enum SubscribeExit {
+    /// 客户端断开了连接，或者服务器正在关闭。
+    Closed,
+    /// 客户端发送了 `RESET`。所有订阅已经取消，连接应回到普通模式。
+    Reset,
+    /// 客户端发送了 `QUIT`，连接应被关闭。
+    Quit,
+    /// 客户端取消了所有订阅，连接回到普通模式。与 `Reset` 不同，连接的其他状态保持不变，也不需要再回复。
+    Unsubscribed,
+    /// RESP3 客户端在订阅模式下发送了其他命令。
+    ///
+    /// 连接处理程序执行该命令，然后调用 [`Subscribed::resume`] 继续订阅。帧是命令的原始帧，用于追加到 AOF。
+    Command(Box<Command>, Frame, Subscribed),
+}
+
+/// 被其他命令暂时中断的订阅状态，见 [`SubscribeExit::Command`]。
+pub(crate) struct Subscribed {
+    subscriptions: Subscriptions,
+}
+
+/// `handle_command` 要求订阅循环停下的原因。
+enum Break {
+    /// 退出订阅模式。
+    Exit(SubscribeExit),
+    /// 把命令交给连接处理程序执行，订阅保留。
+    Command(Command, Frame),
+}
+
 /// 消息流。该流从 `broadcast::Receiver` 接收消息。我们使用 `stream!` 创建一个消费消息的 `Stream`。
 /// 因为 `stream!` 值不能被命名，所以我们使用特征对象将流装箱。
-type Messages = Pin<Box<dyn Stream<Item = Bytes> + Send>>;
+type Messages = Pin<Box<dyn Stream<Item = Delivery<Bytes>> + Send>>;
+
+/// 模式订阅的消息流。每条消息附带它被发布到的频道。
+type PMessages = Pin<Box<dyn Stream<Item = Delivery<(String, Bytes)>> + Send>>;
+
+/// 订阅流中的一项。
+enum Delivery<T> {
+    /// 收到的消息。
+    Message(T),
+    /// 订阅者太慢，广播通道中尚未读取的消息被覆盖，一共丢弃了这么多条。只有开启了滞后通知时才会出现。
+    Lagged(u64),
+}
+
+/// 一个连接的所有订阅：频道订阅和模式订阅。
+///
+/// 取消订阅或者连接断开时，最后一个订阅者离开的频道会从 `Db` 中移除，避免大量临时频道的发送器一直留在内存中。
+struct Subscriptions {
+    channels: StreamMap<String, Messages>,
+    patterns: StreamMap<String, PMessages>,
+    db: Db,
+}
+
+impl Subscriptions {
+    fn new(db: Db) -> Self {
+        Self {
+            channels: StreamMap::new(),
+            patterns: StreamMap::new(),
+            db,
+        }
+    }
+
+    /// 订阅总数，即确认帧中报告的数量。
+    fn len(&self) -> usize {
+        self.channels.len() + self.patterns.len()
+    }
+
+    /// 取消订阅 `channel`。接收器必须先被丢弃，`Db` 才能看到频道已经没有订阅者。
+    fn unsubscribe(&mut self, channel: &str) {
+        if self.channels.remove(channel).is_some() {
+            self.db.unsubscribed(channel);
+        }
+    }
+
+    /// 取消订阅 `pattern`，与 `unsubscribe` 相同。
+    fn punsubscribe(&mut self, pattern: &str) {
+        if self.patterns.remove(pattern).is_some() {
+            self.db.punsubscribed(pattern);
+        }
+    }
+}
+
+impl Drop for Subscriptions {
+    fn drop(&mut self) {
+        // 连接断开或者退出订阅模式时，清理剩余的所有订阅。
+        let channels = std::mem::take(&mut self.channels);
+        let patterns = std::mem::take(&mut self.patterns);
+        let channel_names: Vec<String> = channels.keys().cloned().collect();
+        let pattern_names: Vec<String> = patterns.keys().cloned().collect();
+        drop((channels, patterns));
+
+        for channel in &channel_names {
+            self.db.unsubscribed(channel);
+        }
+        for pattern in &pattern_names {
+            self.db.punsubscribed(pattern);
+        }
+    }
+}
 
 impl Subscribe {
     /// 创建一个新的 `Subscribe` 命令来监听指定的频道。
     pub(crate) fn new(channels: Vec<String>) -> Self {
-        Self { channels }
+        Self {
+            channels,
+            patterns: vec![],
+        }
     }
 
     /// 将 `Subscribe` 命令应用于指定的 `Db` 实例。
@@ -38,53 +160,128 @@ impl Subscribe {
     /// 此函数是入口点，包括初始的订阅频道列表。客户端可能会接收到额外的 `subscribe` 和 `unsubscribe` 命令，
     /// 并且订阅列表会相应更新。
     ///
+    /// 返回值说明订阅结束的原因：连接关闭，或者客户端通过 `RESET` 要求回到普通模式。
+    ///
     /// [here]: https://redis.io/topics/pubsub
-    pub(crate) async fn apply(mut self, db: &Db, dst: &mut Connection, shutdown: &mut Shutdown) -> crate::Result<()> {
+    pub(crate) async fn apply(
+        self,
+        db: &Db,
+        dst: &mut Connection,
+        shutdown: &mut Shutdown,
+    ) -> crate::Result<SubscribeExit> {
         // 每个单独的频道订阅都使用 `sync::broadcast` 频道处理。消息然后被分发到所有当前订阅频道的客户端。
         //
         // 单个客户端可以订阅多个频道，并且可以动态地添加和删除其订阅集中的频道。为了解决这个问题，
         // 使用 `StreamMap` 来跟踪活动订阅。`StreamMap` 合并来自各个广播频道的消息。
-        let mut subscriptions = StreamMap::new();
+        //
+        // 模式订阅使用另一个 `StreamMap` 跟踪，它的消息带有实际的频道名称。
+        self.run(Subscriptions::new(db.clone()), db, dst, shutdown).await
+    }
+
+    /// 订阅循环：订阅 `self` 中的频道和模式，然后推送消息并处理客户端的命令，直到订阅结束或者被其他命令中断。
+    async fn run(
+        mut self,
+        mut subscriptions: Subscriptions,
+        db: &Db,
+        dst: &mut Connection,
+        shutdown: &mut Shutdown,
+    ) -> crate::Result<SubscribeExit> {
+        // 配置了保活间隔时，订阅空闲达到该间隔就推送一个 `ping`。每次推送消息之后重新计时，
+        // 因此有消息的订阅不会收到保活推送。
+        let mut keepalive = db.keepalive_interval().map(|period| {
+            let mut interval = time::interval_at(Instant::now() + period, period);
+            interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            interval
+        });
 
         loop {
             // `self.channels` 用于跟踪要订阅的额外频道。当在 `apply` 执行期间接收到新的 `SUBSCRIBE` 命令时，
-            // 新的频道会被推入这个 vec。
+            // 新的频道会被推入这个 vec。`self.patterns` 对 `PSUBSCRIBE` 起同样的作用。
             for channel_name in self.channels.drain(..) {
                 subscribe_to_channel(channel_name, &mut subscriptions, db, dst).await?;
             }
+            for pattern in self.patterns.drain(..) {
+                subscribe_to_pattern(pattern, &mut subscriptions, db, dst).await?;
+            }
 
             // 等待以下情况之一发生：
             //
             // - 从订阅的频道接收消息。
             // - 从客户端接收订阅或取消订阅命令。
+            // - 保活间隔到期。
             // - 服务器关闭信号。
+            //
+            // 每个分支都在下一次等待之前写完自己的帧，保活推送不会插入到其他帧的中间。
             select! {
                 // 从订阅的频道接收消息
-                Some((channel_name, msg)) = subscriptions.next() => {
-                    dst.write_frame(&make_message_frame(channel_name, msg)).await?;
+                Some((channel_name, delivery)) = subscriptions.channels.next() => {
+                    let frame = match delivery {
+                        Delivery::Message(msg) => make_message_frame(channel_name, msg),
+                        Delivery::Lagged(dropped) => make_lagged_frame(channel_name, dropped),
+                    };
+                    dst.write_frame(&frame).await?;
+                    if let Some(keepalive) = &mut keepalive {
+                        keepalive.reset();
+                    }
+                }
+                // 从订阅的模式接收消息
+                Some((pattern, delivery)) = subscriptions.patterns.next() => {
+                    let frame = match delivery {
+                        Delivery::Message((channel_name, msg)) => make_pmessage_frame(pattern, channel_name, msg),
+                        Delivery::Lagged(dropped) => make_lagged_frame(pattern, dropped),
+                    };
+                    dst.write_frame(&frame).await?;
+                    if let Some(keepalive) = &mut keepalive {
+                        keepalive.reset();
+                    }
+                }
+                _ = tick(&mut keepalive) => {
+                    dst.write_frame(&make_keepalive_frame()).await?;
                 }
                 res = dst.read_frame() => {
                     let frame = match res? {
                         Some(frame) => frame,
                         // 这发生在远程客户端断开连接时。
-                        None => return Ok(())
+                        None => return Ok(SubscribeExit::Closed)
                     };
 
-                    handle_command(
+                    let flow = handle_command(
                         frame,
-                        &mut self.channels,
+                        &mut self,
                         &mut subscriptions,
                         dst,
                     ).await?;
+                    match flow {
+                        ControlFlow::Continue(()) => {}
+                        // 订阅随 `subscriptions` 一起被丢弃。
+                        ControlFlow::Break(Break::Exit(exit)) => return Ok(exit),
+                        // 订阅交给连接处理程序保管，命令执行之后继续。
+                        ControlFlow::Break(Break::Command(command, frame)) => {
+                            let subscribed = Subscribed { subscriptions };
+                            return Ok(SubscribeExit::Command(Box::new(command), frame, subscribed));
+                        }
+                    }
                 }
                 _ = shutdown.recv() => {
-                    return Ok(());
+                    return Ok(SubscribeExit::Closed);
                 }
             };
         }
     }
 }
 
+impl Subscribed {
+    /// 继续被 [`SubscribeExit::Command`] 中断的订阅。
+    pub(crate) async fn resume(
+        self,
+        db: &Db,
+        dst: &mut Connection,
+        shutdown: &mut Shutdown,
+    ) -> crate::Result<SubscribeExit> {
+        Subscribe::new(vec![]).run(self.subscriptions, db, dst, shutdown).await
+    }
+}
+
 /// 从接收到的帧中解析出一个 `Subscribe` 实例。
 ///
 /// `Parse` 参数提供了一个类似游标的 API 来从 `Frame` 中读取字段。此时，整个帧已经从套接字接收到。
@@ -111,7 +308,7 @@ impl TryFrom<&mut Parser> for Subscribe {
         // `SUBSCRIBE` 字符串已经被消费。此时，`parse` 中剩下一个或多个字符串。
         // 这些代表要订阅的频道。
         //
-        // 提取第一个字符串。如果没有，则帧格式错误，错误会冒泡。
+        // 提取第一个字符串。如果没有，则参数个数不对，错误会冒泡，连接处理程序回复错误而不关闭连接。
         let mut channels = vec![parse.next_string()?];
 
         // 现在，帧的其余部分被消费。每个值必须是字符串，否则帧格式错误。
@@ -127,7 +324,7 @@ impl TryFrom<&mut Parser> for Subscribe {
             }
         }
 
-        Ok(Self { channels })
+        Ok(Self::new(channels))
     }
 }
 
@@ -148,18 +345,20 @@ impl From<Subscribe> for Frame {
 
 async fn subscribe_to_channel(
     channel_name: String,
-    subscriptions: &mut StreamMap<String, Messages>,
+    subscriptions: &mut Subscriptions,
     db: &Db,
     dst: &mut Connection,
 ) -> crate::Result<()> {
     let mut rx = db.subscribe(channel_name.clone());
+    let notify_lagged = db.notify_lagged();
 
     // 订阅频道。
     let rx = Box::pin(async_stream::stream! {
         loop {
             match rx.recv().await {
-                Ok(msg) => yield msg,
-                // 如果我们在消费消息时滞后了，只需恢复。
+                Ok(msg) => yield Delivery::Message(msg),
+                // 如果我们在消费消息时滞后了，开启了滞后通知时告诉客户端丢弃了多少条消息，否则只需恢复。
+                Err(broadcast::error::RecvError::Lagged(dropped)) if notify_lagged => yield Delivery::Lagged(dropped),
                 Err(broadcast::error::RecvError::Lagged(_)) => {}
                 Err(_) => break,
             }
@@ -167,7 +366,7 @@ async fn subscribe_to_channel(
     });
 
     // 在此客户端的订阅集中跟踪订阅。
-    subscriptions.insert(channel_name.clone(), rx);
+    subscriptions.channels.insert(channel_name.clone(), rx);
 
     // 响应成功订阅
     let response = make_subscribe_frame(channel_name, subscriptions.len());
@@ -176,69 +375,205 @@ async fn subscribe_to_channel(
     Ok(())
 }
 
-/// 处理在 `Subscribe::apply` 内接收到的命令。在此上下文中仅允许订阅和取消订阅命令。
+async fn subscribe_to_pattern(
+    pattern: String,
+    subscriptions: &mut Subscriptions,
+    db: &Db,
+    dst: &mut Connection,
+) -> crate::Result<()> {
+    let mut rx = db.psubscribe(pattern.clone());
+    let notify_lagged = db.notify_lagged();
+
+    // 订阅模式，与 `subscribe_to_channel` 相同。
+    let rx = Box::pin(async_stream::stream! {
+        loop {
+            match rx.recv().await {
+                Ok(msg) => yield Delivery::Message(msg),
+                Err(broadcast::error::RecvError::Lagged(dropped)) if notify_lagged => yield Delivery::Lagged(dropped),
+                Err(broadcast::error::RecvError::Lagged(_)) => {}
+                Err(_) => break,
+            }
+        }
+    });
+
+    subscriptions.patterns.insert(pattern.clone(), rx);
+
+    let response = make_psubscribe_frame(pattern, subscriptions.len());
+    dst.write_frame(&response).await?;
+
+    Ok(())
+}
+
+/// 处理在 `Subscribe::apply` 内接收到的命令。
+///
+/// RESP2 连接在此上下文中仅允许订阅、取消订阅和 `PING` 命令。RESP3 客户端可以区分推送和回复，
+/// 因此与 Redis 一样可以执行其他命令，只有事务不能与订阅混合。
 ///
-/// 任何新的订阅都被附加到 `subscribe_to` 而不是修改 `subscriptions`。
+/// 任何新的订阅都被附加到 `subscribe_to` 的频道或模式列表，而不是修改 `subscriptions`。
+///
+/// 收到 `RESET` 或 `QUIT`、或者所有订阅都被取消时返回 `Break::Exit`，调用者应退出订阅模式；需要连接处理程序执行的命令返回
+/// `Break::Command`。
 async fn handle_command(
     frame: Frame,
-    subscribe_to: &mut Vec<String>,
-    subscriptions: &mut StreamMap<String, Messages>,
+    subscribe_to: &mut Subscribe,
+    subscriptions: &mut Subscriptions,
     dst: &mut Connection,
-) -> crate::Result<()> {
-    // 从客户端接收到一个命令。
-    //
-    // 在此上下文中仅允许 `SUBSCRIBE` 和 `UNSUBSCRIBE` 命令。
-    match Command::try_from(frame)? {
+) -> crate::Result<ControlFlow<Break>> {
+    // 从客户端接收到一个命令。交给连接处理程序执行的命令需要原始帧来追加到 AOF。
+    let logged = (dst.protocol() == Protocol::Resp3).then(|| frame.clone());
+    let command = match Command::try_from(frame) {
+        Ok(command) => command,
+        // 参数个数不对、选项或者参数值无效时只回复错误，连接保持在订阅模式。
+        Err(err) if err.is::<WrongArity>() || err.is::<SyntaxError>() || err.is::<InvalidArgument>() => {
+            let response = Frame::Error(format!("ERR {}", err));
+            dst.write_frame(&response).await?;
+            return Ok(ControlFlow::Continue(()));
+        }
+        Err(err) => return Err(err),
+    };
+    match command {
         Command::Subscribe(subscribe) => {
             // `apply` 方法将订阅我们添加到此向量中的频道。
-            subscribe_to.extend(subscribe.channels.into_iter());
+            subscribe_to.channels.extend(subscribe.channels);
+        }
+        Command::PSubscribe(psubscribe) => {
+            subscribe_to.patterns.extend(psubscribe.patterns);
         }
         Command::Unsubscribe(mut unsubscribe) => {
             // 如果没有指定频道，这请求从 **所有** 频道取消订阅。为了实现这一点，
             // `unsubscribe.channels` vec 被填充为当前订阅的频道列表。
             if unsubscribe.channels.is_empty() {
-                unsubscribe.channels = subscriptions.keys().map(|channel_name| channel_name.to_string()).collect();
+                unsubscribe.channels = subscriptions
+                    .channels
+                    .keys()
+                    .map(|channel_name| channel_name.to_string())
+                    .collect();
             }
 
             for channel_name in unsubscribe.channels {
-                subscriptions.remove(&channel_name);
+                subscriptions.unsubscribe(&channel_name);
 
                 let response = make_unsubscribe_frame(channel_name, subscriptions.len());
                 dst.write_frame(&response).await?;
             }
+            if subscriptions.len() == 0 {
+                return Ok(ControlFlow::Break(Break::Exit(SubscribeExit::Unsubscribed)));
+            }
         }
-        command => {
-            let cmd = Unknown::new(command.get_name());
-            cmd.apply(dst).await?;
+        Command::PUnsubscribe(mut punsubscribe) => {
+            // 与 `UNSUBSCRIBE` 相同，没有指定模式时取消订阅所有模式。
+            if punsubscribe.patterns.is_empty() {
+                punsubscribe.patterns = subscriptions.patterns.keys().map(|pattern| pattern.to_string()).collect();
+            }
+
+            for pattern in punsubscribe.patterns {
+                subscriptions.punsubscribe(&pattern);
+
+                let response = make_punsubscribe_frame(pattern, subscriptions.len());
+                dst.write_frame(&response).await?;
+            }
+            // 与 Redis 一样，最后一个订阅被取消之后回到普通模式。
+            if subscriptions.len() == 0 {
+                return Ok(ControlFlow::Break(Break::Exit(SubscribeExit::Unsubscribed)));
+            }
         }
+        Command::Ping(ping) => ping.apply_subscribed(dst).await?,
+        // `RESET` 和 `QUIT` 的响应由连接处理程序在退出订阅模式后写入。
+        Command::Reset(_) => return Ok(ControlFlow::Break(Break::Exit(SubscribeExit::Reset))),
+        Command::Quit(_) => return Ok(ControlFlow::Break(Break::Exit(SubscribeExit::Quit))),
+        command => match logged {
+            Some(frame) if !matches!(command, Command::Multi(_) | Command::Exec(_) | Command::Discard(_)) => {
+                return Ok(ControlFlow::Break(Break::Command(command, frame)));
+            }
+            _ => {
+                let cmd = Unknown::new(command.get_name());
+                cmd.apply(dst).await?;
+            }
+        },
     }
-    Ok(())
+    Ok(ControlFlow::Continue(()))
 }
 
 /// 创建订阅请求的响应。
 ///
 /// 所有这些函数都将 `channel_name` 作为 `String` 而不是 `&str`，因为 `Bytes::from` 可以重用 `String` 中的分配，
 /// 并且使用 `&str` 会要求复制数据。这允许调用者决定是否克隆频道名称。
+///
+/// 这些帧都是推送：RESP3 连接上客户端据此把它们与命令的回复区分开，RESP2 连接上它们仍然编码为数组。
 fn make_subscribe_frame(channel_name: String, num_subs: usize) -> Frame {
-    let mut response = Frame::array();
+    let mut response = Frame::push();
     response.push_bulk(Bytes::from_static(b"subscribe"));
     response.push_bulk(Bytes::from(channel_name));
-    response.push_int(num_subs as u64);
+    response.push_int(num_subs as i64);
     response
 }
 
+/// 创建保活推送，订阅空闲达到配置的间隔时发送。
+fn make_keepalive_frame() -> Frame {
+    let mut response = Frame::push();
+    response.push_bulk(Bytes::from_static(b"ping"));
+    response
+}
+
+/// 等待保活间隔的下一次到期。没有配置保活间隔时永远不会完成。
+async fn tick(keepalive: &mut Option<Interval>) {
+    match keepalive {
+        Some(interval) => {
+            interval.tick().await;
+        }
+        None => future::pending().await,
+    }
+}
+
 /// 创建取消订阅请求的响应。
 fn make_unsubscribe_frame(channel_name: String, num_subs: usize) -> Frame {
-    let mut response = Frame::array();
+    let mut response = Frame::push();
     response.push_bulk(Bytes::from_static(b"unsubscribe"));
     response.push_bulk(Bytes::from(channel_name));
-    response.push_int(num_subs as u64);
+    response.push_int(num_subs as i64);
+    response
+}
+
+/// 创建模式订阅请求的响应。
+fn make_psubscribe_frame(pattern: String, num_subs: usize) -> Frame {
+    let mut response = Frame::push();
+    response.push_bulk(Bytes::from_static(b"psubscribe"));
+    response.push_bulk(Bytes::from(pattern));
+    response.push_int(num_subs as i64);
+    response
+}
+
+/// 创建取消模式订阅请求的响应。
+fn make_punsubscribe_frame(pattern: String, num_subs: usize) -> Frame {
+    let mut response = Frame::push();
+    response.push_bulk(Bytes::from_static(b"punsubscribe"));
+    response.push_bulk(Bytes::from(pattern));
+    response.push_int(num_subs as i64);
+    response
+}
+
+/// 创建一个消息，通知客户端关于与其订阅的模式匹配的频道上的新消息。
+fn make_pmessage_frame(pattern: String, channel_name: String, msg: Bytes) -> Frame {
+    let mut response = Frame::push();
+    response.push_bulk(Bytes::from_static(b"pmessage"));
+    response.push_bulk(Bytes::from(pattern));
+    response.push_bulk(Bytes::from(channel_name));
+    response.push_bulk(msg);
+    response
+}
+
+/// 创建一个消息，通知客户端它在频道或模式 `name` 上滞后了，`dropped` 条消息被丢弃。
+fn make_lagged_frame(name: String, dropped: u64) -> Frame {
+    let mut response = Frame::push();
+    response.push_bulk(Bytes::from_static(b"lagged"));
+    response.push_bulk(Bytes::from(name));
+    response.push_int(dropped as i64);
     response
 }
 
 /// 创建一个消息，通知客户端关于其订阅的频道上的新消息。
 fn make_message_frame(channel_name: String, msg: Bytes) -> Frame {
-    let mut response = Frame::array();
+    let mut response = Frame::push();
     response.push_bulk(Bytes::from_static(b"message"));
     response.push_bulk(Bytes::from(channel_name));
     response.push_bulk(msg);
@@ -252,6 +587,17 @@ impl Unsubscribe {
             channels: channels.to_vec(),
         }
     }
+
+    /// 在订阅模式之外应用 `Unsubscribe` 命令。
+    ///
+    /// 连接没有任何订阅，因此只为每个给定的频道回复一个订阅数为 `0` 的确认，与订阅模式中的回复格式相同。
+    pub(crate) async fn apply_unsubscribed(self, dst: &mut Connection) -> crate::Result<()> {
+        for channel_name in self.channels {
+            dst.write_frame(&make_unsubscribe_frame(channel_name, 0)).await?;
+        }
+
+        Ok(())
+    }
 }
 
 /// 从接收到的帧中解析出一个 `Unsubscribe` 实例。
@@ -312,3 +658,126 @@ impl From<Unsubscribe> for Frame {
         frame
     }
 }
+
+impl PSubscribe {
+    /// 创建一个新的 `PSubscribe` 命令来监听与指定模式匹配的频道。
+    pub(crate) fn new(patterns: Vec<String>) -> Self {
+        Self { patterns }
+    }
+
+    /// 将 `PSubscribe` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 进入与 `SUBSCRIBE` 相同的订阅状态，之后可以混合使用频道订阅和模式订阅。
+    pub(crate) async fn apply(
+        self,
+        db: &Db,
+        dst: &mut Connection,
+        shutdown: &mut Shutdown,
+    ) -> crate::Result<SubscribeExit> {
+        let subscribe = Subscribe {
+            channels: vec![],
+            patterns: self.patterns,
+        };
+        subscribe.apply(db, dst, shutdown).await
+    }
+}
+
+/// 从接收到的帧中解析出一个 `PSubscribe` 实例。
+///
+/// `PSUBSCRIBE` 字符串已经被消费。
+///
+/// # 格式
+///
+/// ```text
+/// PSUBSCRIBE pattern [pattern ...]
+/// ```
+impl TryFrom<&mut Parser> for PSubscribe {
+    type Error = crate::Error;
+
+    fn try_from(parser: &mut Parser) -> crate::Result<Self> {
+        use ParserError::EndOfStream;
+
+        let mut patterns = vec![parser.next_string()?];
+        loop {
+            match parser.next_string() {
+                Ok(s) => patterns.push(s),
+                Err(EndOfStream) => break,
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        Ok(Self { patterns })
+    }
+}
+
+/// 将命令转换为等效的 `Frame`。
+impl From<PSubscribe> for Frame {
+    fn from(psubscribe: PSubscribe) -> Self {
+        let mut frame = Frame::array();
+        frame.push_bulk(Bytes::from("psubscribe".as_bytes()));
+        for pattern in psubscribe.patterns {
+            frame.push_bulk(Bytes::from(pattern.into_bytes()));
+        }
+
+        frame
+    }
+}
+
+impl PUnsubscribe {
+    /// 创建一个带有给定 `patterns` 的新 `PUnsubscribe` 命令。
+    pub(crate) fn new(patterns: &[String]) -> Self {
+        Self {
+            patterns: patterns.to_vec(),
+        }
+    }
+
+    /// 在订阅模式之外应用 `PUnsubscribe` 命令，与 [`Unsubscribe::apply_unsubscribed`] 相同。
+    pub(crate) async fn apply_unsubscribed(self, dst: &mut Connection) -> crate::Result<()> {
+        for pattern in self.patterns {
+            dst.write_frame(&make_punsubscribe_frame(pattern, 0)).await?;
+        }
+
+        Ok(())
+    }
+}
+
+/// 从接收到的帧中解析出一个 `PUnsubscribe` 实例。
+///
+/// `PUNSUBSCRIBE` 字符串已经被消费。
+///
+/// # 格式
+///
+/// ```text
+/// PUNSUBSCRIBE [pattern [pattern ...]]
+/// ```
+impl TryFrom<&mut Parser> for PUnsubscribe {
+    type Error = crate::Error;
+
+    fn try_from(parser: &mut Parser) -> crate::Result<Self> {
+        use ParserError::EndOfStream;
+
+        let mut patterns = vec![];
+        loop {
+            match parser.next_string() {
+                Ok(s) => patterns.push(s),
+                Err(EndOfStream) => break,
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        Ok(Self { patterns })
+    }
+}
+
+/// 将命令转换为等效的 `Frame`。
+impl From<PUnsubscribe> for Frame {
+    fn from(punsubscribe: PUnsubscribe) -> Self {
+        let mut frame = Self::array();
+        frame.push_bulk(Bytes::from("punsubscribe".as_bytes()));
+        for pattern in punsubscribe.patterns {
+            frame.push_bulk(Bytes::from(pattern.into_bytes()));
+        }
+
+        frame
+    }
+}
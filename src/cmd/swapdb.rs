@@ -0,0 +1,74 @@
+use crate::cmd::InvalidArgument;
+use crate::{AsyncStream, Connection, Db, Frame, Parser};
+
+use bytes::Bytes;
+use tracing::{debug, instrument};
+
+/// 交换两个数据库的内容。
+///
+/// 交换是原子的：其他连接要么看到交换之前的两个数据库，要么看到交换之后的。已经选择了这两个数据库的连接
+/// 不需要重新选择，之后的命令直接看到交换之后的内容。
+#[derive(Debug)]
+pub struct SwapDb {
+    /// 第一个数据库的编号
+    first: usize,
+    /// 第二个数据库的编号
+    second: usize,
+}
+
+impl SwapDb {
+    /// 创建一个新的 `SwapDb` 命令，交换编号为 `first` 和 `second` 的数据库。
+    pub fn new(first: usize, second: usize) -> Self {
+        Self { first, second }
+    }
+
+    /// 将 `SwapDb` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 响应写入 `dst`。这是由服务器调用以执行接收到的命令。
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection<impl AsyncStream>) -> crate::Result<()> {
+        let response = if db.swap(self.first, self.second) {
+            Frame::Simple("OK".to_string())
+        } else {
+            Frame::Error("ERR DB index is out of range".to_string())
+        };
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+}
+
+/// 从接收到的帧中解析出一个 `SwapDb` 实例。
+///
+/// `SWAPDB` 字符串已经被消费。
+///
+/// # 格式
+///
+/// ```text
+/// SWAPDB index1 index2
+/// ```
+impl TryFrom<&mut Parser> for SwapDb {
+    type Error = crate::Error;
+
+    fn try_from(parser: &mut Parser) -> crate::Result<Self> {
+        let first = usize::try_from(parser.next_int()?).map_err(|_| InvalidArgument::new("invalid first DB index"))?;
+        let second = usize::try_from(parser.next_int()?).map_err(|_| InvalidArgument::new("invalid second DB index"))?;
+
+        Ok(Self { first, second })
+    }
+}
+
+/// 将命令转换为等效的 `Frame`。
+impl From<SwapDb> for Frame {
+    fn from(cmd: SwapDb) -> Self {
+        let mut frame = Self::array();
+        frame.push_bulk(Bytes::from("swapdb".as_bytes()));
+        frame.push_bulk(Bytes::from(cmd.first.to_string()));
+        frame.push_bulk(Bytes::from(cmd.second.to_string()));
+
+        frame
+    }
+}
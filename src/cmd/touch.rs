@@ -0,0 +1,83 @@
+use crate::cmd::{Parser, ParserError};
+use crate::{AsyncStream, Connection, Db, Frame};
+
+use bytes::Bytes;
+use tracing::{debug, instrument};
+
+/// 更新键的最近访问时间，而不读取它们的值。
+///
+/// 回复给定的键中存在的键数，不存在的键不计入。与 `EXISTS` 不同，`TOUCH` 会把键标记为刚刚使用过，
+/// 设置了内存上限时，这些键会最后被驱逐。
+#[derive(Debug)]
+pub struct Touch {
+    /// 要更新的键
+    keys: Vec<String>,
+}
+
+impl Touch {
+    /// 创建一个新的 `Touch` 命令，更新 `keys` 的最近访问时间。
+    pub fn new(keys: Vec<String>) -> Self {
+        Self { keys }
+    }
+
+    /// 获取要更新的键
+    pub fn keys(&self) -> &[String] {
+        &self.keys
+    }
+
+    /// 将 `Touch` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 响应写入 `dst`。这是由服务器调用以执行接收到的命令。
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection<impl AsyncStream>) -> crate::Result<()> {
+        let response = Frame::Integer(db.touch(&self.keys) as i64);
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+}
+
+/// 从接收到的帧中解析出一个 `Touch` 实例。
+///
+/// `TOUCH` 字符串已经被消费。
+///
+/// # 格式
+///
+/// ```text
+/// TOUCH key [key ...]
+/// ```
+impl TryFrom<&mut Parser> for Touch {
+    type Error = crate::Error;
+
+    fn try_from(parser: &mut Parser) -> crate::Result<Self> {
+        use ParserError::EndOfStream;
+
+        // 至少需要一个键
+        let mut keys = vec![parser.next_string()?];
+        loop {
+            match parser.next_string() {
+                Ok(key) => keys.push(key),
+                Err(EndOfStream) => break,
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        Ok(Self { keys })
+    }
+}
+
+/// 将命令转换为等效的 `Frame`。
+impl From<Touch> for Frame {
+    fn from(cmd: Touch) -> Self {
+        let mut frame = Self::array();
+        frame.push_bulk(Bytes::from("touch".as_bytes()));
+        for key in cmd.keys {
+            frame.push_bulk(Bytes::from(key.into_bytes()));
+        }
+
+        frame
+    }
+}
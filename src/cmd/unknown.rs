@@ -1,5 +1,6 @@
-use crate::{Connection, Frame};
+use crate::{AsyncStream, Connection, Frame};
 
+use std::fmt;
 use tracing::{debug, instrument};
 
 /// 表示一个“未知”命令。这不是一个真正的 `Redis` 命令。
@@ -25,7 +26,7 @@ impl Unknown {
     ///
     /// 这通常意味着该命令尚未被 `mini-redis` 实现。
     #[instrument(skip(self, dst))]
-    pub(crate) async fn apply(self, dst: &mut Connection) -> crate::Result<()> {
+    pub(crate) async fn apply(self, dst: &mut Connection<impl AsyncStream>) -> crate::Result<()> {
         let response = Frame::Error(format!("ERR unknown command '{}'", self.cmd_name));
 
         debug!(?response);
@@ -34,3 +35,82 @@ impl Unknown {
         Ok(())
     }
 }
+
+/// 已知命令的参数个数不对。
+///
+/// 与其他解析错误不同，它不表示协议被破坏：帧本身是完整的，只是参数不够或者太多。
+/// 因此连接处理程序把它作为错误帧回复给客户端，而不是关闭连接，与 Redis 一致。
+#[derive(Debug)]
+pub(crate) struct WrongArity {
+    cmd_name: String,
+}
+
+impl WrongArity {
+    /// 创建一个新的 `WrongArity` 错误，`cmd_name` 是参数个数不对的命令。
+    pub(crate) fn new(cmd_name: impl ToString) -> Self {
+        Self {
+            cmd_name: cmd_name.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for WrongArity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "wrong number of arguments for '{}' command", self.cmd_name)
+    }
+}
+
+impl std::error::Error for WrongArity {}
+
+/// 已知命令的选项无法识别、重复或者互相冲突，例如 `SET key value FOO`。
+///
+/// 与 [`WrongArity`] 一样，帧本身是完整的，连接处理程序只回复 `ERR syntax error`，不关闭连接。
+#[derive(Debug)]
+pub(crate) struct SyntaxError;
+
+impl fmt::Display for SyntaxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        "syntax error".fmt(f)
+    }
+}
+
+impl std::error::Error for SyntaxError {}
+
+/// 已知命令的参数值无效，例如数字无法解析或者超出范围、未知的子命令。
+///
+/// 与 [`WrongArity`] 一样，帧本身是完整的，连接处理程序回复 `ERR` 加上错误信息，不关闭连接。
+#[derive(Debug)]
+pub(crate) struct InvalidArgument {
+    message: String,
+}
+
+impl InvalidArgument {
+    /// 创建一个新的 `InvalidArgument` 错误，`message` 是回复给客户端的错误信息，不包括 `ERR` 前缀。
+    pub(crate) fn new(message: impl ToString) -> Self {
+        Self {
+            message: message.to_string(),
+        }
+    }
+
+    /// 参数不是整数或者超出范围。
+    pub(crate) fn not_an_integer() -> Self {
+        Self::new("value is not an integer or out of range")
+    }
+
+    /// `cmd_name` 命令没有名为 `subcommand` 的子命令。
+    pub(crate) fn unknown_subcommand(cmd_name: &str, subcommand: &str) -> Self {
+        Self::new(format!(
+            "unknown subcommand '{}'. Try {} HELP.",
+            subcommand,
+            cmd_name.to_uppercase()
+        ))
+    }
+}
+
+impl fmt::Display for InvalidArgument {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.message.fmt(f)
+    }
+}
+
+impl std::error::Error for InvalidArgument {}
@@ -0,0 +1,83 @@
+use crate::cmd::{Parser, ParserError};
+use crate::{AsyncStream, Connection, Db, Frame};
+
+use bytes::Bytes;
+use tracing::{debug, instrument};
+
+/// 删除键，与 `DEL` 相同，但在后台释放大的值。
+///
+/// 键在回复之前就已经被删除，之后的命令看不到它们；只有释放值占用的内存被推迟，
+/// 因此删除很大的列表、哈希或集合不会让请求的延迟变长。回复被删除的键数。
+#[derive(Debug)]
+pub struct Unlink {
+    /// 要删除的键
+    keys: Vec<String>,
+}
+
+impl Unlink {
+    /// 创建一个新的 `Unlink` 命令，删除 `keys`。
+    pub fn new(keys: Vec<String>) -> Self {
+        Self { keys }
+    }
+
+    /// 获取要删除的键
+    pub fn keys(&self) -> &[String] {
+        &self.keys
+    }
+
+    /// 将 `Unlink` 命令应用于指定的 `Db` 实例。
+    ///
+    /// 响应写入 `dst`。这是由服务器调用以执行接收到的命令。
+    #[instrument(skip(self, db, dst))]
+    pub(crate) async fn apply(self, db: &Db, dst: &mut Connection<impl AsyncStream>) -> crate::Result<()> {
+        let response = Frame::Integer(db.unlink(self.keys) as i64);
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+}
+
+/// 从接收到的帧中解析出一个 `Unlink` 实例。
+///
+/// `UNLINK` 字符串已经被消费。
+///
+/// # 格式
+///
+/// ```text
+/// UNLINK key [key ...]
+/// ```
+impl TryFrom<&mut Parser> for Unlink {
+    type Error = crate::Error;
+
+    fn try_from(parser: &mut Parser) -> crate::Result<Self> {
+        use ParserError::EndOfStream;
+
+        // 至少需要一个键
+        let mut keys = vec![parser.next_string()?];
+        loop {
+            match parser.next_string() {
+                Ok(key) => keys.push(key),
+                Err(EndOfStream) => break,
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        Ok(Self { keys })
+    }
+}
+
+/// 将命令转换为等效的 `Frame`。
+impl From<Unlink> for Frame {
+    fn from(cmd: Unlink) -> Self {
+        let mut frame = Self::array();
+        frame.push_bulk(Bytes::from("unlink".as_bytes()));
+        for key in cmd.keys {
+            frame.push_bulk(Bytes::from(key.into_bytes()));
+        }
+
+        frame
+    }
+}
@@ -0,0 +1,104 @@
+use crate::aof::AofWriter;
+use crate::cmd::{InvalidArgument, Parser};
+use crate::{AsyncStream, Connection, Frame};
+
+use bytes::Bytes;
+use tokio::time::{self, Duration};
+use tracing::{debug, instrument};
+
+/// 等待当前连接之前写入的命令被同步到 AOF 所在的磁盘。
+///
+/// 回复一个包含两个整数的数组：第一个是完成同步的本地 AOF 数量，第二个是确认的副本数量。mini-redis 没有副本，
+/// 因此第二个总是 0，`numreplicas` 也不会让命令等待。
+///
+/// 开启 AOF 且 `numlocal` 大于 0 时，命令等待下一次同步完成之后回复 `[1, 0]`，不论 `appendfsync` 是什么策略；
+/// 在 `timeout` 毫秒之内没有完成时回复 `[0, 0]`，`timeout` 为 0 表示一直等待。没有开启 AOF 时立即回复 `[0, 0]`。
+#[derive(Debug)]
+pub struct WaitAof {
+    /// 需要完成同步的本地 AOF 数量
+    numlocal: u64,
+
+    /// 需要确认的副本数量
+    numreplicas: u64,
+
+    /// 最多等待多久，`None` 表示一直等待
+    timeout: Option<Duration>,
+}
+
+impl WaitAof {
+    /// 创建一个新的 `WAITAOF` 命令。
+    ///
+    /// `timeout` 为 `None` 时一直等待。
+    pub fn new(numlocal: u64, numreplicas: u64, timeout: Option<Duration>) -> Self {
+        Self {
+            numlocal,
+            numreplicas,
+            timeout,
+        }
+    }
+
+    /// 将 `WaitAof` 命令应用于服务器的 AOF `aof`，没有开启 AOF 时为 `None`。
+    ///
+    /// 响应写入 `dst`。AOF 属于服务器，因此由连接处理程序调用。
+    #[instrument(skip(self, aof, dst))]
+    pub(crate) async fn apply(
+        self,
+        aof: Option<&AofWriter>,
+        dst: &mut Connection<impl AsyncStream>,
+    ) -> crate::Result<()> {
+        let synced = match aof {
+            Some(aof) if self.numlocal > 0 => match self.timeout {
+                Some(timeout) => time::timeout(timeout, aof.sync()).await.unwrap_or(false),
+                None => aof.sync().await,
+            },
+            _ => false,
+        };
+
+        let mut response = Frame::array();
+        response.push_int(synced as i64);
+        response.push_int(0);
+
+        debug!(?response);
+
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+}
+
+/// 从接收到的帧中解析出一个 `WaitAof` 实例。
+///
+/// `WAITAOF` 字符串已经被消费。
+///
+/// # 格式
+///
+/// ```text
+/// WAITAOF numlocal numreplicas timeout
+/// ```
+impl TryFrom<&mut Parser> for WaitAof {
+    type Error = crate::Error;
+
+    fn try_from(parser: &mut Parser) -> crate::Result<Self> {
+        let numlocal = u64::try_from(parser.next_int()?).map_err(|_| InvalidArgument::new("numlocal is negative"))?;
+        let numreplicas = parser.next_int()?;
+        let numreplicas = u64::try_from(numreplicas).map_err(|_| InvalidArgument::new("numreplicas is negative"))?;
+        let timeout = u64::try_from(parser.next_int()?).map_err(|_| InvalidArgument::new("timeout is negative"))?;
+        let timeout = Some(Duration::from_millis(timeout)).filter(|timeout| !timeout.is_zero());
+
+        Ok(Self::new(numlocal, numreplicas, timeout))
+    }
+}
+
+/// 将命令转换为等效的 `Frame`。
+impl From<WaitAof> for Frame {
+    fn from(cmd: WaitAof) -> Self {
+        let mut frame = Self::array();
+        frame.push_bulk(Bytes::from("waitaof".as_bytes()));
+        frame.push_bulk(Bytes::from(cmd.numlocal.to_string()));
+        frame.push_bulk(Bytes::from(cmd.numreplicas.to_string()));
+        let millis = cmd.timeout.map_or(0, |timeout| timeout.as_millis());
+        frame.push_bulk(Bytes::from(millis.to_string()));
+
+        frame
+    }
+}
@@ -2,7 +2,11 @@ use crate::frame::Frame;
 
 use bytes::{Buf, BytesMut};
 use std::io::{self, Cursor};
-use tokio::io::{AsyncReadExt, AsyncWriteExt, BufWriter};
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufWriter, ReadBuf};
 use tokio::net::TcpStream;
 
 /// 从远程对等方发送和接收 `Frame` 值。
@@ -10,30 +14,126 @@ use tokio::net::TcpStream;
 /// 在实现网络协议时，协议上的消息通常由几个较小的消息组成，称为帧。
 /// `Connection` 的目的是在底层的 `TcpStream` 上读取和写入帧。
 ///
+/// 底层流可以是任何实现了 `AsyncRead + AsyncWrite` 的类型，默认为 `TcpStream`。
+/// 测试可以使用 `tokio::io::duplex()` 这样的内存流，无需创建套接字。
+///
 /// 为了读取帧，`Connection` 使用内部缓冲区，直到有足够的字节来创建完整的帧。
 /// 一旦发生这种情况，`Connection` 创建帧并将其返回给调用者。
 ///
 /// 在发送帧时，帧首先被编码到写缓冲区中。然后将写缓冲区的内容写入套接字。
 #[derive(Debug)]
-pub struct Connection {
-    // `TcpStream`。它被 `BufWriter` 装饰，提供写级别的缓冲。
+pub struct Connection<T = TcpStream> {
+    // 底层流，通常是 `TcpStream`。它被 `BufWriter` 装饰，提供写级别的缓冲。
     // Tokio 提供的 `BufWriter` 实现足以满足我们的需求。
-    stream: BufWriter<TcpStream>,
+    stream: BufWriter<T>,
     // 用于读取帧的缓冲区。
     buffer: BytesMut,
+    // 为 `true` 时，`write_frame` 只把帧写入缓冲区而不刷新，直到调用 `flush`。
+    // 服务器在处理流水线请求时使用它来合并多个响应。
+    defer_flush: bool,
+    // 单个帧允许的最大字节数。声明的 bulk 或数组长度超过此值的帧会导致协议错误，
+    // 防止对等方让读取缓冲区无限增长。
+    max_frame_size: usize,
+    // 通过 `HELLO` 协商的协议版本。新连接使用 RESP2。
+    protocol: Protocol,
+    // 从流中读取的总字节数。
+    bytes_read: u64,
+    // 写入的总字节数，包括还在写缓冲区中、尚未刷新的字节。
+    bytes_written: u64,
+}
+
+/// `Connection` 的底层流需要实现的特性，即 `AsyncRead + AsyncWrite + Unpin` 的简写。
+///
+/// 命令把响应写入 `Connection<impl AsyncStream>`，因此除了客户端连接，也可以在内存流上执行，例如启动时重放 AOF。
+pub(crate) trait AsyncStream: AsyncRead + AsyncWrite + Unpin {}
+
+impl<T: AsyncRead + AsyncWrite + Unpin> AsyncStream for T {}
+
+/// RESP 协议的版本。
+///
+/// RESP3 独有的帧类型（map、double 和 boolean）只在 RESP3 连接上按原样编码，RESP2 连接上会被转换为等效的
+/// RESP2 类型。因此命令可以总是使用最合适的类型，而不需要关心连接的协议。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Protocol {
+    /// RESP2，所有客户端都支持的版本。
+    #[default]
+    Resp2,
+    /// RESP3，增加了 map、double 和 boolean 等类型。
+    Resp3,
+}
+
+impl Protocol {
+    /// 返回协议的版本号，即 `HELLO` 的参数。
+    pub fn version(&self) -> i64 {
+        match self {
+            Self::Resp2 => 2,
+            Self::Resp3 => 3,
+        }
+    }
 }
 
-impl Connection {
+/// 读取缓冲区的默认初始容量。
+///
+/// 默认使用 4KB 的读取缓冲区。对于 mini redis 的用例，这是可以的。
+/// 然而，实际应用程序将希望根据其特定用例调整此值。很有可能较大的读取缓冲区会更好。
+pub(crate) const DEFAULT_BUFFER_CAPACITY: usize = 4 * 1024;
+
+/// 单个帧默认允许的最大字节数。
+pub(crate) const DEFAULT_MAX_FRAME_SIZE: usize = 8 * 1024 * 1024;
+
+impl<T: AsyncRead + AsyncWrite + Unpin> Connection<T> {
     /// 创建一个新的 `Connection`，由 `socket` 支持。读写缓冲区被初始化。
-    pub fn new(socket: TcpStream) -> Self {
+    pub fn new(socket: T) -> Self {
+        Self::with_capacity(socket, DEFAULT_BUFFER_CAPACITY)
+    }
+
+    /// 创建一个新的 `Connection`，其读取缓冲区的初始容量为 `capacity` 字节。
+    ///
+    /// 传输大 bulk 值时，较大的缓冲区可以减少 `read_buf` 系统调用的次数。缓冲区在需要时仍会增长。
+    pub fn with_capacity(socket: T, capacity: usize) -> Self {
         Self {
             stream: BufWriter::new(socket),
-            // 默认使用 4KB 的读取缓冲区。对于 mini redis 的用例，这是可以的。
-            // 然而，实际应用程序将希望根据其特定用例调整此值。很有可能较大的读取缓冲区会更好。
-            buffer: BytesMut::with_capacity(4 * 1024),
+            buffer: BytesMut::with_capacity(capacity),
+            max_frame_size: DEFAULT_MAX_FRAME_SIZE,
+            defer_flush: false,
+            protocol: Protocol::default(),
+            bytes_read: 0,
+            bytes_written: 0,
         }
     }
 
+    /// 设置单个帧允许的最大字节数。
+    ///
+    /// 超过此限制的帧会使 `read_frame` 返回错误，调用者应关闭连接。默认值为 8MB。
+    pub fn set_max_frame_size(&mut self, max_frame_size: usize) {
+        self.max_frame_size = max_frame_size;
+    }
+
+    /// 返回连接协商的协议版本。
+    pub fn protocol(&self) -> Protocol {
+        self.protocol
+    }
+
+    /// 设置写入帧时使用的协议版本。服务器在 `HELLO` 协商之后调用。
+    pub fn set_protocol(&mut self, protocol: Protocol) {
+        self.protocol = protocol;
+    }
+
+    /// 返回从流中读取的总字节数。
+    pub fn bytes_read(&self) -> u64 {
+        self.bytes_read
+    }
+
+    /// 返回写入的总字节数，包括还在写缓冲区中、尚未刷新的字节。
+    pub fn bytes_written(&self) -> u64 {
+        self.bytes_written
+    }
+
+    /// 返回底层流的引用。写缓冲区中还没有刷新的字节不在其中。
+    pub(crate) fn get_ref(&self) -> &T {
+        self.stream.get_ref()
+    }
+
     /// 从底层流中读取单个 `Frame` 值。
     ///
     /// 该函数等待，直到它检索到足够的数据来解析帧。
@@ -49,23 +149,93 @@ impl Connection {
             if let Some(frame) = MaybeFrame::try_from(&mut *self)? {
                 return Ok(Some(frame));
             }
-
             // 缓冲的数据不足以读取帧。尝试从套接字读取更多数据。
-            //
-            // 成功时，返回字节数。`0` 表示“流结束”。
-            if 0 != self.stream.read_buf(&mut self.buffer).await? {
-                continue;
-            }
-            // 远程关闭了连接。为了实现干净的关闭，读取缓冲区中不应有数据。
-            // 如果有，这意味着对等方在发送帧时关闭了套接字。
-            if self.buffer.is_empty() {
+            if !self.read_more().await? {
                 return Ok(None);
-            } else {
-                return Err("connection reset by peer".into());
             }
         }
     }
 
+    /// 读取下一个帧，但长度达到 `threshold` 的 bulk 字符串不缓冲，而是返回一个按块读取载荷的 [`BulkReader`]。
+    ///
+    /// 只有顶层的 bulk 字符串会被流式读取，数组中的 bulk 字符串仍然完整地缓冲，其他帧与 `read_frame` 相同。
+    ///
+    /// # 取舍
+    ///
+    /// * 载荷不经过读取缓冲区，因此不受 [`set_max_frame_size`](Self::set_max_frame_size) 的限制。
+    ///   调用者需要自己决定能接受多大的载荷，例如读到一定数量之后关闭连接。
+    /// * `BulkReader` 借用了连接，读完载荷之前不能读写其他帧。
+    /// * 读到一半就丢弃 `BulkReader` 会让连接停在帧的中间，之后的读取会得到错误的数据，只能关闭连接。
+    /// * 每次读取都可能是一次系统调用。调用者应该使用足够大的缓冲区，小的读取没有 `read_frame` 高效。
+    pub async fn read_frame_streaming(&mut self, threshold: usize) -> crate::Result<Option<StreamedFrame<'_, T>>> {
+        loop {
+            let mut buf = Cursor::new(&self.buffer[..]);
+            match Frame::parse_bulk_header(&mut buf) {
+                Ok(Some(len)) if len >= threshold => {
+                    // 只消费头部，载荷留给 `BulkReader`。
+                    let header = buf.position() as usize;
+                    self.buffer.advance(header);
+                    return Ok(Some(StreamedFrame::Bulk(BulkReader {
+                        conn: self,
+                        remaining: len,
+                        trailer: 2,
+                    })));
+                }
+                // 还没有收到完整的头部，读取更多数据之后再判断。
+                Err(crate::frame::FrameError::Incomplete) => {
+                    if !self.read_more().await? {
+                        return Ok(None);
+                    }
+                }
+                // 不是需要流式读取的 bulk 字符串，或者头部无效，交给 `read_frame` 处理。
+                _ => return Ok(self.read_frame().await?.map(StreamedFrame::Frame)),
+            }
+        }
+    }
+
+    /// 从套接字读取更多数据到读取缓冲区。
+    ///
+    /// 对等方干净地关闭了连接时返回 `false`。如果在帧的中间关闭，则返回错误。
+    async fn read_more(&mut self) -> crate::Result<bool> {
+        // 即使每个声明的长度都在限制内，一个由许多元素组成的帧也可能无限增长。
+        // 已缓冲的数据超过限制却仍不完整时，放弃该连接。
+        if self.buffer.len() > self.max_frame_size {
+            return Err("protocol error; frame exceeds maximum size".into());
+        }
+
+        // 成功时，返回字节数。`0` 表示“流结束”。
+        let n = self.stream.read_buf(&mut self.buffer).await?;
+        if n != 0 {
+            self.bytes_read += n as u64;
+            return Ok(true);
+        }
+        // 远程关闭了连接。为了实现干净的关闭，读取缓冲区中不应有数据。
+        // 如果有，这意味着对等方在发送帧时关闭了套接字。
+        if self.buffer.is_empty() {
+            Ok(false)
+        } else {
+            Err(io::Error::new(io::ErrorKind::ConnectionReset, "connection reset by peer").into())
+        }
+    }
+
+    /// 仅从已缓冲的数据中解析下一个帧，不从套接字读取。
+    ///
+    /// 如果缓冲区中没有完整的帧，则返回 `None`。
+    pub(crate) fn read_buffered_frame(&mut self) -> crate::Result<MaybeFrame> {
+        MaybeFrame::try_from(self)
+    }
+
+    /// 推迟刷新：之后的 `write_frame` 调用只写入写缓冲区，直到调用 `flush`。
+    pub(crate) fn defer_flush(&mut self) {
+        self.defer_flush = true;
+    }
+
+    /// 将写缓冲区中的所有数据写入套接字，并恢复每次 `write_frame` 后立即刷新。
+    pub(crate) async fn flush(&mut self) -> io::Result<()> {
+        self.defer_flush = false;
+        self.stream.flush().await
+    }
+
     /// 将单个 `Frame` 值写入底层流。
     ///
     /// 使用 `AsyncWrite` 提供的各种 `write_*` 函数将 `Frame` 值写入套接字。
@@ -73,25 +243,71 @@ impl Connection {
     /// 但是，在*缓冲*写流上调用这些函数是可以的。数据将被写入缓冲区。
     /// 一旦缓冲区满了，它将被刷新到底层套接字。
     pub async fn write_frame(&mut self, frame: &Frame) -> io::Result<()> {
-        // 数组通过编码每个条目来编码。所有其他帧类型都被视为文字。
-        // 目前，mini-redis 无法编码递归帧结构。有关更多详细信息，请参见下文。
-        match frame {
-            Frame::Array(value) => {
-                // 编码帧类型前缀。对于数组，它是 `*`。
-                self.stream.write_u8(b'*').await?;
-                // 编码数组的长度。
-                self.write_decimal(value.len() as u64).await?;
-                // 迭代并编码数组中的每个条目。
-                for frame in value.iter() {
-                    self.write_value(frame).await?;
+        // 数组通过编码每个条目来编码，数组可以任意嵌套。所有其他帧类型都被视为文字。
+        //
+        // 一般来说，异步函数不支持递归，因此这里使用一个显式的迭代器栈代替递归。
+        // 栈顶是当前正在编码的数组的剩余条目。遇到数组时写入其头部并将其条目压栈；
+        // 一个数组的条目耗尽时将其弹出，继续编码外层数组。
+        let mut stack = vec![Entries::Array(std::slice::from_ref(frame).iter())];
+        while let Some(entries) = stack.last_mut() {
+            match entries.next() {
+                Some(Frame::Array(value)) => {
+                    // 编码帧类型前缀。对于数组，它是 `*`。
+                    self.write_bytes(b"*").await?;
+                    // 编码数组的长度。
+                    self.write_decimal(value.len() as i64).await?;
+                    // 接下来编码数组中的每个条目。
+                    stack.push(Entries::Array(value.iter()));
+                }
+                Some(Frame::Push(value)) => {
+                    // RESP2 没有推送类型，编码为普通数组，客户端根据第一个元素识别消息。
+                    match self.protocol {
+                        Protocol::Resp3 => self.write_bytes(b">").await?,
+                        Protocol::Resp2 => self.write_bytes(b"*").await?,
+                    }
+                    self.write_decimal(value.len() as i64).await?;
+                    stack.push(Entries::Array(value.iter()));
+                }
+                Some(Frame::Map(pairs)) => {
+                    // RESP2 没有 map 类型，键和值交替组成一个数组。
+                    match self.protocol {
+                        Protocol::Resp3 => {
+                            self.write_bytes(b"%").await?;
+                            self.write_decimal(pairs.len() as i64).await?;
+                        }
+                        Protocol::Resp2 => {
+                            self.write_bytes(b"*").await?;
+                            self.write_decimal(pairs.len() as i64 * 2).await?;
+                        }
+                    }
+                    stack.push(Entries::Map(pairs.iter(), None));
+                }
+                // 帧类型是文字。直接编码值。
+                Some(frame) => self.write_value(frame).await?,
+                None => {
+                    stack.pop();
                 }
             }
-            // 帧类型是文字。直接编码值。
-            _ => self.write_value(frame).await?,
         }
 
         // 确保编码的帧被写入套接字。上面的调用是对缓冲流和写入的调用。
-        // 调用 `flush` 将缓冲区的剩余内容写入套接字。
+        // 调用 `flush` 将缓冲区的剩余内容写入套接字。推迟刷新时由调用者负责稍后刷新。
+        if self.defer_flush {
+            return Ok(());
+        }
+        self.stream.flush().await
+    }
+
+    /// 只写入数组帧的头部 `*<len>\r\n`，之后写入的 `len` 个帧成为该数组的条目。
+    ///
+    /// `EXEC` 用它把事务中每个命令各自写入的响应组合成一个数组，而不需要先把响应收集起来。
+    pub(crate) async fn write_array_header(&mut self, len: usize) -> io::Result<()> {
+        self.write_bytes(b"*").await?;
+        self.write_decimal(len as i64).await?;
+
+        if self.defer_flush {
+            return Ok(());
+        }
         self.stream.flush().await
     }
 
@@ -99,41 +315,75 @@ impl Connection {
     async fn write_value(&mut self, frame: &Frame) -> io::Result<()> {
         match frame {
             Frame::Simple(value) => {
-                self.stream.write_u8(b'+').await?;
-                self.stream.write_all(value.as_bytes()).await?;
-                self.stream.write_all(b"\r\n").await?;
+                self.write_bytes(b"+").await?;
+                self.write_bytes(value.as_bytes()).await?;
+                self.write_bytes(b"\r\n").await?;
             }
             Frame::Error(value) => {
-                self.stream.write_u8(b'-').await?;
-                self.stream.write_all(value.as_bytes()).await?;
-                self.stream.write_all(b"\r\n").await?;
+                self.write_bytes(b"-").await?;
+                self.write_bytes(value.as_bytes()).await?;
+                self.write_bytes(b"\r\n").await?;
             }
             Frame::Integer(value) => {
-                self.stream.write_u8(b':').await?;
+                self.write_bytes(b":").await?;
                 self.write_decimal(*value).await?;
             }
             Frame::Null => {
-                self.stream.write_all(b"$-1\r\n").await?;
+                self.write_bytes(b"$-1\r\n").await?;
+            }
+            Frame::NullArray => {
+                self.write_bytes(b"*-1\r\n").await?;
             }
             Frame::Bulk(value) => {
                 let len = value.len();
 
-                self.stream.write_u8(b'$').await?;
-                self.write_decimal(len as u64).await?;
-                self.stream.write_all(value).await?;
-                self.stream.write_all(b"\r\n").await?;
+                self.write_bytes(b"$").await?;
+                self.write_decimal(len as i64).await?;
+                self.write_bytes(value).await?;
+                self.write_bytes(b"\r\n").await?;
             }
-            // 在值中编码 `Array` 不能使用递归策略。
-            // 一般来说，异步函数不支持递归。
-            // Mini-redis 还不需要编码嵌套数组，所以目前跳过它。
-            Frame::Array(_value) => unreachable!(),
+            Frame::Double(value) => {
+                let value = format_double(*value);
+                match self.protocol {
+                    Protocol::Resp3 => {
+                        self.write_bytes(b",").await?;
+                        self.write_bytes(value.as_bytes()).await?;
+                        self.write_bytes(b"\r\n").await?;
+                    }
+                    // RESP2 中浮点数以字符串形式返回，与 Redis 一致。
+                    Protocol::Resp2 => {
+                        self.write_bytes(b"$").await?;
+                        self.write_decimal(value.len() as i64).await?;
+                        self.write_bytes(value.as_bytes()).await?;
+                        self.write_bytes(b"\r\n").await?;
+                    }
+                }
+            }
+            Frame::Boolean(value) => match self.protocol {
+                Protocol::Resp3 => {
+                    self.write_bytes(if *value { b"#t\r\n" } else { b"#f\r\n" }).await?;
+                }
+                Protocol::Resp2 => {
+                    self.write_bytes(b":").await?;
+                    self.write_decimal(*value as i64).await?;
+                }
+            },
+            // 数组、推送和 map 由 `write_frame` 使用显式栈编码，永远不会作为文字传入这里。
+            Frame::Array(_) | Frame::Push(_) | Frame::Map(_) => unreachable!(),
         }
 
         Ok(())
     }
 
+    /// 将 `bytes` 写入写缓冲区并计入写入的字节数。所有写入都经过这里。
+    async fn write_bytes(&mut self, bytes: &[u8]) -> io::Result<()> {
+        self.stream.write_all(bytes).await?;
+        self.bytes_written += bytes.len() as u64;
+        Ok(())
+    }
+
     /// 将十进制帧写入流
-    async fn write_decimal(&mut self, value: u64) -> io::Result<()> {
+    async fn write_decimal(&mut self, value: i64) -> io::Result<()> {
         use std::io::Write;
 
         // Convert the value to a string
@@ -142,30 +392,192 @@ impl Connection {
         write!(&mut buf, "{}", value)?;
 
         let pos = buf.position() as usize;
-        self.stream.write_all(&buf.get_ref()[..pos]).await?;
-        self.stream.write_all(b"\r\n").await?;
+        self.write_bytes(&buf.get_ref()[..pos]).await?;
+        self.write_bytes(b"\r\n").await?;
 
         Ok(())
     }
 }
 
+/// [`Connection::read_frame_streaming`] 读取的帧。
+#[derive(Debug)]
+pub enum StreamedFrame<'a, T = TcpStream> {
+    /// 一个完整的帧，与 `read_frame` 返回的相同。
+    Frame(Frame),
+    /// 一个长度达到阈值的 bulk 字符串。载荷还留在流中，通过 `BulkReader` 按块读取。
+    Bulk(BulkReader<'a, T>),
+}
+
+/// 按块读取 bulk 字符串的载荷，实现了 `AsyncRead`。
+///
+/// 读到流结束时，载荷和结尾的 `\r\n` 都已经被消费，连接可以继续读取下一个帧。
+/// 在此之前丢弃 `BulkReader` 会让连接停在帧的中间，调用者应该读完载荷或者关闭连接。
+#[derive(Debug)]
+pub struct BulkReader<'a, T = TcpStream> {
+    /// 载荷所在的连接。读取缓冲区中可能已经有一部分载荷。
+    conn: &'a mut Connection<T>,
+    /// 载荷中还没有读取的字节数。
+    remaining: usize,
+    /// 结尾的 `\r\n` 中还没有消费的字节数。
+    trailer: usize,
+}
+
+impl<T> BulkReader<'_, T> {
+    /// 返回载荷中还没有读取的字节数。
+    pub fn remaining(&self) -> usize {
+        self.remaining
+    }
+}
+
+impl<T: AsyncRead + AsyncWrite + Unpin> AsyncRead for BulkReader<'_, T> {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        let conn = &mut *this.conn;
+
+        if this.remaining > 0 {
+            let n = if conn.buffer.is_empty() {
+                // 直接读入调用者的缓冲区，不超过载荷的剩余部分，以免读走下一个帧。
+                let dst = buf.initialize_unfilled_to(this.remaining.min(buf.remaining()));
+                let mut dst = ReadBuf::new(dst);
+                ready!(Pin::new(&mut conn.stream).poll_read(cx, &mut dst))?;
+                let n = dst.filled().len();
+                if n == 0 && buf.remaining() > 0 {
+                    return Poll::Ready(Err(io::ErrorKind::UnexpectedEof.into()));
+                }
+                conn.bytes_read += n as u64;
+                buf.advance(n);
+                n
+            } else {
+                // 读取头部时已经缓冲的那部分载荷。
+                let n = this.remaining.min(conn.buffer.len()).min(buf.remaining());
+                buf.put_slice(&conn.buffer[..n]);
+                conn.buffer.advance(n);
+                n
+            };
+            this.remaining -= n;
+            return Poll::Ready(Ok(()));
+        }
+
+        // 载荷读完之后消费结尾的 `\r\n`，然后才报告流结束。
+        while this.trailer > 0 {
+            if conn.buffer.is_empty() {
+                let mut trailer = [0; 2];
+                let mut dst = ReadBuf::new(&mut trailer[..this.trailer]);
+                ready!(Pin::new(&mut conn.stream).poll_read(cx, &mut dst))?;
+                if dst.filled().is_empty() {
+                    return Poll::Ready(Err(io::ErrorKind::UnexpectedEof.into()));
+                }
+                conn.bytes_read += dst.filled().len() as u64;
+                conn.buffer.extend_from_slice(dst.filled());
+            }
+            if conn.buffer[0] != b"\r\n"[2 - this.trailer] {
+                return Poll::Ready(Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "protocol error; invalid frame format",
+                )));
+            }
+            conn.buffer.advance(1);
+            this.trailer -= 1;
+        }
+
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl Connection<TcpStream> {
+    /// 返回对等方的地址。
+    ///
+    /// 只有基于 `TcpStream` 的连接才有对等方地址。
+    pub fn peer_addr(&self) -> io::Result<SocketAddr> {
+        self.stream.get_ref().peer_addr()
+    }
+}
+
+/// 设置 TCP 套接字的选项。服务器对接受的套接字、客户端对建立的套接字调用它。
+///
+/// `nodelay` 为 `true` 时禁用 Nagle 算法，小的响应不会为了等待合并而延迟发送。`Connection` 已经在
+/// `BufWriter` 中合并了写入，只在刷新时才写入套接字，因此禁用 Nagle 算法不会产生大量小的报文。
+///
+/// `keepalive` 为 `Some` 时启用 TCP keepalive，连接空闲该时间之后开始探测对等方，
+/// 这样可以发现已经消失而没有关闭连接的对等方。
+pub(crate) fn configure_socket(socket: &TcpStream, nodelay: bool, keepalive: Option<Duration>) -> io::Result<()> {
+    socket.set_nodelay(nodelay)?;
+
+    if let Some(time) = keepalive {
+        let keepalive = socket2::TcpKeepalive::new().with_time(time);
+        socket2::SockRef::from(socket).set_tcp_keepalive(&keepalive)?;
+    }
+
+    Ok(())
+}
+
+/// `write_frame` 中正在编码的数组或 map 的剩余条目。map 的每个条目依次产生键和值。
+enum Entries<'a> {
+    Array(std::slice::Iter<'a, Frame>),
+    /// 剩余的键值对，以及已经编码了键、尚未编码的值。
+    Map(std::slice::Iter<'a, (Frame, Frame)>, Option<&'a Frame>),
+}
+
+impl<'a> Iterator for Entries<'a> {
+    type Item = &'a Frame;
+
+    fn next(&mut self) -> Option<&'a Frame> {
+        match self {
+            Self::Array(entries) => entries.next(),
+            Self::Map(pairs, pending) => pending.take().or_else(|| {
+                let (key, value) = pairs.next()?;
+                *pending = Some(value);
+                Some(key)
+            }),
+        }
+    }
+}
+
+/// 按 RESP3 的格式编码浮点数。无穷大和 NaN 编码为 `inf`、`-inf` 和 `nan`。
+fn format_double(value: f64) -> String {
+    if value.is_nan() {
+        "nan".to_string()
+    } else if value.is_infinite() {
+        if value > 0.0 { "inf" } else { "-inf" }.to_string()
+    } else {
+        value.to_string()
+    }
+}
+
 type MaybeFrame = Option<Frame>;
 
 /// 尝试从缓冲区解析帧。如果缓冲区包含足够的数据，则返回帧并从缓冲区中移除数据。
 /// 如果缓冲的数据不足，则返回 `Ok(None)`。如果缓冲的数据不是有效的帧，则返回 `Err`。
-impl TryFrom<&mut Connection> for MaybeFrame {
+impl<T> TryFrom<&mut Connection<T>> for MaybeFrame {
     type Error = crate::Error;
 
-    fn try_from(conn: &mut Connection) -> crate::Result<Self> {
+    fn try_from(conn: &mut Connection<T>) -> crate::Result<Self> {
         use crate::frame::FrameError::Incomplete;
 
+        // 不以类型字节开头的是内联命令，例如通过 `telnet` 输入的 `PING\r\n`。把它转换为等效的数组帧，
+        // 与 Redis 一样忽略空行。
+        while conn.buffer.first().is_some_and(|&first| Frame::is_inline(first)) {
+            let mut buf = Cursor::new(&conn.buffer[..]);
+            match Frame::parse_inline(&mut buf, conn.max_frame_size) {
+                Ok(frame) => {
+                    let len = buf.position() as usize;
+                    conn.buffer.advance(len);
+                    if frame != Frame::array() {
+                        return Ok(Some(frame));
+                    }
+                }
+                Err(Incomplete) => return Ok(None),
+                Err(e) => return Err(e.into()),
+            }
+        }
+
         // Cursor 用于跟踪缓冲区中的“当前位置”。Cursor 还实现了 `bytes` crate 中的 `Buf`，
         // 提供了许多处理字节的有用工具。
         let mut buf = Cursor::new(&conn.buffer[..]);
         // 第一步是检查是否已缓冲足够的数据来解析单个帧。
         // 这一步通常比进行完整的帧解析要快得多，并且允许我们跳过分配数据结构来保存帧数据，
         // 除非我们知道已接收到完整的帧。
-        match Frame::check(&mut buf) {
+        match Frame::check_bounded(&mut buf, conn.max_frame_size) {
             Ok(_) => {
                 // `check` 函数将把光标推进到帧的末尾。
                 // 由于在调用 `Frame::check` 之前光标的位置设置为零，
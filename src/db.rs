@@ -1,9 +1,21 @@
-use tokio::sync::{broadcast, Notify};
+use crate::cmd::SetCondition;
+use crate::glob;
+use crate::server::MaxMemoryPolicy;
+
+use tokio::sync::{broadcast, mpsc, Notify, OwnedRwLockReadGuard, OwnedRwLockWriteGuard};
 use tokio::time::{self, Duration, Instant};
 
-use bytes::Bytes;
-use std::collections::{BTreeSet, HashMap};
-use std::sync::{Arc, Mutex};
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use std::collections::hash_map::{self, RandomState};
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+use std::future::{self, Future};
+use std::io;
+use std::hash::BuildHasher;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::task::Poll;
+use std::time::{SystemTime, UNIX_EPOCH};
 use tracing::debug;
 
 /// `Db` 实例的包装器。此结构体存在的目的是在此结构体被丢弃时，通过通知后台清理任务关闭来有序地清理 `Db`。
@@ -17,6 +29,9 @@ pub(crate) struct DbDropGuard {
 ///
 /// `Db` 包含一个 `HashMap`，用于存储键/值数据和所有活动的 pub/sub 频道的 `broadcast::Sender` 值。
 ///
+/// 服务器有多个编号的数据库，每个都是独立的键空间，pub/sub 则由所有数据库共享。`Db` 句柄指向其中一个数据库，
+/// 键相关的方法都作用于它；连接执行 `SELECT` 时换成指向另一个数据库的句柄。
+///
 /// `Db` 实例是共享状态的句柄。克隆 `Db` 是浅拷贝，只会增加一个原子引用计数。
 ///
 /// 当创建 `Db` 值时，会生成一个后台任务。此任务用于在请求的持续时间过后过期值。任务运行直到所有 `Db` 实例被丢弃，此时任务终止。
@@ -24,28 +39,93 @@ pub(crate) struct DbDropGuard {
 pub(crate) struct Db {
     /// 共享状态的句柄。后台任务也将拥有一个 `Arc<Shared>`。
     shared: Arc<Shared>,
+    /// 句柄指向的数据库的编号。
+    index: usize,
 }
 
 #[derive(Debug)]
 struct Shared {
-    /// 共享状态由互斥锁保护。这是一个 `std::sync::Mutex`，而不是 Tokio 互斥锁。
-    /// 这是因为在持有互斥锁时没有执行异步操作。此外，临界区非常小。
+    /// 键空间被分成 [`SHARDS`] 个分片，键按哈希值分配到分片，每个分片由自己的读写锁保护。
+    /// 访问不同分片中的键的连接不会互相等待；只读取键的命令（例如 `GET`）持有读锁，同一个分片中的读取也可以并发进行。
+    ///
+    /// 每个数据库有自己的一组分片，`databases[i]` 是编号为 `i` 的数据库。数据库在第一次被选择时才分配分片，
+    /// 配置了很多数据库但只使用其中几个时不会为其余的数据库分配内存。
+    ///
+    /// 这些是 `std::sync::RwLock`，而不是 Tokio 读写锁。
+    /// 这是因为在持有锁时没有执行异步操作。此外，临界区非常小。
     ///
-    /// Tokio 互斥锁主要用于需要在 `.await` 让步点持有锁的情况。所有其他情况通常最好使用 std 互斥锁。
-    /// 如果临界区不包括任何异步操作但很长（CPU 密集型或执行阻塞操作），则整个操作，包括等待互斥锁，都会被视为“阻塞”操作，
+    /// Tokio 的锁主要用于需要在 `.await` 让步点持有锁的情况。所有其他情况通常最好使用 std 的锁。
+    /// 如果临界区不包括任何异步操作但很长（CPU 密集型或执行阻塞操作），则整个操作，包括等待锁，都会被视为“阻塞”操作，
     /// 应使用 `tokio::task::spawn_blocking`。
-    state: Mutex<State>,
+    databases: Box<[OnceLock<Shards>]>,
+    /// 用于把键分配到分片的哈希函数。所有数据库使用同一个哈希函数，同一个键在每个数据库中都位于相同编号的分片。
+    hasher: RandomState,
+    /// pub/sub 状态。频道与键空间无关，因此不分片。
+    pub_sub: Mutex<PubSub>,
     /// 通知处理条目过期的后台任务。后台任务等待此通知，然后检查过期值或关闭信号。
     background_task: Notify,
+    /// 事务锁。普通命令执行时持有读锁，`EXEC` 执行整个事务时持有写锁，因此其他连接的命令不会穿插在事务中间。
+    ///
+    /// 与分片的锁不同，这是一个 Tokio 读写锁：命令在持有它时需要向连接写入响应。
+    transaction: Arc<tokio::sync::RwLock<()>>,
+    /// 当 Db 实例正在关闭时为 true。当所有 `Db` 值都丢弃时会发生这种情况。
+    /// 将此设置为 `true` 会向后台任务发出退出信号。
+    is_shutdown: AtomicBool,
+    /// 为 `true` 时，后台任务每清理一个过期的键，就把键名发布到 [`EXPIRED_CHANNEL`]。
+    notify_expired: AtomicBool,
+    /// 为 `true` 时，订阅者因为太慢而丢弃消息时会收到一条 `lagged` 消息。
+    notify_lagged: AtomicBool,
+    /// 为 `false` 时，后台任务不再主动清理过期的键，由 `DEBUG SET-ACTIVE-EXPIRE 0` 设置，用于测试。
+    active_expire: AtomicBool,
+    /// 为 `true` 时允许执行 `DEBUG SLEEP` 等只用于测试的 `DEBUG` 子命令。
+    debug_hooks: AtomicBool,
+    /// 为 `true` 时 `HGETALL` 和 `SMEMBERS` 等命令按字节序返回元素，用于测试。
+    sorted_replies: AtomicBool,
+    /// 新建的频道和模式的广播通道能容纳多少条消息。
+    pubsub_capacity: AtomicUsize,
+    /// 订阅空闲多少毫秒之后推送一个保活的 `ping`。为 `0` 时不推送。
+    keepalive_interval: AtomicU64,
+    /// 内存上限，单位是字节。为 `0` 时不限制。
+    maxmemory: AtomicUsize,
+    /// `SCAN` 等遍历大量元素的命令最多执行多少微秒。为 `0` 时不限制。
+    command_time_limit: AtomicU64,
+    /// 为 `true` 时超过内存上限不驱逐任何键，即 [`MaxMemoryPolicy::NoEviction`]。
+    noeviction: AtomicBool,
+    /// 逻辑时钟，每次访问键时递增。条目记录最近一次访问时的值，用于找出最久未使用的键。
+    clock: AtomicU64,
+    /// `SAVE` 写入快照的文件。为 `None` 时 `SAVE` 回复错误。
+    snapshot_path: Mutex<Option<PathBuf>>,
+    /// `UNLINK` 删除的大值被发送到后台任务丢弃，释放内存不占用请求的时间。
+    lazy_free: mpsc::UnboundedSender<Value>,
 }
 
-#[derive(Debug)]
+/// 键空间的分片数。
+const SHARDS: usize = 16;
+
+/// 一个数据库的 [`SHARDS`] 个分片。
+type Shards = Box<[RwLock<State>]>;
+
+/// 元素数超过该值的列表、哈希和集合被 `UNLINK` 删除时在后台释放。更小的值直接释放比发送到后台任务更快。
+const LAZYFREE_THRESHOLD: usize = 64;
+
+/// 长度不超过该值的字符串在 `OBJECT ENCODING` 中报告为 `embstr`，更长的报告为 `raw`，与 Redis 相同。
+const EMBSTR_MAX_LEN: usize = 44;
+
+/// 元素数不超过该值的列表、哈希和集合在 `OBJECT ENCODING` 中报告为 `listpack`，与 Redis 的默认配置相同。
+const LISTPACK_MAX_ENTRIES: usize = 128;
+
+/// 遍历大量元素的命令每处理这么多个元素检查一次截止时间，避免每个元素都读取时钟。
+const DEADLINE_CHECK_INTERVAL: usize = 1024;
+
+/// 键空间的一个分片。
+#[derive(Debug, Default)]
 struct State {
     /// 键值数据。我们不打算做任何花哨的事情，所以 `std::collections::HashMap` 就可以了。
     entries: HashMap<String, Entry>,
-    /// pub/sub 键空间。Redis 使用一个**单独的**键空间来存储键值和 pub/sub。
-    /// `mini-redis` 通过使用一个单独的 `HashMap` 来处理这个问题。
-    pub_sub: HashMap<String, broadcast::Sender<Bytes>>,
+    /// 在列表上阻塞的客户端。推入列表时唤醒该键上的所有等待者。
+    ///
+    /// 条目由 [`ListWaiter`] 创建，最后一个等待者离开时删除。
+    list_waiters: HashMap<String, Arc<Notify>>,
     /// 跟踪键的 TTL。
     ///
     /// 使用 `BTreeSet` 来维护按过期时间排序的过期条目。这允许后台任务迭代此映射以找到下一个过期的值。
@@ -53,24 +133,254 @@ struct State {
     /// 虽然极不可能，但有可能在同一时刻创建多个过期条目。
     /// 因此，`Instant` 对于键来说是不够的。使用唯一键（`String`）来解决这些冲突。
     expirations: BTreeSet<(Instant, String)>,
-    /// 当 Db 实例正在关闭时为 true。当所有 `Db` 值都丢弃时会发生这种情况。
-    /// 将此设置为 `true` 会向后台任务发出退出信号。
-    is_shutdown: bool,
+    /// 分片中所有键和值的近似字节数，即键的长度加上值的长度。每次修改条目时更新。
+    used_memory: usize,
+}
+
+/// pub/sub 状态。
+///
+/// Redis 使用一个**单独的**键空间来存储键值和 pub/sub。`mini-redis` 通过使用单独的 `HashMap` 来处理这个问题。
+#[derive(Debug, Default)]
+struct PubSub {
+    /// 每个频道的广播发送器。
+    channels: HashMap<String, broadcast::Sender<Bytes>>,
+    /// 模式订阅。键是 glob 模式，广播的值是 `(频道, 消息)`，因为订阅者需要知道消息来自哪个频道。
+    patterns: HashMap<String, broadcast::Sender<(String, Bytes)>>,
 }
 
+/// 键过期时发布通知的频道。消息内容是过期的键名。
+pub(crate) const EXPIRED_CHANNEL: &str = "__keyevent__:expired";
+
+/// 快照文件开头的魔数，其后是一个字节的格式版本 [`SNAPSHOT_VERSION`]。
+///
+/// 魔数和版本之后是一系列记录，每个键一条，最后以一个 [`SNAPSHOT_EOF`] 字节结束。所有整数都是大端序。
+///
+/// ```text
+/// 记录类型  u8   0 字符串，1 列表，2 哈希，3 集合
+/// 过期时间  u64  Unix 毫秒时间戳，0 表示没有过期时间
+/// 键        u32 长度 + 字节
+/// 值        字符串为 u32 长度 + 字节；列表和集合为 u32 元素数 + 每个元素；哈希为 u32 字段数 + 每个字段和值
+/// ```
+///
+/// 类型为 [`SNAPSHOT_SELECTDB`] 的记录只包含一个 u32 数据库编号，之后的键属于该数据库；第一条这样的记录之前的键
+/// 属于 0 号数据库。
+///
+/// 过期时间保存为绝对时间，因此服务器重启期间流逝的时间同样计入键的生存时间。
+const SNAPSHOT_MAGIC: &[u8] = b"MINIREDIS";
+
+/// 快照的格式版本。版本 1 没有 [`SNAPSHOT_SELECTDB`] 记录，所有键都属于 0 号数据库，仍然可以加载。
+const SNAPSHOT_VERSION: u8 = 2;
+
+/// 快照中切换数据库的记录类型。
+const SNAPSHOT_SELECTDB: u8 = 0xFE;
+
+/// 快照中标记文件结束的记录类型。
+const SNAPSHOT_EOF: u8 = 0xFF;
+
 /// 键值存储中的条目
 #[derive(Debug)]
 struct Entry {
     /// 存储的数据
-    data: Bytes,
+    data: Value,
     /// 条目过期并应从数据库中删除的时间点。
     expires_at: Option<Instant>,
+    /// 最近一次访问条目时 [`Shared::clock`] 的值。`GET` 只持有读锁，因此这是一个原子类型。
+    last_access: AtomicU64,
+}
+
+/// 键保存的值。每种 Redis 数据类型对应一个变体。
+///
+/// 克隆是浅克隆：元素都是 `Bytes`，只复制集合本身，不复制数据。
+#[derive(Debug, Clone)]
+enum Value {
+    /// 字符串，由 `SET`、`APPEND` 等命令操作。
+    String(Bytes),
+    /// 列表，由 `LPUSH`、`RPOP` 等命令操作。两端的推入和弹出都是 O(1)。
+    List(VecDeque<Bytes>),
+    /// 哈希，由 `HSET`、`HGET` 等命令操作。
+    Hash(HashMap<String, Bytes>),
+    /// 集合，由 `SADD`、`SMEMBERS` 等命令操作。
+    Set(HashSet<Bytes>),
+}
+
+/// 列表的一端。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ListEnd {
+    /// 列表头部，即 `LPUSH` 和 `LPOP` 操作的一端。
+    Left,
+    /// 列表尾部，即 `RPUSH` 和 `RPOP` 操作的一端。
+    Right,
+}
+
+/// 等待列表被推入的句柄，由 `BLPOP` 等阻塞命令使用。
+///
+/// 丢弃时删除不再有人等待的通知，避免 `list_waiters` 无限增长。
+#[derive(Debug)]
+pub(crate) struct ListWaiter {
+    db: Db,
+    /// 每个等待的键及其通知。
+    notifies: Vec<(String, Arc<Notify>)>,
+}
+
+impl ListWaiter {
+    /// 返回一个在任意一个列表被推入时完成的 future。
+    ///
+    /// 返回的 future 在这里创建时就开始监听，而不是在第一次轮询时。因此应该在检查列表**之前**调用，
+    /// 这样检查之后、开始等待之前发生的推入也不会被错过。
+    pub(crate) fn pushed(&self) -> impl Future<Output = ()> + '_ {
+        let mut notified: Vec<_> = self.notifies.iter().map(|(_, notify)| Box::pin(notify.notified())).collect();
+
+        future::poll_fn(move |cx| {
+            for notified in &mut notified {
+                if notified.as_mut().poll(cx).is_ready() {
+                    return Poll::Ready(());
+                }
+            }
+            Poll::Pending
+        })
+    }
+}
+
+impl Drop for ListWaiter {
+    fn drop(&mut self) {
+        for (key, notify) in self.notifies.drain(..) {
+            let mut state = self.db.write(&key);
+            drop(notify);
+            // 通知只在持有锁时克隆，因此引用计数为 1 说明只剩 `list_waiters` 自己。
+            if state.list_waiters.get(&key).is_some_and(|notify| Arc::strong_count(notify) == 1) {
+                state.list_waiters.remove(&key);
+            }
+        }
+    }
+}
+
+/// 对保存了其他类型的值的键执行命令时返回的错误。
+///
+/// 命令把它作为错误帧回复给客户端，连接保持可用。
+#[derive(Debug)]
+pub(crate) struct WrongType;
+
+impl std::fmt::Display for WrongType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        "WRONGTYPE Operation against a key holding the wrong kind of value".fmt(f)
+    }
+}
+
+impl std::error::Error for WrongType {}
+
+/// `SINTER`、`SUNION` 和 `SDIFF` 对多个集合执行的运算。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SetOp {
+    /// 交集：所有集合中都有的成员。
+    Inter,
+    /// 并集：任何一个集合中有的成员。
+    Union,
+    /// 差集：第一个集合中有、其他集合中都没有的成员。
+    Diff,
+}
+
+/// 写入会超过内存上限，并且驱逐其他所有键之后仍然放不下时返回的错误。
+#[derive(Debug)]
+pub(crate) struct OutOfMemory;
+
+impl std::fmt::Display for OutOfMemory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        "OOM command not allowed when used memory > 'maxmemory'".fmt(f)
+    }
+}
+
+impl std::error::Error for OutOfMemory {}
+
+/// 遍历大量元素的命令超过 [`ServerConfig::command_time_limit`](crate::server::ServerConfig) 时返回的错误。
+///
+/// 命令在遍历中途放弃，不返回部分结果，把它作为错误帧回复给客户端。
+#[derive(Debug)]
+pub(crate) struct TimeLimitExceeded;
+
+impl std::fmt::Display for TimeLimitExceeded {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        "ERR command exceeded time limit".fmt(f)
+    }
+}
+
+impl std::error::Error for TimeLimitExceeded {}
+
+/// `SINTER`、`SUNION` 和 `SDIFF` 返回的错误。
+#[derive(Debug)]
+pub(crate) enum SetOpError {
+    /// 某个键保存的不是集合。
+    WrongType,
+    /// 运算超过了时间限制。
+    TimeLimitExceeded,
+}
+
+impl std::fmt::Display for SetOpError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::WrongType => WrongType.fmt(f),
+            Self::TimeLimitExceeded => TimeLimitExceeded.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for SetOpError {}
+
+impl From<WrongType> for SetOpError {
+    fn from(_: WrongType) -> Self {
+        Self::WrongType
+    }
+}
+
+impl From<TimeLimitExceeded> for SetOpError {
+    fn from(_: TimeLimitExceeded) -> Self {
+        Self::TimeLimitExceeded
+    }
+}
+
+/// `INCRBY` 等命令无法把键的值当作数字修改时返回的错误。
+///
+/// 与 [`WrongType`] 一样，命令把它作为错误帧回复给客户端，连接保持可用。
+#[derive(Debug)]
+pub(crate) enum IncrError {
+    /// 键保存的不是字符串。
+    WrongType,
+    /// 值或增量不是整数，或者超出了 64 位有符号整数的范围。
+    NotInteger,
+    /// 哈希字段的值不是整数。
+    HashNotInteger,
+    /// 值或增量不是有效的浮点数。
+    NotFloat,
+    /// 整数结果溢出。
+    Overflow,
+    /// 浮点数结果是 NaN 或无穷大。
+    NotFinite,
+}
+
+impl std::fmt::Display for IncrError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::WrongType => WrongType.fmt(f),
+            Self::NotInteger => "ERR value is not an integer or out of range".fmt(f),
+            Self::HashNotInteger => "ERR hash value is not an integer".fmt(f),
+            Self::NotFloat => "ERR value is not a valid float".fmt(f),
+            Self::Overflow => "ERR increment or decrement would overflow".fmt(f),
+            Self::NotFinite => "ERR increment would produce NaN or Infinity".fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for IncrError {}
+
+impl From<WrongType> for IncrError {
+    fn from(_: WrongType) -> Self {
+        Self::WrongType
+    }
 }
 
 impl DbDropGuard {
     /// 创建一个新的 `DbDropGuard`，包装一个 `Db` 实例。当此实例被丢弃时，`Db` 的清理任务将被关闭。
-    pub(crate) fn new() -> Self {
-        Self { db: Db::new() }
+    pub(crate) fn new(databases: usize) -> Self {
+        Self { db: Db::new(databases) }
     }
 
     /// 获取共享数据库。在内部，这是一个 `Arc`，所以克隆只会增加引用计数。
@@ -87,53 +397,172 @@ impl Drop for DbDropGuard {
 }
 
 impl Db {
-    /// 创建一个新的、空的 `Db` 实例。分配共享状态并生成一个后台任务来管理键过期。
-    pub(crate) fn new() -> Self {
+    /// 创建一个新的 `Db` 实例，包含 `databases` 个空的数据库，返回的句柄指向 0 号数据库。
+    /// 分配共享状态并生成一个后台任务来管理键过期。只有 0 号数据库立即分配，其余的在第一次被选择时分配。
+    pub(crate) fn new(databases: usize) -> Self {
+        let (lazy_free, values) = mpsc::unbounded_channel();
         let shared = Arc::new(Shared {
-            state: Mutex::new(State {
-                entries: HashMap::new(),
-                pub_sub: HashMap::new(),
-                expirations: BTreeSet::new(),
-                is_shutdown: false,
-            }),
+            databases: (0..databases.max(1)).map(|_| OnceLock::new()).collect(),
+            hasher: RandomState::new(),
+            pub_sub: Mutex::default(),
             background_task: Notify::new(),
+            transaction: Arc::new(tokio::sync::RwLock::new(())),
+            is_shutdown: AtomicBool::new(false),
+            notify_expired: AtomicBool::new(false),
+            notify_lagged: AtomicBool::new(false),
+            active_expire: AtomicBool::new(true),
+            debug_hooks: AtomicBool::new(false),
+            sorted_replies: AtomicBool::new(false),
+            pubsub_capacity: AtomicUsize::new(crate::server::PUBSUB_CAPACITY),
+            keepalive_interval: AtomicU64::new(0),
+            maxmemory: AtomicUsize::new(0),
+            command_time_limit: AtomicU64::new(0),
+            noeviction: AtomicBool::new(false),
+            clock: AtomicU64::new(0),
+            snapshot_path: Mutex::default(),
+            lazy_free,
         });
         // 启动后台任务。
         tokio::spawn(purge_expired_tasks(shared.clone()));
+        tokio::spawn(free_values(values));
+
+        shared.database(0);
+        Self { shared, index: 0 }
+    }
+
+    /// 返回指向编号为 `index` 的数据库的句柄。编号超出范围时返回 `None`。
+    ///
+    /// 数据库第一次被选择时分配它的分片。
+    pub(crate) fn select(&self, index: usize) -> Option<Db> {
+        (index < self.shared.databases.len()).then(|| {
+            self.shared.database(index);
+            Db {
+                shared: self.shared.clone(),
+                index,
+            }
+        })
+    }
+
+    /// 返回已经分配了分片的数据库个数，即曾经被选择过的数据库个数。
+    pub(crate) fn allocated_databases(&self) -> usize {
+        self.shared.databases.iter().filter(|database| database.get().is_some()).count()
+    }
 
-        Self { shared }
+    /// 返回句柄指向的数据库的编号。
+    pub(crate) fn index(&self) -> usize {
+        self.index
     }
 
     /// 获取与键关联的值。
     ///
     /// 如果没有与键关联的值，则返回 `None`。这可能是因为从未为键分配过值，或者先前分配的值已过期。
-    pub(crate) fn get(&self, key: &str) -> Option<Bytes> {
+    ///
+    /// 如果键保存的不是字符串，则返回 `WrongType` 错误。
+    pub(crate) fn get(&self, key: &str) -> Result<Option<Bytes>, WrongType> {
         // 获取锁，获取条目并克隆值。
         //
         // 因为数据是使用 `Bytes` 存储的，所以这里的克隆是浅克隆。数据不会被复制。
-        let state = self.shared.state.lock().unwrap();
-        state.entries.get(key).map(|entry| entry.data.clone())
+        let state = self.read(key);
+        match state.entries.get(key) {
+            // 键已经过期，但后台任务还没有清理它。与 Redis 一样在访问时删除它，不返回过期的值。
+            Some(entry) if entry.is_expired(Instant::now()) => {
+                drop(state);
+                self.expire_if_due(key);
+                Ok(None)
+            }
+            Some(entry) => {
+                let data = entry.data.as_string()?.clone();
+                entry.touch(self.shared.tick());
+                Ok(Some(data))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// 如果 `key` 已经过期，删除它，并像后台任务清理它时一样发布过期通知。
+    ///
+    /// 只持有读锁的命令发现键已经过期时调用。释放读锁之后键可能已经被修改，因此以写入方式锁定分片之后再检查一次。
+    fn expire_if_due(&self, key: &str) {
+        let mut state = self.write(key);
+        if !state.entries.get(key).is_some_and(|entry| entry.is_expired(Instant::now())) {
+            return;
+        }
+        // `remove` 同时删除 `expirations` 中的记录，后台任务不会再处理这个键。
+        state.remove(key);
+        drop(state);
+
+        self.shared.publish_expired(vec![key.to_string()]);
     }
 
     /// 设置与键关联的值以及可选的过期持续时间。
     ///
-    /// 如果已经有值与键关联，则将其删除。
-    pub(crate) fn set(&self, key: String, value: Bytes, expire: Option<Duration>) {
-        let mut state = self.shared.state.lock().unwrap();
+    /// 如果已经有值与键关联，则将其删除。`keep_ttl` 为 `true` 时忽略 `expire`，保留键原有的过期时间，
+    /// 键不存在或者没有过期时间时写入的值不过期。
+    ///
+    /// 如果给出了 `condition`，则只有在条件满足时才写入值。检查和写入在同一个锁内完成。
+    ///
+    /// 返回值是否被写入，以及键先前的值（如果有）。`SET` 会覆盖任何类型的值；先前的值不是字符串时视为 `None`。
+    ///
+    /// 设置了内存上限时，先驱逐最久未使用的键，直到写入之后不超过上限。驱逐所有其他键之后仍然放不下时返回
+    /// `OutOfMemory` 错误，不做任何修改。条件不满足、不会写入时不驱逐任何键。
+    pub(crate) fn set(
+        &self,
+        key: String,
+        value: Bytes,
+        expire: Option<Duration>,
+        keep_ttl: bool,
+        condition: Option<SetCondition>,
+    ) -> Result<(bool, Option<Bytes>), OutOfMemory> {
+        // 驱逐需要锁定其他分片，因此在锁定键所在的分片之前进行。条件在下面锁定分片之后才最终确定，
+        // 这里只是避免为一定不会发生的写入驱逐其他键。
+        if let Some(maxmemory) = self.maxmemory() {
+            if self.condition_holds(&key, condition) {
+                self.evict(&key, key.len() + value.len(), maxmemory)?;
+            }
+        }
+
+        let now = Instant::now();
+        let mut state = self.write(&key);
+        // 与 `get` 相同，已经过期但还没有被清理的键视为不存在：先删除它，`NX` 和 `XX` 看到的是删除之后的键空间。
+        let expired = state.entries.get(&key).is_some_and(|entry| entry.is_expired(now));
+        if expired {
+            state.remove(&key);
+        }
+        let exists = state.entries.contains_key(&key);
+        let previous = state.entries.get(&key).and_then(|entry| entry.data.as_string().ok().cloned());
+        // 检查 NX/XX 条件。条件不满足时不做任何修改。
+        let allowed = match condition {
+            Some(SetCondition::NotExists) => !exists,
+            Some(SetCondition::Exists) => exists,
+            None => true,
+        };
+        if !allowed {
+            drop(state);
+            if expired {
+                self.shared.publish_expired(vec![key]);
+            }
+            return Ok((false, previous));
+        }
         // 如果此 `set` 成为**下一个**过期的键，则需要通知后台任务以便它可以更新其状态。
         //
         // 是否需要通知任务是在 `set` 例程中计算的。
         let mut notify = false;
-        let expires_at = expire.map(|duration| {
-            // 键过期的 `Instant`。
-            let when = Instant::now() + duration;
-            // 仅当新插入的过期时间是下一个要驱逐的键时才通知工作任务。在这种情况下，需要唤醒工作任务以更新其状态。
-            notify = state.next_expiration().map(|expiration| expiration > when).unwrap_or(true);
+        let expires_at = if keep_ttl {
+            // 过期时间不变，后台任务不需要通知。已经过期的键在上面被删除，写入的值不过期。
+            state.entries.get(&key).and_then(|entry| entry.expires_at)
+        } else {
+            expire.map(|duration| {
+                // 键过期的 `Instant`。
+                let when = now + duration;
+                // 仅当新插入的过期时间是下一个要驱逐的键时才通知工作任务。在这种情况下，需要唤醒工作任务以更新其状态。
+                // 这里只比较同一个分片中的过期时间，因此可能多通知一次，但不会漏掉通知。
+                notify = state.next_expiration().map(|expiration| expiration > when).unwrap_or(true);
 
-            when
-        });
+                when
+            })
+        };
         // 将条目插入 `HashMap`。
-        let prev = state.entries.insert(key.clone(), Entry { data: value, expires_at });
+        let prev = state.insert(key.clone(), Entry::new(Value::String(value), expires_at, self.shared.tick()));
         // 如果先前有值与键关联**并且**它有过期时间。必须删除 `expirations` 映射中的关联条目。这可以避免数据泄漏。
         if let Some(entry) = prev {
             if let Some(when) = entry.expires_at {
@@ -142,140 +571,1845 @@ impl Db {
             }
         }
         // 跟踪过期时间。如果我们在删除之前插入，当当前 `(when, key)` 等于之前的 `(when, key)` 时会导致错误。
-        // 先删除再插入可以避免这种情况。
+        // 先删除再插入可以避免这种情况，保留过期时间时 `expirations` 中的记录因此保持不变。
         if let Some(when) = expires_at {
-            state.expirations.insert((when, key));
+            state.expirations.insert((when, key.clone()));
         }
         // 在通知后台任务之前释放互斥锁。这有助于减少争用，避免后台任务唤醒后无法获取互斥锁，因为此函数仍在持有它。
         drop(state);
 
+        if expired {
+            self.shared.publish_expired(vec![key]);
+        }
         if notify {
             // 最后，仅当后台任务需要更新其状态以反映新的过期时间时才通知它。
             self.shared.background_task.notify_one();
         }
+
+        Ok((true, previous))
+    }
+
+    /// 返回 `SET` 的 `NX`/`XX` 条件对 `key` 当前是否满足。与 `set` 一样，已经过期的键视为不存在。
+    fn condition_holds(&self, key: &str, condition: Option<SetCondition>) -> bool {
+        let state = self.read(key);
+        let exists = state.entries.get(key).is_some_and(|entry| !entry.is_expired(Instant::now()));
+        match condition {
+            Some(SetCondition::NotExists) => !exists,
+            Some(SetCondition::Exists) => exists,
+            None => true,
+        }
     }
 
     pub(crate) fn del(&self, keys: Vec<String>) {
-        let mut state = self.shared.state.lock().unwrap();
         for key in keys {
-            // 删除键的条目。如果存在，则返回条目；否则返回 `None`。
-            let entry = state.entries.remove(&key);
-            // 如果条目存在并且有过期时间，则从 `expirations` 映射中删除它。
+            // 删除键的条目，同时从 `expirations` 映射中删除它的过期时间。
+            self.write(&key).remove(&key);
+        }
+    }
+
+    /// 删除 `keys`，返回被删除的键数。
+    ///
+    /// 与 `del` 一样在锁内删除条目，但是元素很多的值被发送到后台任务释放，而不是在这里丢弃。
+    pub(crate) fn unlink(&self, keys: Vec<String>) -> usize {
+        let mut count = 0;
+        for key in keys {
+            // 语句结束时释放锁，之后再处理删除的值。
+            let entry = self.write(&key).remove(&key);
             if let Some(entry) = entry {
-                if let Some(when) = entry.expires_at {
-                    state.expirations.remove(&(when, key));
+                count += 1;
+                if entry.data.element_count() > LAZYFREE_THRESHOLD {
+                    // 后台任务只会在运行时关闭时退出，这时发送失败，值在这里直接丢弃。
+                    let _ = self.shared.lazy_free.send(entry.data);
                 }
             }
         }
+        count
     }
 
-    /// 返回请求频道的 `Receiver`。
+    /// 更新 `keys` 中存在的键的最近访问时间，返回其中存在的键数。重复的键重复计数。
     ///
-    /// 返回的 `Receiver` 用于接收 `PUBLISH` 命令广播的值。
-    pub(crate) fn subscribe(&self, key: String) -> broadcast::Receiver<Bytes> {
-        use std::collections::hash_map::Entry;
-
-        // 获取互斥锁
-        let mut state = self.shared.state.lock().unwrap();
-        // 如果请求频道没有条目，则创建一个新的广播频道并将其与键关联。如果已经存在，则返回一个关联的接收器。
-        match state.pub_sub.entry(key) {
-            Entry::Occupied(e) => e.get().subscribe(),
-            Entry::Vacant(e) => {
-                // 尚不存在广播频道，因此创建一个。
-                //
-                // 频道的容量为 `1024` 条消息。消息存储在频道中，直到**所有**订阅者都看到它。
-                // 这意味着慢速订阅者可能会导致消息无限期地保留。
-                //
-                // 当频道的容量已满时，发布将导致旧消息被丢弃。这可以防止慢速消费者阻塞整个系统。
-                let (tx, rx) = broadcast::channel(1024);
-                e.insert(tx);
-                rx
+    /// 与 `get` 一样只持有读锁。已经过期但还没有被清理的键视为不存在，既不计数也不更新访问时间。
+    pub(crate) fn touch(&self, keys: &[String]) -> usize {
+        let now = Instant::now();
+        let mut count = 0;
+        for key in keys {
+            if let Some(entry) = self.read(key).entries.get(key).filter(|entry| !entry.is_expired(now)) {
+                entry.touch(self.shared.tick());
+                count += 1;
             }
         }
+        count
     }
 
-    /// 向频道发布消息。返回正在监听频道的订阅者数量。
-    pub(crate) fn publish(&self, key: &str, value: Bytes) -> usize {
-        let state = self.shared.state.lock().unwrap();
+    /// 设置键的值并返回它先前的值，用于 `GETSET`。
+    ///
+    /// 与没有过期时间和条件的 [`Db::set`] 相同：清除原有的过期时间，先前的值不是字符串时视为 `None`。
+    pub(crate) fn get_set(&self, key: String, value: Bytes) -> Result<Option<Bytes>, OutOfMemory> {
+        self.set(key, value, None, false, None).map(|(_, previous)| previous)
+    }
 
-        state
-            .pub_sub
-            .get(key)
-            // 成功在广播频道上发送消息时，返回订阅者数量。错误表示没有接收者，在这种情况下，应返回 `0`。
-            .map(|tx| tx.send(value).unwrap_or(0))
-            // 如果频道键没有条目，则没有订阅者。在这种情况下，返回 `0`。
-            .unwrap()
+    /// 获取与键关联的值并删除该键。
+    ///
+    /// 读取和删除在同一个锁内完成。如果键不存在，则返回 `None`。
+    pub(crate) fn get_del(&self, key: &str) -> Result<Option<Bytes>, WrongType> {
+        let mut state = self.write(key);
+        match state.entries.get(key) {
+            Some(entry) => entry.data.as_string()?,
+            None => return Ok(None),
+        };
+
+        match state.remove(key).map(|entry| entry.data) {
+            Some(Value::String(data)) => Ok(Some(data)),
+            _ => unreachable!("checked to be a string above"),
+        }
     }
 
-    /// 向清理后台任务发出关闭信号。这是由 `DbShutdown` 的 `Drop` 实现调用的。
-    fn shutdown_purge_task(&self) {
-        // 必须向后台任务发出关闭信号。这是通过将 `State::shutdown` 设置为 `true` 并通知任务来完成的。
-        let mut state = self.shared.state.lock().unwrap();
-        state.is_shutdown = true;
-        // 在通知后台任务之前释放锁。这有助于减少锁争用，确保后台任务唤醒后不会因为无法获取互斥锁而无法执行。
+    /// 获取键的值，并在同一个锁内修改它的过期时间。
+    ///
+    /// `expires_at` 为 `None` 时不修改过期时间，与 `get` 相同；为 `Some(None)` 时清除过期时间；
+    /// 为 `Some(Some(when))` 时键在 `when` 过期，`when` 已经过去时删除键，但仍然返回它的值。
+    pub(crate) fn get_ex(&self, key: &str, expires_at: Option<Option<Instant>>) -> Result<Option<Bytes>, WrongType> {
+        let Some(expires_at) = expires_at else {
+            return self.get(key);
+        };
+
+        let now = Instant::now();
+        let mut state = self.write(key);
+        let data = match state.entries.get(key) {
+            // 与 `get` 相同，不返回已经过期但还没有被清理的值
+            Some(entry) if entry.is_expired(now) => {
+                state.remove(key);
+                drop(state);
+                self.shared.publish_expired(vec![key.to_string()]);
+                return Ok(None);
+            }
+            Some(entry) => {
+                entry.touch(self.shared.tick());
+                entry.data.as_string()?.clone()
+            }
+            None => return Ok(None),
+        };
+
+        if expires_at.is_some_and(|when| when <= now) {
+            state.remove(key);
+            return Ok(Some(data));
+        }
+        let notify = state.set_expires_at(key, expires_at);
         drop(state);
-        self.shared.background_task.notify_one();
+
+        if notify {
+            self.shared.background_task.notify_one();
+        }
+
+        Ok(Some(data))
     }
-}
 
-impl Shared {
-    /// 清除所有过期键并返回**下一个**键将过期的 `Instant`。后台任务将睡眠直到此时刻。
-    fn purge_expired_keys(&self) -> Option<Instant> {
-        let mut state = self.state.lock().unwrap();
-        if state.is_shutdown {
-            // 数据库正在关闭。所有共享状态的句柄都已丢弃。后台任务应退出。
-            return None;
+    /// 将 `value` 追加到键的值末尾，键不存在时创建它。返回追加后值的长度。
+    ///
+    /// 读取、拼接和写回在同一个锁内完成，因此并发的追加不会互相覆盖。键原有的过期时间保持不变。
+    pub(crate) fn append(&self, key: String, value: Bytes) -> Result<usize, WrongType> {
+        let now = self.shared.tick();
+        let mut state = self.write(&key);
+        let data = state.entry_or_insert(key, || Entry::empty_string(now)).data.as_string_mut()?;
+
+        // `Bytes` 是不可变的，因此需要复制出新的值。
+        let mut appended = BytesMut::with_capacity(data.len() + value.len());
+        appended.extend_from_slice(data);
+        appended.extend_from_slice(&value);
+        *data = appended.freeze();
+        let len = data.len();
+
+        state.used_memory += value.len();
+        Ok(len)
+    }
+
+    /// 将键的值当作整数加上 `delta`，键不存在时从 `0` 开始。返回新的值。
+    ///
+    /// 值不是整数或者结果溢出时返回错误，不做任何修改。键原有的过期时间保持不变。
+    pub(crate) fn incr_by(&self, key: String, delta: i64) -> Result<i64, IncrError> {
+        self.update_number(key, |data| {
+            let current: i64 = std::str::from_utf8(data)
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .ok_or(IncrError::NotInteger)?;
+            current.checked_add(delta).ok_or(IncrError::Overflow)
+        })
+    }
+
+    /// 将键的值当作浮点数加上 `delta`，键不存在时从 `0` 开始。返回新的值。
+    ///
+    /// 值不是有限的浮点数或者结果是 NaN 或无穷大时返回错误，不做任何修改。键原有的过期时间保持不变。
+    pub(crate) fn incr_by_float(&self, key: String, delta: f64) -> Result<f64, IncrError> {
+        self.update_number(key, |data| {
+            let current: f64 = std::str::from_utf8(data)
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .filter(|value: &f64| value.is_finite())
+                .ok_or(IncrError::NotFloat)?;
+            Some(current + delta).filter(|value| value.is_finite()).ok_or(IncrError::NotFinite)
+        })
+    }
+
+    /// 用 `update` 根据键当前的值计算新的数值，并把它的字符串表示写回键中。键不存在时当前的值为 `"0"`。
+    ///
+    /// 读取、计算和写回在同一个锁内完成，因此并发的修改不会互相覆盖。
+    fn update_number<T: ToString>(
+        &self,
+        key: String,
+        update: impl FnOnce(&[u8]) -> Result<T, IncrError>,
+    ) -> Result<T, IncrError> {
+        let now = self.shared.tick();
+        let mut state = self.write(&key);
+        let zero = || Entry::new(Value::String(Bytes::from_static(b"0")), None, now);
+        let data = state.entry_or_insert(key, zero).data.as_string_mut()?;
+
+        let value = update(data)?;
+        let old_len = data.len();
+        *data = Bytes::from(value.to_string());
+        let new_len = data.len();
+
+        state.used_memory = state.used_memory - old_len + new_len;
+        Ok(value)
+    }
+
+    /// 返回键的值的长度。键不存在时返回 `0`。
+    pub(crate) fn strlen(&self, key: &str) -> Result<usize, WrongType> {
+        let state = self.read(key);
+        match state.entries.get(key) {
+            Some(entry) => Ok(entry.data.as_string()?.len()),
+            None => Ok(0),
         }
-        // 这是为了让借用检查器满意。简而言之，`lock()` 返回一个 `MutexGuard` 而不是 `&mut State`。
-        // 借用检查器无法“透过”互斥锁守卫确定同时访问 `state.expirations` 和 `state.entries` 是安全的，
-        // 因此我们在循环外获取 `State` 的“真实”可变引用。
-        let state = &mut *state;
-        // 查找所有计划在现在之前过期的键。
-        let now = Instant::now();
-        while let Some(&(when, ref key)) = state.expirations.iter().next() {
-            if when > now {
-                // 完成清除，`when` 是下一个键过期的时间点。工作任务将等待直到此时刻。
-                return Some(when);
-            }
-            // 键已过期，删除它
-            state.entries.remove(key);
-            state.expirations.remove(&(when, key.clone()));
+    }
+
+    /// 返回键的值中 `start` 到 `end`（包含两端）的字节。
+    ///
+    /// 负数索引从末尾开始计算。索引被截断到值的范围内，范围为空或键不存在时返回空值。
+    pub(crate) fn get_range(&self, key: &str, start: i64, end: i64) -> Result<Bytes, WrongType> {
+        let state = self.read(key);
+        let data = match state.entries.get(key) {
+            Some(entry) => entry.data.as_string()?,
+            None => return Ok(Bytes::new()),
+        };
+
+        // `Bytes::slice` 只增加引用计数，不复制数据。
+        match normalize_range(data.len(), start, end) {
+            Some((start, end)) => Ok(data.slice(start..=end)),
+            None => Ok(Bytes::new()),
         }
+    }
 
-        None
+    /// 从 `offset` 开始用 `value` 覆盖键的值，返回修改后值的长度。
+    ///
+    /// `offset` 超过当前长度时中间用零字节填充。键不存在且 `value` 为空时不创建键并返回 `0`。
+    /// 键原有的过期时间保持不变。
+    pub(crate) fn set_range(&self, key: String, offset: usize, value: &[u8]) -> Result<usize, WrongType> {
+        let mut state = self.write(&key);
+        if value.is_empty() {
+            return match state.entries.get(&key) {
+                Some(entry) => Ok(entry.data.as_string()?.len()),
+                None => Ok(0),
+            };
+        }
+
+        let now = self.shared.tick();
+        let data = state.entry_or_insert(key, || Entry::empty_string(now)).data.as_string_mut()?;
+        let before = data.len();
+
+        let mut written = BytesMut::from(&data[..]);
+        let end = offset + value.len();
+        if written.len() < end {
+            written.resize(end, 0);
+        }
+        written[offset..end].copy_from_slice(value);
+        *data = written.freeze();
+        let len = data.len();
+
+        state.used_memory += len - before;
+        Ok(len)
     }
 
-    /// 返回 `true` 如果数据库正在关闭
+    /// 将 `values` 依次推入列表的 `end` 端，键不存在时创建一个空列表。返回推入后列表的长度。
     ///
-    /// 当所有 `Db` 值都已丢弃时，设置 `shutdown` 标志，表示共享状态不再可访问。
-    fn is_shutdown(&self) -> bool {
-        self.state.lock().unwrap().is_shutdown
+    /// 推入左端时每个值都成为新的第一个元素，因此 `LPUSH key a b c` 得到 `c b a`，与 Redis 一致。
+    pub(crate) fn push(&self, key: String, values: Vec<Bytes>, end: ListEnd) -> Result<usize, WrongType> {
+        let now = self.shared.tick();
+        let mut state = self.write(&key);
+        let list = state
+            .entry_or_insert(key.clone(), || Entry::new(Value::List(VecDeque::new()), None, now))
+            .data
+            .as_list_mut()?;
+
+        let mut added = 0;
+        for value in values {
+            added += value.len();
+            match end {
+                ListEnd::Left => list.push_front(value),
+                ListEnd::Right => list.push_back(value),
+            }
+        }
+        let len = list.len();
+        state.used_memory += added;
+
+        // 唤醒在这个列表上阻塞的客户端。它们会重新检查列表，没有抢到元素的继续等待。
+        if let Some(notify) = state.list_waiters.get(&key) {
+            notify.notify_waiters();
+        }
+
+        Ok(len)
     }
-}
 
-impl State {
-    fn next_expiration(&self) -> Option<Instant> {
-        self.expirations.iter().next().map(|expiration| expiration.0)
+    /// 从列表的 `end` 端弹出一个元素。键不存在时返回 `None`。
+    ///
+    /// 列表被弹空后删除该键，与 Redis 一样不保留空列表。
+    pub(crate) fn pop(&self, key: &str, end: ListEnd) -> Result<Option<Bytes>, WrongType> {
+        let mut state = self.write(key);
+        state.pop(key, end)
     }
-}
 
-/// 由后台任务执行的例程。
-///
-/// 等待通知。收到通知后，从共享状态句柄中清除任何过期的键。如果设置了 `shutdown`，则终止任务。
-async fn purge_expired_tasks(shared: Arc<Shared>) {
-    // 如果设置了关闭标志，则任务应退出。
-    while !shared.is_shutdown() {
-        // 清除所有过期的键。函数返回下一个键将过期的时间点。工作任务应等待直到此时刻，然后再次清除。
-        if let Some(when) = shared.purge_expired_keys() {
-            // 等待直到下一个键过期**或**直到后台任务被通知。如果任务被通知，则必须重新加载其状态，因为新键已设置为提前过期。这是通过循环完成的。
-            tokio::select! {
-                _ = time::sleep_until(when) => {}
-                _ = shared.background_task.notified() => {}
+    /// 从列表的 `end` 端弹出最多 `count` 个元素，列表较短时弹出所有元素。键不存在时返回 `None`。
+    ///
+    /// 所有元素在同一个锁内弹出，不会与其他客户端的弹出交错。
+    pub(crate) fn pop_count(&self, key: &str, end: ListEnd, count: usize) -> Result<Option<Vec<Bytes>>, WrongType> {
+        let mut state = self.write(key);
+        match state.entries.get(key) {
+            // `count` 为 `0` 时也要检查类型。
+            Some(entry) => entry.data.as_list()?,
+            None => return Ok(None),
+        };
+
+        let mut values = Vec::new();
+        while values.len() < count {
+            match state.pop(key, end)? {
+                Some(value) => values.push(value),
+                None => break,
             }
-        } else {
-            // 将来没有键过期。等待任务被通知。
-            shared.background_task.notified().await;
         }
+
+        Ok(Some(values))
     }
-    debug!("清理后台任务已关闭")
+
+    /// 按顺序从 `keys` 中第一个非空列表的 `end` 端弹出一个元素，返回该列表的键和元素。
+    ///
+    /// 所有键都不存在时返回 `None`。遇到保存了其他类型的键时返回错误，与 Redis 一致。
+    pub(crate) fn pop_first(&self, keys: &[String], end: ListEnd) -> Result<Option<(String, Bytes)>, WrongType> {
+        for key in keys {
+            if let Some(value) = self.write(key).pop(key, end)? {
+                return Ok(Some((key.clone(), value)));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// 返回一个用于等待 `keys` 中任意一个列表被推入的句柄。
+    pub(crate) fn list_waiter(&self, keys: &[String]) -> ListWaiter {
+        let notifies = keys
+            .iter()
+            .map(|key| {
+                let notify = self.write(key).list_waiters.entry(key.clone()).or_default().clone();
+                (key.clone(), notify)
+            })
+            .collect();
+
+        ListWaiter {
+            db: self.clone(),
+            notifies,
+        }
+    }
+
+    /// 返回列表中 `start` 到 `stop`（包含两端）的元素。
+    ///
+    /// 索引的处理与 [`get_range`](Db::get_range) 相同：负数索引从末尾开始计算，超出范围的索引被截断。
+    /// 只克隆范围内的元素，而不是整个列表。
+    pub(crate) fn lrange(&self, key: &str, start: i64, stop: i64) -> Result<Vec<Bytes>, WrongType> {
+        let state = self.read(key);
+        let list = match state.entries.get(key) {
+            Some(entry) => entry.data.as_list()?,
+            None => return Ok(vec![]),
+        };
+
+        match normalize_range(list.len(), start, stop) {
+            Some((start, stop)) => Ok(list.range(start..=stop).cloned().collect()),
+            None => Ok(vec![]),
+        }
+    }
+
+    /// 返回列表的长度。键不存在时返回 `0`。
+    pub(crate) fn llen(&self, key: &str) -> Result<usize, WrongType> {
+        let state = self.read(key);
+        match state.entries.get(key) {
+            Some(entry) => Ok(entry.data.as_list()?.len()),
+            None => Ok(0),
+        }
+    }
+
+    /// 设置哈希中的字段，键不存在时创建一个空哈希。返回新增的字段数，被覆盖的字段不计入。
+    pub(crate) fn hset(&self, key: String, fields: Vec<(String, Bytes)>) -> Result<usize, WrongType> {
+        let now = self.shared.tick();
+        let mut state = self.write(&key);
+        let hash = state
+            .entry_or_insert(key, || Entry::new(Value::Hash(HashMap::new()), None, now))
+            .data
+            .as_hash_mut()?;
+
+        let (mut added, mut freed, mut created) = (0, 0, 0);
+        for (field, value) in fields {
+            added += value.len();
+            let field_len = field.len();
+            match hash.insert(field, value) {
+                Some(prev) => freed += prev.len(),
+                None => {
+                    added += field_len;
+                    created += 1;
+                }
+            }
+        }
+
+        state.used_memory = state.used_memory + added - freed;
+        Ok(created)
+    }
+
+    /// 获取哈希中字段的值。字段或键不存在时返回 `None`。
+    pub(crate) fn hget(&self, key: &str, field: &str) -> Result<Option<Bytes>, WrongType> {
+        let state = self.read(key);
+        match state.entries.get(key) {
+            Some(entry) => Ok(entry.data.as_hash()?.get(field).cloned()),
+            None => Ok(None),
+        }
+    }
+
+    /// 将哈希中字段的值当作整数加上 `delta`，键或字段不存在时从 `0` 开始。返回新的值。
+    ///
+    /// 字段的值不是整数或者结果溢出时返回错误，不做任何修改。
+    pub(crate) fn hincr_by(&self, key: String, field: String, delta: i64) -> Result<i64, IncrError> {
+        let now = self.shared.tick();
+        let mut state = self.write(&key);
+        let hash = state
+            .entry_or_insert(key, || Entry::new(Value::Hash(HashMap::new()), None, now))
+            .data
+            .as_hash_mut()?;
+
+        // 字段不存在时从 `0` 开始，不会溢出，因此出错时哈希一定已经存在，不会留下刚创建的空哈希。
+        let current: i64 = match hash.get(&field) {
+            Some(value) => std::str::from_utf8(value)
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .ok_or(IncrError::HashNotInteger)?,
+            None => 0,
+        };
+        let value = current.checked_add(delta).ok_or(IncrError::Overflow)?;
+
+        let data = Bytes::from(value.to_string());
+        let (mut added, mut freed) = (data.len(), 0);
+        let field_len = field.len();
+        match hash.insert(field, data) {
+            Some(prev) => freed = prev.len(),
+            None => added += field_len,
+        }
+
+        state.used_memory = state.used_memory + added - freed;
+        Ok(value)
+    }
+
+    /// 删除哈希中的字段，返回实际删除的字段数。哈希被删空后删除该键。
+    pub(crate) fn hdel(&self, key: &str, fields: &[String]) -> Result<usize, WrongType> {
+        let mut state = self.write(key);
+        let hash = match state.entries.get_mut(key) {
+            Some(entry) => entry.data.as_hash_mut()?,
+            None => return Ok(0),
+        };
+
+        let (mut removed, mut freed) = (0, 0);
+        for field in fields {
+            if let Some(value) = hash.remove(field) {
+                removed += 1;
+                freed += field.len() + value.len();
+            }
+        }
+        let empty = hash.is_empty();
+
+        state.used_memory -= freed;
+        if empty {
+            state.remove(key);
+        }
+
+        Ok(removed)
+    }
+
+    /// 返回哈希中的所有字段和值，顺序不确定；启用了 [`set_sorted_replies`](Self::set_sorted_replies) 时按字段排序。
+    /// 键不存在时返回空列表。
+    pub(crate) fn hgetall(&self, key: &str) -> Result<Vec<(String, Bytes)>, WrongType> {
+        let state = self.read(key);
+        let mut pairs: Vec<_> = match state.entries.get(key) {
+            Some(entry) => {
+                let hash = entry.data.as_hash()?;
+                hash.iter().map(|(field, value)| (field.clone(), value.clone())).collect()
+            }
+            None => vec![],
+        };
+        if self.sorted_replies() {
+            pairs.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+        }
+        Ok(pairs)
+    }
+
+    /// 向集合中添加成员，键不存在时创建一个空集合。返回新增的成员数，已经存在的成员不计入。
+    pub(crate) fn sadd(&self, key: String, members: Vec<Bytes>) -> Result<usize, WrongType> {
+        let now = self.shared.tick();
+        let mut state = self.write(&key);
+        let set = state
+            .entry_or_insert(key, || Entry::new(Value::Set(HashSet::new()), None, now))
+            .data
+            .as_set_mut()?;
+
+        let (mut added, mut created) = (0, 0);
+        for member in members {
+            let len = member.len();
+            if set.insert(member) {
+                added += len;
+                created += 1;
+            }
+        }
+
+        state.used_memory += added;
+        Ok(created)
+    }
+
+    /// 从集合中删除成员，返回实际删除的成员数。集合被删空后删除该键。
+    pub(crate) fn srem(&self, key: &str, members: &[Bytes]) -> Result<usize, WrongType> {
+        let mut state = self.write(key);
+        let set = match state.entries.get_mut(key) {
+            Some(entry) => entry.data.as_set_mut()?,
+            None => return Ok(0),
+        };
+
+        let (mut removed, mut freed) = (0, 0);
+        for member in members {
+            if set.remove(member) {
+                removed += 1;
+                freed += member.len();
+            }
+        }
+        let empty = set.is_empty();
+
+        state.used_memory -= freed;
+        if empty {
+            state.remove(key);
+        }
+
+        Ok(removed)
+    }
+
+    /// 返回集合中的所有成员，顺序不确定；启用了 [`set_sorted_replies`](Self::set_sorted_replies) 时按字节序排序。
+    /// 键不存在时返回空列表。
+    pub(crate) fn smembers(&self, key: &str) -> Result<Vec<Bytes>, WrongType> {
+        let state = self.read(key);
+        let mut members: Vec<_> = match state.entries.get(key) {
+            Some(entry) => entry.data.as_set()?.iter().cloned().collect(),
+            None => vec![],
+        };
+        if self.sorted_replies() {
+            members.sort_unstable();
+        }
+        Ok(members)
+    }
+
+    /// 判断 `member` 是否是集合的成员。键不存在时视为空集合。
+    pub(crate) fn sismember(&self, key: &str, member: &Bytes) -> Result<bool, WrongType> {
+        let state = self.read(key);
+        match state.entries.get(key) {
+            Some(entry) => Ok(entry.data.as_set()?.contains(member)),
+            None => Ok(false),
+        }
+    }
+
+    /// 对 `keys` 中的集合执行 `op`，返回结果中的成员，顺序与 [`smembers`](Self::smembers) 相同。
+    /// 不存在的键视为空集合。
+    ///
+    /// 所有键所在的分片按分片编号的顺序以读取方式锁定，结果是某一时刻的快照。任何一个键保存的不是集合时返回
+    /// `WrongType`，运算超过时间限制时返回 `TimeLimitExceeded`。
+    pub(crate) fn set_op(&self, keys: &[String], op: SetOp) -> Result<Vec<Bytes>, SetOpError> {
+        let deadline = self.deadline();
+        let mut indices: Vec<usize> = keys.iter().map(|key| self.shared.shard_index(key)).collect();
+        indices.sort_unstable();
+        indices.dedup();
+        let guards: HashMap<usize, RwLockReadGuard<'_, State>> =
+            indices.into_iter().map(|index| (index, self.shards()[index].read().unwrap())).collect();
+
+        let empty = HashSet::new();
+        let mut sets = Vec::with_capacity(keys.len());
+        for key in keys {
+            let state = &guards[&self.shared.shard_index(key)];
+            match state.entries.get(key) {
+                Some(entry) => sets.push(entry.data.as_set()?),
+                None => sets.push(&empty),
+            }
+        }
+
+        let Some((first, others)) = sets.split_first() else {
+            return Ok(vec![]);
+        };
+        let mut members = vec![];
+        match op {
+            // 从最小的集合开始检查，需要比较的成员最少。
+            SetOp::Inter => {
+                let smallest = sets.iter().min_by_key(|set| set.len()).unwrap();
+                for (i, member) in smallest.iter().enumerate() {
+                    check_deadline(deadline, i)?;
+                    if sets.iter().all(|set| set.contains(member)) {
+                        members.push(member.clone());
+                    }
+                }
+            }
+            SetOp::Union => {
+                let mut union: HashSet<&Bytes> = HashSet::new();
+                for (i, member) in sets.iter().flat_map(|set| set.iter()).enumerate() {
+                    check_deadline(deadline, i)?;
+                    union.insert(member);
+                }
+                members.extend(union.into_iter().cloned());
+            }
+            SetOp::Diff => {
+                for (i, member) in first.iter().enumerate() {
+                    check_deadline(deadline, i)?;
+                    if !others.iter().any(|set| set.contains(member)) {
+                        members.push(member.clone());
+                    }
+                }
+            }
+        }
+        if self.sorted_replies() {
+            members.sort_unstable();
+        }
+
+        Ok(members)
+    }
+
+    /// 将 `key` 重命名为 `new_key`，生存时间随值一起转移。
+    ///
+    /// 如果 `key` 不存在，返回 `None`。如果 `nx` 为 `true` 且 `new_key` 已经存在，则不做任何修改并返回
+    /// `Some(false)`。否则覆盖 `new_key`（丢弃它原来的生存时间）并返回 `Some(true)`。与 `move_key` 一样，
+    /// 已经过期但还没有被清理的键视为不存在。
+    pub(crate) fn rename(&self, key: &str, new_key: String, nx: bool) -> Option<bool> {
+        let now = Instant::now();
+        let (mut state, mut other) = self.write_pair(key, &new_key);
+
+        if state.entries.get(key).is_none_or(|entry| entry.is_expired(now)) {
+            return None;
+        }
+        let dst = other.as_deref().unwrap_or(&state);
+        if nx && dst.entries.get(&new_key).is_some_and(|entry| !entry.is_expired(now)) {
+            return Some(false);
+        }
+        if key == new_key {
+            return Some(true);
+        }
+
+        let entry = state.remove(key).unwrap();
+
+        let dst = other.as_deref_mut().unwrap_or(&mut state);
+        // 目标键原来的生存时间随旧值一起丢弃，已经过期的目标键同样被直接覆盖
+        dst.remove(&new_key);
+
+        // 过期时间不变，最早的过期时间也不会变早，因此不需要通知后台任务
+        if let Some(when) = entry.expires_at {
+            dst.expirations.insert((when, new_key.clone()));
+        }
+        dst.insert(new_key, entry);
+
+        Some(true)
+    }
+
+    /// 将 `src` 的值和剩余的生存时间复制到 `target` 指向的数据库中的 `dst`。
+    ///
+    /// 如果 `src` 不存在，或 `dst` 已经存在且 `replace` 为 `false`，则不做任何修改并返回 `false`。
+    /// 在同一个数据库中 `src` 与 `dst` 相同时也返回 `false`。否则覆盖 `dst`（丢弃它原来的生存时间）并返回 `true`。
+    /// 与 `move_key` 一样，已经过期但还没有被清理的键视为不存在。
+    pub(crate) fn copy(&self, src: &str, target: &Db, dst: String, replace: bool) -> bool {
+        let now = Instant::now();
+        let (mut state, mut other) = self.write_pair_in(src, target, &dst);
+
+        let (data, expires_at) = match state.entries.get(src) {
+            Some(entry) if !entry.is_expired(now) && (src != dst || self.index != target.index) => {
+                (entry.data.clone(), entry.expires_at)
+            }
+            _ => return false,
+        };
+
+        let state = other.as_deref_mut().unwrap_or(&mut state);
+        if !replace && state.entries.get(&dst).is_some_and(|entry| !entry.is_expired(now)) {
+            return false;
+        }
+
+        // 目标键原来的生存时间随旧值一起丢弃，已经过期的目标键同样被直接覆盖
+        state.remove(&dst);
+
+        // 与源键的过期时间相同，最早的过期时间不会变早，因此不需要通知后台任务
+        if let Some(when) = expires_at {
+            state.expirations.insert((when, dst.clone()));
+        }
+        state.insert(dst, Entry::new(data, expires_at, self.shared.tick()));
+
+        true
+    }
+
+    /// 将 `key` 连同它的生存时间从当前数据库移动到 `other` 指向的数据库。
+    ///
+    /// 如果 `key` 不存在，或者目标数据库中已经存在同名的键，则不做任何修改并返回 `false`。已经过期但还没有被清理的键
+    /// 视为不存在。删除和插入在同时持有两个分片的锁时完成，其他连接不会看到键同时存在或同时不存在于两个数据库中。
+    pub(crate) fn move_key(&self, key: &str, other: &Db) -> bool {
+        let now = Instant::now();
+        let (mut state, mut dst) = self.write_pair_in(key, other, key);
+        // 同一个键在每个数据库中都位于相同编号的分片，`dst` 只在两个句柄指向同一个数据库时为 `None`
+        let Some(dst) = dst.as_deref_mut() else {
+            return false;
+        };
+
+        if state.entries.get(key).is_none_or(|entry| entry.is_expired(now)) {
+            return false;
+        }
+        if dst.entries.get(key).is_some_and(|entry| !entry.is_expired(now)) {
+            return false;
+        }
+
+        let entry = state.remove(key).unwrap();
+        // 目标数据库中已经过期的同名键被直接覆盖
+        dst.remove(key);
+
+        // 过期时间不变，所有数据库中最早的过期时间也不会变早，因此不需要通知后台任务
+        if let Some(when) = entry.expires_at {
+            dst.expirations.insert((when, key.to_string()));
+        }
+        dst.insert(key.to_string(), entry);
+
+        true
+    }
+
+    /// 将键的过期时间设置为 `when`，替换原来的生存时间。
+    ///
+    /// 如果 `when` 已经过去，键被立即删除。键不存在时返回 `false`，否则返回 `true`。
+    pub(crate) fn expire_at(&self, key: &str, when: Instant) -> bool {
+        let mut state = self.write(key);
+
+        if !state.entries.contains_key(key) {
+            return false;
+        }
+        if when <= Instant::now() {
+            state.remove(key);
+            return true;
+        }
+
+        let notify = state.set_expires_at(key, Some(when));
+        drop(state);
+
+        if notify {
+            self.shared.background_task.notify_one();
+        }
+
+        true
+    }
+
+    /// 返回键的值的类型名称，与 `TYPE` 命令的回复相同。键不存在时返回 `"none"`。
+    pub(crate) fn type_of(&self, key: &str) -> &'static str {
+        let state = self.read(key);
+        state.entries.get(key).map_or("none", |entry| entry.data.type_name())
+    }
+
+    /// 返回键的值的编码名称，与 `OBJECT ENCODING` 命令的回复相同。键不存在时返回 `None`。
+    pub(crate) fn encoding_of(&self, key: &str) -> Option<&'static str> {
+        let state = self.read(key);
+        state.entries.get(key).map(|entry| entry.data.encoding())
+    }
+
+    /// 返回键的值的编码名称和序列化长度，供 `DEBUG OBJECT` 使用。键不存在时返回 `None`。
+    ///
+    /// 序列化长度是值的数据的字节数；列表、哈希和集合是所有元素的字节数之和，只是一个估计。
+    pub(crate) fn debug_object(&self, key: &str) -> Option<(&'static str, usize)> {
+        let state = self.read(key);
+        state.entries.get(key).map(|entry| (entry.data.encoding(), entry.data.size()))
+    }
+
+    /// 删除数据库中的所有键及其过期时间。其他数据库、频道和订阅不受影响。
+    ///
+    /// 不需要通知后台任务：它下次醒来时 `expirations` 已经为空，没有需要清理的键，会继续等待下一次 `set`。
+    pub(crate) fn flush(&self) {
+        for shard in self.shards() {
+            let mut state = shard.write().unwrap();
+            state.entries.clear();
+            state.expirations.clear();
+            state.used_memory = 0;
+        }
+    }
+
+    /// 删除所有数据库中的所有键及其过期时间。频道和订阅不受影响。
+    ///
+    /// 与 [`Db::flush`] 相同，不需要通知后台任务。分片逐个清空，不会同时持有多个锁。
+    pub(crate) fn flush_all(&self) {
+        for shard in self.shared.all_shards() {
+            let mut state = shard.write().unwrap();
+            state.entries.clear();
+            state.expirations.clear();
+            state.used_memory = 0;
+        }
+    }
+
+    /// 交换编号为 `first` 和 `second` 的两个数据库的内容。编号超出范围时返回 `false`，不做任何修改。
+    ///
+    /// 所有句柄都按编号访问数据库，因此已经选择了这两个数据库的连接直接看到交换之后的内容。
+    ///
+    /// 交换期间持有两个数据库所有分片的写锁，其他连接不会看到只交换了一部分的数据库。锁按数据库编号、
+    /// 再按分片编号的顺序获取；其他操作同时最多锁定一个数据库中的两个分片，并且也按分片编号的顺序，
+    /// 因此不会死锁。
+    ///
+    /// 阻塞在列表上的客户端属于连接选择的数据库，它们的等待留在原来的数据库中，但会被唤醒，重新检查交换进来的列表。
+    pub(crate) fn swap(&self, first: usize, second: usize) -> bool {
+        let databases = &self.shared.databases;
+        if first >= databases.len() || second >= databases.len() {
+            return false;
+        }
+        if first == second {
+            return true;
+        }
+
+        let lock = |index: usize| -> Vec<_> {
+            self.shared.database(index).iter().map(|shard| shard.write().unwrap()).collect()
+        };
+        let mut low = lock(first.min(second));
+        let mut high = lock(first.max(second));
+        for (a, b) in low.iter_mut().zip(high.iter_mut()) {
+            std::mem::swap(&mut a.entries, &mut b.entries);
+            std::mem::swap(&mut a.expirations, &mut b.expirations);
+            std::mem::swap(&mut a.used_memory, &mut b.used_memory);
+            for notify in a.list_waiters.values().chain(b.list_waiters.values()) {
+                notify.notify_waiters();
+            }
+        }
+
+        true
+    }
+
+    /// 返回数据库中未过期的键数。
+    ///
+    /// 已经过期但后台任务尚未清理的键不计入，因此结果不依赖于清理的时机。
+    ///
+    /// 各个分片依次计数，因此结果不是某一时刻的快照。
+    pub(crate) fn len(&self) -> usize {
+        let now = Instant::now();
+        self.shards()
+            .iter()
+            .map(|shard| {
+                let state = shard.read().unwrap();
+                state
+                    .entries
+                    .values()
+                    .filter(|entry| entry.expires_at.is_none_or(|when| when > now))
+                    .count()
+            })
+            .sum()
+    }
+
+    /// 从 `cursor` 开始检查最多 `count` 个键，返回下一次迭代的游标和其中与 `pattern` 匹配的键。
+    ///
+    /// 游标是按字典序排序的未过期键列表中的偏移量，迭代结束时返回的游标为 `0`。`HashMap` 没有稳定的迭代顺序，
+    /// 因此每次调用都要对所有键排序。
+    ///
+    /// 收集键的过程超过时间限制时返回错误。
+    pub(crate) fn scan(
+        &self,
+        cursor: u64,
+        pattern: Option<&str>,
+        count: u64,
+    ) -> Result<(u64, Vec<String>), TimeLimitExceeded> {
+        let deadline = self.deadline();
+        let now = Instant::now();
+        let mut keys: Vec<String> = vec![];
+        let mut visited = 0;
+        for shard in self.shards() {
+            let state = shard.read().unwrap();
+            for (key, entry) in &state.entries {
+                check_deadline(deadline, visited)?;
+                visited += 1;
+                if entry.expires_at.is_none_or(|when| when > now) {
+                    keys.push(key.clone());
+                }
+            }
+        }
+        keys.sort_unstable();
+
+        let start = usize::try_from(cursor).unwrap_or(usize::MAX).min(keys.len());
+        let end = usize::try_from(count).unwrap_or(usize::MAX).saturating_add(start).min(keys.len());
+        let next = if end == keys.len() { 0 } else { end as u64 };
+
+        let batch = keys[start..end]
+            .iter()
+            .filter(|key| pattern.is_none_or(|p| glob::matches(p.as_bytes(), key.as_bytes())))
+            .cloned()
+            .collect();
+
+        Ok((next, batch))
+    }
+
+    /// 等待执行单个命令的许可。持有许可期间不会有事务在执行。
+    pub(crate) async fn command_permit(&self) -> OwnedRwLockReadGuard<()> {
+        self.shared.transaction.clone().read_owned().await
+    }
+
+    /// 等待执行事务的许可。持有许可期间其他连接不会执行任何命令。
+    pub(crate) async fn transaction_permit(&self) -> OwnedRwLockWriteGuard<()> {
+        self.shared.transaction.clone().write_owned().await
+    }
+
+    /// 为至少 `additional` 个新键预留空间。
+    ///
+    /// 批量加载大量键之前调用，可以避免加载过程中反复扩容和重新哈希。
+    ///
+    /// 键均匀地分布在各个分片中，因此每个分片预留平均的份额。
+    pub(crate) fn reserve(&self, additional: usize) {
+        for shard in self.shards() {
+            shard.write().unwrap().entries.reserve(additional.div_ceil(SHARDS));
+        }
+    }
+
+    /// 返回键空间在不扩容的情况下能容纳的键数，即所有分片的容量之和。
+    pub(crate) fn capacity(&self) -> usize {
+        self.shards().iter().map(|shard| shard.read().unwrap().entries.capacity()).sum()
+    }
+
+    /// 设置内存上限，单位是字节。`None` 表示不限制。
+    ///
+    /// 只有 `SET` 会检查上限：写入之前驱逐最久未使用的键，直到写入之后不超过上限。
+    pub(crate) fn set_maxmemory(&self, maxmemory: Option<usize>) {
+        self.shared.maxmemory.store(maxmemory.unwrap_or(0), Ordering::SeqCst);
+    }
+
+    /// 返回内存上限，单位是字节。`None` 表示不限制。
+    pub(crate) fn maxmemory(&self) -> Option<usize> {
+        Some(self.shared.maxmemory.load(Ordering::SeqCst)).filter(|&maxmemory| maxmemory > 0)
+    }
+
+    /// 设置 `SCAN` 等遍历大量元素的命令最多执行多久。`None` 表示不限制。不足一微秒的时间按一微秒处理。
+    pub(crate) fn set_command_time_limit(&self, limit: Option<Duration>) {
+        let micros = limit.map_or(0, |limit| (limit.as_micros() as u64).max(1));
+        self.shared.command_time_limit.store(micros, Ordering::SeqCst);
+    }
+
+    /// 返回从现在开始执行的命令的截止时间。没有时间限制时返回 `None`。
+    fn deadline(&self) -> Option<Instant> {
+        let micros = self.shared.command_time_limit.load(Ordering::SeqCst);
+        (micros > 0).then(|| Instant::now() + Duration::from_micros(micros))
+    }
+
+    /// 设置超过内存上限时的处理方式。
+    pub(crate) fn set_maxmemory_policy(&self, policy: MaxMemoryPolicy) {
+        self.shared.noeviction.store(policy == MaxMemoryPolicy::NoEviction, Ordering::SeqCst);
+    }
+
+    /// 返回超过内存上限时的处理方式。
+    pub(crate) fn maxmemory_policy(&self) -> MaxMemoryPolicy {
+        if self.shared.noeviction.load(Ordering::SeqCst) {
+            MaxMemoryPolicy::NoEviction
+        } else {
+            MaxMemoryPolicy::AllKeysLru
+        }
+    }
+
+    /// 返回所有数据库中所有键和值的近似字节数。
+    pub(crate) fn used_memory(&self) -> usize {
+        self.shared.all_shards().map(|shard| shard.read().unwrap().used_memory).sum()
+    }
+
+    /// 驱逐最久未使用的键，直到写入 `key` 的 `size` 字节之后不超过 `maxmemory`。
+    ///
+    /// 内存上限针对所有数据库，因此可能驱逐任何数据库中的键。`key` 是当前数据库中的键，它本身不会被驱逐：
+    /// 它即将被覆盖，覆盖释放的空间已经计入。驱逐所有其他键之后仍然放不下时返回 `OutOfMemory`；
+    /// `size` 本身就超过上限时不驱逐任何键。
+    ///
+    /// 找出最久未使用的键需要扫描并排序所有键，这是 O(n log n) 的操作，只在超过上限时执行。
+    /// 各个分片依次扫描和驱逐，与并发写入之间的结果是近似的。
+    ///
+    /// 策略是 [`MaxMemoryPolicy::NoEviction`] 时不驱逐任何键，放不下就返回 `OutOfMemory`。
+    fn evict(&self, key: &str, size: usize, maxmemory: usize) -> Result<(), OutOfMemory> {
+        if size > maxmemory {
+            return Err(OutOfMemory);
+        }
+
+        let current = self.read(key).entries.get(key).map_or(0, |entry| entry.size(key));
+        let mut used = self.used_memory().saturating_sub(current);
+        if used + size <= maxmemory {
+            return Ok(());
+        }
+        if self.maxmemory_policy() == MaxMemoryPolicy::NoEviction {
+            return Err(OutOfMemory);
+        }
+
+        // 其他所有键按最近访问时间从旧到新排列。
+        let mut candidates = vec![];
+        for (index, shards) in self.shared.allocated_databases() {
+            for shard in shards {
+                let state = shard.read().unwrap();
+                candidates.extend(
+                    state
+                        .entries
+                        .iter()
+                        .filter(|(candidate, _)| index != self.index || *candidate != key)
+                        .map(|(candidate, entry)| {
+                            (entry.last_access.load(Ordering::Relaxed), index, candidate.clone())
+                        }),
+                );
+            }
+        }
+        candidates.sort_unstable();
+
+        for (_, index, candidate) in candidates {
+            if used + size <= maxmemory {
+                break;
+            }
+            // 扫描之后键可能已经被删除
+            let shard = &self.shared.database(index)[self.shared.shard_index(&candidate)];
+            if let Some(entry) = shard.write().unwrap().remove(&candidate) {
+                used = used.saturating_sub(entry.size(&candidate));
+                debug!(key = candidate, "驱逐最久未使用的键");
+            }
+        }
+
+        if used + size <= maxmemory {
+            Ok(())
+        } else {
+            Err(OutOfMemory)
+        }
+    }
+
+    /// 设置 `SAVE` 写入快照的文件。
+    pub(crate) fn set_snapshot_path(&self, path: Option<PathBuf>) {
+        *self.shared.snapshot_path.lock().unwrap() = path;
+    }
+
+    /// 返回 `SAVE` 写入快照的文件，没有设置时返回 `None`。
+    pub(crate) fn snapshot_path(&self) -> Option<PathBuf> {
+        self.shared.snapshot_path.lock().unwrap().clone()
+    }
+
+    /// 把所有数据库中未过期的键、值和过期时间编码为快照，格式见 [`SNAPSHOT_MAGIC`]。
+    ///
+    /// 各个分片依次持有读锁并编码，因此快照不是某一时刻的快照；但每个键都是完整的。
+    pub(crate) fn snapshot(&self) -> Bytes {
+        let now = Instant::now();
+        let mut buf = BytesMut::new();
+        buf.put_slice(SNAPSHOT_MAGIC);
+        buf.put_u8(SNAPSHOT_VERSION);
+
+        for (index, shards) in self.shared.allocated_databases() {
+            buf.put_u8(SNAPSHOT_SELECTDB);
+            buf.put_u32(index as u32);
+
+            for shard in shards {
+                let state = shard.read().unwrap();
+                for (key, entry) in &state.entries {
+                    let expires_at = match entry.expires_at {
+                        Some(when) if when <= now => continue,
+                        Some(when) => unix_millis(when).max(1),
+                        None => 0,
+                    };
+
+                    buf.put_u8(entry.data.snapshot_kind());
+                    buf.put_u64(expires_at);
+                    put_bytes(&mut buf, key.as_bytes());
+                    entry.data.encode(&mut buf);
+                }
+            }
+        }
+
+        buf.put_u8(SNAPSHOT_EOF);
+        buf.freeze()
+    }
+
+    /// 把快照写入 `path`。
+    ///
+    /// 先写入同一目录中的临时文件，再重命名为 `path`，因此写入过程中崩溃不会损坏原有的快照。
+    pub(crate) async fn snapshot_to(&self, path: &Path) -> io::Result<()> {
+        let snapshot = self.snapshot();
+
+        let mut tmp = path.as_os_str().to_owned();
+        tmp.push(".tmp");
+        tokio::fs::write(&tmp, &snapshot).await?;
+        tokio::fs::rename(&tmp, path).await
+    }
+
+    /// 从 `path` 的快照中加载键，返回加载的键数。
+    ///
+    /// 键被加载到快照中记录的数据库，与句柄指向哪个数据库无关。快照中的键覆盖同名的键，其他键保持不变。
+    /// 在快照保存之后、加载之前已经过期的键被跳过。
+    /// 快照格式错误或者包含超出范围的数据库编号时返回 `InvalidData` 错误，此时可能已经加载了部分键。
+    pub(crate) async fn load_from(&self, path: &Path) -> io::Result<usize> {
+        let mut buf = Bytes::from(tokio::fs::read(path).await?);
+
+        if !buf.starts_with(SNAPSHOT_MAGIC) {
+            return Err(invalid_snapshot("not a snapshot file"));
+        }
+        buf.advance(SNAPSHOT_MAGIC.len());
+        if !(1..=SNAPSHOT_VERSION).contains(&get_u8(&mut buf)?) {
+            return Err(invalid_snapshot("unsupported snapshot version"));
+        }
+
+        let mut loaded = 0;
+        let mut db = self.select(0).expect("database 0 always exists");
+        loop {
+            let kind = get_u8(&mut buf)?;
+            if kind == SNAPSHOT_EOF {
+                break;
+            }
+            if kind == SNAPSHOT_SELECTDB {
+                db = self
+                    .select(get_u32(&mut buf)? as usize)
+                    .ok_or_else(|| invalid_snapshot("database index out of range"))?;
+                continue;
+            }
+            let expires_at = get_u64(&mut buf)?;
+            let key = get_string(&mut buf)?;
+            let data = Value::decode(kind, &mut buf)?;
+
+            let expires_at = match expires_at {
+                0 => None,
+                millis => match millis.checked_sub(unix_millis(Instant::now())) {
+                    Some(remaining) if remaining > 0 => Some(Instant::now() + Duration::from_millis(remaining)),
+                    // 已经过期
+                    _ => continue,
+                },
+            };
+
+            let mut state = db.write(&key);
+            state.remove(&key);
+            if let Some(when) = expires_at {
+                state.expirations.insert((when, key.clone()));
+            }
+            state.insert(key, Entry::new(data, expires_at, self.shared.tick()));
+            loaded += 1;
+        }
+
+        // 加载的键可能比现有的键更早过期
+        self.shared.background_task.notify_one();
+
+        Ok(loaded)
+    }
+
+    /// 启用或禁用过期通知。启用后，后台任务清理过期的键时会把键名发布到 [`EXPIRED_CHANNEL`]。
+    pub(crate) fn set_notify_expired(&self, enabled: bool) {
+        self.shared.notify_expired.store(enabled, Ordering::SeqCst);
+    }
+
+    /// 启用或禁用滞后通知。启用后，订阅者读取得太慢、广播通道中的消息被覆盖时，会收到一条
+    /// `lagged` 消息，其中包含丢弃的消息数；否则这些消息被悄悄丢弃。
+    pub(crate) fn set_notify_lagged(&self, enabled: bool) {
+        self.shared.notify_lagged.store(enabled, Ordering::SeqCst);
+    }
+
+    /// 返回是否启用了滞后通知。
+    pub(crate) fn notify_lagged(&self) -> bool {
+        self.shared.notify_lagged.load(Ordering::SeqCst)
+    }
+
+    /// 启用或禁用只用于测试的 `DEBUG` 子命令，例如 `DEBUG SLEEP` 和 `DEBUG SET-ACTIVE-EXPIRE`。
+    pub(crate) fn set_debug_hooks(&self, enabled: bool) {
+        self.shared.debug_hooks.store(enabled, Ordering::SeqCst);
+    }
+
+    /// 返回是否启用了只用于测试的 `DEBUG` 子命令。
+    pub(crate) fn debug_hooks(&self) -> bool {
+        self.shared.debug_hooks.load(Ordering::SeqCst)
+    }
+
+    /// 启用或禁用排序的回复：启用后 `HGETALL`、`SMEMBERS`、`SINTER`、`SUNION` 和 `SDIFF` 按字节序返回元素。
+    pub(crate) fn set_sorted_replies(&self, enabled: bool) {
+        self.shared.sorted_replies.store(enabled, Ordering::SeqCst);
+    }
+
+    /// 返回是否启用了排序的回复。
+    fn sorted_replies(&self) -> bool {
+        self.shared.sorted_replies.load(Ordering::SeqCst)
+    }
+
+    /// 启用或禁用后台任务对过期键的主动清理。禁用后过期的键留在键空间中，直到被访问或重新启用。
+    ///
+    /// 重新启用时立即唤醒后台任务，清理禁用期间过期的键。
+    pub(crate) fn set_active_expire(&self, enabled: bool) {
+        self.shared.active_expire.store(enabled, Ordering::SeqCst);
+        if enabled {
+            self.shared.background_task.notify_one();
+        }
+    }
+
+    /// 设置新建的频道和模式的广播通道容量。已经存在的频道保持原来的容量。
+    ///
+    /// 广播通道至少需要容纳一条消息，`0` 按 `1` 处理。
+    pub(crate) fn set_pubsub_capacity(&self, capacity: usize) {
+        self.shared.pubsub_capacity.store(capacity.max(1), Ordering::SeqCst);
+    }
+
+    fn pubsub_capacity(&self) -> usize {
+        self.shared.pubsub_capacity.load(Ordering::SeqCst)
+    }
+
+    /// 设置订阅空闲多久之后推送一个保活的 `ping`。`None` 表示不推送。
+    ///
+    /// 只影响之后进入订阅模式的连接。不足一毫秒的间隔按一毫秒处理。
+    pub(crate) fn set_keepalive_interval(&self, interval: Option<Duration>) {
+        let millis = interval.map_or(0, |interval| (interval.as_millis() as u64).max(1));
+        self.shared.keepalive_interval.store(millis, Ordering::SeqCst);
+    }
+
+    /// 返回订阅空闲多久之后推送一个保活的 `ping`。
+    pub(crate) fn keepalive_interval(&self) -> Option<Duration> {
+        Some(self.shared.keepalive_interval.load(Ordering::SeqCst))
+            .filter(|&millis| millis > 0)
+            .map(Duration::from_millis)
+    }
+
+    /// 返回请求频道的 `Receiver`。
+    ///
+    /// 返回的 `Receiver` 用于接收 `PUBLISH` 命令广播的值。
+    pub(crate) fn subscribe(&self, key: String) -> broadcast::Receiver<Bytes> {
+        use std::collections::hash_map::Entry;
+
+        // 获取互斥锁
+        let mut pub_sub = self.shared.pub_sub.lock().unwrap();
+        // 如果请求频道没有条目，则创建一个新的广播频道并将其与键关联。如果已经存在，则返回一个关联的接收器。
+        match pub_sub.channels.entry(key) {
+            Entry::Occupied(e) => e.get().subscribe(),
+            Entry::Vacant(e) => {
+                // 尚不存在广播频道，因此创建一个。
+                //
+                // 频道的容量由 `set_pubsub_capacity` 配置，默认为 `1024` 条消息。消息存储在频道中，
+                // 直到**所有**订阅者都看到它。这意味着慢速订阅者可能会导致消息无限期地保留。
+                //
+                // 当频道的容量已满时，发布将导致旧消息被丢弃。这可以防止慢速消费者阻塞整个系统。
+                let (tx, rx) = broadcast::channel(self.pubsub_capacity());
+                e.insert(tx);
+                rx
+            }
+        }
+    }
+
+    /// 返回请求模式的 `Receiver`。
+    ///
+    /// 返回的 `Receiver` 接收发布到任何与 `pattern` 匹配的频道的消息，以及消息所在的频道。
+    pub(crate) fn psubscribe(&self, pattern: String) -> broadcast::Receiver<(String, Bytes)> {
+        let mut pub_sub = self.shared.pub_sub.lock().unwrap();
+        // 容量与频道订阅相同，参见 `subscribe`。
+        pub_sub
+            .patterns
+            .entry(pattern)
+            .or_insert_with(|| broadcast::channel(self.pubsub_capacity()).0)
+            .subscribe()
+    }
+
+    /// 一个订阅者取消订阅 `channel` 之后调用，调用之前它的接收器必须已经被丢弃。
+    /// 如果频道已经没有订阅者，就移除它的 `Sender`。
+    ///
+    /// 检查和移除都在持有 `pub_sub` 锁时进行，而 `subscribe` 也在持有这把锁时创建接收器，因此同时到来的新订阅者
+    /// 要么让频道保留下来，要么在移除之后创建一个新的频道，不会拿到一个已经被移除的频道的接收器。
+    pub(crate) fn unsubscribed(&self, channel: &str) {
+        let mut pub_sub = self.shared.pub_sub.lock().unwrap();
+        if pub_sub.channels.get(channel).is_some_and(|tx| tx.receiver_count() == 0) {
+            pub_sub.channels.remove(channel);
+        }
+    }
+
+    /// 一个订阅者取消订阅 `pattern` 之后调用，与 `unsubscribed` 相同。
+    pub(crate) fn punsubscribed(&self, pattern: &str) {
+        let mut pub_sub = self.shared.pub_sub.lock().unwrap();
+        if pub_sub.patterns.get(pattern).is_some_and(|tx| tx.receiver_count() == 0) {
+            pub_sub.patterns.remove(pattern);
+        }
+    }
+
+    /// 返回当前至少有一个订阅者的频道名称，按名称排序。给定 `pattern` 时只返回与其匹配的频道。
+    ///
+    /// 最后一个订阅者离开时频道会被 `unsubscribed` 移除，因此 `channels` 中只有仍有订阅者的频道。
+    pub(crate) fn channels(&self, pattern: Option<&str>) -> Vec<String> {
+        let pub_sub = self.shared.pub_sub.lock().unwrap();
+
+        let mut channels: Vec<String> = pub_sub
+            .channels
+            .iter()
+            .filter(|(channel, _)| pattern.is_none_or(|p| glob::matches(p.as_bytes(), channel.as_bytes())))
+            .map(|(channel, _)| channel.clone())
+            .collect();
+        channels.sort();
+        channels
+    }
+
+    /// 返回每个给定频道的订阅者数量，顺序与 `channels` 相同。没有订阅者的频道数量为 `0`。
+    pub(crate) fn num_sub(&self, channels: Vec<String>) -> Vec<(String, usize)> {
+        let pub_sub = self.shared.pub_sub.lock().unwrap();
+
+        channels
+            .into_iter()
+            .map(|channel| {
+                let count = pub_sub.channels.get(&channel).map_or(0, |tx| tx.receiver_count());
+                (channel, count)
+            })
+            .collect()
+    }
+
+    /// 向频道发布消息。返回收到消息的订阅者数量，包括与频道匹配的模式订阅者。
+    ///
+    /// 每次发布都要将频道与所有活动的模式逐一匹配，因此开销随模式数量线性增长。
+    pub(crate) fn publish(&self, key: &str, value: Bytes) -> usize {
+        let pub_sub = self.shared.pub_sub.lock().unwrap();
+
+        let num_subscribers = pub_sub
+            .channels
+            .get(key)
+            // 成功在广播频道上发送消息时，返回订阅者数量。错误表示没有接收者，在这种情况下，应返回 `0`。
+            .map(|tx| tx.send(value.clone()).unwrap_or(0))
+            // 如果频道键没有条目，则没有订阅者。在这种情况下，返回 `0`。
+            .unwrap_or(0);
+
+        let num_pattern_subscribers: usize = pub_sub
+            .patterns
+            .iter()
+            .filter(|(pattern, _)| glob::matches(pattern.as_bytes(), key.as_bytes()))
+            .map(|(_, tx)| tx.send((key.to_string(), value.clone())).unwrap_or(0))
+            .sum();
+
+        num_subscribers + num_pattern_subscribers
+    }
+
+    /// 返回句柄指向的数据库的分片。
+    fn shards(&self) -> &[RwLock<State>] {
+        self.shared.database(self.index)
+    }
+
+    /// 以读取方式锁定 `key` 所在的分片。只读取分片的操作使用它，它们之间可以并发进行。
+    fn read(&self, key: &str) -> RwLockReadGuard<'_, State> {
+        self.shards()[self.shared.shard_index(key)].read().unwrap()
+    }
+
+    /// 以写入方式锁定 `key` 所在的分片。
+    fn write(&self, key: &str) -> RwLockWriteGuard<'_, State> {
+        self.shards()[self.shared.shard_index(key)].write().unwrap()
+    }
+
+    /// 同时以写入方式锁定 `first` 和 `second` 所在的分片，用于 `RENAME` 等涉及两个键的操作。
+    ///
+    /// 返回 `first` 所在分片的锁，以及 `second` 所在分片的锁；两个键在同一个分片时第二个为 `None`。
+    /// 总是先锁定索引较小的分片，因此两个方向相反的操作不会死锁。
+    fn write_pair(
+        &self,
+        first: &str,
+        second: &str,
+    ) -> (RwLockWriteGuard<'_, State>, Option<RwLockWriteGuard<'_, State>>) {
+        self.write_pair_in(first, self, second)
+    }
+
+    /// 与 [`write_pair`](Db::write_pair) 相同，但 `second` 位于 `other` 指向的数据库，用于 `COPY ... DB` 和 `MOVE`
+    /// 等跨数据库的操作。
+    ///
+    /// 锁按数据库编号、再按分片编号的顺序获取，与 [`swap`](Db::swap) 相同，因此不会死锁。
+    fn write_pair_in<'a>(
+        &'a self,
+        first: &str,
+        other: &'a Db,
+        second: &str,
+    ) -> (RwLockWriteGuard<'a, State>, Option<RwLockWriteGuard<'a, State>>) {
+        let i = (self.index, self.shared.shard_index(first));
+        let j = (other.index, self.shared.shard_index(second));
+        let shard = |(index, shard): (usize, usize)| self.shared.database(index)[shard].write().unwrap();
+        if i == j {
+            return (shard(i), None);
+        }
+
+        let low = shard(i.min(j));
+        let high = shard(i.max(j));
+        if i < j {
+            (low, Some(high))
+        } else {
+            (high, Some(low))
+        }
+    }
+
+    /// 向清理后台任务发出关闭信号。这是由 `DbShutdown` 的 `Drop` 实现调用的。
+    fn shutdown_purge_task(&self) {
+        // 必须向后台任务发出关闭信号。这是通过将 `Shared::is_shutdown` 设置为 `true` 并通知任务来完成的。
+        self.shared.is_shutdown.store(true, Ordering::SeqCst);
+        self.shared.background_task.notify_one();
+    }
+}
+
+impl Shared {
+    /// 返回 `key` 所在分片的索引。
+    fn shard_index(&self, key: &str) -> usize {
+        self.hasher.hash_one(key) as usize % SHARDS
+    }
+
+    /// 推进逻辑时钟，返回推进之前的值。
+    fn tick(&self) -> u64 {
+        self.clock.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// 返回编号为 `index` 的数据库的分片，第一次访问时分配它们。
+    fn database(&self, index: usize) -> &[RwLock<State>] {
+        self.databases[index].get_or_init(|| (0..SHARDS).map(|_| RwLock::default()).collect())
+    }
+
+    /// 依次返回已经分配的数据库的编号和分片。没有分配的数据库一定是空的。
+    fn allocated_databases(&self) -> impl Iterator<Item = (usize, &[RwLock<State>])> {
+        self.databases.iter().enumerate().filter_map(|(index, database)| Some((index, &**database.get()?)))
+    }
+
+    /// 依次返回所有已经分配的数据库的所有分片。
+    fn all_shards(&self) -> impl Iterator<Item = &RwLock<State>> {
+        self.allocated_databases().flat_map(|(_, shards)| shards.iter())
+    }
+
+    /// 清除所有分片中的过期键并返回**下一个**键将过期的 `Instant`。后台任务将睡眠直到此时刻。
+    fn purge_expired_keys(&self) -> Option<Instant> {
+        if self.is_shutdown() {
+            // 数据库正在关闭。所有共享状态的句柄都已丢弃。后台任务应退出。
+            return None;
+        }
+        // 主动清理被禁用。返回 `None` 让后台任务等待，直到重新启用时被唤醒。
+        if !self.active_expire.load(Ordering::SeqCst) {
+            return None;
+        }
+
+        let mut expired = vec![];
+        let next = self.all_shards().filter_map(|shard| shard.write().unwrap().purge_expired_keys(&mut expired)).min();
+
+        // 在释放分片的锁之后通知订阅者，避免同时持有两个锁。
+        self.publish_expired(expired);
+
+        next
+    }
+
+    /// 启用了过期通知时，把过期的键名发布到 [`EXPIRED_CHANNEL`]。
+    ///
+    /// 没有订阅者时发送失败，这是正常的。调用者不能持有分片的锁。
+    fn publish_expired(&self, expired: Vec<String>) {
+        if self.notify_expired.load(Ordering::SeqCst) && !expired.is_empty() {
+            let pub_sub = self.pub_sub.lock().unwrap();
+            if let Some(tx) = pub_sub.channels.get(EXPIRED_CHANNEL) {
+                for key in expired {
+                    let _ = tx.send(Bytes::from(key));
+                }
+            }
+        }
+    }
+
+    /// 返回 `true` 如果数据库正在关闭
+    ///
+    /// 当所有 `Db` 值都已丢弃时，设置 `shutdown` 标志，表示共享状态不再可访问。
+    fn is_shutdown(&self) -> bool {
+        self.is_shutdown.load(Ordering::SeqCst)
+    }
+}
+
+impl State {
+    /// 清除分片中的过期键，把它们的键名加入 `expired`，返回分片中**下一个**键将过期的 `Instant`。
+    fn purge_expired_keys(&mut self, expired: &mut Vec<String>) -> Option<Instant> {
+        // 查找所有计划在现在之前过期的键。
+        let now = Instant::now();
+        while let Some(&(when, ref key)) = self.expirations.iter().next() {
+            if when > now {
+                // 完成清除，`when` 是下一个键过期的时间点。工作任务将等待直到此时刻。
+                return Some(when);
+            }
+            // 键已过期，删除它
+            let key = key.clone();
+            self.remove(&key);
+            expired.push(key);
+        }
+
+        None
+    }
+
+    fn next_expiration(&self) -> Option<Instant> {
+        self.expirations.iter().next().map(|expiration| expiration.0)
+    }
+
+    /// 将已经存在的键的过期时间替换为 `expires_at`，`None` 清除过期时间。
+    ///
+    /// 返回是否需要通知后台任务：与 `set` 相同，只有新的过期时间成为下一个要过期的键时才需要。
+    fn set_expires_at(&mut self, key: &str, expires_at: Option<Instant>) -> bool {
+        let notify = match expires_at {
+            Some(when) => self.next_expiration().is_none_or(|expiration| expiration > when),
+            None => false,
+        };
+
+        let entry = self.entries.get_mut(key).expect("key must exist");
+        if let Some(prev) = std::mem::replace(&mut entry.expires_at, expires_at) {
+            self.expirations.remove(&(prev, key.to_string()));
+        }
+        if let Some(when) = expires_at {
+            self.expirations.insert((when, key.to_string()));
+        }
+
+        notify
+    }
+
+    /// 从列表的 `end` 端弹出一个元素，列表被弹空后删除该键。
+    fn pop(&mut self, key: &str, end: ListEnd) -> Result<Option<Bytes>, WrongType> {
+        let list = match self.entries.get_mut(key) {
+            Some(entry) => entry.data.as_list_mut()?,
+            None => return Ok(None),
+        };
+
+        let value = match end {
+            ListEnd::Left => list.pop_front(),
+            ListEnd::Right => list.pop_back(),
+        };
+        let empty = list.is_empty();
+
+        self.used_memory -= value.as_ref().map_or(0, Bytes::len);
+        if empty {
+            self.remove(key);
+        }
+
+        Ok(value)
+    }
+
+    /// 删除键的条目，同时清除它的过期时间，避免 `expirations` 中残留指向已删除键的条目。
+    fn remove(&mut self, key: &str) -> Option<Entry> {
+        let entry = self.entries.remove(key)?;
+        if let Some(when) = entry.expires_at {
+            self.expirations.remove(&(when, key.to_string()));
+        }
+        self.used_memory -= entry.size(key);
+        Some(entry)
+    }
+
+    /// 插入键的条目，返回被替换的条目。与 `HashMap::insert` 相同，但同时更新 `used_memory`。
+    ///
+    /// 不处理过期时间，调用者负责维护 `expirations`。
+    fn insert(&mut self, key: String, entry: Entry) -> Option<Entry> {
+        self.used_memory += entry.size(&key);
+        let prev = self.entries.insert(key, entry)?;
+        // 键本身没有变，只减去旧值的大小
+        self.used_memory -= prev.data.size();
+        Some(prev)
+    }
+
+    /// 返回键的条目，键不存在时插入 `default` 返回的条目。
+    ///
+    /// 调用者修改返回的条目之后，需要把值的大小变化计入 `used_memory`。
+    fn entry_or_insert(&mut self, key: String, default: impl FnOnce() -> Entry) -> &mut Entry {
+        match self.entries.entry(key) {
+            hash_map::Entry::Occupied(entry) => entry.into_mut(),
+            hash_map::Entry::Vacant(entry) => {
+                let value = default();
+                self.used_memory += value.size(entry.key());
+                entry.insert(value)
+            }
+        }
+    }
+}
+
+impl Entry {
+    /// 创建一个条目，`now` 是创建时 [`Shared::clock`] 的值。
+    fn new(data: Value, expires_at: Option<Instant>, now: u64) -> Self {
+        Self {
+            data,
+            expires_at,
+            last_access: AtomicU64::new(now),
+        }
+    }
+
+    /// 一个没有过期时间的空字符串，用于 `APPEND` 等在键不存在时创建它的命令。
+    fn empty_string(now: u64) -> Self {
+        Self::new(Value::String(Bytes::new()), None, now)
+    }
+
+    /// 如果条目在 `now` 时已经过期，则返回 `true`。
+    fn is_expired(&self, now: Instant) -> bool {
+        self.expires_at.is_some_and(|when| when <= now)
+    }
+
+    /// 记录条目在逻辑时钟为 `now` 时被访问。
+    fn touch(&self, now: u64) {
+        self.last_access.store(now, Ordering::Relaxed);
+    }
+
+    /// 返回键和条目的值一共占用的近似字节数。
+    fn size(&self, key: &str) -> usize {
+        key.len() + self.data.size()
+    }
+}
+
+impl Value {
+    /// 返回值的类型名称，与 `TYPE` 命令的回复相同。
+    fn type_name(&self) -> &'static str {
+        match self {
+            Self::String(_) => "string",
+            Self::List(_) => "list",
+            Self::Hash(_) => "hash",
+            Self::Set(_) => "set",
+        }
+    }
+
+    /// 返回值的编码名称，与 `OBJECT ENCODING` 命令的回复相同。
+    ///
+    /// mini-redis 对每种类型只有一种表示，这里按大小报告 Redis 会使用的编码，方便依赖它的工具。
+    fn encoding(&self) -> &'static str {
+        match self {
+            Self::String(data) if data.len() <= EMBSTR_MAX_LEN => "embstr",
+            Self::String(_) => "raw",
+            Self::List(list) if list.len() <= LISTPACK_MAX_ENTRIES => "listpack",
+            Self::List(_) => "quicklist",
+            Self::Hash(hash) if hash.len() <= LISTPACK_MAX_ENTRIES => "listpack",
+            Self::Set(set) if set.len() <= LISTPACK_MAX_ENTRIES => "listpack",
+            Self::Hash(_) | Self::Set(_) => "hashtable",
+        }
+    }
+
+    /// 返回值在快照中的记录类型。
+    fn snapshot_kind(&self) -> u8 {
+        match self {
+            Self::String(_) => 0,
+            Self::List(_) => 1,
+            Self::Hash(_) => 2,
+            Self::Set(_) => 3,
+        }
+    }
+
+    /// 把值编码到快照中。
+    fn encode(&self, buf: &mut BytesMut) {
+        match self {
+            Self::String(data) => put_bytes(buf, data),
+            Self::List(list) => {
+                buf.put_u32(list.len() as u32);
+                for value in list {
+                    put_bytes(buf, value);
+                }
+            }
+            Self::Hash(hash) => {
+                buf.put_u32(hash.len() as u32);
+                for (field, value) in hash {
+                    put_bytes(buf, field.as_bytes());
+                    put_bytes(buf, value);
+                }
+            }
+            Self::Set(set) => {
+                buf.put_u32(set.len() as u32);
+                for member in set {
+                    put_bytes(buf, member);
+                }
+            }
+        }
+    }
+
+    /// 从快照中解码一个记录类型为 `kind` 的值。
+    fn decode(kind: u8, buf: &mut Bytes) -> io::Result<Self> {
+        match kind {
+            0 => Ok(Self::String(get_bytes(buf)?)),
+            1 => {
+                let len = get_u32(buf)?;
+                (0..len).map(|_| get_bytes(buf)).collect::<io::Result<_>>().map(Self::List)
+            }
+            2 => {
+                let len = get_u32(buf)?;
+                (0..len)
+                    .map(|_| Ok((get_string(buf)?, get_bytes(buf)?)))
+                    .collect::<io::Result<_>>()
+                    .map(Self::Hash)
+            }
+            3 => {
+                let len = get_u32(buf)?;
+                (0..len).map(|_| get_bytes(buf)).collect::<io::Result<_>>().map(Self::Set)
+            }
+            _ => Err(invalid_snapshot("unknown record type")),
+        }
+    }
+
+    /// 返回值的数据的字节数。哈希同时计入字段和值。
+    fn size(&self) -> usize {
+        match self {
+            Self::String(data) => data.len(),
+            Self::List(list) => list.iter().map(Bytes::len).sum(),
+            Self::Hash(hash) => hash.iter().map(|(field, value)| field.len() + value.len()).sum(),
+            Self::Set(set) => set.iter().map(Bytes::len).sum(),
+        }
+    }
+
+    /// 返回值中的元素数。字符串只有一个元素。
+    fn element_count(&self) -> usize {
+        match self {
+            Self::String(_) => 1,
+            Self::List(list) => list.len(),
+            Self::Hash(hash) => hash.len(),
+            Self::Set(set) => set.len(),
+        }
+    }
+
+    fn as_string(&self) -> Result<&Bytes, WrongType> {
+        match self {
+            Self::String(data) => Ok(data),
+            _ => Err(WrongType),
+        }
+    }
+
+    fn as_string_mut(&mut self) -> Result<&mut Bytes, WrongType> {
+        match self {
+            Self::String(data) => Ok(data),
+            _ => Err(WrongType),
+        }
+    }
+
+    fn as_list(&self) -> Result<&VecDeque<Bytes>, WrongType> {
+        match self {
+            Self::List(list) => Ok(list),
+            _ => Err(WrongType),
+        }
+    }
+
+    fn as_list_mut(&mut self) -> Result<&mut VecDeque<Bytes>, WrongType> {
+        match self {
+            Self::List(list) => Ok(list),
+            _ => Err(WrongType),
+        }
+    }
+
+    fn as_hash(&self) -> Result<&HashMap<String, Bytes>, WrongType> {
+        match self {
+            Self::Hash(hash) => Ok(hash),
+            _ => Err(WrongType),
+        }
+    }
+
+    fn as_hash_mut(&mut self) -> Result<&mut HashMap<String, Bytes>, WrongType> {
+        match self {
+            Self::Hash(hash) => Ok(hash),
+            _ => Err(WrongType),
+        }
+    }
+
+    fn as_set(&self) -> Result<&HashSet<Bytes>, WrongType> {
+        match self {
+            Self::Set(set) => Ok(set),
+            _ => Err(WrongType),
+        }
+    }
+
+    fn as_set_mut(&mut self) -> Result<&mut HashSet<Bytes>, WrongType> {
+        match self {
+            Self::Set(set) => Ok(set),
+            _ => Err(WrongType),
+        }
+    }
+}
+
+/// 已经处理了 `processed` 个元素时检查截止时间，超过时返回错误。
+///
+/// 每 [`DEADLINE_CHECK_INTERVAL`] 个元素才读取一次时钟，处理的元素更少的命令从不检查。
+fn check_deadline(deadline: Option<Instant>, processed: usize) -> Result<(), TimeLimitExceeded> {
+    match deadline {
+        Some(deadline) if processed > 0 && processed.is_multiple_of(DEADLINE_CHECK_INTERVAL) => {
+            if Instant::now() >= deadline {
+                Err(TimeLimitExceeded)
+            } else {
+                Ok(())
+            }
+        }
+        _ => Ok(()),
+    }
+}
+
+/// 将 Redis 风格的闭区间 `start..=end` 转换为长度为 `len` 的序列中的有效索引。
+///
+/// 负数索引从末尾开始计算，`-1` 是最后一个元素。超出范围的索引被截断，截断后范围为空时返回 `None`。
+fn normalize_range(len: usize, start: i64, end: i64) -> Option<(usize, usize)> {
+    let len = len as i64;
+    let start = if start < 0 { (len + start).max(0) } else { start };
+    let end = if end < 0 { len + end } else { end.min(len - 1) };
+    if start > end || len == 0 {
+        return None;
+    }
+
+    Some((start as usize, end as usize))
+}
+
+/// 把 `when` 转换为 Unix 毫秒时间戳。
+fn unix_millis(when: Instant) -> u64 {
+    let now = Instant::now();
+    let time = if when >= now {
+        SystemTime::now() + (when - now)
+    } else {
+        SystemTime::now() - (now - when)
+    };
+
+    time.duration_since(UNIX_EPOCH).map_or(0, |since| since.as_millis() as u64)
+}
+
+fn invalid_snapshot(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+/// 写入 u32 长度和数据。
+fn put_bytes(buf: &mut BytesMut, data: &[u8]) {
+    buf.put_u32(data.len() as u32);
+    buf.put_slice(data);
+}
+
+fn get_u8(buf: &mut Bytes) -> io::Result<u8> {
+    if buf.remaining() < 1 {
+        return Err(invalid_snapshot("unexpected end of snapshot"));
+    }
+    Ok(buf.get_u8())
+}
+
+fn get_u32(buf: &mut Bytes) -> io::Result<u32> {
+    if buf.remaining() < 4 {
+        return Err(invalid_snapshot("unexpected end of snapshot"));
+    }
+    Ok(buf.get_u32())
+}
+
+fn get_u64(buf: &mut Bytes) -> io::Result<u64> {
+    if buf.remaining() < 8 {
+        return Err(invalid_snapshot("unexpected end of snapshot"));
+    }
+    Ok(buf.get_u64())
+}
+
+/// 读取 u32 长度和数据。`Bytes::split_to` 只增加引用计数，不复制数据。
+fn get_bytes(buf: &mut Bytes) -> io::Result<Bytes> {
+    let len = get_u32(buf)? as usize;
+    if buf.remaining() < len {
+        return Err(invalid_snapshot("unexpected end of snapshot"));
+    }
+    Ok(buf.split_to(len))
+}
+
+fn get_string(buf: &mut Bytes) -> io::Result<String> {
+    String::from_utf8(get_bytes(buf)?.to_vec()).map_err(|_| invalid_snapshot("key is not valid UTF-8"))
+}
+
+/// 由后台任务执行的例程。
+///
+/// 等待通知。收到通知后，从共享状态句柄中清除任何过期的键。如果设置了 `shutdown`，则终止任务。
+async fn purge_expired_tasks(shared: Arc<Shared>) {
+    // 如果设置了关闭标志，则任务应退出。
+    while !shared.is_shutdown() {
+        // 清除所有过期的键。函数返回下一个键将过期的时间点。工作任务应等待直到此时刻，然后再次清除。
+        if let Some(when) = shared.purge_expired_keys() {
+            // 等待直到下一个键过期**或**直到后台任务被通知。如果任务被通知，则必须重新加载其状态，因为新键已设置为提前过期。这是通过循环完成的。
+            tokio::select! {
+                _ = time::sleep_until(when) => {}
+                _ = shared.background_task.notified() => {}
+            }
+        } else {
+            // 将来没有键过期。等待任务被通知。
+            shared.background_task.notified().await;
+        }
+    }
+    debug!("清理后台任务已关闭")
+}
+
+/// 在后台丢弃 `UNLINK` 删除的值。
+///
+/// 发送端保存在 `Shared` 中，所有 `Db` 句柄和清理任务都结束之后它被丢弃，此任务随之退出。
+async fn free_values(mut values: mpsc::UnboundedReceiver<Value>) {
+    while let Some(value) = values.recv().await {
+        drop(value);
+    }
+    debug!("释放内存的后台任务已关闭")
 }
@@ -8,14 +8,26 @@ use std::num::TryFromIntError;
 use std::string::FromUtf8Error;
 
 /// Redis 协议中的帧。
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum Frame {
     Simple(String),
     Error(String),
-    Integer(u64),
+    Integer(i64),
     Bulk(Bytes),
     Null,
     Array(Vec<Frame>),
+    /// 空数组 `*-1`，例如阻塞弹出超时时的响应。与空 bulk `$-1` 不同。
+    NullArray,
+    /// RESP3 的 map `%`，由键值对组成。RESP2 连接上编码为键和值交替组成的数组。
+    Map(Vec<(Frame, Frame)>),
+    /// RESP3 的浮点数 `,`。RESP2 连接上编码为 bulk 字符串。
+    Double(f64),
+    /// RESP3 的布尔值 `#`。RESP2 连接上编码为整数 `1` 或 `0`。
+    Boolean(bool),
+    /// RESP3 的推送 `>`，服务器主动发送的带外数据，例如发布/订阅消息。RESP2 连接上编码为数组。
+    ///
+    /// 客户端据此区分推送和命令的回复，因此同一个连接可以在接收消息的同时执行其他命令。
+    Push(Vec<Frame>),
 }
 
 #[derive(Debug)]
@@ -32,28 +44,33 @@ impl Frame {
         Self::Array(vec![])
     }
 
-    /// 将一个“bulk”帧推入数组。`self` 必须是一个 Array 帧。
+    /// 返回一个空的推送帧
+    pub(crate) fn push() -> Self {
+        Self::Push(vec![])
+    }
+
+    /// 将一个“bulk”帧推入数组。`self` 必须是一个 Array 或 Push 帧。
     ///
     /// # Panics
     ///
     /// 如果 `self` 不是数组，则会 panic
     pub(crate) fn push_bulk(&mut self, bytes: Bytes) {
         match self {
-            Self::Array(vec) => {
+            Self::Array(vec) | Self::Push(vec) => {
                 vec.push(Self::Bulk(bytes));
             }
             _ => panic!("not an array frame"),
         }
     }
 
-    /// 将一个“integer”帧推入数组。`self` 必须是一个 Array 帧。
+    /// 将一个“integer”帧推入数组。`self` 必须是一个 Array 或 Push 帧。
     ///
     /// # Panics
     ///
     /// 如果 `self` 不是数组，则会 panic
-    pub(crate) fn push_int(&mut self, value: u64) {
+    pub(crate) fn push_int(&mut self, value: i64) {
         match self {
-            Self::Array(vec) => {
+            Self::Array(vec) | Self::Push(vec) => {
                 vec.push(Self::Integer(value));
             }
             _ => panic!("not an array frame"),
@@ -62,6 +79,13 @@ impl Frame {
 
     /// 检查是否可以从 `src` 解码整个消息
     pub fn check(src: &mut Cursor<&[u8]>) -> Result<(), FrameError> {
+        Self::check_bounded(src, usize::MAX)
+    }
+
+    /// 与 `check` 相同，但拒绝声明的 bulk 长度或数组长度超过 `max_len` 的帧。
+    ///
+    /// 长度在读取到完整数据之前就被检查，因此恶意的长度头部会立即导致错误，而不是让调用者无限地缓冲数据。
+    pub(crate) fn check_bounded(src: &mut Cursor<&[u8]>, max_len: usize) -> Result<(), FrameError> {
         match get_u8(src)? {
             b'+' => {
                 get_line(src)?;
@@ -82,20 +106,94 @@ impl Frame {
                 } else {
                     // 读取 bulk 字符串
                     let len: usize = get_decimal(src)?.try_into()?;
+                    check_len(len, max_len)?;
 
                     // 跳过该数量的字节 + 2 (\r\n)。
                     skip(src, len + 2)
                 }
             }
             b'*' => {
-                let len = get_decimal(src)?;
+                if b'-' == peek_u8(src)? {
+                    // 跳过 '-1\r\n'
+                    skip(src, 4)
+                } else {
+                    let len: usize = get_decimal(src)?.try_into()?;
+                    check_len(len, max_len)?;
 
-                (0..len).try_for_each(|_| Self::check(src))
+                    (0..len).try_for_each(|_| Self::check_bounded(src, max_len))
+                }
+            }
+            b'>' => {
+                let len: usize = get_decimal(src)?.try_into()?;
+                check_len(len, max_len)?;
+
+                (0..len).try_for_each(|_| Self::check_bounded(src, max_len))
+            }
+            b'%' => {
+                let len: usize = get_decimal(src)?.try_into()?;
+                check_len(len, max_len)?;
+
+                // 每个条目由键和值两个帧组成。
+                (0..len).try_for_each(|_| {
+                    Self::check_bounded(src, max_len)?;
+                    Self::check_bounded(src, max_len)
+                })
+            }
+            b',' => {
+                get_double(src)?;
+                Ok(())
+            }
+            b'#' => {
+                get_boolean(src)?;
+                Ok(())
             }
             actual => Err(format!("protocol error; invalid frame type byte `{}`", actual).into()),
         }
     }
 
+    /// 解析 `src` 开头的 bulk 字符串的头部，返回声明的长度，并把光标推进到载荷的开头。
+    ///
+    /// 开头不是 bulk 字符串（包括空 bulk `$-1`）时返回 `None`，此时光标的位置没有意义。头部不完整时返回 `Incomplete`。
+    pub(crate) fn parse_bulk_header(src: &mut Cursor<&[u8]>) -> Result<Option<usize>, FrameError> {
+        if get_u8(src)? != b'$' || peek_u8(src)? == b'-' {
+            return Ok(None);
+        }
+
+        Ok(Some(get_decimal(src)?.try_into()?))
+    }
+
+    /// 返回以 `first` 开头的请求是否是内联命令。
+    ///
+    /// RESP 编码的帧总是以类型字节开头，以其他字节开头的是内联命令，例如通过 `telnet` 输入的 `GET foo`。
+    pub(crate) fn is_inline(first: u8) -> bool {
+        !matches!(first, b'+' | b'-' | b':' | b'$' | b'*' | b'>' | b'%' | b',' | b'#')
+    }
+
+    /// 解析一条内联命令：以新行结束的一行文本，参数以空白分隔。
+    ///
+    /// 结果与等效的 RESP 请求相同，是一个 bulk 字符串数组。空行得到空数组。行尾可以是 `\r\n` 或单独的 `\n`，
+    /// 不支持引号。还没有收到完整的一行时返回 `Incomplete`；行的长度超过 `max_len` 时返回错误。
+    pub(crate) fn parse_inline(src: &mut Cursor<&[u8]>, max_len: usize) -> Result<Frame, FrameError> {
+        let start = src.position() as usize;
+        let rest = &src.get_ref()[start..];
+        let Some(end) = rest.iter().position(|&byte| byte == b'\n') else {
+            check_len(rest.len(), max_len)?;
+            return Err(FrameError::Incomplete);
+        };
+        check_len(end, max_len)?;
+        src.set_position((start + end + 1) as u64);
+
+        let line = &rest[..end];
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        let args = line
+            .split(u8::is_ascii_whitespace)
+            .filter(|arg| !arg.is_empty())
+            .map(|arg| Frame::Bulk(Bytes::copy_from_slice(arg)))
+            .collect();
+
+        Ok(Frame::Array(args))
+    }
+
     /// 将帧转换为“unexpected frame”错误
     pub(crate) fn to_error(&self) -> crate::Error {
         format!("unexpected frame: {}", self).into()
@@ -123,9 +221,9 @@ impl From<&mut Cursor<&[u8]>> for Frame {
                 Self::Error(string)
             }
             b':' => {
-                let len = get_decimal(src).unwrap();
+                let value = get_decimal(src).unwrap();
 
-                Self::Integer(len)
+                Self::Integer(value)
             }
             b'$' => {
                 if b'-' == peek_u8(src).unwrap() {
@@ -144,12 +242,39 @@ impl From<&mut Cursor<&[u8]>> for Frame {
                 }
             }
             b'*' => {
+                if b'-' == peek_u8(src).unwrap() {
+                    let _ = get_line(src);
+
+                    Self::NullArray
+                } else {
+                    let len = get_decimal(src).unwrap().try_into().unwrap();
+                    // 必须顺序执行map, 不可以使用par_iter, 否则会导致顺序错乱
+                    let vec = (0..len).map(|_| Self::from(&mut *src)).collect();
+
+                    Self::Array(vec)
+                }
+            }
+            b'>' => {
                 let len = get_decimal(src).unwrap().try_into().unwrap();
-                // 必须顺序执行map, 不可以使用par_iter, 否则会导致顺序错乱
                 let vec = (0..len).map(|_| Self::from(&mut *src)).collect();
 
-                Self::Array(vec)
+                Self::Push(vec)
+            }
+            b'%' => {
+                let len = get_decimal(src).unwrap().try_into().unwrap();
+                // 与数组相同，必须按顺序解析键和值。
+                let pairs = (0..len)
+                    .map(|_| {
+                        let key = Self::from(&mut *src);
+                        let value = Self::from(&mut *src);
+                        (key, value)
+                    })
+                    .collect();
+
+                Self::Map(pairs)
             }
+            b',' => Self::Double(get_double(src).unwrap()),
+            b'#' => Self::Boolean(get_boolean(src).unwrap()),
             _ => unimplemented!(),
         }
     }
@@ -177,8 +302,10 @@ impl fmt::Display for Frame {
                 Ok(string) => string.fmt(fmt),
                 Err(_) => write!(fmt, "{:?}", msg),
             },
-            Self::Null => "(nil)".fmt(fmt),
-            Self::Array(parts) => {
+            Self::Null | Self::NullArray => "(nil)".fmt(fmt),
+            Self::Double(num) => num.fmt(fmt),
+            Self::Boolean(value) => value.fmt(fmt),
+            Self::Array(parts) | Self::Push(parts) => {
                 parts.iter().enumerate().try_for_each(|(i, part)| {
                     if i > 0 {
                         // 使用空格作为数组元素显示分隔符
@@ -188,6 +315,15 @@ impl fmt::Display for Frame {
                     part.fmt(fmt)
                 })
             }
+            Self::Map(pairs) => {
+                pairs.iter().enumerate().try_for_each(|(i, (key, value))| {
+                    if i > 0 {
+                        write!(fmt, " ")?;
+                    }
+
+                    write!(fmt, "{}: {}", key, value)
+                })
+            }
         }
     }
 }
@@ -227,6 +363,15 @@ impl fmt::Display for FrameError {
     }
 }
 
+/// 如果声明的长度超过上限，则返回协议错误
+fn check_len(len: usize, max_len: usize) -> Result<(), FrameError> {
+    if len > max_len {
+        return Err(format!("protocol error; frame length {} exceeds maximum of {}", len, max_len).into());
+    }
+
+    Ok(())
+}
+
 fn peek_u8(src: &mut Cursor<&[u8]>) -> Result<u8, FrameError> {
     if !src.has_remaining() {
         return Err(FrameError::Incomplete);
@@ -252,13 +397,32 @@ fn skip(src: &mut Cursor<&[u8]>, n: usize) -> Result<(), FrameError> {
     Ok(())
 }
 
-/// 读取一个以新行终止的十进制数
-fn get_decimal(src: &mut Cursor<&[u8]>) -> Result<u64, FrameError> {
+/// 读取一个以新行终止的十进制数。RESP 整数可以是负数。
+fn get_decimal(src: &mut Cursor<&[u8]>) -> Result<i64, FrameError> {
     use atoi::atoi;
 
     let line = get_line(src)?;
 
-    atoi::<u64>(line).ok_or_else(|| "protocol error; invalid frame format".into())
+    atoi::<i64>(line).ok_or_else(|| "protocol error; invalid frame format".into())
+}
+
+/// 读取一个以新行终止的浮点数。除了普通的十进制数，还可以是 `inf`、`-inf` 或 `nan`。
+fn get_double(src: &mut Cursor<&[u8]>) -> Result<f64, FrameError> {
+    let line = get_line(src)?;
+
+    std::str::from_utf8(line)
+        .ok()
+        .and_then(|line| line.parse().ok())
+        .ok_or_else(|| "protocol error; invalid frame format".into())
+}
+
+/// 读取一个以新行终止的布尔值，`t` 或 `f`。
+fn get_boolean(src: &mut Cursor<&[u8]>) -> Result<bool, FrameError> {
+    match get_line(src)? {
+        b"t" => Ok(true),
+        b"f" => Ok(false),
+        _ => Err("protocol error; invalid frame format".into()),
+    }
 }
 
 /// 查找一行
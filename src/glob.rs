@@ -0,0 +1,108 @@
+//! Redis 风格的 glob 模式匹配。
+//!
+//! 支持以下语法：
+//!
+//! * `*` -- 匹配任意长度（包括零）的任意字节序列。
+//! * `?` -- 匹配任意单个字节。
+//! * `[abc]`、`[a-z]`、`[^a]` -- 匹配集合或范围中的一个字节，`^` 表示取反。
+//! * `\x` -- 按字面匹配 `x`。
+//!
+//! 用于 `PSUBSCRIBE` 等需要按模式匹配名称的命令。
+
+/// 如果 `string` 与 `pattern` 匹配，则返回 `true`。
+pub(crate) fn matches(pattern: &[u8], string: &[u8]) -> bool {
+    let (mut p, mut s) = (0, 0);
+    // 最近一个 `*` 之后的模式位置，以及它当前匹配到的字符串位置。不匹配时从这里回溯，
+    // 让 `*` 多吃掉一个字节再试。
+    let mut star: Option<(usize, usize)> = None;
+
+    while s < string.len() {
+        if p < pattern.len() {
+            match pattern[p] {
+                b'*' => {
+                    star = Some((p + 1, s));
+                    p += 1;
+                    continue;
+                }
+                b'?' => {
+                    p += 1;
+                    s += 1;
+                    continue;
+                }
+                b'[' => {
+                    if let Some((matched, next)) = match_class(pattern, p, string[s]) {
+                        if matched {
+                            p = next;
+                            s += 1;
+                            continue;
+                        }
+                    } else if string[s] == b'[' {
+                        // 没有闭合的 `]`，按字面匹配 `[`
+                        p += 1;
+                        s += 1;
+                        continue;
+                    }
+                }
+                b'\\' if p + 1 < pattern.len() => {
+                    if pattern[p + 1] == string[s] {
+                        p += 2;
+                        s += 1;
+                        continue;
+                    }
+                }
+                c => {
+                    if c == string[s] {
+                        p += 1;
+                        s += 1;
+                        continue;
+                    }
+                }
+            }
+        }
+
+        // 不匹配。如果之前有 `*`，让它多匹配一个字节后重试，否则匹配失败。
+        match star {
+            Some((star_p, star_s)) => {
+                p = star_p;
+                s = star_s + 1;
+                star = Some((star_p, star_s + 1));
+            }
+            None => return false,
+        }
+    }
+
+    // 字符串已经耗尽，模式中剩下的只能是 `*`。
+    pattern[p..].iter().all(|&c| c == b'*')
+}
+
+/// 匹配从 `pattern[start]`（即 `[`）开始的字符集合。
+///
+/// 返回是否匹配以及集合之后的模式位置。如果集合没有闭合的 `]`，返回 `None`。
+fn match_class(pattern: &[u8], start: usize, c: u8) -> Option<(bool, usize)> {
+    let mut p = start + 1;
+    let negate = pattern.get(p) == Some(&b'^');
+    if negate {
+        p += 1;
+    }
+
+    let mut matched = false;
+    loop {
+        match *pattern.get(p)? {
+            b']' => return Some((matched != negate, p + 1)),
+            b'\\' => {
+                matched |= *pattern.get(p + 1)? == c;
+                p += 2;
+            }
+            low if pattern.get(p + 1) == Some(&b'-') && pattern.get(p + 2).is_some_and(|&high| high != b']') => {
+                let high = pattern[p + 2];
+                let (low, high) = if low <= high { (low, high) } else { (high, low) };
+                matched |= low <= c && c <= high;
+                p += 3;
+            }
+            other => {
+                matched |= other == c;
+                p += 1;
+            }
+        }
+    }
+}
@@ -8,18 +8,23 @@ pub mod cmd;
 pub use cmd::Command;
 
 mod connection;
-pub use connection::Connection;
+pub use connection::{BulkReader, Connection, Protocol, StreamedFrame};
+use connection::AsyncStream;
 
 mod db;
 use db::{Db, DbDropGuard};
 
+mod aof;
+
 pub mod clients;
-pub use clients::{BlockingClient, BufferedClient, Client};
+pub use clients::{BlockingClient, BufferedClient, Client, ReconnectingClient};
 
 pub mod server;
 
 mod shutdown;
 use shutdown::Shutdown;
+
+mod glob;
 /// Redis 服务器监听的默认端口。
 ///
 /// 如果未指定端口，则使用此端口。
@@ -1,3 +1,4 @@
+use crate::cmd::InvalidArgument;
 use crate::Frame;
 
 use bytes::Bytes;
@@ -16,7 +17,8 @@ pub(crate) struct Parser {
 
 /// 解析帧时遇到的错误。
 ///
-/// 只有 `EndOfStream` 错误在运行时处理。所有其他错误都会导致连接终止。
+/// `EndOfStream` 表示参数不够。`Other` 包装的 [`InvalidArgument`] 表示参数值无效。连接处理程序对这两种错误
+/// 只回复错误，所有其他错误都会导致连接终止。
 #[derive(Debug)]
 pub(crate) enum ParserError {
     /// 由于帧已完全消耗，尝试提取值失败。
@@ -77,18 +79,19 @@ impl Parser {
     ///
     /// 这包括 `Simple`、`Bulk` 和 `Integer` 帧类型。`Simple` 和 `Bulk` 帧类型被解析。
     ///
-    /// 如果下一个条目不能表示为整数，则返回错误。
-    pub(crate) fn next_int(&mut self) -> Result<u64, ParserError> {
+    /// 如果下一个条目不能表示为整数，则返回错误。字符串不是整数时返回 [`InvalidArgument`]，
+    /// 连接处理程序只回复错误，不关闭连接。
+    pub(crate) fn next_int(&mut self) -> Result<i64, ParserError> {
         use atoi::atoi;
 
-        const MSG: &str = "协议错误；无效数字";
+        let invalid = || ParserError::Other(InvalidArgument::not_an_integer().into());
 
         match self.next()? {
             // 整数帧类型已存储为整数。
             Frame::Integer(v) => Ok(v),
             // 简单和批量帧必须解析为整数。如果解析失败，则返回错误。
-            Frame::Simple(data) => atoi::<u64>(data.as_bytes()).ok_or_else(|| MSG.into()),
-            Frame::Bulk(data) => atoi::<u64>(&data).ok_or_else(|| MSG.into()),
+            Frame::Simple(data) => atoi::<i64>(data.as_bytes()).ok_or_else(invalid),
+            Frame::Bulk(data) => atoi::<i64>(&data).ok_or_else(invalid),
             frame => Err(format!("协议错误；预期整数帧，但得到 {:?}", frame).into()),
         }
     }
@@ -2,14 +2,27 @@
 //!
 //! 提供一个异步的 `run` 函数，用于监听入站连接，为每个连接生成一个任务。
 
-use crate::{Command, Connection, Db, DbDropGuard, Shutdown};
+use crate::aof::{self, AofWriter};
+use crate::cmd::{InvalidArgument, Permissions, SubscribeExit, SyntaxError, WrongArity};
+use crate::connection::configure_socket;
+use crate::{Command, Connection, Db, DbDropGuard, Frame, Shutdown};
 
+use bytes::Bytes;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
 use std::future::Future;
-use std::sync::Arc;
+use std::io;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
 use tokio::net::{TcpListener, TcpStream};
 use tokio::sync::{broadcast, mpsc, Semaphore};
-use tokio::time::{self, Duration};
-use tracing::{debug, error, info, instrument};
+use tokio::task::JoinHandle;
+use tokio::time::{self, Duration, Instant};
+use tracing::{debug, error, info, instrument, warn};
 
 /// 服务器监听器状态。在 `run` 调用中创建。它包括一个 `run` 方法
 /// 用于执行 TCP 监听和每个连接状态的初始化。
@@ -50,6 +63,393 @@ struct Server {
     /// 这会导致 `shutdown_complete_rx.recv()` 完成并返回 `None`。
     /// 此时，可以安全地退出服务器进程。
     shutdown_complete_tx: mpsc::Sender<()>,
+    /// 服务器的负载，即当前正在处理的连接数。
+    ///
+    /// 连接任务生成前加一，处理程序结束后减一。关闭时读取它，得到需要排空的连接数。
+    /// 每个处理程序持有一个克隆，用于在过载时拒绝命令。
+    load: Load,
+    /// 验证 `AUTH` 的密码并返回连接的身份。`None` 表示不需要验证。
+    auth: Option<Arc<dyn AuthProvider>>,
+    /// 服务器的运行状态，每个处理程序持有一个克隆，供 `INFO` 读取。
+    stats: Stats,
+    /// 所有活动连接的登记表。每个处理程序持有自己的登记项。
+    clients: Clients,
+    /// 是否对接受的套接字设置 `TCP_NODELAY`。
+    nodelay: bool,
+    /// 接受的套接字的 TCP keepalive 空闲时间。`None` 表示不启用。
+    keepalive: Option<Duration>,
+    /// AOF 的写入端，每个处理程序持有一个克隆。`None` 表示不记录命令。
+    aof: Option<AofWriter>,
+    /// 接收连接和命令事件的指标接收端，每个处理程序持有一个克隆。
+    metrics: Arc<dyn Metrics>,
+    /// 执行得慢的命令，每个处理程序持有一个克隆。
+    slowlog: SlowLog,
+}
+
+/// 服务器负载的共享视图。
+#[derive(Debug, Clone)]
+struct Load {
+    /// 当前正在处理的连接数。
+    active: Arc<AtomicUsize>,
+    /// 过载保护配置。`None` 表示不启用。
+    overload: Option<OverloadConfig>,
+}
+
+/// 服务器运行状态的共享视图，供 `INFO` 等需要了解服务器本身的命令读取。
+#[derive(Debug, Clone)]
+pub(crate) struct Stats {
+    /// 服务器启动的时间。
+    started: Instant,
+    /// 当前正在处理的连接数，与 [`Load::active`] 共享。
+    ///
+    /// 没有根据信号量的可用许可计算，因为监听器在等待下一个连接时已经持有一个许可。
+    active: Arc<AtomicUsize>,
+    /// 服务器将接受的最大并发连接数，可以通过 `CONFIG SET maxclients` 修改。
+    max_connections: Arc<AtomicUsize>,
+    /// 监听器用来限制连接数的信号量，修改最大连接数时调整其中的许可数。
+    limit_connections: Arc<Semaphore>,
+    /// 所有连接从套接字读取的总字节数，包括已经关闭的连接。
+    net_input_bytes: Arc<AtomicU64>,
+    /// 所有连接写入套接字的总字节数，包括已经关闭的连接。
+    net_output_bytes: Arc<AtomicU64>,
+}
+
+/// 所有活动连接的登记表，供 `CLIENT LIST` 读取。
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Clients {
+    /// 最近分配的连接 id。id 从 1 开始单调递增，不会重复使用。
+    last_id: Arc<AtomicU64>,
+    /// 每个活动连接的信息，按 id 索引。
+    connections: Arc<Mutex<HashMap<u64, ClientInfo>>>,
+}
+
+/// 登记表中一个连接的信息。
+#[derive(Debug)]
+struct ClientInfo {
+    /// 对等方的地址。
+    addr: SocketAddr,
+    /// 通过 `CLIENT SETNAME` 设置的连接名称。
+    name: Option<String>,
+}
+
+/// 一个连接在 [`Clients`] 中的登记项，由连接的处理程序持有。
+///
+/// 被丢弃时将连接从登记表中移除。处理程序无论正常结束还是出错退出都会被丢弃，因此登记表中不会残留已经关闭的连接。
+#[derive(Debug)]
+pub(crate) struct ClientRegistration {
+    /// 连接的 id。
+    id: u64,
+    /// 对等方的地址。
+    addr: SocketAddr,
+    /// 连接所在的登记表。
+    clients: Clients,
+}
+
+/// 慢日志，记录执行时间超过阈值的命令，供 `SLOWLOG` 读取。
+///
+/// 条目保存在容量有限的环形缓冲区中，写满之后丢弃最旧的条目。
+#[derive(Debug, Clone)]
+pub(crate) struct SlowLog {
+    /// 执行时间达到该值的命令会被记录。`None` 表示不记录。
+    threshold: Option<Duration>,
+    /// 最多保留的条目数。
+    max_len: usize,
+    /// 最近分配的条目 id。id 从 0 开始单调递增，`SLOWLOG RESET` 之后也不会重复使用。
+    next_id: Arc<AtomicU64>,
+    /// 记录的条目，最新的在前面。
+    entries: Arc<Mutex<VecDeque<SlowLogEntry>>>,
+}
+
+/// 慢日志中的一个条目。
+#[derive(Debug, Clone)]
+pub(crate) struct SlowLogEntry {
+    /// 条目的 id。
+    pub(crate) id: u64,
+    /// 命令开始执行的 Unix 时间戳，单位是秒。
+    pub(crate) timestamp: u64,
+    /// 命令的执行时间。
+    pub(crate) duration: Duration,
+    /// 命令名称和参数。太多的参数和太长的参数会被截断，见 [`SLOWLOG_MAX_ARGC`] 和 [`SLOWLOG_MAX_ARGLEN`]。
+    pub(crate) args: Vec<Bytes>,
+    /// 执行命令的连接的对等方地址。
+    pub(crate) addr: SocketAddr,
+    /// 执行命令的连接的名称。
+    pub(crate) name: Option<String>,
+}
+
+/// 服务器的启动配置。
+///
+/// 传给 [`run_with_config`]。默认值与 [`run`] 的行为相同。
+///
+/// 新增的字段都有默认值，因此构造时建议使用 `..Default::default()`。
+#[derive(Debug, Clone)]
+pub struct ServerConfig {
+    /// 服务器将接受的最大并发连接数。达到此限制时，服务器停止接受连接，直到一个活动连接终止。
+    /// 默认为 [`MAX_CONNECTIONS`]。
+    pub max_connections: usize,
+    /// 启动时为多少个键预留空间。批量加载大量键时可以避免反复扩容。默认为 0。
+    pub preallocate: usize,
+    /// 为 `true` 时，后台任务每清理一个过期的键，就把键名发布到 `__keyevent__:expired` 频道。
+    /// 默认为 `false`，避免不需要时的开销。
+    pub notify_expired: bool,
+    /// 为 `true` 时，订阅者读取得太慢、丢弃了消息时，服务器推送一条 `lagged` 消息，其中包含频道（或模式）
+    /// 和丢弃的消息数。默认为 `false`，与之前一样悄悄丢弃。
+    pub notify_lagged: bool,
+    /// 每个频道（以及每个模式订阅）的广播通道能容纳多少条消息。默认为 [`PUBSUB_CAPACITY`]。
+    ///
+    /// 消息保留在通道中，直到所有订阅者都读到它，通道满了之后最旧的消息被丢弃。容量越大，越能容忍读取慢的订阅者，
+    /// 但每个频道占用的内存也越多：最坏情况下每个频道都保留这么多条消息。
+    pub pubsub_capacity: usize,
+    /// 数据库的数量。连接通过 `SELECT` 选择其中一个，新连接使用 0 号数据库。默认为 [`DATABASES`]，至少为 1。
+    ///
+    /// 数据库在第一次被选择时才分配，因此没有用到的数据库几乎不占用内存。
+    pub databases: usize,
+    /// 连接必须通过 `AUTH` 验证的密码。默认为 `None`，即不需要验证。
+    ///
+    /// 通过这个密码验证的连接可以执行所有命令。配置了 [`auth`](Self::auth) 时忽略该字段。
+    pub requirepass: Option<String>,
+    /// 验证 `AUTH` 的密码、决定连接可以执行哪些命令的验证器。默认为 `None`，即使用 `requirepass`。
+    pub auth: Option<Arc<dyn AuthProvider>>,
+    /// 过载保护。默认为 `None`，即不启用：连接数达到上限后新连接只是等待。
+    pub overload: Option<OverloadConfig>,
+    /// 是否对接受的套接字设置 `TCP_NODELAY`，禁用 Nagle 算法。默认为 `true`，响应不会为了等待合并而延迟发送。
+    pub nodelay: bool,
+    /// 启用 TCP keepalive，连接空闲该时间之后开始探测对等方。默认为 `None`，即不启用。
+    pub keepalive: Option<Duration>,
+    /// 订阅者在该时间内没有收到任何消息时，服务器推送一个 `ping`，防止负载均衡器等中间设备关闭空闲的连接。
+    /// 默认为 `None`，即不推送。
+    ///
+    /// RESP2 连接上推送的是数组 `["ping"]`，RESP3 连接上是同样内容的推送帧。
+    pub keepalive_interval: Option<Duration>,
+    /// 内存上限，单位是字节，按所有键和值的长度之和近似计算。超过上限时 `SET` 先驱逐最久未使用的键，
+    /// 驱逐之后仍然放不下时回复 OOM 错误。默认为 `None`，即不限制。
+    pub maxmemory: Option<usize>,
+    /// 超过内存上限时的处理方式。默认为 [`MaxMemoryPolicy::AllKeysLru`]。
+    pub maxmemory_policy: MaxMemoryPolicy,
+    /// `SCAN`、`SINTER`、`SUNION` 和 `SDIFF` 等遍历大量元素的命令最多执行多久，超过时放弃并回复
+    /// `ERR command exceeded time limit`，不再长时间持有锁、拖慢其他连接。默认为 `None`，即不限制。
+    ///
+    /// 命令在遍历过程中定期检查时间，只处理少量元素的命令不受影响。目前只有 `SCAN` 的遍历和
+    /// `SINTER`、`SUNION`、`SDIFF` 检查这个期限；`SMEMBERS`、`HGETALL`、`LRANGE` 等返回整个值的命令，
+    /// 以及 `SCAN` 遍历之前对键的排序都不受限制。
+    pub command_time_limit: Option<Duration>,
+    /// 快照文件。服务器启动时从中恢复键空间，`SAVE` 命令把键空间写入其中。默认为 `None`，即不持久化，
+    /// `SAVE` 回复错误。
+    pub dbfilename: Option<PathBuf>,
+    /// 追加写入日志（AOF）文件。服务器启动时重放其中的命令，之后把每个修改键空间的命令追加到其中。
+    /// 默认为 `None`，即不记录。
+    ///
+    /// 同时配置了快照文件时，先加载快照，再重放 AOF。
+    pub appendfilename: Option<PathBuf>,
+    /// 何时把 AOF 同步到磁盘。默认为 [`AppendFsync::EverySec`]。
+    pub appendfsync: AppendFsync,
+    /// 收到关闭信号之后，最多等待多久让活动连接结束。超时后服务器不再等待剩余的连接，直接完成关闭，
+    /// [`ShutdownReport::forced`] 为 `true`。默认为 `None`，即一直等待所有连接结束。
+    pub shutdown_timeout: Option<Duration>,
+    /// 接收连接打开和关闭、收到的命令以及读写字节数等事件的指标接收端。默认为 [`NoopMetrics`]，不做任何事情。
+    pub metrics: Arc<dyn Metrics>,
+    /// 执行时间达到该值的命令被记录到慢日志中，通过 `SLOWLOG GET` 查看。默认为 [`SLOWLOG_THRESHOLD`]；
+    /// `None` 表示不记录，`Duration::ZERO` 记录所有命令。
+    ///
+    /// 计时只包括命令的执行，不包括读取请求和等待写入响应，订阅和阻塞命令不计时。
+    pub slowlog_threshold: Option<Duration>,
+    /// 慢日志最多保留多少个条目，超过后丢弃最旧的条目。默认为 [`SLOWLOG_MAX_LEN`]。
+    pub slowlog_max_len: usize,
+    /// 为 `true` 时允许执行只用于测试的 `DEBUG` 子命令：`DEBUG SLEEP` 让连接暂停一段时间，
+    /// `DEBUG SET-ACTIVE-EXPIRE` 开关后台的过期清理。默认为 `false`，生产环境中不应该启用。
+    pub debug_hooks: bool,
+    /// 为 `true` 时 `HGETALL` 按字段、`SMEMBERS`、`SINTER`、`SUNION` 和 `SDIFF` 按成员的字节序返回元素，
+    /// 让测试的输出是确定的。默认为 `false`：这些命令返回的顺序不确定，也不付出排序的开销，调用方不应该依赖它。
+    ///
+    /// `SCAN` 总是按键的字典序迭代，不受该选项影响。
+    pub sorted_replies: bool,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            max_connections: MAX_CONNECTIONS,
+            preallocate: 0,
+            notify_expired: false,
+            notify_lagged: false,
+            pubsub_capacity: PUBSUB_CAPACITY,
+            databases: DATABASES,
+            requirepass: None,
+            auth: None,
+            overload: None,
+            nodelay: true,
+            keepalive: None,
+            keepalive_interval: None,
+            maxmemory: None,
+            maxmemory_policy: MaxMemoryPolicy::default(),
+            command_time_limit: None,
+            dbfilename: None,
+            appendfilename: None,
+            appendfsync: AppendFsync::default(),
+            shutdown_timeout: None,
+            metrics: Arc::new(NoopMetrics),
+            slowlog_threshold: Some(SLOWLOG_THRESHOLD),
+            slowlog_max_len: SLOWLOG_MAX_LEN,
+            debug_hooks: false,
+            sorted_replies: false,
+        }
+    }
+}
+
+/// 超过 [`ServerConfig::maxmemory`] 时 `SET` 的处理方式。名称与 Redis 的 `maxmemory-policy` 一致。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MaxMemoryPolicy {
+    /// `allkeys-lru`：在所有键中驱逐最久未使用的键，驱逐之后仍然放不下时回复 OOM 错误。
+    #[default]
+    AllKeysLru,
+    /// `noeviction`：不驱逐任何键，直接回复 OOM 错误。
+    NoEviction,
+}
+
+impl MaxMemoryPolicy {
+    /// 返回 Redis 风格的策略名称，例如 `allkeys-lru`。
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::AllKeysLru => "allkeys-lru",
+            Self::NoEviction => "noeviction",
+        }
+    }
+}
+
+/// 按 Redis 风格的名称（不区分大小写）解析策略。
+impl FromStr for MaxMemoryPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match &s.to_lowercase()[..] {
+            "allkeys-lru" => Ok(Self::AllKeysLru),
+            "noeviction" => Ok(Self::NoEviction),
+            _ => Err(format!("unsupported maxmemory policy {}", s)),
+        }
+    }
+}
+
+/// 何时把 [`ServerConfig::appendfilename`] 同步到磁盘。名称与 Redis 的 `appendfsync` 一致。
+///
+/// 无论哪种策略，写入的命令都会立即刷新到操作系统，进程崩溃不会丢失它们；策略只决定操作系统崩溃或断电时
+/// 最多丢失多少命令。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AppendFsync {
+    /// `always`：每次写入之后都调用 `fsync`，最安全也最慢。
+    Always,
+    /// `everysec`：每秒最多调用一次 `fsync`，最多丢失最近一秒的命令。
+    #[default]
+    EverySec,
+    /// `no`：从不调用 `fsync`，由操作系统决定何时写入磁盘。
+    No,
+}
+
+impl AppendFsync {
+    /// 返回 Redis 风格的策略名称，例如 `everysec`。
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Always => "always",
+            Self::EverySec => "everysec",
+            Self::No => "no",
+        }
+    }
+}
+
+/// 按 Redis 风格的名称（不区分大小写）解析策略。
+impl FromStr for AppendFsync {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match &s.to_lowercase()[..] {
+            "always" => Ok(Self::Always),
+            "everysec" => Ok(Self::EverySec),
+            "no" => Ok(Self::No),
+            _ => Err(format!("unsupported appendfsync policy {}", s)),
+        }
+    }
+}
+
+/// 过载保护配置。
+///
+/// 当正在处理的连接数超过 `threshold` 时，服务器处于过载状态：所有连接上的新命令都会收到
+/// `-BUSY server is overloaded, try again later`，而不是排队等待；空闲超过 `idle_timeout` 的连接会被关闭，
+/// 优先释放空闲连接占用的资源。连接数回落到 `threshold` 以下后，命令恢复正常执行。
+#[derive(Debug, Clone)]
+pub struct OverloadConfig {
+    /// 正在处理的连接数超过该值时视为过载。
+    pub threshold: usize,
+    /// 过载时，空闲超过该时间的连接会被关闭。
+    pub idle_timeout: Duration,
+}
+
+/// 服务器事件的接收端，用于把连接数、命令数和流量等指标导出到监控系统，而不需要解析日志。
+///
+/// 通过 [`ServerConfig::metrics`] 安装。所有方法都有什么都不做的默认实现，只需要实现关心的事件。
+/// 方法在处理连接的任务中同步调用，因此应该很快返回，例如只更新原子计数器；耗时的工作应该交给其他任务。
+pub trait Metrics: fmt::Debug + Send + Sync {
+    /// 接受了来自 `addr` 的连接。
+    fn connection_opened(&self, _addr: SocketAddr) {}
+
+    /// 来自 `addr` 的连接已经关闭。
+    fn connection_closed(&self, _addr: SocketAddr) {}
+
+    /// 收到了一个命令。`name` 是小写的命令名称，例如 `"get"`；事务中的命令在排队时报告。
+    fn command(&self, _name: &str) {}
+
+    /// 从连接中读取了 `n` 字节。每处理完一批请求报告一次，而不是每次读取都报告。
+    fn bytes_read(&self, _n: u64) {}
+
+    /// 向连接写入了 `n` 字节。与 `bytes_read` 一样每批报告一次。
+    fn bytes_written(&self, _n: u64) {}
+}
+
+/// 忽略所有事件的 [`Metrics`]，[`ServerConfig`] 的默认值。
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoopMetrics;
+
+impl Metrics for NoopMetrics {}
+
+/// 验证 `AUTH` 提供的密码，并决定通过验证的连接可以执行哪些命令。
+///
+/// 通过 [`ServerConfig::auth`] 安装。安装之后连接必须先通过验证才能执行 `AUTH`、`PING` 和 `QUIT` 以外的命令。
+pub trait AuthProvider: fmt::Debug + Send + Sync {
+    /// 验证 `password`。通过时返回连接的身份，否则返回 `None`，连接收到 `WRONGPASS` 错误。
+    fn authenticate(&self, password: &str) -> Option<Identity>;
+}
+
+/// 通过验证的连接的身份，由 [`AuthProvider`] 返回。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Identity {
+    /// 用户名。
+    pub name: String,
+    /// 连接可以执行的命令类别。
+    pub permissions: Permissions,
+}
+
+/// 由 [`ServerConfig::requirepass`] 构造的验证器：密码正确的连接可以执行所有命令。
+#[derive(Debug)]
+struct RequirePass(String);
+
+impl AuthProvider for RequirePass {
+    fn authenticate(&self, password: &str) -> Option<Identity> {
+        (password == self.0).then(|| Identity {
+            name: "default".to_string(),
+            permissions: Permissions::all(),
+        })
+    }
+}
+
+/// 服务器关闭后返回的报告。
+///
+/// 由 [`run_reporting`] 返回，让嵌入服务器的程序和测试知道关闭是否干净地完成。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShutdownReport {
+    /// 收到关闭信号时仍在处理、随后被排空的连接数。
+    pub connections_drained: usize,
+    /// 如果排空没有在 [`ServerConfig::shutdown_timeout`] 内完成、服务器放弃等待剩余的连接，则为 `true`。
+    pub forced: bool,
+    /// 从服务器启动到关闭完成的时间。
+    pub uptime: Duration,
 }
 
 /// 每个连接的处理程序。从 `connection` 读取请求并将命令应用到 `db`。
@@ -67,6 +467,8 @@ struct Handler {
     /// 它初始化相关的缓冲区。`Connection` 允许处理程序在“帧”级别操作，
     /// 并将字节级协议解析细节封装在 `Connection` 中。
     connection: Connection,
+    /// 连接可以执行的命令类别。默认允许所有类别，通过验证之后由验证器返回的身份决定。
+    permissions: Permissions,
     /// 监听关闭通知。
     ///
     /// `broadcast::Receiver` 的包装器，与 `Listener` 中的发送器配对。
@@ -76,16 +478,77 @@ struct Handler {
     shutdown: Shutdown,
     /// 不直接使用。相反，当 `Handler` 被丢弃时...？
     _shutdown_complete: mpsc::Sender<()>,
+    /// 服务器负载，用于在过载时拒绝命令和关闭空闲连接。
+    load: Load,
+    /// 服务器配置的验证器。`None` 表示不需要验证。
+    auth: Option<Arc<dyn AuthProvider>>,
+    /// 连接是否已经通过验证。未配置验证器时始终为 `true`。
+    ///
+    /// 未通过验证的连接只能执行 `AUTH` 和 `PING`。
+    authenticated: bool,
+    /// 服务器的运行状态。
+    stats: Stats,
+    /// `MULTI` 开启的事务。`None` 表示连接不在事务中。
+    transaction: Option<Transaction>,
+    /// 客户端发送了 `QUIT`。响应刷新之后关闭连接。
+    quit: bool,
+    /// 连接在服务器连接登记表中的登记项，记录连接的 id 和名称。
+    ///
+    /// 处理程序被丢弃时，连接从登记表中移除。
+    client: ClientRegistration,
+    /// 开启 AOF 时，修改键空间的命令执行之后追加到其中。
+    aof: Option<AofWriter>,
+    /// 接收命令和流量事件。
+    metrics: Arc<dyn Metrics>,
+    /// 已经报告给 `metrics` 的读取和写入字节数。
+    bytes_reported: (u64, u64),
+    /// 执行时间超过阈值的命令记录到其中。
+    slowlog: SlowLog,
+}
+
+/// 连接上正在进行的事务。
+#[derive(Debug, Default)]
+struct Transaction {
+    /// 排队等待 `EXEC` 执行的命令，以及开启 AOF 时命令的原始帧。
+    queued: Vec<(Command, Option<Frame>)>,
+    /// 排队时出现了错误，`EXEC` 将放弃整个事务。
+    aborted: bool,
 }
 
 /// Redis 服务器将接受的最大并发连接数。
 ///
 /// 当达到此限制时，服务器将停止接受连接，直到一个活动连接终止。
 ///
-/// 实际应用程序可能希望使此值可配置，但在此示例中，它是硬编码的。
+/// 这是默认值，可以通过 [`ServerConfig::max_connections`] 修改。
 ///
 /// 此值也设置得非常低，以阻止在生产中使用（你可能认为所有免责声明都表明这不是一个严肃的项目……但我对 mini-http 也有同样的想法）。
-const MAX_CONNECTIONS: usize = 250;
+pub const MAX_CONNECTIONS: usize = 250;
+
+/// 默认的数据库数量，与 Redis 相同。
+///
+/// 可以通过 [`ServerConfig::databases`] 修改。
+pub const DATABASES: usize = 16;
+
+/// 每个频道的广播通道默认能容纳的消息数。
+///
+/// 可以通过 [`ServerConfig::pubsub_capacity`] 修改。
+pub const PUBSUB_CAPACITY: usize = 1024;
+
+/// 默认的慢日志阈值，与 Redis 相同。
+///
+/// 可以通过 [`ServerConfig::slowlog_threshold`] 修改。
+pub const SLOWLOG_THRESHOLD: Duration = Duration::from_millis(10);
+
+/// 慢日志默认最多保留的条目数，与 Redis 相同。
+///
+/// 可以通过 [`ServerConfig::slowlog_max_len`] 修改。
+pub const SLOWLOG_MAX_LEN: usize = 128;
+
+/// 慢日志条目最多保留的参数个数（包括命令名称）。更多的参数被替换为一个说明省略了多少个参数的参数。
+const SLOWLOG_MAX_ARGC: usize = 32;
+
+/// 慢日志条目中每个参数最多保留的字节数。
+const SLOWLOG_MAX_ARGLEN: usize = 128;
 
 /// 运行 mini-redis 服务器。
 ///
@@ -93,19 +556,89 @@ const MAX_CONNECTIONS: usize = 250;
 /// 服务器运行直到 `shutdown` future 完成，此时服务器优雅地关闭。
 ///
 /// `tokio::signal::ctrl_c()` 可以用作 `shutdown` 参数。这将监听 SIGINT 信号。
+///
+/// 如果需要知道关闭的结果，请使用 [`run_reporting`]。
 pub async fn run(listener: TcpListener, shutdown: impl Future) {
+    run_reporting(listener, shutdown).await;
+}
+
+/// 运行 mini-redis 服务器，并在关闭后返回 [`ShutdownReport`]。
+///
+/// 行为与 [`run`] 相同。
+pub async fn run_reporting(listener: TcpListener, shutdown: impl Future) -> ShutdownReport {
+    run_with_config(listener, shutdown, ServerConfig::default()).await
+}
+
+/// 使用给定的 `config` 运行 mini-redis 服务器，并在关闭后返回 [`ShutdownReport`]。
+pub async fn run_with_config(listener: TcpListener, shutdown: impl Future, config: ServerConfig) -> ShutdownReport {
+    let started = Instant::now();
+    let shutdown_timeout = config.shutdown_timeout;
     // 当提供的 `shutdown` future 完成时，我们必须向所有活动连接发送关闭消息。
     // 为此，我们使用广播通道。下面的调用忽略了广播对的接收器，当需要接收器时，
     // 使用发送器上的 subscribe() 方法创建一个。
     let (notify_shutdown, _) = broadcast::channel(1);
     let (shutdown_complete_tx, mut shutdown_complete_rx) = mpsc::channel(1);
+    let db_holder = DbDropGuard::new(config.databases);
+    db_holder.db().reserve(config.preallocate);
+    db_holder.db().set_notify_expired(config.notify_expired);
+    db_holder.db().set_notify_lagged(config.notify_lagged);
+    db_holder.db().set_debug_hooks(config.debug_hooks);
+    db_holder.db().set_sorted_replies(config.sorted_replies);
+    db_holder.db().set_pubsub_capacity(config.pubsub_capacity);
+    db_holder.db().set_keepalive_interval(config.keepalive_interval);
+    db_holder.db().set_maxmemory(config.maxmemory);
+    db_holder.db().set_maxmemory_policy(config.maxmemory_policy);
+    db_holder.db().set_command_time_limit(config.command_time_limit);
+    if let Some(path) = &config.dbfilename {
+        // 文件不存在说明还没有保存过快照，从空的键空间开始。
+        match db_holder.db().load_from(path).await {
+            Ok(keys) => info!(keys, path = %path.display(), "从快照恢复"),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+            Err(err) => error!(cause = %err, path = %path.display(), "无法加载快照"),
+        }
+    }
+    db_holder.db().set_snapshot_path(config.dbfilename);
+    // 在接受连接之前重放 AOF。
+    let (aof, aof_task) = match &config.appendfilename {
+        Some(path) => open_aof(path, config.appendfsync, &db_holder.db()).await.unzip(),
+        None => (None, None),
+    };
+    let active = Arc::new(AtomicUsize::new(0));
+    let limit_connections = Arc::new(Semaphore::new(config.max_connections));
     // 初始化监听器状态
     let mut server = Server {
         listener,
-        db_holder: DbDropGuard::new(),
-        limit_connections: Arc::new(Semaphore::new(MAX_CONNECTIONS)),
+        db_holder,
+        limit_connections: limit_connections.clone(),
         notify_shutdown,
         shutdown_complete_tx,
+        load: Load {
+            active: active.clone(),
+            overload: config.overload,
+        },
+        auth: config.auth.or_else(|| {
+            let requirepass = config.requirepass?;
+            Some(Arc::new(RequirePass(requirepass)) as Arc<dyn AuthProvider>)
+        }),
+        stats: Stats {
+            started,
+            active,
+            max_connections: Arc::new(AtomicUsize::new(config.max_connections)),
+            limit_connections,
+            net_input_bytes: Arc::new(AtomicU64::new(0)),
+            net_output_bytes: Arc::new(AtomicU64::new(0)),
+        },
+        clients: Clients::default(),
+        nodelay: config.nodelay,
+        keepalive: config.keepalive,
+        aof,
+        metrics: config.metrics,
+        slowlog: SlowLog {
+            threshold: config.slowlog_threshold,
+            max_len: config.slowlog_max_len,
+            next_id: Arc::new(AtomicU64::new(0)),
+            entries: Arc::new(Mutex::new(VecDeque::new())),
+        },
     };
     // 并发运行服务器并监听 `shutdown` 信号。
     // 服务器任务运行直到遇到错误，因此在正常情况下，
@@ -143,8 +676,12 @@ pub async fn run(listener: TcpListener, shutdown: impl Future) {
     let Server {
         notify_shutdown,
         shutdown_complete_tx,
+        load,
+        aof,
         ..
     } = server;
+    // 记录需要排空的连接数。此后不会再接受新连接。
+    let connections_drained = load.active.load(Ordering::SeqCst);
     // 当 `notify_shutdown` 被丢弃时，所有 `subscribe` 的任务将
     // 收到关闭信号并可以退出
     drop(notify_shutdown);
@@ -154,7 +691,57 @@ pub async fn run(listener: TcpListener, shutdown: impl Future) {
     // 等待所有活动连接完成处理。由于上面监听器持有的 `Sender` 句柄已被丢弃，
     // 唯一剩下的 `Sender` 实例由连接处理程序任务持有。
     // 当这些任务丢弃时，`mpsc` 通道将关闭，`recv()` 将返回 `None`。
-    let _ = shutdown_complete_rx.recv().await;
+    //
+    // 配置了 `shutdown_timeout` 时最多等待这么久，卡住的连接（例如一直不读取响应的客户端）不会让关闭永远无法完成。
+    let forced = match shutdown_timeout {
+        Some(timeout) => tokio::select! {
+            _ = shutdown_complete_rx.recv() => false,
+            _ = time::sleep(timeout) => {
+                warn!(active = load.active.load(Ordering::SeqCst), "等待连接结束超时，强制关闭");
+                true
+            }
+        },
+        None => {
+            let _ = shutdown_complete_rx.recv().await;
+            false
+        }
+    };
+
+    // 所有处理程序都已经退出，丢弃最后一个 AOF 写入端，等待后台任务写完剩余的命令。
+    // 强制关闭时仍有处理程序持有写入端，后台任务不会退出，因此不等待它。
+    drop(aof);
+    if let Some(task) = aof_task.filter(|_| !forced) {
+        let _ = task.await;
+    }
+
+    let report = ShutdownReport {
+        connections_drained,
+        forced,
+        uptime: started.elapsed(),
+    };
+    info!(?report, "关闭完成");
+    report
+}
+
+/// 重放 `path` 中的 AOF，然后打开它以追加之后的命令。
+///
+/// 重放失败时不打开 AOF，以免在不完整或损坏的日志后面继续追加；服务器仍然启动，但不会记录命令。
+async fn open_aof(path: &Path, fsync: AppendFsync, db: &Db) -> Option<(AofWriter, JoinHandle<()>)> {
+    match aof::replay(path, db).await {
+        Ok(commands) => info!(commands, path = %path.display(), "从 AOF 恢复"),
+        Err(err) => {
+            error!(cause = %err, path = %path.display(), "无法重放 AOF，不再记录命令");
+            return None;
+        }
+    }
+
+    match AofWriter::open(path, fsync).await {
+        Ok(aof) => Some(aof),
+        Err(err) => {
+            error!(cause = %err, path = %path.display(), "无法打开 AOF");
+            None
+        }
+    }
 }
 
 impl Server {
@@ -182,23 +769,58 @@ impl Server {
             // 接受一个新套接字。这将尝试执行错误处理。
             // `accept` 方法内部尝试恢复错误，因此此处的错误是不可恢复的。
             let socket = self.accept().await?;
+            if let Err(err) = configure_socket(&socket, self.nodelay, self.keepalive) {
+                debug!(cause = %err, "设置套接字选项失败");
+                continue;
+            }
+            // 初始化连接状态。这会分配读/写缓冲区以执行 Redis 协议帧解析。
+            let connection = Connection::new(socket);
+            // 记录对等方的地址，连接出错时的日志可以据此找到是哪个客户端。
+            //
+            // 对等方可能在被接受之后立即断开，此时没有地址，也没有需要处理的请求。
+            let peer_addr = match connection.peer_addr() {
+                Ok(peer_addr) => peer_addr,
+                Err(err) => {
+                    debug!(cause = %err, "对等方已断开");
+                    continue;
+                }
+            };
+            self.metrics.connection_opened(peer_addr);
             // 创建必要的每个连接处理程序状态。
             let mut handler = Handler::new(
                 // 获取共享数据库的句柄。
                 self.db_holder.db(),
-                // 初始化连接状态。这会分配读/写缓冲区以执行 Redis 协议帧解析。
-                Connection::new(socket),
+                connection,
                 // 接收关闭通知。
                 Shutdown::new(self.notify_shutdown.subscribe()),
                 // 一旦所有克隆被丢弃，通知接收器。
                 self.shutdown_complete_tx.clone(),
+                // 共享服务器负载。
+                self.load.clone(),
+                // 验证连接的密码。
+                self.auth.clone(),
+                // 共享服务器运行状态。
+                self.stats.clone(),
+                // 分配连接 id 并登记连接。
+                self.clients.register(peer_addr),
+                // 记录修改键空间的命令。
+                self.aof.clone(),
+                // 报告命令和流量。
+                self.metrics.clone(),
+                // 记录慢命令。
+                self.slowlog.clone(),
             );
             // 生成一个新任务来处理连接。Tokio 任务类似于异步绿色线程，并发执行。
+            let active = self.load.active.clone();
+            active.fetch_add(1, Ordering::SeqCst);
             tokio::spawn(async move {
                 // 处理连接。如果遇到错误，记录它。
                 if let Err(err) = handler.run().await {
-                    error!(cause = ?err, "连接错误");
+                    error!(%peer_addr, cause = ?err, "连接错误");
                 }
+                handler.report_bytes();
+                handler.metrics.connection_closed(peer_addr);
+                active.fetch_sub(1, Ordering::SeqCst);
                 // 将许可移入任务并在完成后丢弃它。这将许可返回给信号量。
                 drop(permit);
             });
@@ -234,26 +856,66 @@ impl Server {
 }
 
 impl Handler {
-    /// 处理单个连接。
-    ///
-    /// 从套接字读取请求帧并处理。响应写回到套接字。
-    ///
-    /// 目前，未实现流水线。流水线是每个连接并发处理多个请求而不交错帧的能力。
-    /// 有关更多详细信息，请参阅：
-    /// https://redis.io/topics/pipelining
-    ///
-    /// 当收到关闭信号时，连接会处理直到达到安全状态，此时它会终止。
-
-    fn new(db: Db, connection: Connection, shutdown: Shutdown, _shutdown_complete: mpsc::Sender<()>) -> Self {
+    /// 创建一个新的连接处理程序。
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        db: Db,
+        connection: Connection,
+        shutdown: Shutdown,
+        _shutdown_complete: mpsc::Sender<()>,
+        load: Load,
+        auth: Option<Arc<dyn AuthProvider>>,
+        stats: Stats,
+        client: ClientRegistration,
+        aof: Option<AofWriter>,
+        metrics: Arc<dyn Metrics>,
+        slowlog: SlowLog,
+    ) -> Self {
         Self {
             db,
             connection,
+            permissions: Permissions::all(),
             shutdown,
             _shutdown_complete,
+            load,
+            authenticated: auth.is_none(),
+            auth,
+            stats,
+            transaction: None,
+            quit: false,
+            client,
+            aof,
+            metrics,
+            bytes_reported: (0, 0),
+            slowlog,
         }
     }
 
-    #[instrument(skip(self))]
+    /// 把上次报告之后读取和写入的字节数报告给 `metrics`，并累加到 `INFO` 报告的总数中。
+    fn report_bytes(&mut self) {
+        let (read, written) = (self.connection.bytes_read(), self.connection.bytes_written());
+        let (reported_read, reported_written) = self.bytes_reported;
+        if read > reported_read {
+            self.metrics.bytes_read(read - reported_read);
+            self.stats.net_input_bytes.fetch_add(read - reported_read, Ordering::Relaxed);
+        }
+        if written > reported_written {
+            self.metrics.bytes_written(written - reported_written);
+            self.stats.net_output_bytes.fetch_add(written - reported_written, Ordering::Relaxed);
+        }
+        self.bytes_reported = (read, written);
+    }
+
+    /// 处理单个连接。
+    ///
+    /// 从套接字读取请求帧并处理。响应写回到套接字。
+    ///
+    /// 支持流水线：客户端可以不等待响应就连续发送多个请求。一次读取之后，读取缓冲区中所有完整的帧
+    /// 会按顺序应用，它们的响应按请求顺序写入并在最后统一刷新。有关更多详细信息，请参阅：
+    /// https://redis.io/topics/pipelining
+    ///
+    /// 当收到关闭信号时，连接会处理直到达到安全状态，此时它会终止。
+    #[instrument(skip(self), fields(peer_addr = %self.client.addr))]
     async fn run(&mut self) -> crate::Result<()> {
         // 只要未收到关闭信号，尝试读取新请求帧。
         while !self.shutdown.is_shutdown() {
@@ -265,6 +927,11 @@ impl Handler {
                     // 这将导致任务终止。
                     return Ok(());
                 }
+                _ = self.load.idle_while_overloaded() => {
+                    // 服务器过载且连接空闲，关闭它以释放资源。
+                    info!("服务器过载，关闭空闲连接");
+                    return Ok(());
+                }
             };
             // 如果 `read_frame()` 返回 `None`，则对等方关闭了套接字。
             // 没有进一步的工作要做，任务可以终止。
@@ -272,8 +939,62 @@ impl Handler {
                 Some(frame) => frame,
                 None => return Ok(()),
             };
+            // 应用这一帧以及已经缓冲的后续帧。即使中途出错，也要先把已经排队的响应刷新给客户端。
+            let res = self.apply_pipeline(frame).await;
+            self.connection.flush().await?;
+            self.report_bytes();
+            res?;
+            // 客户端要求关闭连接。返回会丢弃套接字。
+            if self.quit {
+                return Ok(());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 应用 `frame`，然后应用读取缓冲区中已经完整到达的所有帧。
+    ///
+    /// 在此期间响应只写入缓冲区而不刷新，避免每个请求一次系统调用。这里不会再从套接字读取，
+    /// 因此一批的大小受已缓冲数据的限制，处理完一批后 `run` 会重新检查关闭信号。
+    async fn apply_pipeline(&mut self, frame: Frame) -> crate::Result<()> {
+        self.connection.defer_flush();
+
+        let mut next = Some(frame);
+        while let Some(frame) = next {
+            // 服务器过载时不执行命令，直接让客户端稍后重试。
+            if self.load.is_overloaded() {
+                let response = Frame::Error("BUSY server is overloaded, try again later".to_string());
+                debug!(?response);
+                self.connection.write_frame(&response).await?;
+
+                next = self.connection.read_buffered_frame()?;
+                continue;
+            }
+
+            // 开启 AOF 时保留原始帧，命令执行之后追加到日志。
+            let logged = self.aof.is_some().then(|| frame.clone());
+            // 开启慢日志时同样保留原始帧，命令执行得慢时记录它的参数。
+            let args = self.slowlog.is_enabled().then(|| frame.clone());
             // 将 Redis 帧转换为命令结构。如果帧不是有效的 Redis 命令或是不支持的命令，则返回错误。
-            let cmd = Command::try_from(frame)?;
+            let cmd = match Command::try_from(frame) {
+                Ok(cmd) => cmd,
+                // 事务中的解析错误不关闭连接，而是让整个事务在 `EXEC` 时被放弃，与 Redis 一致。
+                Err(err) if self.transaction.is_some() => {
+                    self.abort_transaction(format!("ERR {}", err)).await?;
+                    next = self.connection.read_buffered_frame()?;
+                    continue;
+                }
+                // 参数个数不对、选项或者参数值无效不表示协议被破坏，同样只回复错误，不关闭连接。
+                Err(err) if err.is::<WrongArity>() || err.is::<SyntaxError>() || err.is::<InvalidArgument>() => {
+                    let response = Frame::Error(format!("ERR {}", err));
+                    debug!(?response);
+                    self.connection.write_frame(&response).await?;
+                    next = self.connection.read_buffered_frame()?;
+                    continue;
+                }
+                Err(err) => return Err(err),
+            };
             // 记录 `cmd` 对象。这里的语法是 `tracing` crate 提供的简写。
             // 它可以被认为类似于：
             //
@@ -283,13 +1004,506 @@ impl Handler {
             //
             // `tracing` 提供结构化日志记录，因此信息作为键值对“记录”。
             debug!(?cmd);
+            self.metrics.command(cmd.get_name());
+            // 连接未通过验证或者没有权限时不执行命令。在事务中同时让事务失败。
+            if let Some(error) = self.access_error(&cmd) {
+                self.abort_transaction(error).await?;
+                next = self.connection.read_buffered_frame()?;
+                continue;
+            }
+            match cmd {
+                Command::Multi(_) => self.multi().await?,
+                Command::Exec(_) => self.exec().await?,
+                Command::Discard(_) => self.discard().await?,
+                // `RESET` 在事务中也立即执行，它会丢弃事务。
+                Command::Reset(_) => self.reset().await?,
+                // `QUIT` 同样立即执行。
+                Command::Quit(_) => self.quit().await?,
+                // 事务中的命令只排队，等待 `EXEC` 执行。
+                cmd if self.transaction.is_some() => self.queue(cmd, logged).await?,
+                cmd => {
+                    // 订阅会接管连接并持续推送消息，因此在此之前刷新已排队的响应，并恢复每次写入后立即刷新。
+                    // 阻塞命令可能等待很久，同样需要先把之前的响应发给客户端。
+                    //
+                    // 这些命令可能无限期地运行，因此也不能持有事务许可，否则事务永远无法执行。
+                    let takes_over = matches!(
+                        cmd,
+                        Command::Subscribe(_)
+                            | Command::PSubscribe(_)
+                            | Command::BLPop(_)
+                            | Command::BRPop(_)
+                            | Command::WaitAof(_)
+                    );
+                    if takes_over {
+                        self.connection.flush().await?;
+                        self.apply(cmd, logged).await?;
+                    } else {
+                        let _permit = self.db.command_permit().await;
+                        let start = Instant::now();
+                        self.apply(cmd, logged).await?;
+                        if let Some(frame) = args {
+                            self.slowlog.record(start.elapsed(), frame, &self.client);
+                        }
+                    }
+                }
+            }
+
+            // 连接即将关闭，`QUIT` 之后的命令不再处理。
+            if self.quit {
+                break;
+            }
+            next = self.connection.read_buffered_frame()?;
+        }
+
+        Ok(())
+    }
+
+    /// 执行单个命令。`frame` 是开启 AOF 时命令的原始帧，修改键空间的命令执行之后把它追加到日志。
+    async fn apply(&mut self, cmd: Command, frame: Option<Frame>) -> crate::Result<()> {
+        match cmd {
+            // `AUTH` 修改的是连接的状态，因此由处理程序执行。
+            Command::Auth(cmd) => {
+                if let Some(identity) = cmd.apply(self.auth.as_deref(), &mut self.connection).await? {
+                    self.authenticated = true;
+                    self.permissions = identity.permissions;
+                }
+            }
+            // `INFO` 需要服务器的运行状态。
+            Command::Info(cmd) => cmd.apply(&self.db, &self.stats, &mut self.connection).await?,
+            // `CONFIG` 可以修改最大连接数，它属于服务器。
+            Command::Config(cmd) => cmd.apply(&self.db, &self.stats, &mut self.connection).await?,
+            // 慢日志属于服务器。
+            Command::SlowLog(cmd) => cmd.apply(&self.slowlog, &mut self.connection).await?,
+            // AOF 同样属于服务器。
+            Command::WaitAof(cmd) => cmd.apply(self.aof.as_ref(), &mut self.connection).await?,
+            // 选择的数据库属于连接。
+            Command::Select(cmd) => {
+                if let Some(db) = cmd.apply(&self.db, &mut self.connection).await? {
+                    self.db = db;
+                }
+            }
+            // `CLIENT` 读取和修改连接的状态。
+            Command::Client(cmd) => cmd.apply(&self.client, &mut self.connection).await?,
+            // 订阅接管连接，直到连接关闭，或者客户端发送 `RESET` 或取消所有订阅回到普通模式。
+            Command::Subscribe(cmd) => {
+                let exit = cmd.apply(&self.db, &mut self.connection, &mut self.shutdown).await?;
+                self.leave_subscribe(exit).await?;
+            }
+            Command::PSubscribe(cmd) => {
+                let exit = cmd.apply(&self.db, &mut self.connection, &mut self.shutdown).await?;
+                self.leave_subscribe(exit).await?;
+            }
+            // 阻塞弹出自己在顺序许可下记录弹出的元素，等待期间不持有许可。
+            Command::BLPop(cmd) => {
+                let aof = self.aof.as_ref().filter(|_| frame.is_some());
+                cmd.apply(&self.db, aof, &mut self.connection, &mut self.shutdown).await?
+            }
+            Command::BRPop(cmd) => {
+                let aof = self.aof.as_ref().filter(|_| frame.is_some());
+                cmd.apply(&self.db, aof, &mut self.connection, &mut self.shutdown).await?
+            }
+            // 取消所有订阅之后连接回到普通模式，客户端可能还会发送取消订阅的命令。
+            Command::Unsubscribe(cmd) => cmd.apply_unsubscribed(&mut self.connection).await?,
+            Command::PUnsubscribe(cmd) => cmd.apply_unsubscribed(&mut self.connection).await?,
             // 执行应用命令所需的工作。这可能会导致数据库状态发生变化。
             //
             // 连接被传递到应用函数中，允许命令直接向连接写入响应帧。
             // 在发布/订阅的情况下，可能会向对等方发送多个帧。
-            cmd.apply(&self.db, &mut self.connection, &mut self.shutdown).await?;
+            cmd => {
+                let logged = frame.filter(|_| cmd.is_write());
+                let aof = self.aof.as_ref().filter(|_| logged.is_some());
+                let _order = match aof {
+                    Some(aof) => Some(aof.order().await),
+                    None => None,
+                };
+                // 取得许可之后才换算过期时间，记录的时间与命令执行时计算的过期时间一致。
+                let logged = logged.map(|frame| cmd.absolute_expiry().unwrap_or(frame));
+                cmd.apply(&self.db, &mut self.connection, &mut self.shutdown).await?;
+                if let (Some(aof), Some(frame)) = (aof, logged) {
+                    aof.append(self.db.index(), frame);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 开启事务。
+    async fn multi(&mut self) -> crate::Result<()> {
+        let response = if self.transaction.is_some() {
+            Frame::Error("ERR MULTI calls can not be nested".to_string())
+        } else {
+            self.transaction = Some(Transaction::default());
+            Frame::Simple("OK".to_string())
+        };
+
+        debug!(?response);
+        self.connection.write_frame(&response).await?;
+        Ok(())
+    }
+
+    /// 将 `cmd` 加入事务队列。
+    ///
+    /// 会接管连接或者阻塞的命令不能在事务中执行；未知命令在排队时就回复错误。两种情况都会使事务被放弃。
+    async fn queue(&mut self, cmd: Command, frame: Option<Frame>) -> crate::Result<()> {
+        if matches!(
+            cmd,
+            Command::Subscribe(_)
+                | Command::Unsubscribe(_)
+                | Command::PSubscribe(_)
+                | Command::PUnsubscribe(_)
+                | Command::BLPop(_)
+                | Command::BRPop(_)
+                | Command::WaitAof(_)
+        ) {
+            return self.abort_transaction("ERR Command not allowed inside a transaction".to_string()).await;
+        }
+        if !cmd.is_supported() {
+            // 由命令自己回复错误。
+            self.apply(cmd, None).await?;
+            if let Some(transaction) = &mut self.transaction {
+                transaction.aborted = true;
+            }
+            return Ok(());
+        }
+
+        if let Some(transaction) = &mut self.transaction {
+            transaction.queued.push((cmd, frame));
+        }
+
+        let response = Frame::Simple("QUEUED".to_string());
+        debug!(?response);
+        self.connection.write_frame(&response).await?;
+        Ok(())
+    }
+
+    /// 检查连接是否可以执行 `cmd`，不可以时返回应该回复的错误。
+    ///
+    /// 管道中的命令和订阅模式下 RESP3 客户端发送的命令都经过这里。未通过验证的连接只能执行 `AUTH`、`PING`
+    /// 和 `QUIT`；通过验证之后，命令的所有类别都必须被连接的权限允许。只改变连接自身状态的命令不检查权限，
+    /// 未知命令同样不检查，客户端得到的是“未知命令”而不是 `NOPERM`。
+    fn access_error(&self, cmd: &Command) -> Option<String> {
+        if matches!(cmd, Command::Auth(_) | Command::Ping(_) | Command::Quit(_)) {
+            return None;
+        }
+        if !self.authenticated {
+            return Some("NOAUTH Authentication required".to_string());
+        }
+        let exempt = matches!(
+            cmd,
+            Command::Select(_) | Command::Hello(_) | Command::Reset(_) | Command::Client(_) | Command::Unknown(_)
+        );
+        if exempt || self.permissions.allows(&cmd.categories()) {
+            return None;
+        }
+        Some(format!("NOPERM this user has no permissions to run the '{}' command", cmd.get_name()))
+    }
+
+    /// 回复 `error`。连接在事务中时同时标记事务在 `EXEC` 时被放弃。
+    async fn abort_transaction(&mut self, error: String) -> crate::Result<()> {
+        if let Some(transaction) = &mut self.transaction {
+            transaction.aborted = true;
+        }
+
+        let response = Frame::Error(error);
+        debug!(?response);
+        self.connection.write_frame(&response).await?;
+        Ok(())
+    }
+
+    /// 执行事务中排队的所有命令。
+    ///
+    /// 执行期间持有事务许可，其他连接的命令要等到整个事务执行完毕。每个命令照常把响应写入连接，
+    /// 事先写入的数组头部把它们组合成一个数组。
+    async fn exec(&mut self) -> crate::Result<()> {
+        let response = match self.transaction.take() {
+            None => Frame::Error("ERR EXEC without MULTI".to_string()),
+            Some(transaction) if transaction.aborted => {
+                Frame::Error("EXECABORT Transaction discarded because of previous errors.".to_string())
+            }
+            Some(transaction) => {
+                let _permit = self.db.transaction_permit().await;
+                self.connection.write_array_header(transaction.queued.len()).await?;
+                for (cmd, frame) in transaction.queued {
+                    self.apply(cmd, frame).await?;
+                }
+                return Ok(());
+            }
+        };
+
+        debug!(?response);
+        self.connection.write_frame(&response).await?;
+        Ok(())
+    }
+
+    /// 根据订阅结束的原因完成客户端的请求。订阅被 RESP3 客户端的其他命令中断时，执行该命令并继续订阅。
+    async fn leave_subscribe(&mut self, mut exit: SubscribeExit) -> crate::Result<()> {
+        loop {
+            match exit {
+                SubscribeExit::Closed => return Ok(()),
+                SubscribeExit::Reset => return self.reset().await,
+                SubscribeExit::Unsubscribed => return Ok(()),
+                SubscribeExit::Quit => return self.quit().await,
+                // RESP3 客户端在订阅模式下发送的其他命令，执行之后继续订阅。
+                SubscribeExit::Command(cmd, frame, subscribed) => {
+                    self.metrics.command(cmd.get_name());
+                    if let Some(error) = self.access_error(&cmd) {
+                        let response = Frame::Error(error);
+                        debug!(?response);
+                        self.connection.write_frame(&response).await?;
+                    } else {
+                        let _permit = self.db.command_permit().await;
+                        // `apply` 执行订阅时会调用这里，因此需要装箱才能递归。订阅命令本身由订阅循环处理，
+                        // 不会出现在这里。
+                        Box::pin(self.apply(*cmd, Some(frame))).await?;
+                    }
+                    exit = subscribed.resume(&self.db, &mut self.connection, &mut self.shutdown).await?;
+                }
+            }
         }
+    }
+
+    /// 回复 `OK` 并标记连接在响应刷新之后关闭。
+    async fn quit(&mut self) -> crate::Result<()> {
+        self.quit = true;
+
+        let response = Frame::Simple("OK".to_string());
+        debug!(?response);
+        self.connection.write_frame(&response).await?;
+        Ok(())
+    }
 
+    /// 将连接恢复到初始状态并回复 `RESET`。
+    ///
+    /// 订阅模式中收到的 `RESET` 由订阅循环处理，订阅结束后同样调用这里。
+    async fn reset(&mut self) -> crate::Result<()> {
+        self.transaction = None;
+        self.db = self.db.select(0).expect("database 0 always exists");
+
+        let response = Frame::Simple("RESET".to_string());
+        debug!(?response);
+        self.connection.write_frame(&response).await?;
         Ok(())
     }
+
+    /// 丢弃事务中排队的所有命令。
+    async fn discard(&mut self) -> crate::Result<()> {
+        let response = match self.transaction.take() {
+            Some(_) => Frame::Simple("OK".to_string()),
+            None => Frame::Error("ERR DISCARD without MULTI".to_string()),
+        };
+
+        debug!(?response);
+        self.connection.write_frame(&response).await?;
+        Ok(())
+    }
+}
+
+impl Stats {
+    /// 服务器已经运行的时间。
+    pub(crate) fn uptime(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    /// 当前连接的客户端数。
+    pub(crate) fn connected_clients(&self) -> usize {
+        self.active.load(Ordering::SeqCst)
+    }
+
+    /// 服务器将接受的最大并发连接数。
+    pub(crate) fn max_connections(&self) -> usize {
+        self.max_connections.load(Ordering::SeqCst)
+    }
+
+    /// 修改最大并发连接数。
+    ///
+    /// 调大时立即增加许可。调小时已经建立的连接不受影响：后台任务等待多出的许可被归还并把它们丢弃，
+    /// 在此之前监听器不接受新连接。
+    pub(crate) fn set_max_connections(&self, max_connections: usize) {
+        let previous = self.max_connections.swap(max_connections, Ordering::SeqCst);
+        if max_connections > previous {
+            self.limit_connections.add_permits(max_connections - previous);
+        } else if max_connections < previous {
+            let excess = u32::try_from(previous - max_connections).unwrap_or(u32::MAX);
+            let limit_connections = self.limit_connections.clone();
+            tokio::spawn(async move {
+                // 信号量从不关闭。
+                if let Ok(permits) = limit_connections.acquire_many_owned(excess).await {
+                    permits.forget();
+                }
+            });
+        }
+    }
+
+    /// 所有连接读取的总字节数。连接在每批请求处理完之后累加，因此不包括正在处理的这一批。
+    pub(crate) fn net_input_bytes(&self) -> u64 {
+        self.net_input_bytes.load(Ordering::Relaxed)
+    }
+
+    /// 所有连接写入的总字节数，与 [`Stats::net_input_bytes`] 一样在每批请求处理完之后累加。
+    pub(crate) fn net_output_bytes(&self) -> u64 {
+        self.net_output_bytes.load(Ordering::Relaxed)
+    }
+}
+
+impl Clients {
+    /// 为对等方地址为 `addr` 的新连接分配 id，并将它加入登记表。
+    fn register(&self, addr: SocketAddr) -> ClientRegistration {
+        let id = self.last_id.fetch_add(1, Ordering::SeqCst) + 1;
+        self.connections
+            .lock()
+            .unwrap()
+            .insert(id, ClientInfo { addr, name: None });
+
+        ClientRegistration {
+            id,
+            addr,
+            clients: self.clone(),
+        }
+    }
+}
+
+impl ClientRegistration {
+    /// 连接的 id。
+    pub(crate) fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// 连接的名称。
+    pub(crate) fn name(&self) -> Option<String> {
+        self.clients.connections.lock().unwrap()[&self.id].name.clone()
+    }
+
+    /// 设置连接的名称。`None` 清除名称。
+    pub(crate) fn set_name(&self, name: Option<String>) {
+        if let Some(info) = self.clients.connections.lock().unwrap().get_mut(&self.id) {
+            info.name = name;
+        }
+    }
+
+    /// 以 `CLIENT LIST` 的格式描述所有活动连接，每行一个连接，按 id 排序。
+    pub(crate) fn list(&self) -> String {
+        let connections = self.clients.connections.lock().unwrap();
+        let mut ids: Vec<_> = connections.keys().copied().collect();
+        ids.sort_unstable();
+
+        let mut list = String::new();
+        for id in ids {
+            let info = &connections[&id];
+            let name = info.name.as_deref().unwrap_or_default();
+            list.push_str(&format!("id={} addr={} name={}\n", id, info.addr, name));
+        }
+        list
+    }
+}
+
+impl Drop for ClientRegistration {
+    fn drop(&mut self) {
+        self.clients.connections.lock().unwrap().remove(&self.id);
+    }
+}
+
+impl SlowLog {
+    /// 慢日志是否开启。
+    fn is_enabled(&self) -> bool {
+        self.threshold.is_some()
+    }
+
+    /// 如果执行时间 `duration` 达到阈值，把命令 `frame` 记录下来。`client` 是执行命令的连接。
+    fn record(&self, duration: Duration, frame: Frame, client: &ClientRegistration) {
+        match self.threshold {
+            Some(threshold) if duration >= threshold => {}
+            _ => return,
+        }
+
+        let args = match frame {
+            Frame::Array(frames) => {
+                let argc = frames.len();
+                let mut args: Vec<_> = frames
+                    .into_iter()
+                    .take(SLOWLOG_MAX_ARGC)
+                    .map(|frame| {
+                        let arg = match frame {
+                            Frame::Bulk(arg) => arg,
+                            frame => Bytes::from(frame.to_string()),
+                        };
+                        if arg.len() > SLOWLOG_MAX_ARGLEN {
+                            let more = arg.len() - SLOWLOG_MAX_ARGLEN;
+                            let mut truncated = arg[..SLOWLOG_MAX_ARGLEN].to_vec();
+                            truncated.extend_from_slice(format!("... ({} more bytes)", more).as_bytes());
+                            Bytes::from(truncated)
+                        } else {
+                            arg
+                        }
+                    })
+                    .collect();
+                // 与 Redis 一致，最后一个位置用来说明省略了多少个参数。
+                if argc > SLOWLOG_MAX_ARGC {
+                    let more = argc - SLOWLOG_MAX_ARGC + 1;
+                    args[SLOWLOG_MAX_ARGC - 1] = Bytes::from(format!("... ({} more arguments)", more));
+                }
+                args
+            }
+            frame => vec![Bytes::from(frame.to_string())],
+        };
+        let timestamp = SystemTime::now()
+            .checked_sub(duration)
+            .and_then(|start| start.duration_since(UNIX_EPOCH).ok())
+            .map_or(0, |since| since.as_secs());
+
+        let entry = SlowLogEntry {
+            id: self.next_id.fetch_add(1, Ordering::SeqCst),
+            timestamp,
+            duration,
+            args,
+            addr: client.addr,
+            name: client.name(),
+        };
+        let mut entries = self.entries.lock().unwrap();
+        entries.push_front(entry);
+        entries.truncate(self.max_len);
+    }
+
+    /// 返回最新的 `count` 个条目，最新的在前面。`None` 返回所有条目。
+    pub(crate) fn get(&self, count: Option<usize>) -> Vec<SlowLogEntry> {
+        let entries = self.entries.lock().unwrap();
+        entries.iter().take(count.unwrap_or(usize::MAX)).cloned().collect()
+    }
+
+    /// 当前的条目数。
+    pub(crate) fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    /// 清空慢日志。
+    pub(crate) fn reset(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+}
+
+impl Load {
+    /// 如果启用了过载保护且正在处理的连接数超过阈值，则返回 `true`。
+    fn is_overloaded(&self) -> bool {
+        match &self.overload {
+            Some(overload) => self.active.load(Ordering::SeqCst) > overload.threshold,
+            None => false,
+        }
+    }
+
+    /// 在服务器过载时，连接空闲超过 `idle_timeout` 后完成。
+    ///
+    /// 未启用过载保护时永远不会完成。每次调用重新计时，因此处理程序每读取一帧都会重置空闲时间。
+    async fn idle_while_overloaded(&self) {
+        let idle_timeout = match &self.overload {
+            Some(overload) => overload.idle_timeout,
+            None => return std::future::pending().await,
+        };
+
+        loop {
+            time::sleep(idle_timeout).await;
+            if self.is_overloaded() {
+                return;
+            }
+        }
+    }
 }
@@ -22,6 +22,29 @@ async fn pool_key_value_get_set() {
     assert_eq!(b"world", &value[..])
 }
 
+/// 通过同一个缓冲客户端删除键和发布消息。
+#[tokio::test]
+async fn buffered_del_publish() {
+    let (addr, _) = start_server().await;
+
+    let client = Client::connect(addr).await.unwrap();
+    let mut client = BufferedClient::buffer(client);
+
+    client.set("hello", "world".into()).await.unwrap();
+    client.del(vec!["hello".into()]).await.unwrap();
+    assert!(client.get("hello").await.unwrap().is_none());
+
+    // 订阅频道，然后通过缓冲客户端发布
+    let subscriber = Client::connect(addr).await.unwrap();
+    let mut subscriber = subscriber.subscribe(vec!["news".into()]).await.unwrap();
+
+    assert_eq!(1, client.publish("news", "hi".into()).await.unwrap());
+
+    let message = subscriber.next_message().await.unwrap().unwrap();
+    assert_eq!("news", &message.channel);
+    assert_eq!(b"hi", &message.content[..]);
+}
+
 /// 启动服务器
 async fn start_server() -> (SocketAddr, JoinHandle<()>) {
     let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
@@ -1,6 +1,17 @@
-use mini_redis::{clients::Client, server};
+use mini_redis::{
+    clients::{Client, ConnectOptions},
+    cmd::{Category, Expiry, Permissions},
+    server::{self, AppendFsync, AuthProvider, Identity, ServerConfig},
+    Connection, Frame,
+};
+
+use bytes::Bytes;
+use std::io::{Error, ErrorKind};
 use std::net::SocketAddr;
-use tokio::net::TcpListener;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::net::{TcpListener, TcpSocket};
+use tokio::sync::oneshot;
 use tokio::task::JoinHandle;
 
 /// 一个没有提供消息的 PING PONG 测试。
@@ -25,6 +36,22 @@ async fn ping_pong_with_message() {
     assert_eq!("你好世界".as_bytes(), &pong[..]);
 }
 
+/// 用 `raw_command` 发送的 PING 与 `ping` 得到相同的回复。
+#[tokio::test]
+async fn raw_command_ping() {
+    let (addr, _) = start_server().await;
+    let mut client = Client::connect(addr).await.unwrap();
+
+    let pong = client.ping(None).await.unwrap();
+    let response = client.raw_command(&[b"PING"]).await.unwrap();
+    assert_eq!(Frame::Simple(String::from_utf8(pong.to_vec()).unwrap()), response);
+
+    let response = client.raw_command(&[b"PING", b"hello"]).await.unwrap();
+    assert_eq!(Frame::Bulk("hello".into()), response);
+
+    assert!(client.raw_command(&[b"NOSUCHCOMMAND"]).await.is_err());
+}
+
 /// 一个基本的 "hello world" 风格的测试。在后台任务中启动一个服务器实例。
 /// 然后建立一个客户端实例并向服务器发送 set 和 get 命令。
 /// 然后评估响应。
@@ -96,6 +123,2155 @@ async fn unsubscribes_from_channels() {
     assert_eq!(subscriber.get_subscribed().len(), 0);
 }
 
+/// 流水线中的命令按顺序执行，每个命令的响应都可以单独检查。
+#[tokio::test]
+async fn pipeline_get_set_del() {
+    let (addr, _) = start_server().await;
+    let mut client = Client::connect(addr).await.unwrap();
+
+    let mut pipeline = client.pipeline();
+    pipeline
+        .get("hello")
+        .set("hello", "world".into())
+        .get("hello")
+        .del(vec!["hello".into()])
+        .get("hello");
+    assert_eq!(5, pipeline.len());
+
+    let responses = pipeline.execute().await.unwrap();
+    let responses: Vec<Frame> = responses.into_iter().map(Result::unwrap).collect();
+    assert_eq!(
+        vec![
+            Frame::Null,
+            Frame::Simple("OK".into()),
+            Frame::Bulk("world".into()),
+            Frame::Simple("OK".into()),
+            Frame::Null,
+        ],
+        responses
+    );
+
+    // 客户端在流水线之后仍然可用
+    assert!(client.get("hello").await.unwrap().is_none());
+}
+
+/// 使用自定义读取缓冲区大小的客户端可以传输远大于缓冲区的值。
+#[tokio::test]
+async fn connect_with_read_buffer_size() {
+    let (addr, _) = start_server().await;
+
+    let options = ConnectOptions::new().read_buffer_size(16);
+    let mut client = Client::connect_with(addr, options).await.unwrap();
+
+    let value = Bytes::from(vec![b'x'; 64 * 1024]);
+    client.set("big", value.clone()).await.unwrap();
+
+    let received = client.get("big").await.unwrap().unwrap();
+    assert_eq!(value, received);
+}
+
+/// 测试取消订阅一个已不在订阅列表中的频道时，订阅者不会出错，并以服务器的确认为准更新频道列表。
+#[tokio::test]
+async fn unsubscribe_tolerates_unknown_channel() {
+    let (addr, _) = start_server().await;
+
+    let client = Client::connect(addr).await.unwrap();
+    let mut subscriber = client.subscribe(vec!["hello".into(), "world".into()]).await.unwrap();
+
+    subscriber.unsubscribe(&["hello".into()]).await.unwrap();
+    // 服务器会再次确认已移除的 `hello`，以及从未订阅过的 `foo`。
+    subscriber.unsubscribe(&["hello".into(), "foo".into()]).await.unwrap();
+    assert_eq!(subscriber.get_subscribed(), &["world".to_string()]);
+
+    // 订阅者仍然可用
+    subscriber.unsubscribe(&[]).await.unwrap();
+    assert!(subscriber.get_subscribed().is_empty());
+}
+
+/// `RENAMENX` 不会覆盖已存在的键；重命名到空闲的键名时生存时间随值转移。
+#[tokio::test]
+async fn renamenx() {
+    let (addr, _) = start_server().await;
+    let mut client = Client::connect(addr).await.unwrap();
+
+    client.set_expires("hello", "world".into(), Duration::from_millis(200)).await.unwrap();
+    client.set("taken", "value".into()).await.unwrap();
+
+    // 目标已存在，两个键都不变
+    assert!(!client.renamenx("hello", "taken").await.unwrap());
+    assert_eq!(b"world", &client.get("hello").await.unwrap().unwrap()[..]);
+    assert_eq!(b"value", &client.get("taken").await.unwrap().unwrap()[..]);
+
+    // 目标空闲，源键消失
+    assert!(client.renamenx("hello", "free").await.unwrap());
+    assert!(client.get("hello").await.unwrap().is_none());
+    assert_eq!(b"world", &client.get("free").await.unwrap().unwrap()[..]);
+
+    // 源键不存在时返回错误
+    assert!(client.renamenx("hello", "other").await.is_err());
+
+    // 生存时间随值一起转移
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    assert!(client.get("free").await.unwrap().is_none());
+}
+
+/// SET KEEPTTL 覆盖值但保留原有的生存时间，普通的 SET 清除它
+#[tokio::test]
+async fn set_keep_ttl() {
+    let (addr, _) = start_server().await;
+    let mut client = Client::connect(addr).await.unwrap();
+
+    client.set_expires("hello", "world".into(), Duration::from_millis(200)).await.unwrap();
+    client.set_keep_ttl("hello", "again".into()).await.unwrap();
+    assert_eq!(b"again", &client.get("hello").await.unwrap().unwrap()[..]);
+
+    client.set_expires("plain", "world".into(), Duration::from_millis(200)).await.unwrap();
+    client.set("plain", "again".into()).await.unwrap();
+
+    // 键不存在时写入的值不过期
+    client.set_keep_ttl("fresh", "value".into()).await.unwrap();
+
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    assert!(client.get("hello").await.unwrap().is_none());
+    assert_eq!(b"again", &client.get("plain").await.unwrap().unwrap()[..]);
+    assert_eq!(b"value", &client.get("fresh").await.unwrap().unwrap()[..]);
+}
+
+/// COPY 复制值和剩余的生存时间，目标已存在时只有 REPLACE 才覆盖
+#[tokio::test]
+async fn copy() {
+    let (addr, _) = start_server().await;
+    let mut client = Client::connect(addr).await.unwrap();
+
+    client.set_expires("hello", "world".into(), Duration::from_millis(200)).await.unwrap();
+    client.set("taken", "value".into()).await.unwrap();
+    client.rpush("list", vec!["a".into(), "b".into()]).await.unwrap();
+
+    // 目标已存在且没有 REPLACE，两个键都不变
+    assert!(!client.copy("hello", "taken", false).await.unwrap());
+    assert_eq!(b"value", &client.get("taken").await.unwrap().unwrap()[..]);
+
+    // 源键保留，目标得到相同的值
+    assert!(client.copy("hello", "free", false).await.unwrap());
+    assert_eq!(b"world", &client.get("hello").await.unwrap().unwrap()[..]);
+    assert_eq!(b"world", &client.get("free").await.unwrap().unwrap()[..]);
+
+    // REPLACE 覆盖目标，没有生存时间的源键复制出持久的目标
+    assert!(client.copy("taken", "free", true).await.unwrap());
+    assert_eq!(b"value", &client.get("free").await.unwrap().unwrap()[..]);
+    assert!(client.copy("hello", "taken", true).await.unwrap());
+
+    // 列表被复制，修改副本不影响源键
+    assert!(client.copy("list", "list2", false).await.unwrap());
+    client.rpush("list2", vec!["c".into()]).await.unwrap();
+    assert_eq!(2, client.llen("list").await.unwrap());
+    assert_eq!(3, client.llen("list2").await.unwrap());
+
+    // 源键不存在返回 false，复制到自身返回错误
+    assert!(!client.copy("missing", "other", false).await.unwrap());
+    let err = client.copy("hello", "hello", true).await.unwrap_err();
+    assert_eq!("ERR source and destination objects are the same", err.to_string());
+
+    // 生存时间随值一起复制
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    assert!(client.get("hello").await.unwrap().is_none());
+    assert!(client.get("taken").await.unwrap().is_none());
+    assert_eq!(b"value", &client.get("free").await.unwrap().unwrap()[..]);
+}
+
+/// COPY ... DB 把值和生存时间复制到另一个数据库，源键留在当前数据库
+#[tokio::test]
+async fn copy_to_db() {
+    let (addr, _) = start_server().await;
+    let mut client = Client::connect(addr).await.unwrap();
+
+    client.set_expires("hello", "world".into(), Duration::from_millis(200)).await.unwrap();
+    assert!(client.copy_to_db("hello", "hello", 1, false).await.unwrap());
+    assert_eq!(b"world", &client.get("hello").await.unwrap().unwrap()[..]);
+
+    // 复制到当前数据库的同名键仍然是错误，超出范围的数据库编号同样返回错误
+    let err = client.copy_to_db("hello", "hello", 0, false).await.unwrap_err();
+    assert_eq!("ERR source and destination objects are the same", err.to_string());
+    let err = client.copy_to_db("hello", "other", 1000, false).await.unwrap_err();
+    assert_eq!("ERR DB index is out of range", err.to_string());
+
+    client.select(1).await.unwrap();
+    assert_eq!(b"world", &client.get("hello").await.unwrap().unwrap()[..]);
+
+    // 目标数据库中已经存在的键只有 REPLACE 才覆盖
+    client.set("taken", "one".into()).await.unwrap();
+    client.select(0).await.unwrap();
+    client.set("taken", "zero".into()).await.unwrap();
+    assert!(!client.copy_to_db("taken", "taken", 1, false).await.unwrap());
+    assert!(client.copy_to_db("taken", "taken", 1, true).await.unwrap());
+
+    // 生存时间随值一起复制，两个数据库中的键都会到期
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    assert!(client.get("hello").await.unwrap().is_none());
+
+    client.select(1).await.unwrap();
+    assert!(client.get("hello").await.unwrap().is_none());
+    assert_eq!(b"zero", &client.get("taken").await.unwrap().unwrap()[..]);
+}
+
+/// MOVE 把键连同生存时间移到另一个数据库，源键不存在或目标已存在时不做修改
+#[tokio::test]
+async fn move_key() {
+    let (addr, _) = start_server().await;
+    let mut client = Client::connect(addr).await.unwrap();
+
+    client.set_expires("hello", "world".into(), Duration::from_millis(200)).await.unwrap();
+    client.set("taken", "zero".into()).await.unwrap();
+    assert!(client.move_key("hello", 1).await.unwrap());
+    assert!(client.get("hello").await.unwrap().is_none());
+
+    // 源键不存在返回 false
+    assert!(!client.move_key("missing", 1).await.unwrap());
+
+    client.select(1).await.unwrap();
+    assert_eq!(b"world", &client.get("hello").await.unwrap().unwrap()[..]);
+
+    // 目标数据库已经存在同名的键，两边都不变
+    client.set("taken", "one".into()).await.unwrap();
+    assert!(!client.move_key("taken", 0).await.unwrap());
+    assert_eq!(b"one", &client.get("taken").await.unwrap().unwrap()[..]);
+
+    // 移动到当前数据库或不存在的数据库返回错误
+    let err = client.move_key("taken", 1).await.unwrap_err();
+    assert_eq!("ERR source and destination objects are the same", err.to_string());
+    let err = client.move_key("taken", 1000).await.unwrap_err();
+    assert_eq!("ERR DB index is out of range", err.to_string());
+
+    // 生存时间随键一起移动，在目标数据库中到期
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    assert!(client.get("hello").await.unwrap().is_none());
+
+    client.select(0).await.unwrap();
+    assert_eq!(b"zero", &client.get("taken").await.unwrap().unwrap()[..]);
+}
+
+/// SETEX/PSETEX 写入带有生存时间的值，非正数的过期时间被拒绝
+#[tokio::test]
+async fn set_ex() {
+    let (addr, _) = start_server().await;
+    let mut client = Client::connect(addr).await.unwrap();
+
+    client.set_ex("hello", "world".into(), 60).await.unwrap();
+    client.pset_ex("short", "value".into(), 100).await.unwrap();
+    assert_eq!(b"world", &client.get("hello").await.unwrap().unwrap()[..]);
+    assert_eq!(b"value", &client.get("short").await.unwrap().unwrap()[..]);
+
+    // 过期时间为零或负数时返回错误且不写入
+    let err = client.set_ex("zero", "value".into(), 0).await.unwrap_err();
+    assert_eq!("ERR invalid expire time in 'setex' command", err.to_string());
+    let err = client.pset_ex("negative", "value".into(), -1).await.unwrap_err();
+    assert_eq!("ERR invalid expire time in 'psetex' command", err.to_string());
+    assert!(client.get("zero").await.unwrap().is_none());
+    assert!(client.get("negative").await.unwrap().is_none());
+
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert!(client.get("short").await.unwrap().is_none());
+    assert_eq!(b"world", &client.get("hello").await.unwrap().unwrap()[..]);
+}
+
+/// EXPIREAT/PEXPIREAT 在绝对时间过期，已经过去的时间立即删除键
+#[tokio::test]
+async fn expireat() {
+    let (addr, _) = start_server().await;
+    let mut client = Client::connect(addr).await.unwrap();
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+
+    // 键不存在
+    assert!(!client.pexpireat("hello", now.as_millis() as i64 + 200).await.unwrap());
+
+    client.set("hello", "world".into()).await.unwrap();
+    assert!(client.pexpireat("hello", now.as_millis() as i64 + 200).await.unwrap());
+    assert_eq!(b"world", &client.get("hello").await.unwrap().unwrap()[..]);
+
+    // 过去的时间立即删除键，仍然报告成功
+    client.set("past", "value".into()).await.unwrap();
+    assert!(client.expireat("past", now.as_secs() as i64 - 10).await.unwrap());
+    assert!(client.get("past").await.unwrap().is_none());
+
+    // 较晚的时间替换原来的生存时间
+    client.set("later", "value".into()).await.unwrap();
+    assert!(client.pexpireat("later", now.as_millis() as i64 + 100).await.unwrap());
+    assert!(client.expireat("later", now.as_secs() as i64 + 3600).await.unwrap());
+
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    assert!(client.get("hello").await.unwrap().is_none());
+    assert_eq!(b"value", &client.get("later").await.unwrap().unwrap()[..]);
+}
+
+/// 多个连接并发读写不同的键，键空间的分片之间保持一致
+#[tokio::test]
+async fn concurrent_clients() {
+    let (addr, _) = start_server().await;
+
+    let tasks: Vec<_> = (0..8)
+        .map(|client_id| {
+            tokio::spawn(async move {
+                let mut client = Client::connect(addr).await.unwrap();
+                for i in 0..100 {
+                    let key = format!("{}:{}", client_id, i);
+                    client.set(&key, key.clone().into()).await.unwrap();
+                    assert_eq!(key.as_bytes(), &client.get(&key).await.unwrap().unwrap()[..]);
+                }
+                // 重命名和复制的两个键通常位于不同的分片
+                for i in 0..10 {
+                    let key = format!("{}:{}", client_id, i);
+                    client.rename(&key, &format!("renamed:{}", key)).await.unwrap();
+                    assert!(client.copy(&format!("renamed:{}", key), &key, false).await.unwrap());
+                }
+            })
+        })
+        .collect();
+    for task in tasks {
+        task.await.unwrap();
+    }
+
+    let mut client = Client::connect(addr).await.unwrap();
+    assert_eq!(880, client.dbsize().await.unwrap());
+    let (next, keys) = client.scan(0, Some("renamed:*"), Some(1000)).await.unwrap();
+    assert_eq!(0, next);
+    assert_eq!(80, keys.len());
+    assert_eq!(b"3:7", &client.get("renamed:3:7").await.unwrap().unwrap()[..]);
+}
+
+/// 服务器不回复时，设置了超时的请求返回超时错误而不是一直等待。
+#[tokio::test]
+async fn request_timeout() {
+    // 一个接受连接但从不回复的监听器
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        let (_socket, _) = listener.accept().await.unwrap();
+        std::future::pending::<()>().await;
+    });
+
+    let mut client = Client::connect(addr).await.unwrap();
+    client.set_timeout(Some(Duration::from_millis(50)));
+
+    let err = client.ping(None).await.unwrap_err();
+    let err = err.downcast_ref::<Error>().unwrap();
+    assert_eq!(ErrorKind::TimedOut, err.kind());
+}
+
+/// 配置了密码时，连接在通过验证之前只能执行 `AUTH` 和 `PING`。
+#[tokio::test]
+async fn auth_required() {
+    let addr = start_server_with_config(ServerConfig {
+        requirepass: Some("secret".into()),
+        ..Default::default()
+    })
+    .await;
+    let mut client = Client::connect(addr).await.unwrap();
+
+    let err = client.get("hello").await.unwrap_err();
+    assert_eq!("NOAUTH Authentication required", err.to_string());
+    assert_eq!(b"PONG", &client.ping(None).await.unwrap()[..]);
+
+    // 密码错误返回错误，但连接仍然可用
+    assert!(client.auth("wrong").await.is_err());
+    assert!(client.set("hello", "world".into()).await.is_err());
+
+    client.auth("secret").await.unwrap();
+    client.set("hello", "world".into()).await.unwrap();
+    assert_eq!(b"world", &client.get("hello").await.unwrap().unwrap()[..]);
+}
+
+/// 按密码区分只读和完全权限两种身份的验证器
+#[derive(Debug)]
+struct ReadOnlyAuth;
+
+impl AuthProvider for ReadOnlyAuth {
+    fn authenticate(&self, password: &str) -> Option<Identity> {
+        let permissions = match password {
+            "reader" => Permissions::only([Category::Read]),
+            "inspector" => Permissions::only([Category::Read, Category::Keyspace, Category::PubSub]),
+            "admin" => Permissions::all(),
+            _ => return None,
+        };
+        Some(Identity {
+            name: password.to_string(),
+            permissions,
+        })
+    }
+}
+
+/// 验证器返回的身份限制连接可以执行的命令类别，不允许的命令回复 NOPERM
+#[tokio::test]
+async fn auth_permissions() {
+    let addr = start_server_with_config(ServerConfig {
+        auth: Some(Arc::new(ReadOnlyAuth)),
+        ..Default::default()
+    })
+    .await;
+    let mut client = Client::connect(addr).await.unwrap();
+
+    let err = client.get("hello").await.unwrap_err();
+    assert_eq!("NOAUTH Authentication required", err.to_string());
+    assert!(client.auth("wrong").await.is_err());
+
+    // 只读身份不能执行 `@write` 命令，`@read` 命令和 PING 不受影响
+    client.auth("reader").await.unwrap();
+    let err = client.set("hello", "world".into()).await.unwrap_err();
+    assert_eq!("NOPERM this user has no permissions to run the 'set' command", err.to_string());
+    assert!(client.get("hello").await.unwrap().is_none());
+    assert_eq!(b"PONG", &client.ping(None).await.unwrap()[..]);
+
+    // 重新验证为完全权限的身份
+    client.auth("admin").await.unwrap();
+    client.set("hello", "world".into()).await.unwrap();
+    assert_eq!(b"world", &client.get("hello").await.unwrap().unwrap()[..]);
+}
+
+/// 修改键空间的 `@keyspace` 命令同时属于 `@write`，只改变连接状态的命令和未知命令不检查权限
+#[tokio::test]
+async fn auth_permissions_keyspace_writes() {
+    let addr = start_server_with_config(ServerConfig {
+        auth: Some(Arc::new(ReadOnlyAuth)),
+        ..Default::default()
+    })
+    .await;
+    let mut admin = Client::connect(addr).await.unwrap();
+    admin.auth("admin").await.unwrap();
+    admin.set("hello", "world".into()).await.unwrap();
+
+    // 允许 `@keyspace` 不等于允许删除键或者清空数据库
+    let mut client = Client::connect(addr).await.unwrap();
+    client.auth("inspector").await.unwrap();
+    let err = client.flushall().await.unwrap_err();
+    assert_eq!("NOPERM this user has no permissions to run the 'flushall' command", err.to_string());
+    let err = client.del(vec!["hello".to_string()]).await.unwrap_err();
+    assert_eq!("NOPERM this user has no permissions to run the 'del' command", err.to_string());
+    assert_eq!("string", client.type_of("hello").await.unwrap());
+
+    client.select(1).await.unwrap();
+    assert_eq!(0, client.dbsize().await.unwrap());
+    client.select(0).await.unwrap();
+    let err = client.raw_command(&[b"FOO"]).await.unwrap_err();
+    assert_eq!("ERR unknown command 'foo'", err.to_string());
+
+    // RESP3 连接在订阅模式下发送的命令同样检查权限
+    client.hello(Some(3)).await.unwrap();
+    client.subscribe_keep_alive(&["news".to_string()]).await.unwrap();
+    let err = client.del(vec!["hello".to_string()]).await.unwrap_err();
+    assert_eq!("NOPERM this user has no permissions to run the 'del' command", err.to_string());
+    assert_eq!(b"world", &client.get("hello").await.unwrap().unwrap()[..]);
+    assert_eq!(b"world", &admin.get("hello").await.unwrap().unwrap()[..]);
+}
+
+/// 服务器和客户端的 TCP 选项不影响命令的执行
+#[tokio::test]
+async fn tcp_options() {
+    let addr = start_server_with_config(ServerConfig {
+        nodelay: false,
+        keepalive: Some(Duration::from_secs(60)),
+        ..Default::default()
+    })
+    .await;
+
+    let options = ConnectOptions::new().nodelay(false).keepalive(Duration::from_secs(60));
+    let mut client = Client::connect_with(addr, options).await.unwrap();
+    client.set("hello", "world".into()).await.unwrap();
+    assert_eq!(b"world", &client.get("hello").await.unwrap().unwrap()[..]);
+
+    // 默认设置 TCP_NODELAY
+    let mut client = Client::connect(addr).await.unwrap();
+    assert_eq!(b"world", &client.get("hello").await.unwrap().unwrap()[..]);
+}
+
+/// 超过内存上限时 `SET` 驱逐最久未使用的键，单个值超过上限时回复 OOM 错误
+#[tokio::test]
+async fn maxmemory_evicts_least_recently_used() {
+    let addr = start_server_with_config(ServerConfig {
+        maxmemory: Some(100),
+        ..Default::default()
+    })
+    .await;
+    let mut client = Client::connect(addr).await.unwrap();
+
+    // 每个键占用 5 + 10 字节，6 个键一共 90 字节
+    for i in 0..6 {
+        client.set(&format!("key:{}", i), "0123456789".into()).await.unwrap();
+    }
+    // 读取 key:0 之后，最久未使用的是 key:1
+    client.get("key:0").await.unwrap().unwrap();
+
+    client.set("key:6", "0123456789".into()).await.unwrap();
+    assert!(client.get("key:1").await.unwrap().is_none());
+    for i in [0, 2, 3, 4, 5, 6] {
+        assert!(client.get(&format!("key:{}", i)).await.unwrap().is_some());
+    }
+
+    // 上面按顺序读取了所有键，接下来最久未使用的是 key:0 和 key:2
+    client.set("key:7", "012345678901234567890".into()).await.unwrap();
+    assert!(client.get("key:0").await.unwrap().is_none());
+    assert!(client.get("key:2").await.unwrap().is_none());
+    assert!(client.get("key:3").await.unwrap().is_some());
+
+    let err = client.set("big", vec![b'x'; 100].into()).await.unwrap_err();
+    assert_eq!("OOM command not allowed when used memory > 'maxmemory'", err.to_string());
+    assert!(client.get("big").await.unwrap().is_none());
+    assert!(client.get("key:3").await.unwrap().is_some());
+}
+
+/// 内存已满时，条件不满足、不会写入的 `SET NX`/`SET XX` 不驱逐任何键，也不回复 OOM
+#[tokio::test]
+async fn maxmemory_set_condition_does_not_evict() {
+    let addr = start_server_with_config(ServerConfig {
+        maxmemory: Some(100),
+        ..Default::default()
+    })
+    .await;
+    let mut client = Client::connect(addr).await.unwrap();
+
+    // 每个键占用 5 + 10 字节，6 个键一共 90 字节
+    for i in 0..6 {
+        client.set(&format!("key:{}", i), "0123456789".into()).await.unwrap();
+    }
+
+    // 写入会超过上限，但键已经存在，NX 不会写入
+    let value = [b'x'; 40];
+    let res = client.raw_command(&[b"SET", b"key:5", &value, b"NX"]).await.unwrap();
+    assert_eq!(Frame::Null, res);
+    // 键不存在，XX 不会写入
+    let res = client.raw_command(&[b"SET", b"missing", &value, b"XX"]).await.unwrap();
+    assert_eq!(Frame::Null, res);
+
+    for i in 0..6 {
+        assert_eq!(b"0123456789", &client.get(&format!("key:{}", i)).await.unwrap().unwrap()[..]);
+    }
+}
+
+/// CONFIG SET 修改的参数立即生效，CONFIG GET 读回新的值；未知的参数 GET 返回空列表，SET 返回错误
+#[tokio::test]
+async fn config_set_get() {
+    let (addr, _) = start_server().await;
+    let mut client = Client::connect(addr).await.unwrap();
+
+    let config = client.config_get("maxmemory*").await.unwrap();
+    assert_eq!(
+        vec![
+            ("maxmemory".to_string(), "0".to_string()),
+            ("maxmemory-policy".to_string(), "allkeys-lru".to_string()),
+        ],
+        config
+    );
+
+    client.config_set("maxmemory", "100").await.unwrap();
+    client.config_set("MAXMEMORY-POLICY", "noeviction").await.unwrap();
+    client.config_set("maxclients", "10").await.unwrap();
+    let config = client.config_get("*").await.unwrap();
+    assert_eq!(
+        vec![
+            ("maxmemory".to_string(), "100".to_string()),
+            ("maxmemory-policy".to_string(), "noeviction".to_string()),
+            ("maxclients".to_string(), "10".to_string()),
+        ],
+        config
+    );
+
+    // 不驱逐任何键，放不下时直接回复 OOM 错误
+    for i in 0..6 {
+        client.set(&format!("key:{}", i), "0123456789".into()).await.unwrap();
+    }
+    let err = client.set("key:6", "0123456789".into()).await.unwrap_err();
+    assert_eq!("OOM command not allowed when used memory > 'maxmemory'", err.to_string());
+    assert!(client.get("key:0").await.unwrap().is_some());
+
+    assert!(client.config_get("no-such-parameter").await.unwrap().is_empty());
+    assert!(client.config_set("no-such-parameter", "1").await.is_err());
+    assert!(client.config_set("maxmemory", "lots").await.is_err());
+    assert!(client.config_set("maxmemory-policy", "volatile-lru").await.is_err());
+    assert_eq!("100", client.config_get("maxmemory").await.unwrap()[0].1);
+}
+
+/// 每个数据库是独立的键空间，SELECT 只影响发出它的连接
+#[tokio::test]
+async fn select_databases() {
+    let (addr, _) = start_server().await;
+    let mut client = Client::connect(addr).await.unwrap();
+    let mut other = Client::connect(addr).await.unwrap();
+
+    client.select(1).await.unwrap();
+    client.set("hello", "one".into()).await.unwrap();
+    assert_eq!(b"one", &client.get("hello").await.unwrap().unwrap()[..]);
+
+    // 另一个连接仍然使用 0 号数据库
+    assert!(other.get("hello").await.unwrap().is_none());
+    other.set("hello", "zero".into()).await.unwrap();
+    assert_eq!(b"one", &client.get("hello").await.unwrap().unwrap()[..]);
+
+    // FLUSHDB 只清空当前数据库
+    client.flushdb().await.unwrap();
+    assert_eq!(0, client.dbsize().await.unwrap());
+    assert_eq!(1, other.dbsize().await.unwrap());
+
+    let err = client.select(16).await.unwrap_err();
+    assert_eq!("ERR DB index is out of range", err.to_string());
+
+    client.select(0).await.unwrap();
+    assert_eq!(b"zero", &client.get("hello").await.unwrap().unwrap()[..]);
+}
+
+/// 数据库个数可以配置，数据库在第一次被选择时才分配
+#[tokio::test]
+async fn configured_databases_are_allocated_lazily() {
+    let addr = start_server_with_config(ServerConfig {
+        databases: 4,
+        ..Default::default()
+    })
+    .await;
+    let mut client = Client::connect(addr).await.unwrap();
+
+    let err = client.select(4).await.unwrap_err();
+    assert_eq!("ERR DB index is out of range", err.to_string());
+    // 只有 0 号数据库被分配
+    assert!(client.info().await.unwrap().contains("allocated_databases:1\r\n"));
+
+    client.select(3).await.unwrap();
+    client.set("hello", "three".into()).await.unwrap();
+    assert_eq!(b"three", &client.get("hello").await.unwrap().unwrap()[..]);
+    // 1 号和 2 号数据库仍然没有分配
+    assert!(client.info().await.unwrap().contains("allocated_databases:2\r\n"));
+}
+
+/// SWAPDB 交换两个数据库的内容，已经选择了它们的连接直接看到交换之后的内容
+#[tokio::test]
+async fn swapdb_swaps_contents() {
+    let (addr, _) = start_server().await;
+    let mut client = Client::connect(addr).await.unwrap();
+    client.set("name", "zero".into()).await.unwrap();
+
+    let mut other = Client::connect(addr).await.unwrap();
+    other.select(1).await.unwrap();
+    other.set("name", "one".into()).await.unwrap();
+    other.set("extra", "value".into()).await.unwrap();
+
+    // 在 1 号数据库上阻塞的客户端在交换之后拿到交换进来的列表
+    let popper = tokio::spawn(async move {
+        let mut client = Client::connect(addr).await.unwrap();
+        client.select(1).await.unwrap();
+        client.blpop(&["jobs".into()], Some(Duration::from_secs(5))).await.unwrap()
+    });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    client.rpush("jobs", vec!["job-1".into()]).await.unwrap();
+
+    client.swapdb(0, 1).await.unwrap();
+    assert_eq!(b"one", &client.get("name").await.unwrap().unwrap()[..]);
+    assert_eq!(2, client.dbsize().await.unwrap());
+    assert_eq!(b"zero", &other.get("name").await.unwrap().unwrap()[..]);
+
+    let (key, value) = popper.await.unwrap().unwrap();
+    assert_eq!("jobs", key);
+    assert_eq!(b"job-1", &value[..]);
+
+    let err = client.swapdb(0, 16).await.unwrap_err();
+    assert_eq!("ERR DB index is out of range", err.to_string());
+}
+
+/// TOUCH 返回存在的键数，并像读取一样更新最近访问时间
+#[tokio::test]
+async fn touch_updates_recency() {
+    let addr = start_server_with_config(ServerConfig {
+        maxmemory: Some(100),
+        ..Default::default()
+    })
+    .await;
+    let mut client = Client::connect(addr).await.unwrap();
+
+    for i in 0..6 {
+        client.set(&format!("key:{}", i), "0123456789".into()).await.unwrap();
+    }
+    let keys = vec!["key:0".into(), "missing".into(), "key:0".into()];
+    assert_eq!(2, client.touch(keys).await.unwrap());
+
+    // key:0 刚被访问过，最久未使用的是 key:1
+    client.set("key:6", "0123456789".into()).await.unwrap();
+    assert!(client.get("key:1").await.unwrap().is_none());
+    assert!(client.get("key:0").await.unwrap().is_some());
+}
+
+/// `SAVE` 保存的快照在另一个服务器启动时恢复，包括各种类型的值和剩余的生存时间
+#[tokio::test]
+async fn save_and_restore_snapshot() {
+    let path = std::env::temp_dir().join(format!("mini-redis-{}-snapshot.rdb", std::process::id()));
+    let config = || ServerConfig {
+        dbfilename: Some(path.clone()),
+        ..Default::default()
+    };
+
+    let addr = start_server_with_config(config()).await;
+    let mut client = Client::connect(addr).await.unwrap();
+    client.set("string", "hello".into()).await.unwrap();
+    client.rpush("list", vec!["a".into(), "b".into(), "c".into()]).await.unwrap();
+    client.hset("hash", vec![("field".into(), "value".into())]).await.unwrap();
+    client.sadd("set", vec!["x".into(), "y".into()]).await.unwrap();
+    client.set_expires("short", "soon".into(), Duration::from_millis(500)).await.unwrap();
+    client.set_expires("long", "later".into(), Duration::from_secs(60)).await.unwrap();
+    client.select(2).await.unwrap();
+    client.set("string", "other".into()).await.unwrap();
+    client.save().await.unwrap();
+
+    let addr = start_server_with_config(config()).await;
+    let mut client = Client::connect(addr).await.unwrap();
+    assert_eq!(6, client.dbsize().await.unwrap());
+    assert_eq!(b"hello", &client.get("string").await.unwrap().unwrap()[..]);
+    assert_eq!(
+        vec![Bytes::from("a"), Bytes::from("b"), Bytes::from("c")],
+        client.lrange("list", 0, -1).await.unwrap()
+    );
+    assert_eq!(b"value", &client.hget("hash", "field").await.unwrap().unwrap()[..]);
+    let mut members = client.smembers("set").await.unwrap();
+    members.sort();
+    assert_eq!(vec![Bytes::from("x"), Bytes::from("y")], members);
+
+    // 生存时间在恢复之后继续计时
+    assert_eq!(b"soon", &client.get("short").await.unwrap().unwrap()[..]);
+    tokio::time::sleep(Duration::from_millis(600)).await;
+    assert!(client.get("short").await.unwrap().is_none());
+    assert_eq!(b"later", &client.get("long").await.unwrap().unwrap()[..]);
+
+    // 每个数据库的键恢复到各自的数据库
+    client.select(2).await.unwrap();
+    assert_eq!(1, client.dbsize().await.unwrap());
+    assert_eq!(b"other", &client.get("string").await.unwrap().unwrap()[..]);
+
+    std::fs::remove_file(&path).unwrap();
+
+    // 没有配置快照文件时 `SAVE` 回复错误
+    let (addr, _) = start_server().await;
+    let mut client = Client::connect(addr).await.unwrap();
+    let err = client.save().await.unwrap_err();
+    assert_eq!("ERR no snapshot file configured", err.to_string());
+}
+
+/// 开启 AOF 时修改键空间的命令被记录下来，使用同一个文件重启的服务器重放它们，恢复键空间
+#[tokio::test]
+async fn aof_replay() {
+    let path = std::env::temp_dir().join(format!("mini-redis-{}-appendonly.aof", std::process::id()));
+    let config = || ServerConfig {
+        appendfilename: Some(path.clone()),
+        ..Default::default()
+    };
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (stop, stopped) = oneshot::channel::<()>();
+    let server = tokio::spawn(server::run_with_config(listener, stopped, config()));
+
+    let mut client = Client::connect(addr).await.unwrap();
+    client.set("hello", "world".into()).await.unwrap();
+    client.set("deleted", "value".into()).await.unwrap();
+    client.del(vec!["deleted".into()]).await.unwrap();
+    client.rpush("list", vec!["a".into(), "b".into()]).await.unwrap();
+    client.lpop("list").await.unwrap();
+    client.hset("hash", vec![("field".into(), "value".into())]).await.unwrap();
+    client.append("hello", "!".into()).await.unwrap();
+    client.select(1).await.unwrap();
+    client.set("hello", "other".into()).await.unwrap();
+    drop(client);
+
+    // 关闭时写完所有记录的命令
+    drop(stop);
+    server.await.unwrap();
+
+    let addr = start_server_with_config(config()).await;
+    let mut client = Client::connect(addr).await.unwrap();
+    assert_eq!(3, client.dbsize().await.unwrap());
+    assert_eq!(b"world!", &client.get("hello").await.unwrap().unwrap()[..]);
+    assert!(client.get("deleted").await.unwrap().is_none());
+    assert_eq!(vec![Bytes::from("b")], client.lrange("list", 0, -1).await.unwrap());
+    assert_eq!(b"value", &client.hget("hash", "field").await.unwrap().unwrap()[..]);
+    client.select(1).await.unwrap();
+    assert_eq!(b"other", &client.get("hello").await.unwrap().unwrap()[..]);
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+/// everysec 策略下写入的命令在一秒之内同步，服务器仍在运行时从同一个文件启动的服务器能重放它们
+#[tokio::test]
+async fn aof_everysec_fsync() {
+    let path = std::env::temp_dir().join(format!("mini-redis-{}-everysec.aof", std::process::id()));
+    let config = || ServerConfig {
+        appendfilename: Some(path.clone()),
+        appendfsync: AppendFsync::EverySec,
+        ..Default::default()
+    };
+
+    let addr = start_server_with_config(config()).await;
+    let mut client = Client::connect(addr).await.unwrap();
+    client.set("hello", "world".into()).await.unwrap();
+
+    tokio::time::sleep(Duration::from_millis(1200)).await;
+
+    let addr = start_server_with_config(config()).await;
+    let mut client = Client::connect(addr).await.unwrap();
+    assert_eq!(b"world", &client.get("hello").await.unwrap().unwrap()[..]);
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+/// 开启 AOF 时 WAITAOF 等待之前的写入同步到磁盘，appendfsync 为 no 时同样如此；没有开启 AOF 时回复 0
+#[tokio::test]
+async fn waitaof() {
+    let path = std::env::temp_dir().join(format!("mini-redis-{}-waitaof.aof", std::process::id()));
+    let addr = start_server_with_config(ServerConfig {
+        appendfilename: Some(path.clone()),
+        appendfsync: AppendFsync::No,
+        ..Default::default()
+    })
+    .await;
+    let mut client = Client::connect(addr).await.unwrap();
+
+    client.set("hello", "world".into()).await.unwrap();
+    let response = client.raw_command(&[b"WAITAOF", b"1", b"0", b"1000"]).await.unwrap();
+    assert_eq!(Frame::Array(vec![Frame::Integer(1), Frame::Integer(0)]), response);
+
+    // 没有新的写入时立即回复，不需要 numlocal 时不等待
+    assert_eq!((1, 0), client.waitaof(1, 0, None).await.unwrap());
+    assert_eq!((0, 0), client.waitaof(0, 0, None).await.unwrap());
+
+    let (addr, _) = start_server().await;
+    let mut client = Client::connect(addr).await.unwrap();
+    client.set("hello", "world".into()).await.unwrap();
+    assert_eq!((0, 0), client.waitaof(1, 0, Some(Duration::from_secs(1))).await.unwrap());
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+/// 相对的过期时间按绝对时间记录：重启之前已经到期的键在重放之后仍然过期，没有到期的键保留原来的过期时间
+#[tokio::test]
+async fn aof_replay_keeps_absolute_expiry() {
+    let path = std::env::temp_dir().join(format!("mini-redis-{}-expiry.aof", std::process::id()));
+    let config = || ServerConfig {
+        appendfilename: Some(path.clone()),
+        ..Default::default()
+    };
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (stop, stopped) = oneshot::channel::<()>();
+    let server = tokio::spawn(server::run_with_config(listener, stopped, config()));
+
+    let mut client = Client::connect(addr).await.unwrap();
+    client.set_expires("set", "value".into(), Duration::from_millis(200)).await.unwrap();
+    client.pset_ex("psetex", "value".into(), 200).await.unwrap();
+    client.set("getex", "value".into()).await.unwrap();
+    client.getex("getex", Some(Expiry::In(Duration::from_millis(200)))).await.unwrap();
+    client.set_expires("later", "value".into(), Duration::from_millis(800)).await.unwrap();
+    drop(client);
+    drop(stop);
+    server.await.unwrap();
+
+    tokio::time::sleep(Duration::from_millis(300)).await;
+
+    let addr = start_server_with_config(config()).await;
+    let mut client = Client::connect(addr).await.unwrap();
+    assert!(client.get("set").await.unwrap().is_none());
+    assert!(client.get("psetex").await.unwrap().is_none());
+    assert!(client.get("getex").await.unwrap().is_none());
+    assert_eq!(b"value", &client.get("later").await.unwrap().unwrap()[..]);
+
+    // 重放的键在原来的时间过期，而不是从重放时重新计时
+    tokio::time::sleep(Duration::from_millis(600)).await;
+    assert!(client.get("later").await.unwrap().is_none());
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+/// 多个连接并发追加同一个键时，日志中的顺序与执行的顺序相同，重放得到的值与重启之前的值相同。
+/// 使用多线程运行时，命令才会真正并行执行。
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn aof_replay_preserves_concurrent_order() {
+    let path = std::env::temp_dir().join(format!("mini-redis-{}-order.aof", std::process::id()));
+    let config = || ServerConfig {
+        appendfilename: Some(path.clone()),
+        ..Default::default()
+    };
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (stop, stopped) = oneshot::channel::<()>();
+    let server = tokio::spawn(server::run_with_config(listener, stopped, config()));
+
+    let writers: Vec<_> = ["a", "b", "c", "d"]
+        .into_iter()
+        .map(|value| {
+            tokio::spawn(async move {
+                let mut client = Client::connect(addr).await.unwrap();
+                for _ in 0..200 {
+                    client.append("key", value.into()).await.unwrap();
+                }
+            })
+        })
+        .collect();
+    for writer in writers {
+        writer.await.unwrap();
+    }
+
+    let mut client = Client::connect(addr).await.unwrap();
+    let expected = client.get("key").await.unwrap().unwrap();
+    drop(client);
+    drop(stop);
+    server.await.unwrap();
+
+    let addr = start_server_with_config(config()).await;
+    let mut client = Client::connect(addr).await.unwrap();
+    assert_eq!(expected, client.get("key").await.unwrap().unwrap());
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+/// BLPOP 弹出之后、返回之前另一个推入到达，日志中弹出仍然记录在推入之前，重放得到的列表与重启之前相同
+#[tokio::test]
+async fn aof_replay_blocking_pop_races_push() {
+    let path = std::env::temp_dir().join(format!("mini-redis-{}-blpop.aof", std::process::id()));
+    let config = || ServerConfig {
+        appendfilename: Some(path.clone()),
+        ..Default::default()
+    };
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (stop, stopped) = oneshot::channel::<()>();
+    let server = tokio::spawn(server::run_with_config(listener, stopped, config()));
+
+    // BLPOP 的连接几乎不接收数据，弹出一个大元素之后回复写不出去，命令停在弹出和返回之间
+    let socket = TcpSocket::new_v4().unwrap();
+    socket.set_recv_buffer_size(4096).unwrap();
+    let mut popper = Connection::new(socket.connect(addr).await.unwrap());
+    let blpop = ["BLPOP", "list", "0"].map(|arg| Frame::Bulk(Bytes::from(arg)));
+    popper.write_frame(&Frame::Array(blpop.to_vec())).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    let mut client = Client::connect(addr).await.unwrap();
+    let large = Bytes::from(vec![b'x'; 6 * 1024 * 1024]);
+    client.lpush("list", vec![large.clone()]).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    client.lpush("list", vec!["small".into()]).await.unwrap();
+
+    let expected = Frame::Array(vec![Frame::Bulk(Bytes::from("list")), Frame::Bulk(large)]);
+    assert_eq!(Some(expected), popper.read_frame().await.unwrap());
+    assert_eq!(vec![Bytes::from("small")], client.lrange("list", 0, -1).await.unwrap());
+    drop(client);
+    drop(popper);
+    drop(stop);
+    server.await.unwrap();
+
+    let addr = start_server_with_config(config()).await;
+    let mut client = Client::connect(addr).await.unwrap();
+    assert_eq!(vec![Bytes::from("small")], client.lrange("list", 0, -1).await.unwrap());
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+/// 启用滞后通知后，读取得太慢的订阅者会被告知丢弃了多少条消息，之后仍能继续接收消息。
+#[tokio::test]
+async fn lagged_subscriber_notification() {
+    let addr = start_server_with_config(ServerConfig {
+        notify_lagged: true,
+        ..Default::default()
+    })
+    .await;
+
+    let subscriber = Client::connect(addr).await.unwrap();
+    let mut subscriber = subscriber.subscribe(vec!["news".into()]).await.unwrap();
+
+    // 订阅者暂时不读取。消息足够大，先填满套接字缓冲区，服务器的写入被阻塞后，
+    // 容量为 1024 的广播通道也被填满，最旧的消息被覆盖。
+    let mut publisher = Client::connect(addr).await.unwrap();
+    let content = Bytes::from(vec![b'x'; 16 * 1024]);
+    for _ in 0..2048 {
+        publisher.publish("news", content.clone()).await.unwrap();
+    }
+
+    let mut received = 0;
+    let err = loop {
+        match subscriber.next_message().await {
+            Ok(Some(_)) => received += 1,
+            Ok(None) => panic!("subscription ended"),
+            Err(err) => break err,
+        }
+        assert!(received < 2048, "subscriber never lagged");
+    };
+    assert!(err.to_string().contains("lagged behind on news"), "{}", err);
+
+    // 订阅仍然可用
+    publisher.publish("news", "latest".into()).await.unwrap();
+    let message = loop {
+        let message = subscriber.next_message().await.unwrap().unwrap();
+        if message.content != content {
+            break message;
+        }
+    };
+    assert_eq!(b"latest", &message.content[..]);
+}
+
+/// 频道的容量可以配置。容量很小时，即使发布的消息数远少于默认的 1024 条，读取慢的订阅者也会丢弃消息。
+#[tokio::test]
+async fn pubsub_capacity_is_configurable() {
+    let addr = start_server_with_config(ServerConfig {
+        notify_lagged: true,
+        pubsub_capacity: 8,
+        ..Default::default()
+    })
+    .await;
+
+    let subscriber = Client::connect(addr).await.unwrap();
+    let mut subscriber = subscriber.subscribe(vec!["news".into()]).await.unwrap();
+
+    let mut publisher = Client::connect(addr).await.unwrap();
+    let content = Bytes::from(vec![b'x'; 16 * 1024]);
+    for _ in 0..512 {
+        publisher.publish("news", content.clone()).await.unwrap();
+    }
+
+    let mut received = 0;
+    let err = loop {
+        match subscriber.next_message().await {
+            Ok(Some(_)) => received += 1,
+            Ok(None) => panic!("subscription ended"),
+            Err(err) => break err,
+        }
+        assert!(received < 512, "subscriber never lagged");
+    };
+    assert!(err.to_string().contains("lagged behind on news"), "{}", err);
+}
+
+/// 启用过期通知后，过期的键名被发布到 `__keyevent__:expired`。
+#[tokio::test]
+async fn expired_key_notification() {
+    let addr = start_server_with_config(ServerConfig {
+        notify_expired: true,
+        ..Default::default()
+    })
+    .await;
+
+    let subscriber = Client::connect(addr).await.unwrap();
+    let mut subscriber = subscriber.subscribe(vec!["__keyevent__:expired".into()]).await.unwrap();
+
+    let mut client = Client::connect(addr).await.unwrap();
+    client.set_expires("hello", "world".into(), Duration::from_millis(50)).await.unwrap();
+
+    let message = subscriber.next_message().await.unwrap().unwrap();
+    assert_eq!("__keyevent__:expired", &message.channel);
+    assert_eq!(b"hello", &message.content[..]);
+}
+
+/// 订阅 `news.*` 模式后，发布到 `news.sports` 的消息带着模式和实际频道送达，不匹配的频道则不会送达
+#[tokio::test]
+async fn psubscribe_pattern() {
+    let (addr, _) = start_server().await;
+
+    let subscriber = Client::connect(addr).await.unwrap();
+    let mut subscriber = subscriber.psubscribe(vec!["news.*".into()]).await.unwrap();
+    assert_eq!(&["news.*".to_string()], subscriber.get_subscribed_patterns());
+
+    let mut client = Client::connect(addr).await.unwrap();
+    assert_eq!(0, client.publish("weather", "sunny".into()).await.unwrap());
+    assert_eq!(1, client.publish("news.sports", "goal".into()).await.unwrap());
+
+    let message = subscriber.next_message().await.unwrap().unwrap();
+    assert_eq!(Some("news.*"), message.pattern.as_deref());
+    assert_eq!("news.sports", &message.channel);
+    assert_eq!(b"goal", &message.content[..]);
+
+    subscriber.punsubscribe(&[]).await.unwrap();
+    assert!(subscriber.get_subscribed_patterns().is_empty());
+}
+
+/// HELLO 返回服务器信息并协商协议版本
+#[tokio::test]
+async fn hello() {
+    let (addr, _) = start_server().await;
+    let mut client = Client::connect(addr).await.unwrap();
+
+    let info = client.hello(None).await.unwrap();
+    assert_eq!(info["server"], "mini-redis");
+    assert_eq!(Frame::Integer(2), info["proto"]);
+    assert_eq!(info["role"], "master");
+
+    let err = client.hello(Some(4)).await.unwrap_err();
+    assert_eq!("NOPROTO unsupported protocol version", err.to_string());
+
+    let info = client.hello(Some(3)).await.unwrap();
+    assert_eq!(Frame::Integer(3), info["proto"]);
+
+    // RESP3 连接上 HGETALL 返回 map
+    client.hset("hash", vec![("a".to_string(), "1".into())]).await.unwrap();
+    let all = client.hgetall("hash").await.unwrap();
+    assert_eq!(b"1", &all["a"][..]);
+}
+
+/// RESP3 连接订阅之后仍然可以执行其他命令，等待回复时到达的消息留给 `next_message`
+#[tokio::test]
+async fn subscribe_keep_alive() {
+    let (addr, _) = start_server().await;
+    let mut client = Client::connect(addr).await.unwrap();
+    let mut publisher = Client::connect(addr).await.unwrap();
+
+    // RESP2 连接无法区分消息和回复
+    assert!(client.subscribe_keep_alive(&["news".to_string()]).await.is_err());
+
+    client.hello(Some(3)).await.unwrap();
+    client.subscribe_keep_alive(&["news".to_string()]).await.unwrap();
+    client.set("hello", "world".into()).await.unwrap();
+
+    // 消息在 GET 的回复之前到达
+    assert_eq!(1, publisher.publish("news", "first".into()).await.unwrap());
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert_eq!(b"world", &client.get("hello").await.unwrap().unwrap()[..]);
+
+    let message = client.next_message().await.unwrap().unwrap();
+    assert_eq!("news", message.channel);
+    assert_eq!(b"first", &message.content[..]);
+
+    // 没有缓存的消息时等待下一条推送
+    assert_eq!(1, publisher.publish("news", "second".into()).await.unwrap());
+    let message = client.next_message().await.unwrap().unwrap();
+    assert_eq!(b"second", &message.content[..]);
+    assert_eq!(b"world", &client.get("hello").await.unwrap().unwrap()[..]);
+}
+
+/// CLIENT SETNAME 为连接设置名称，GETNAME 返回它
+#[tokio::test]
+async fn client_name() {
+    let (addr, _) = start_server().await;
+    let mut client = Client::connect(addr).await.unwrap();
+
+    assert_eq!("", client.get_name().await.unwrap());
+
+    client.set_name("worker-1").await.unwrap();
+    assert_eq!("worker-1", client.get_name().await.unwrap());
+
+    // 名称是每个连接各自的
+    let mut other = Client::connect(addr).await.unwrap();
+    assert_eq!("", other.get_name().await.unwrap());
+
+    let err = client.set_name("has space").await.unwrap_err();
+    assert_eq!(
+        "ERR Client names cannot contain spaces, newlines or special characters.",
+        err.to_string()
+    );
+    let err = client.set_name("line\nbreak").await.unwrap_err();
+    assert!(err.to_string().starts_with("ERR Client names"));
+    assert_eq!("worker-1", client.get_name().await.unwrap());
+
+    // 空名称清除名称
+    client.set_name("").await.unwrap();
+    assert_eq!("", client.get_name().await.unwrap());
+}
+
+/// 每个连接有唯一的 id，CLIENT LIST 列出所有活动连接
+#[tokio::test]
+async fn client_id_and_list() {
+    let (addr, _) = start_server().await;
+    let mut client = Client::connect(addr).await.unwrap();
+    let mut other = Client::connect(addr).await.unwrap();
+
+    let id = client.client_id().await.unwrap();
+    let other_id = other.client_id().await.unwrap();
+    assert!(other_id > id);
+    // id 在连接的生命周期内不变
+    assert_eq!(id, client.client_id().await.unwrap());
+
+    other.set_name("worker-2").await.unwrap();
+    let list = client.client_list().await.unwrap();
+    let lines: Vec<_> = list.lines().collect();
+    assert_eq!(2, lines.len());
+    assert!(lines[0].starts_with(&format!("id={} addr=127.0.0.1:", id)));
+    assert!(lines[0].ends_with(" name="));
+    assert!(lines[1].starts_with(&format!("id={} addr=127.0.0.1:", other_id)));
+    assert!(lines[1].ends_with(" name=worker-2"));
+
+    // 关闭的连接从列表中移除
+    drop(other);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    let list = client.client_list().await.unwrap();
+    assert_eq!(format!("id={}", id), list.split(' ').next().unwrap());
+    assert_eq!(1, list.lines().count());
+}
+
+/// QUIT 之后服务器关闭连接
+#[tokio::test]
+async fn quit() {
+    let (addr, _) = start_server().await;
+
+    let client = Client::connect(addr).await.unwrap();
+    client.quit().await.unwrap();
+}
+
+/// RESET 取消所有订阅，连接回到普通模式
+#[tokio::test]
+async fn subscriber_reset() {
+    let (addr, _) = start_server().await;
+
+    let mut client = Client::connect(addr).await.unwrap();
+    client.set("hello", "world".into()).await.unwrap();
+
+    let subscriber = client.subscribe(vec!["news".into()]).await.unwrap();
+    let mut client = subscriber.reset().await.unwrap();
+
+    // 同一个连接可以再次执行普通命令
+    assert_eq!(Some("world".into()), client.get("hello").await.unwrap());
+    // 订阅已经取消
+    assert_eq!(0, client.publish("news", "goal".into()).await.unwrap());
+}
+
+/// 借用客户端的订阅者在取消所有订阅之后交还客户端，连接的其他状态保持不变
+#[tokio::test]
+async fn subscribe_by_ref() {
+    let (addr, _) = start_server().await;
+
+    let mut client = Client::connect(addr).await.unwrap();
+    client.select(1).await.unwrap();
+    client.set("hello", "world".into()).await.unwrap();
+
+    let mut subscriber = client.subscribe_by_ref(vec!["news".into()]).await.unwrap();
+    subscriber.psubscribe(&["sports.*".into()]).await.unwrap();
+
+    let mut publisher = Client::connect(addr).await.unwrap();
+    assert_eq!(1, publisher.publish("news", "goal".into()).await.unwrap());
+    let message = subscriber.next_message().await.unwrap().unwrap();
+    assert_eq!(b"goal", &message.content[..]);
+
+    // 取消订阅之前发布的消息被丢弃
+    assert_eq!(1, publisher.publish("sports.football", "kickoff".into()).await.unwrap());
+    subscriber.unsubscribe_all().await.unwrap();
+
+    // 仍然选择着 1 号数据库
+    assert_eq!(Some("world".into()), client.get("hello").await.unwrap());
+    assert_eq!(0, publisher.publish("news", "goal".into()).await.unwrap());
+    assert_eq!(0, publisher.publish("sports.football", "goal".into()).await.unwrap());
+}
+
+/// 取消最后一个订阅之后服务器回到普通模式，再次取消订阅也不会出错
+#[tokio::test]
+async fn unsubscribe_last_channel() {
+    let (addr, _) = start_server().await;
+
+    let client = Client::connect(addr).await.unwrap();
+    let mut subscriber = client.subscribe(vec!["news".into()]).await.unwrap();
+    subscriber.unsubscribe(&["news".into()]).await.unwrap();
+    subscriber.unsubscribe(&["news".into()]).await.unwrap();
+    assert!(subscriber.get_subscribed().is_empty());
+
+    let mut client = subscriber.unsubscribe_all().await.unwrap();
+    client.set("hello", "world".into()).await.unwrap();
+    assert_eq!(Some("world".into()), client.get("hello").await.unwrap());
+}
+
+/// 订阅数随服务器的确认增加和减少，包括模式订阅
+#[tokio::test]
+async fn subscription_count() {
+    let (addr, _) = start_server().await;
+
+    let client = Client::connect(addr).await.unwrap();
+    let mut subscriber = client.subscribe(vec!["a".into(), "b".into()]).await.unwrap();
+    assert_eq!(2, subscriber.subscription_count());
+
+    subscriber.subscribe(&["c".into()]).await.unwrap();
+    subscriber.psubscribe(&["d.*".into()]).await.unwrap();
+    assert_eq!(4, subscriber.subscription_count());
+
+    subscriber.unsubscribe(&["a".into()]).await.unwrap();
+    assert_eq!(3, subscriber.subscription_count());
+    subscriber.unsubscribe(&[]).await.unwrap();
+    assert_eq!(1, subscriber.subscription_count());
+    subscriber.punsubscribe(&[]).await.unwrap();
+    assert_eq!(0, subscriber.subscription_count());
+}
+
+/// 不要求顺序的订阅把确认作为集合检查，并返回订阅总数
+#[tokio::test]
+async fn subscribe_unordered() {
+    let (addr, _) = start_server().await;
+
+    let client = Client::connect(addr).await.unwrap();
+    let mut subscriber = client.subscribe(vec!["a".into()]).await.unwrap();
+    let channels: Vec<String> = vec!["b".into(), "c".into(), "d".into()];
+    assert_eq!(4, subscriber.subscribe_unordered(&channels).await.unwrap());
+    assert_eq!(5, subscriber.psubscribe_unordered(&["e.*".into()]).await.unwrap());
+    assert_eq!(&["a", "b", "c", "d"], subscriber.get_subscribed());
+}
+
+/// 服务器打乱确认的顺序时，按位置检查的订阅失败，不要求顺序的订阅成功
+#[tokio::test]
+async fn subscribe_unordered_acks() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    // 一个按相反顺序确认订阅的服务器
+    tokio::spawn(async move {
+        let (socket, _) = listener.accept().await.unwrap();
+        let mut conn = Connection::new(socket);
+        let mut count = 0;
+        while let Some(Frame::Array(request)) = conn.read_frame().await.unwrap() {
+            for channel in request[1..].iter().rev() {
+                count += 1;
+                let ack = Frame::Array(vec![Frame::Bulk("subscribe".into()), channel.clone(), Frame::Integer(count)]);
+                conn.write_frame(&ack).await.unwrap();
+            }
+        }
+    });
+
+    let client = Client::connect(addr).await.unwrap();
+    let mut subscriber = client.subscribe(vec!["a".into()]).await.unwrap();
+    let channels: Vec<String> = vec!["b".into(), "c".into(), "d".into()];
+    assert_eq!(4, subscriber.subscribe_unordered(&channels).await.unwrap());
+
+    let channels: Vec<String> = vec!["e".into(), "f".into()];
+    assert!(subscriber.subscribe(&channels).await.is_err());
+}
+
+/// PUBSUB CHANNELS 只列出仍有订阅者的频道，NUMSUB 返回每个频道的订阅者数量
+#[tokio::test]
+async fn pubsub_channels_numsub() {
+    let (addr, _) = start_server().await;
+
+    let subscriber = Client::connect(addr).await.unwrap();
+    let mut subscriber = subscriber.subscribe(vec!["news.sports".into(), "weather".into()]).await.unwrap();
+
+    let mut client = Client::connect(addr).await.unwrap();
+    assert_eq!(vec!["news.sports", "weather"], client.pubsub_channels(None).await.unwrap());
+    assert_eq!(vec!["news.sports"], client.pubsub_channels(Some("news.*")).await.unwrap());
+
+    let counts = client.pubsub_numsub(&["weather".into(), "missing".into()]).await.unwrap();
+    assert_eq!(vec![("weather".to_string(), 1), ("missing".to_string(), 0)], counts);
+
+    // 取消订阅后频道不再出现在列表中
+    subscriber.unsubscribe(&["weather".into()]).await.unwrap();
+    assert_eq!(vec!["news.sports"], client.pubsub_channels(None).await.unwrap());
+}
+
+/// 最后一个订阅者取消订阅或断开连接后，频道被清理；之后重新订阅的频道仍能正常收到消息
+#[tokio::test]
+async fn empty_channels_are_removed() {
+    let (addr, _) = start_server().await;
+    let mut client = Client::connect(addr).await.unwrap();
+
+    let first = Client::connect(addr).await.unwrap();
+    let mut first = first.subscribe(vec!["ephemeral".into()]).await.unwrap();
+    let second = Client::connect(addr).await.unwrap();
+    let second = second.subscribe(vec!["ephemeral".into()]).await.unwrap();
+
+    // 还有一个订阅者，频道保留
+    first.unsubscribe(&["ephemeral".into()]).await.unwrap();
+    assert_eq!(vec!["ephemeral"], client.pubsub_channels(None).await.unwrap());
+
+    // 最后一个订阅者断开连接，服务器异步地处理断开
+    drop(second);
+    let mut channels = client.pubsub_channels(None).await.unwrap();
+    for _ in 0..100 {
+        if channels.is_empty() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        channels = client.pubsub_channels(None).await.unwrap();
+    }
+    assert!(channels.is_empty(), "{:?}", channels);
+
+    // 清理之后再次订阅会创建一个新的频道
+    first.subscribe(&["ephemeral".into()]).await.unwrap();
+    assert_eq!(1, client.publish("ephemeral", "hello".into()).await.unwrap());
+    let message = first.next_message().await.unwrap().unwrap();
+    assert_eq!(b"hello", &message.content[..]);
+
+    first.unsubscribe(&[]).await.unwrap();
+    assert!(client.pubsub_channels(None).await.unwrap().is_empty());
+}
+
+/// UNLINK 返回被删除的键数，键立即不可见，即使值在后台释放
+#[tokio::test]
+async fn unlink_removes_keys_immediately() {
+    let (addr, _) = start_server().await;
+    let mut client = Client::connect(addr).await.unwrap();
+
+    client.set("small", "value".into()).await.unwrap();
+    let items: Vec<Bytes> = (0..1000).map(|i| Bytes::from(i.to_string())).collect();
+    client.rpush("large", items).await.unwrap();
+
+    let keys = vec!["small".into(), "large".into(), "missing".into()];
+    assert_eq!(2, client.unlink(keys).await.unwrap());
+    assert!(client.get("small").await.unwrap().is_none());
+    assert!(client.lrange("large", 0, -1).await.unwrap().is_empty());
+    assert_eq!("none", client.type_of("large").await.unwrap());
+    assert_eq!(0, client.dbsize().await.unwrap());
+}
+
+/// GETDEL 返回旧值并删除键，包括带有过期时间的键
+#[tokio::test]
+async fn get_del() {
+    let (addr, _) = start_server().await;
+    let mut client = Client::connect(addr).await.unwrap();
+
+    client.set_expires("token", "abc".into(), Duration::from_secs(60)).await.unwrap();
+    assert_eq!(b"abc", &client.get_del("token").await.unwrap().unwrap()[..]);
+    assert_eq!(None, client.get("token").await.unwrap());
+    assert_eq!(None, client.get_del("token").await.unwrap());
+}
+
+/// GETSET 写入新值并返回旧值，同时清除过期时间
+#[tokio::test]
+async fn getset() {
+    let (addr, _) = start_server().await;
+    let mut client = Client::connect(addr).await.unwrap();
+
+    assert_eq!(None, client.getset("counter", "1".into()).await.unwrap());
+    assert_eq!(b"1", &client.getset("counter", "2".into()).await.unwrap().unwrap()[..]);
+    assert_eq!(b"2", &client.get("counter").await.unwrap().unwrap()[..]);
+
+    client.set_expires("short", "a".into(), Duration::from_millis(50)).await.unwrap();
+    assert_eq!(b"a", &client.getset("short", "b".into()).await.unwrap().unwrap()[..]);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(b"b", &client.get("short").await.unwrap().unwrap()[..]);
+}
+
+/// GETEX 返回值并修改过期时间，没有选项时与 GET 相同
+#[tokio::test]
+async fn getex() {
+    let (addr, _) = start_server().await;
+    let mut client = Client::connect(addr).await.unwrap();
+
+    // 键不存在
+    assert_eq!(None, client.getex("missing", Some(Expiry::Persist)).await.unwrap());
+
+    // 没有选项时不修改过期时间
+    client.set_expires("short", "value".into(), Duration::from_millis(100)).await.unwrap();
+    assert_eq!(b"value", &client.getex("short", None).await.unwrap().unwrap()[..]);
+
+    // 给没有过期时间的键设置过期时间
+    client.set("hello", "world".into()).await.unwrap();
+    let expiry = Some(Expiry::In(Duration::from_millis(100)));
+    assert_eq!(b"world", &client.getex("hello", expiry).await.unwrap().unwrap()[..]);
+
+    // PERSIST 清除过期时间；键没有过期时间时什么都不做
+    client.set_expires("kept", "value".into(), Duration::from_millis(100)).await.unwrap();
+    assert_eq!(b"value", &client.getex("kept", Some(Expiry::Persist)).await.unwrap().unwrap()[..]);
+    client.set("plain", "value".into()).await.unwrap();
+    assert_eq!(b"value", &client.getex("plain", Some(Expiry::Persist)).await.unwrap().unwrap()[..]);
+
+    // 已经过去的 EXAT 删除键，但仍然返回它的值
+    client.set("past", "value".into()).await.unwrap();
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+    let expiry = Some(Expiry::At(now.as_secs() - 10));
+    assert_eq!(b"value", &client.getex("past", expiry).await.unwrap().unwrap()[..]);
+    assert!(client.get("past").await.unwrap().is_none());
+
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert!(client.get("short").await.unwrap().is_none());
+    assert!(client.get("hello").await.unwrap().is_none());
+    assert_eq!(b"value", &client.get("kept").await.unwrap().unwrap()[..]);
+    assert_eq!(b"value", &client.get("plain").await.unwrap().unwrap()[..]);
+
+    client.lpush("list", vec!["a".into()]).await.unwrap();
+    let err = client.getex("list", None).await.unwrap_err();
+    assert!(err.to_string().starts_with("WRONGTYPE"));
+}
+
+/// APPEND 在键不存在时创建它，返回追加后的长度；STRLEN 返回值的长度
+#[tokio::test]
+async fn append_strlen() {
+    let (addr, _) = start_server().await;
+    let mut client = Client::connect(addr).await.unwrap();
+
+    assert_eq!(0, client.strlen("greeting").await.unwrap());
+    assert_eq!(5, client.append("greeting", "hello".into()).await.unwrap());
+    assert_eq!(11, client.append("greeting", " world".into()).await.unwrap());
+    assert_eq!(11, client.strlen("greeting").await.unwrap());
+    assert_eq!(b"hello world", &client.get("greeting").await.unwrap().unwrap()[..]);
+}
+
+/// INCRBY 和 DECRBY 在原有的整数值上修改，键不存在时从 0 开始，并保留过期时间
+#[tokio::test]
+async fn incr_by_decr_by() {
+    let (addr, _) = start_server().await;
+    let mut client = Client::connect(addr).await.unwrap();
+
+    assert_eq!(5, client.incr_by("counter", 5).await.unwrap());
+    assert_eq!(-2, client.decr_by("counter", 7).await.unwrap());
+    assert_eq!(b"-2", &client.get("counter").await.unwrap().unwrap()[..]);
+
+    client.set_expires("short", "10".into(), Duration::from_millis(100)).await.unwrap();
+    assert_eq!(11, client.incr_by("short", 1).await.unwrap());
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert!(client.get("short").await.unwrap().is_none());
+}
+
+/// 无法完成的 INCRBY 回复错误，不修改键，也不关闭连接
+#[tokio::test]
+async fn incr_by_errors() {
+    let (addr, _) = start_server().await;
+    let mut client = Client::connect(addr).await.unwrap();
+
+    client.set("max", i64::MAX.to_string().into()).await.unwrap();
+    let err = client.incr_by("max", 1).await.unwrap_err();
+    assert_eq!("ERR increment or decrement would overflow", err.to_string());
+    let err = client.decr_by("max", i64::MIN).await.unwrap_err();
+    assert_eq!("ERR increment or decrement would overflow", err.to_string());
+    assert_eq!(i64::MAX.to_string().as_bytes(), &client.get("max").await.unwrap().unwrap()[..]);
+
+    client.set("name", "alice".into()).await.unwrap();
+    let err = client.incr_by("name", 1).await.unwrap_err();
+    assert_eq!("ERR value is not an integer or out of range", err.to_string());
+
+    client.lpush("list", vec!["a".into()]).await.unwrap();
+    let err = client.incr_by("list", 1).await.unwrap_err();
+    assert!(err.to_string().starts_with("WRONGTYPE"));
+
+    // 连接仍然可用
+    assert_eq!(1, client.incr_by("counter", 1).await.unwrap());
+}
+
+/// INCRBYFLOAT 返回新的值，值不是数字时回复错误
+#[tokio::test]
+async fn incr_by_float() {
+    let (addr, _) = start_server().await;
+    let mut client = Client::connect(addr).await.unwrap();
+
+    assert_eq!(10.5, client.incr_by_float("price", 10.5).await.unwrap());
+    assert_eq!(3.0, client.incr_by_float("price", -7.5).await.unwrap());
+    assert_eq!(b"3", &client.get("price").await.unwrap().unwrap()[..]);
+
+    client.set("name", "alice".into()).await.unwrap();
+    let err = client.incr_by_float("name", 1.0).await.unwrap_err();
+    assert_eq!("ERR value is not a valid float", err.to_string());
+
+    client.set("big", f64::MAX.to_string().into()).await.unwrap();
+    let err = client.incr_by_float("big", f64::MAX).await.unwrap_err();
+    assert_eq!("ERR increment would produce NaN or Infinity", err.to_string());
+}
+
+/// GETRANGE 支持负数索引，超出范围的索引被截断而不是报错
+#[tokio::test]
+async fn get_range() {
+    let (addr, _) = start_server().await;
+    let mut client = Client::connect(addr).await.unwrap();
+
+    // 不存在的键返回空值
+    assert!(client.get_range("missing", 0, -1).await.unwrap().is_empty());
+
+    client.set("greeting", "hello world".into()).await.unwrap();
+    assert_eq!(b"hello", &client.get_range("greeting", 0, 4).await.unwrap()[..]);
+    assert_eq!(b"world", &client.get_range("greeting", -5, -1).await.unwrap()[..]);
+    assert_eq!(b"hello world", &client.get_range("greeting", -100, 100).await.unwrap()[..]);
+    assert!(client.get_range("greeting", 5, 2).await.unwrap().is_empty());
+    assert!(client.get_range("greeting", 20, 30).await.unwrap().is_empty());
+}
+
+/// SETRANGE 覆盖值的一部分，偏移量超过末尾时用零字节填充
+#[tokio::test]
+async fn set_range() {
+    let (addr, _) = start_server().await;
+    let mut client = Client::connect(addr).await.unwrap();
+
+    // 空值不会创建键
+    assert_eq!(0, client.set_range("missing", 5, "".into()).await.unwrap());
+    assert_eq!(None, client.get("missing").await.unwrap());
+
+    client.set("greeting", "hello world".into()).await.unwrap();
+    assert_eq!(11, client.set_range("greeting", 6, "redis".into()).await.unwrap());
+    assert_eq!(b"hello redis", &client.get("greeting").await.unwrap().unwrap()[..]);
+
+    assert_eq!(8, client.set_range("padded", 5, "abc".into()).await.unwrap());
+    assert_eq!(b"\0\0\0\0\0abc", &client.get("padded").await.unwrap().unwrap()[..]);
+
+    let err = client.set_range("greeting", -1, "x".into()).await.unwrap_err();
+    assert_eq!("ERR offset is out of range", err.to_string());
+}
+
+/// INFO 以 `field:value` 行报告连接数、键数和活动频道数
+#[tokio::test]
+async fn info() {
+    let (addr, _) = start_server().await;
+
+    let subscriber = Client::connect(addr).await.unwrap();
+    let _subscriber = subscriber.subscribe(vec!["news".into()]).await.unwrap();
+
+    let mut client = Client::connect(addr).await.unwrap();
+    client.set("a", "1".into()).await.unwrap();
+    client.set("b", "2".into()).await.unwrap();
+
+    let info = client.info().await.unwrap();
+    let field = |name: &str| {
+        info.lines()
+            .find_map(|line| line.strip_prefix(name)?.strip_prefix(':'))
+            .map(str::to_string)
+    };
+    assert_eq!(Some("2"), field("connected_clients").as_deref());
+    assert_eq!(Some("2"), field("db_keys").as_deref());
+    assert_eq!(Some("1"), field("pubsub_channels").as_deref());
+    assert!(field("uptime_in_seconds").is_some());
+    assert!(info.lines().any(|line| line == "# Server"));
+}
+
+/// INFO 报告所有连接读取和写入的总字节数
+#[tokio::test]
+async fn info_net_bytes() {
+    let (addr, _) = start_server().await;
+    let mut client = Client::connect(addr).await.unwrap();
+
+    let field = |info: &str, name: &str| -> u64 {
+        info.lines()
+            .find_map(|line| line.strip_prefix(name)?.strip_prefix(':'))
+            .unwrap()
+            .parse()
+            .unwrap()
+    };
+    let info = client.info().await.unwrap();
+    let (input, output) = (field(&info, "total_net_input_bytes"), field(&info, "total_net_output_bytes"));
+
+    let value = Bytes::from(vec![b'x'; 1000]);
+    client.set("big", value.clone()).await.unwrap();
+    assert_eq!(Some(value), client.get("big").await.unwrap());
+
+    // 计数器在每批请求处理完之后更新，`SET` 和 `GET` 都已经计入
+    let info = client.info().await.unwrap();
+    assert!(field(&info, "total_net_input_bytes") >= input + 1000);
+    assert!(field(&info, "total_net_output_bytes") >= output + 1000);
+}
+
+/// 订阅者跳过服务器推送的保活 `ping`，只收到发布的消息
+#[tokio::test]
+async fn subscriber_skips_keepalive() {
+    let addr = start_server_with_config(ServerConfig {
+        keepalive_interval: Some(Duration::from_millis(20)),
+        ..Default::default()
+    })
+    .await;
+
+    let client = Client::connect(addr).await.unwrap();
+    let mut subscriber = client.subscribe(vec!["hello".into()]).await.unwrap();
+
+    // 等待几次保活推送之后再发布消息，并且保活推送可能插在订阅确认之前
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    subscriber.subscribe(&["world".into()]).await.unwrap();
+
+    let mut client = Client::connect(addr).await.unwrap();
+    client.publish("hello", "1".into()).await.unwrap();
+
+    let message = subscriber.next_message().await.unwrap().unwrap();
+    assert_eq!("hello", &message.channel);
+    assert_eq!(b"1", &message.content[..]);
+}
+
+/// 执行时间达到阈值的命令被记录到慢日志，最新的在前面，太长的参数被截断
+#[tokio::test]
+async fn slowlog_records_slow_commands() {
+    // 阈值为零时记录所有命令
+    let addr = start_server_with_config(ServerConfig {
+        slowlog_threshold: Some(Duration::ZERO),
+        ..Default::default()
+    })
+    .await;
+    let mut client = Client::connect(addr).await.unwrap();
+
+    client.set_name("slow").await.unwrap();
+    client.set("big", Bytes::from(vec![b'x'; 200])).await.unwrap();
+    client.get("big").await.unwrap();
+
+    let entries = client.slowlog_get(Some(2)).await.unwrap();
+    assert_eq!(2, entries.len());
+    assert_eq!(vec![Bytes::from("get"), Bytes::from("big")], entries[0].args);
+    assert_eq!("slow", entries[0].name);
+    assert!(entries[0].id > entries[1].id);
+
+    let value = &entries[1].args[2];
+    assert!(value.ends_with(b"... (72 more bytes)"));
+    assert_eq!(&[b'x'; 128][..], &value[..128]);
+
+    // RESET 之后只剩下 RESET 自己
+    client.slowlog_reset().await.unwrap();
+    let entries = client.slowlog_get(None).await.unwrap();
+    assert_eq!(1, entries.len());
+    assert_eq!(vec![Bytes::from("slowlog"), Bytes::from("reset")], entries[0].args);
+}
+
+/// 执行得快的命令不会被记录
+#[tokio::test]
+async fn slowlog_ignores_fast_commands() {
+    let addr = start_server_with_config(ServerConfig {
+        slowlog_threshold: Some(Duration::from_secs(3600)),
+        ..Default::default()
+    })
+    .await;
+    let mut client = Client::connect(addr).await.unwrap();
+
+    client.set("hello", "world".into()).await.unwrap();
+    client.get("hello").await.unwrap();
+    assert_eq!(0, client.slowlog_len().await.unwrap());
+}
+
+/// DBSIZE 不计入已经过期的键
+#[tokio::test]
+async fn dbsize() {
+    let (addr, _) = start_server().await;
+    let mut client = Client::connect(addr).await.unwrap();
+
+    assert_eq!(0, client.dbsize().await.unwrap());
+
+    client.set("a", "1".into()).await.unwrap();
+    client.set("b", "2".into()).await.unwrap();
+    client.set_expires("c", "3".into(), Duration::from_millis(1)).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(20)).await;
+
+    assert_eq!(2, client.dbsize().await.unwrap());
+}
+
+/// FLUSHDB 删除所有键，包括带有过期时间的键，之后可以正常写入和过期
+#[tokio::test]
+async fn flushdb() {
+    let (addr, _) = start_server().await;
+    let mut client = Client::connect(addr).await.unwrap();
+
+    client.set("a", "1".into()).await.unwrap();
+    client.set_expires("b", "2".into(), Duration::from_millis(50)).await.unwrap();
+    client.flushdb().await.unwrap();
+
+    assert_eq!(0, client.dbsize().await.unwrap());
+    assert_eq!(None, client.get("a").await.unwrap());
+
+    // 后台清理任务不受影响
+    client.set_expires("c", "3".into(), Duration::from_millis(50)).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(150)).await;
+    assert_eq!(None, client.get("c").await.unwrap());
+}
+
+/// FLUSHALL 删除所有数据库中的键，之后后台清理任务照常工作
+#[tokio::test]
+async fn flushall() {
+    let (addr, _) = start_server().await;
+    let mut client = Client::connect(addr).await.unwrap();
+
+    client.set("a", "1".into()).await.unwrap();
+    client.set_expires("b", "2".into(), Duration::from_millis(50)).await.unwrap();
+    client.select(1).await.unwrap();
+    client.set("c", "3".into()).await.unwrap();
+    client.set_expires("d", "4".into(), Duration::from_millis(50)).await.unwrap();
+
+    client.flushall().await.unwrap();
+
+    assert_eq!(0, client.dbsize().await.unwrap());
+    client.select(0).await.unwrap();
+    assert_eq!(0, client.dbsize().await.unwrap());
+
+    // 后台清理任务不受影响
+    client.set_expires("e", "5".into(), Duration::from_millis(50)).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(150)).await;
+    assert_eq!(None, client.get("e").await.unwrap());
+}
+
+/// TYPE 对字符串键返回 `string`，对不存在的键返回 `none`
+#[tokio::test]
+async fn type_of() {
+    let (addr, _) = start_server().await;
+    let mut client = Client::connect(addr).await.unwrap();
+
+    assert_eq!("none", client.type_of("hello").await.unwrap());
+    client.set("hello", "world".into()).await.unwrap();
+    assert_eq!("string", client.type_of("hello").await.unwrap());
+}
+
+/// OBJECT ENCODING 按值的类型和大小返回编码名称，键不存在时返回错误
+#[tokio::test]
+async fn object_encoding() {
+    let (addr, _) = start_server().await;
+    let mut client = Client::connect(addr).await.unwrap();
+
+    assert!(client.object_encoding("missing").await.is_err());
+
+    client.set("short", "hello".into()).await.unwrap();
+    assert_eq!("embstr", client.object_encoding("short").await.unwrap());
+    client.set("long", Bytes::from(vec![b'x'; 45])).await.unwrap();
+    assert_eq!("raw", client.object_encoding("long").await.unwrap());
+
+    client.rpush("list", vec!["a".into()]).await.unwrap();
+    assert_eq!("listpack", client.object_encoding("list").await.unwrap());
+    let values = (0..128).map(|i| Bytes::from(i.to_string())).collect();
+    client.rpush("list", values).await.unwrap();
+    assert_eq!("quicklist", client.object_encoding("list").await.unwrap());
+}
+
+/// DEBUG OBJECT 报告值的字节数，容器类型是所有元素的字节数之和，键不存在时返回错误
+#[tokio::test]
+async fn debug_serialized_length() {
+    let (addr, _) = start_server().await;
+    let mut client = Client::connect(addr).await.unwrap();
+
+    assert!(client.debug_serialized_length("missing").await.is_err());
+
+    client.set("hello", "world".into()).await.unwrap();
+    assert_eq!(5, client.debug_serialized_length("hello").await.unwrap());
+
+    client.rpush("list", vec!["a".into(), "bcd".into()]).await.unwrap();
+    assert_eq!(4, client.debug_serialized_length("list").await.unwrap());
+}
+
+/// 列表两端的推入和弹出，弹空后键被删除
+#[tokio::test]
+async fn list_push_pop() {
+    let (addr, _) = start_server().await;
+    let mut client = Client::connect(addr).await.unwrap();
+
+    assert_eq!(2, client.lpush("list", vec!["b".into(), "a".into()]).await.unwrap());
+    assert_eq!(3, client.rpush("list", vec!["c".into()]).await.unwrap());
+    assert_eq!(3, client.llen("list").await.unwrap());
+    assert_eq!("list", client.type_of("list").await.unwrap());
+
+    assert_eq!(b"a", &client.lpop("list").await.unwrap().unwrap()[..]);
+    assert_eq!(b"c", &client.rpop("list").await.unwrap().unwrap()[..]);
+    assert_eq!(b"b", &client.lpop("list").await.unwrap().unwrap()[..]);
+    assert_eq!(None, client.lpop("list").await.unwrap());
+
+    assert_eq!(0, client.llen("list").await.unwrap());
+    assert_eq!("none", client.type_of("list").await.unwrap());
+}
+
+/// 带 count 的 LPOP/RPOP 最多弹出 count 个元素，键不存在时返回 nil 数组
+#[tokio::test]
+async fn list_pop_count() {
+    let (addr, _) = start_server().await;
+    let mut client = Client::connect(addr).await.unwrap();
+
+    let values: Vec<Bytes> = vec!["a".into(), "b".into(), "c".into(), "d".into()];
+    client.rpush("list", values).await.unwrap();
+
+    let popped = client.lpop_count("list", 2).await.unwrap().unwrap();
+    assert_eq!(vec!["a", "b"], popped);
+    let popped = client.rpop_count("list", 1).await.unwrap().unwrap();
+    assert_eq!(vec!["d"], popped);
+
+    // count 比列表长时返回所有元素，弹空后键被删除
+    let popped = client.lpop_count("list", 10).await.unwrap().unwrap();
+    assert_eq!(vec!["c"], popped);
+    assert_eq!("none", client.type_of("list").await.unwrap());
+
+    // count 恰好等于列表长度
+    client.rpush("list", vec!["a".into(), "b".into()]).await.unwrap();
+    let popped = client.rpop_count("list", 2).await.unwrap().unwrap();
+    assert_eq!(vec!["b", "a"], popped);
+    assert_eq!(0, client.llen("list").await.unwrap());
+
+    assert_eq!(None, client.lpop_count("missing", 2).await.unwrap());
+    assert_eq!(None, client.rpop_count("missing", 2).await.unwrap());
+
+    client.set("string", "value".into()).await.unwrap();
+    let err = client.lpop_count("string", 2).await.unwrap_err();
+    assert_eq!("WRONGTYPE Operation against a key holding the wrong kind of value", err.to_string());
+}
+
+/// LRANGE 支持负数索引，超出范围的索引被截断
+#[tokio::test]
+async fn lrange() {
+    let (addr, _) = start_server().await;
+    let mut client = Client::connect(addr).await.unwrap();
+
+    assert!(client.lrange("list", 0, -1).await.unwrap().is_empty());
+
+    let values: Vec<Bytes> = vec!["a".into(), "b".into(), "c".into(), "d".into()];
+    client.rpush("list", values.clone()).await.unwrap();
+
+    assert_eq!(values, client.lrange("list", 0, -1).await.unwrap());
+    assert_eq!(values[1..3], client.lrange("list", 1, 2).await.unwrap());
+    assert_eq!(values[2..], client.lrange("list", -2, -1).await.unwrap());
+    assert_eq!(values, client.lrange("list", -100, 100).await.unwrap());
+    assert!(client.lrange("list", 3, 1).await.unwrap().is_empty());
+    assert!(client.lrange("list", 10, 20).await.unwrap().is_empty());
+    assert!(client.lrange("list", 0, -10).await.unwrap().is_empty());
+}
+
+/// BLPOP 阻塞到另一个客户端推入列表
+#[tokio::test]
+async fn blpop_wakes_on_push() {
+    let (addr, _) = start_server().await;
+
+    let popper = tokio::spawn(async move {
+        let mut client = Client::connect(addr).await.unwrap();
+        client
+            .blpop(&["empty".into(), "jobs".into()], Some(Duration::from_secs(5)))
+            .await
+            .unwrap()
+    });
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    let mut client = Client::connect(addr).await.unwrap();
+    client.lpush("jobs", vec!["job-1".into()]).await.unwrap();
+
+    let (key, value) = popper.await.unwrap().unwrap();
+    assert_eq!("jobs", key);
+    assert_eq!(b"job-1", &value[..]);
+    assert_eq!(0, client.llen("jobs").await.unwrap());
+}
+
+/// BLPOP 超时后返回空值；列表非空时 BRPOP 立即返回
+#[tokio::test]
+async fn blpop_timeout() {
+    let (addr, _) = start_server().await;
+    let mut client = Client::connect(addr).await.unwrap();
+
+    let res = client.blpop(&["jobs".into()], Some(Duration::from_millis(50))).await.unwrap();
+    assert_eq!(None, res);
+
+    client.rpush("jobs", vec!["a".into(), "b".into()]).await.unwrap();
+    let (_, value) = client.brpop(&["jobs".into()], None).await.unwrap().unwrap();
+    assert_eq!(b"b", &value[..]);
+}
+
+/// 对保存了其他类型的键执行命令时回复 WRONGTYPE，连接仍然可用
+#[tokio::test]
+async fn list_wrong_type() {
+    let (addr, _) = start_server().await;
+    let mut client = Client::connect(addr).await.unwrap();
+
+    const WRONGTYPE: &str = "WRONGTYPE Operation against a key holding the wrong kind of value";
+
+    client.set("string", "value".into()).await.unwrap();
+    let err = client.lpush("string", vec!["a".into()]).await.unwrap_err();
+    assert_eq!(WRONGTYPE, err.to_string());
+    let err = client.rpop("string").await.unwrap_err();
+    assert_eq!(WRONGTYPE, err.to_string());
+
+    client.rpush("list", vec!["a".into()]).await.unwrap();
+    let err = client.get("list").await.unwrap_err();
+    assert_eq!(WRONGTYPE, err.to_string());
+    let err = client.append("list", "b".into()).await.unwrap_err();
+    assert_eq!(WRONGTYPE, err.to_string());
+
+    // SET 覆盖任何类型的值
+    client.set("list", "value".into()).await.unwrap();
+    assert_eq!("string", client.type_of("list").await.unwrap());
+}
+
+#[tokio::test]
+async fn hash_commands() {
+    let (addr, _) = start_server().await;
+    let mut client = Client::connect(addr).await.unwrap();
+
+    let fields = vec![("a".to_string(), "1".into()), ("b".to_string(), "2".into())];
+    assert_eq!(2, client.hset("hash", fields).await.unwrap());
+    // 覆盖已有字段不计入新增数
+    let fields = vec![("b".to_string(), "3".into()), ("c".to_string(), "4".into())];
+    assert_eq!(1, client.hset("hash", fields).await.unwrap());
+    assert_eq!("hash", client.type_of("hash").await.unwrap());
+
+    assert_eq!(Some("3".into()), client.hget("hash", "b").await.unwrap());
+    assert_eq!(None, client.hget("hash", "missing").await.unwrap());
+    assert_eq!(None, client.hget("missing", "a").await.unwrap());
+
+    let all = client.hgetall("hash").await.unwrap();
+    assert_eq!(3, all.len());
+    assert_eq!(b"1", &all["a"][..]);
+    assert_eq!(b"4", &all["c"][..]);
+
+    let fields = ["a".to_string(), "missing".to_string()];
+    assert_eq!(1, client.hdel("hash", &fields).await.unwrap());
+
+    // 删空哈希后键也被删除
+    let fields = ["b".to_string(), "c".to_string()];
+    assert_eq!(2, client.hdel("hash", &fields).await.unwrap());
+    assert_eq!("none", client.type_of("hash").await.unwrap());
+    assert!(client.hgetall("hash").await.unwrap().is_empty());
+
+    const WRONGTYPE: &str = "WRONGTYPE Operation against a key holding the wrong kind of value";
+
+    client.set("string", "value".into()).await.unwrap();
+    let err = client.hget("string", "a").await.unwrap_err();
+    assert_eq!(WRONGTYPE, err.to_string());
+    let err = client.hset("string", vec![("a".to_string(), "1".into())]).await.unwrap_err();
+    assert_eq!(WRONGTYPE, err.to_string());
+
+    client.hset("hash", vec![("a".to_string(), "1".into())]).await.unwrap();
+    let err = client.get("hash").await.unwrap_err();
+    assert_eq!(WRONGTYPE, err.to_string());
+    let err = client.lpush("hash", vec!["a".into()]).await.unwrap_err();
+    assert_eq!(WRONGTYPE, err.to_string());
+}
+
+/// HINCRBY 在不存在的字段上从 0 开始累加，结果可以用 HGET 读取
+#[tokio::test]
+async fn hincrby() {
+    let (addr, _) = start_server().await;
+    let mut client = Client::connect(addr).await.unwrap();
+
+    assert_eq!(5, client.hincrby("counters", "visits", 5).await.unwrap());
+    assert_eq!(3, client.hincrby("counters", "visits", -2).await.unwrap());
+    assert_eq!(Some("3".into()), client.hget("counters", "visits").await.unwrap());
+    assert_eq!("hash", client.type_of("counters").await.unwrap());
+
+    client.hset("counters", vec![("name".to_string(), "abc".into())]).await.unwrap();
+    let err = client.hincrby("counters", "name", 1).await.unwrap_err();
+    assert_eq!("ERR hash value is not an integer", err.to_string());
+
+    client.hincrby("counters", "max", i64::MAX).await.unwrap();
+    let err = client.hincrby("counters", "max", 1).await.unwrap_err();
+    assert_eq!("ERR increment or decrement would overflow", err.to_string());
+
+    client.set("string", "value".into()).await.unwrap();
+    let err = client.hincrby("string", "visits", 1).await.unwrap_err();
+    assert_eq!("WRONGTYPE Operation against a key holding the wrong kind of value", err.to_string());
+}
+
+#[tokio::test]
+async fn set_commands() {
+    let addr = start_server_with_config(ServerConfig {
+        sorted_replies: true,
+        ..Default::default()
+    })
+    .await;
+    let mut client = Client::connect(addr).await.unwrap();
+
+    assert_eq!(2, client.sadd("set", vec!["a".into(), "b".into()]).await.unwrap());
+    // 重复添加已有成员不计入新增数
+    assert_eq!(0, client.sadd("set", vec!["a".into()]).await.unwrap());
+    assert_eq!(1, client.sadd("set", vec!["b".into(), "c".into(), "c".into()]).await.unwrap());
+    assert_eq!("set", client.type_of("set").await.unwrap());
+
+    assert!(client.sismember("set", "a".into()).await.unwrap());
+    assert!(!client.sismember("set", "missing".into()).await.unwrap());
+    assert!(!client.sismember("missing", "a".into()).await.unwrap());
+
+    assert_eq!(vec!["a", "b", "c"], client.smembers("set").await.unwrap());
+
+    assert_eq!(1, client.srem("set", vec!["a".into(), "missing".into()]).await.unwrap());
+    assert!(!client.sismember("set", "a".into()).await.unwrap());
+
+    // 删空集合后键也被删除
+    assert_eq!(2, client.srem("set", vec!["b".into(), "c".into()]).await.unwrap());
+    assert_eq!("none", client.type_of("set").await.unwrap());
+    assert!(client.smembers("set").await.unwrap().is_empty());
+
+    const WRONGTYPE: &str = "WRONGTYPE Operation against a key holding the wrong kind of value";
+
+    client.set("string", "value".into()).await.unwrap();
+    let err = client.sadd("string", vec!["a".into()]).await.unwrap_err();
+    assert_eq!(WRONGTYPE, err.to_string());
+    let err = client.sismember("string", "a".into()).await.unwrap_err();
+    assert_eq!(WRONGTYPE, err.to_string());
+}
+
+/// SINTER、SUNION 和 SDIFF 组合多个集合，不存在的键视为空集合，不是集合的键返回 WRONGTYPE
+#[tokio::test]
+async fn set_operations() {
+    let addr = start_server_with_config(ServerConfig {
+        sorted_replies: true,
+        ..Default::default()
+    })
+    .await;
+    let mut client = Client::connect(addr).await.unwrap();
+
+    client.sadd("a", vec!["1".into(), "2".into(), "3".into()]).await.unwrap();
+    client.sadd("b", vec!["2".into(), "3".into(), "4".into()]).await.unwrap();
+    client.sadd("c", vec!["5".into()]).await.unwrap();
+
+    let keys = |keys: &[&str]| keys.iter().map(|key| key.to_string()).collect::<Vec<_>>();
+
+    // 有重叠的集合
+    assert_eq!(vec!["2", "3"], client.sinter(&keys(&["a", "b"])).await.unwrap());
+    assert_eq!(vec!["1", "2", "3", "4"], client.sunion(&keys(&["a", "b"])).await.unwrap());
+    assert_eq!(vec!["1"], client.sdiff(&keys(&["a", "b"])).await.unwrap());
+    assert_eq!(vec!["4"], client.sdiff(&keys(&["b", "a"])).await.unwrap());
+
+    // 不相交的集合
+    assert!(client.sinter(&keys(&["a", "c"])).await.unwrap().is_empty());
+    assert_eq!(vec!["1", "2", "3", "5"], client.sunion(&keys(&["a", "c"])).await.unwrap());
+    assert_eq!(vec!["1", "2", "3"], client.sdiff(&keys(&["a", "c"])).await.unwrap());
+
+    // 不存在的键视为空集合
+    assert!(client.sinter(&keys(&["a", "missing"])).await.unwrap().is_empty());
+    assert_eq!(vec!["5"], client.sunion(&keys(&["c", "missing"])).await.unwrap());
+    assert!(client.sdiff(&keys(&["missing", "a"])).await.unwrap().is_empty());
+    assert_eq!(vec!["1", "2", "3"], client.sinter(&keys(&["a"])).await.unwrap());
+
+    client.set("string", "value".into()).await.unwrap();
+    let err = client.sunion(&keys(&["a", "string"])).await.unwrap_err();
+    assert_eq!("WRONGTYPE Operation against a key holding the wrong kind of value", err.to_string());
+}
+
+#[tokio::test]
+async fn scan() {
+    let (addr, _) = start_server().await;
+    let mut client = Client::connect(addr).await.unwrap();
+
+    for i in 0..25 {
+        client.set(&format!("key:{}", i), "v".into()).await.unwrap();
+    }
+    client.set("other", "v".into()).await.unwrap();
+
+    // 按默认批次大小迭代，每个键恰好返回一次
+    let mut seen = vec![];
+    let mut cursor = 0;
+    let mut calls = 0;
+    loop {
+        let (next, keys) = client.scan(cursor, None, None).await.unwrap();
+        assert!(keys.len() <= 10);
+        seen.extend(keys);
+        calls += 1;
+        if next == 0 {
+            break;
+        }
+        cursor = next;
+    }
+    assert_eq!(3, calls);
+    seen.sort();
+    seen.dedup();
+    assert_eq!(26, seen.len());
+
+    // MATCH 过滤键，COUNT 控制批次大小
+    let (next, keys) = client.scan(0, Some("key:*"), Some(100)).await.unwrap();
+    assert_eq!(0, next);
+    assert_eq!(25, keys.len());
+    assert!(!keys.contains(&"other".to_string()));
+
+    // 超出范围的游标直接结束迭代
+    assert_eq!((0, vec![]), client.scan(1000, None, None).await.unwrap());
+}
+
+/// 遍历大量元素的命令超过时间限制时放弃并回复错误，处理少量元素的命令不受影响
+#[tokio::test]
+async fn command_time_limit() {
+    let addr = start_server_with_config(ServerConfig {
+        command_time_limit: Some(Duration::from_micros(1)),
+        ..Default::default()
+    })
+    .await;
+    let mut client = Client::connect(addr).await.unwrap();
+
+    let members = (0..100_000).map(|i| Bytes::from(i.to_string())).collect();
+    client.sadd("big", members).await.unwrap();
+    let mut pipeline = client.pipeline();
+    for i in 0..5000 {
+        pipeline.set(&format!("key:{}", i), "v".into());
+    }
+    pipeline.execute().await.unwrap();
+
+    let err = client.sunion(&["big".to_string()]).await.unwrap_err();
+    assert_eq!("ERR command exceeded time limit", err.to_string());
+    let err = client.sinter(&["big".to_string(), "big".to_string()]).await.unwrap_err();
+    assert_eq!("ERR command exceeded time limit", err.to_string());
+    let err = client.scan(0, None, None).await.unwrap_err();
+    assert_eq!("ERR command exceeded time limit", err.to_string());
+
+    // 连接仍然可用，小的集合和键空间不受影响
+    client.sadd("small", vec!["a".into(), "b".into()]).await.unwrap();
+    assert_eq!(2, client.sunion(&["small".to_string()]).await.unwrap().len());
+    client.select(1).await.unwrap();
+    client.set("hello", "world".into()).await.unwrap();
+    assert_eq!((0, vec!["hello".to_string()]), client.scan(0, None, None).await.unwrap());
+}
+
+/// 使用给定的配置启动服务器
+async fn start_server_with_config(config: ServerConfig) -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move { server::run_with_config(listener, tokio::signal::ctrl_c(), config).await });
+
+    addr
+}
+
 /// 启动服务器
 async fn start_server() -> (SocketAddr, JoinHandle<()>) {
     let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
@@ -0,0 +1,300 @@
+mod support;
+use support::SlowStream;
+
+use mini_redis::{Connection, Frame, Protocol, StreamedFrame};
+
+use bytes::Bytes;
+use tokio::io::{self, AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::time::{self, Duration};
+
+/// 嵌套数组经过编码和解码后保持结构不变。
+#[tokio::test]
+async fn nested_array_round_trip() {
+    let (mut tx, mut rx) = connection_pair().await;
+
+    let frame = Frame::Array(vec![
+        Frame::Array(vec![
+            Frame::Bulk(Bytes::from_static(b"field")),
+            Frame::Array(vec![Frame::Integer(1), Frame::Null]),
+        ]),
+        Frame::Array(vec![]),
+        Frame::Bulk(Bytes::from_static(b"value")),
+        Frame::Simple("OK".to_string()),
+    ]);
+    tx.write_frame(&frame).await.unwrap();
+
+    let received = rx.read_frame().await.unwrap().unwrap();
+    assert_eq!(frame, received);
+}
+
+/// 负整数经过编码和解码后保持不变。
+#[tokio::test]
+async fn negative_integer_round_trip() {
+    let (mut tx, mut rx) = connection_pair().await;
+
+    tx.write_frame(&Frame::Integer(-2)).await.unwrap();
+    tx.write_frame(&Frame::Array(vec![Frame::Integer(i64::MIN), Frame::Integer(-1)]))
+        .await
+        .unwrap();
+
+    assert_eq!(Frame::Integer(-2), rx.read_frame().await.unwrap().unwrap());
+    assert_eq!(
+        Frame::Array(vec![Frame::Integer(i64::MIN), Frame::Integer(-1)]),
+        rx.read_frame().await.unwrap().unwrap()
+    );
+}
+
+/// RESP3 的 map、double 和 boolean 经过编码和解码后保持不变，map 也可以嵌套在数组中。
+#[tokio::test]
+async fn resp3_round_trip() {
+    let (mut tx, mut rx) = connection_pair().await;
+    tx.set_protocol(Protocol::Resp3);
+
+    let frames = [
+        Frame::Map(vec![
+            (Frame::Bulk(Bytes::from_static(b"proto")), Frame::Integer(3)),
+            (Frame::Simple("nested".to_string()), Frame::Map(vec![])),
+        ]),
+        Frame::Double(1.5),
+        Frame::Double(-0.25),
+        Frame::Double(f64::INFINITY),
+        Frame::Double(f64::NEG_INFINITY),
+        Frame::Boolean(true),
+        Frame::Boolean(false),
+        Frame::Array(vec![
+            Frame::Map(vec![(Frame::Bulk(Bytes::from_static(b"key")), Frame::Boolean(true))]),
+            Frame::Double(2.0),
+        ]),
+    ];
+    for frame in &frames {
+        tx.write_frame(frame).await.unwrap();
+    }
+    for frame in &frames {
+        assert_eq!(*frame, rx.read_frame().await.unwrap().unwrap());
+    }
+
+    // NaN 不等于自身，单独检查
+    tx.write_frame(&Frame::Double(f64::NAN)).await.unwrap();
+    match rx.read_frame().await.unwrap().unwrap() {
+        Frame::Double(value) => assert!(value.is_nan()),
+        frame => panic!("unexpected frame {:?}", frame),
+    }
+}
+
+/// RESP2 连接上 RESP3 类型被转换为等效的 RESP2 类型。
+#[tokio::test]
+async fn resp3_types_downgraded_on_resp2() {
+    let (mut tx, mut rx) = connection_pair().await;
+    assert_eq!(Protocol::Resp2, tx.protocol());
+
+    let map = Frame::Map(vec![(Frame::Bulk(Bytes::from_static(b"proto")), Frame::Integer(2))]);
+    tx.write_frame(&map).await.unwrap();
+    tx.write_frame(&Frame::Double(1.5)).await.unwrap();
+    tx.write_frame(&Frame::Boolean(true)).await.unwrap();
+
+    assert_eq!(
+        Frame::Array(vec![Frame::Bulk(Bytes::from_static(b"proto")), Frame::Integer(2)]),
+        rx.read_frame().await.unwrap().unwrap()
+    );
+    assert_eq!(Frame::Bulk(Bytes::from_static(b"1.5")), rx.read_frame().await.unwrap().unwrap());
+    assert_eq!(Frame::Integer(1), rx.read_frame().await.unwrap().unwrap());
+}
+
+/// 推送在 RESP3 连接上以 `>` 编码并解码为 `Push`，在 RESP2 连接上编码为普通数组。
+#[tokio::test]
+async fn push_encoding() {
+    let push = Frame::Push(vec![
+        Frame::Bulk(Bytes::from_static(b"message")),
+        Frame::Bulk(Bytes::from_static(b"hello")),
+        Frame::Bulk(Bytes::from_static(b"world")),
+    ]);
+
+    let (mut tx, mut rx) = connection_pair().await;
+    tx.set_protocol(Protocol::Resp3);
+    tx.write_frame(&push).await.unwrap();
+    assert_eq!(push, rx.read_frame().await.unwrap().unwrap());
+
+    let (mut tx, mut rx) = connection_pair().await;
+    tx.write_frame(&push).await.unwrap();
+    let Frame::Push(elements) = push else { unreachable!() };
+    assert_eq!(Frame::Array(elements), rx.read_frame().await.unwrap().unwrap());
+}
+
+/// 拆分成两次写入的帧在内存流上被正确地组装，不需要套接字。
+#[tokio::test(start_paused = true)]
+async fn frame_split_across_duplex_writes() {
+    let (mut writer, reader) = io::duplex(64);
+    let mut conn = Connection::new(reader);
+
+    writer.write_all(b"*2\r\n$3\r\nGET\r\n$5\r\nhel").await.unwrap();
+
+    // 只收到半个帧时，`read_frame` 继续等待
+    assert!(time::timeout(Duration::from_secs(1), conn.read_frame()).await.is_err());
+
+    writer.write_all(b"lo\r\n").await.unwrap();
+
+    let frame = conn.read_frame().await.unwrap().unwrap();
+    assert_eq!(
+        Frame::Array(vec![
+            Frame::Bulk(Bytes::from_static(b"GET")),
+            Frame::Bulk(Bytes::from_static(b"hello")),
+        ]),
+        frame
+    );
+
+    // 写入端关闭后返回 `None`
+    drop(writer);
+    assert!(conn.read_frame().await.unwrap().is_none());
+}
+
+/// 逐字节到达的帧仍然被正确地组装。
+#[tokio::test]
+async fn frame_assembled_from_single_bytes() {
+    let (mut slow, server) = SlowStream::pair(1, Duration::from_millis(1));
+    let mut conn = Connection::new(server);
+
+    tokio::spawn(async move {
+        slow.write_all(b"*3\r\n$3\r\nSET\r\n$5\r\nhello\r\n:42\r\n").await.unwrap();
+    });
+
+    let frame = conn.read_frame().await.unwrap().unwrap();
+    assert_eq!(
+        Frame::Array(vec![
+            Frame::Bulk(Bytes::from_static(b"SET")),
+            Frame::Bulk(Bytes::from_static(b"hello")),
+            Frame::Integer(42),
+        ]),
+        frame
+    );
+}
+
+/// 缓慢到达的过大 bulk 在其数据完全到达之前就被拒绝。
+#[tokio::test]
+async fn oversized_bulk_rejected_before_full_receipt() {
+    let (mut slow, server) = SlowStream::pair(64, Duration::from_millis(1));
+    let mut conn = Connection::new(server);
+    conn.set_max_frame_size(1024);
+
+    // 头部声明的长度超过限制，后面的数据永远不会全部发送。
+    tokio::spawn(async move {
+        let mut src = b"*2\r\n$3\r\nSET\r\n$4096\r\n".to_vec();
+        src.extend_from_slice(&[b'x'; 256]);
+        let _ = slow.write_all(&src).await;
+        time::sleep(Duration::from_secs(10)).await;
+    });
+
+    assert!(conn.read_frame().await.is_err());
+}
+
+/// 由许多小元素组成的帧在累计超过限制时被拒绝。
+#[tokio::test]
+async fn oversized_array_rejected() {
+    let (mut slow, server) = SlowStream::pair(256, Duration::from_millis(1));
+    let mut conn = Connection::new(server);
+    conn.set_max_frame_size(1024);
+
+    tokio::spawn(async move {
+        let mut src = b"*1000\r\n".to_vec();
+        for _ in 0..1000 {
+            src.extend_from_slice(b"$1\r\nx\r\n");
+        }
+        let _ = slow.write_all(&src).await;
+        // 保持管道打开，确保错误来自大小限制而不是连接关闭。
+        time::sleep(Duration::from_secs(10)).await;
+    });
+
+    assert!(conn.read_frame().await.is_err());
+}
+
+/// 内联命令被解析为等效的数组帧，空行被忽略。
+#[tokio::test]
+async fn inline_command() {
+    let (mut client, server) = socket_pair().await;
+    let mut conn = Connection::new(server);
+
+    client.write_all(b"PING\r\n\r\nSET  hello world\n*1\r\n$4\r\nPING\r\n").await.unwrap();
+
+    let ping = Frame::Array(vec![Frame::Bulk(Bytes::from("PING"))]);
+    let set = Frame::Array(vec![
+        Frame::Bulk(Bytes::from("SET")),
+        Frame::Bulk(Bytes::from("hello")),
+        Frame::Bulk(Bytes::from("world")),
+    ]);
+
+    assert_eq!(Some(ping.clone()), conn.read_frame().await.unwrap());
+    assert_eq!(Some(set), conn.read_frame().await.unwrap());
+    // 内联命令之后仍然可以发送 RESP 编码的帧
+    assert_eq!(Some(ping), conn.read_frame().await.unwrap());
+}
+
+/// 达到阈值的 bulk 字符串按块读取，不受最大帧大小的限制；读完之后连接继续读取下一个帧。
+#[tokio::test]
+async fn streaming_bulk_read() {
+    let (mut slow, server) = SlowStream::pair(1024, Duration::from_millis(1));
+    let mut conn = Connection::new(server);
+    conn.set_max_frame_size(1024);
+
+    let payload: Vec<u8> = (0..16 * 1024).map(|i| (i % 251) as u8).collect();
+    let mut src = format!("$3\r\nfoo\r\n${}\r\n", payload.len()).into_bytes();
+    src.extend_from_slice(&payload);
+    src.extend_from_slice(b"\r\n+OK\r\n");
+    tokio::spawn(async move {
+        slow.write_all(&src).await.unwrap();
+        time::sleep(Duration::from_secs(10)).await;
+    });
+
+    // 短于阈值的 bulk 字符串与 `read_frame` 一样完整地返回
+    match conn.read_frame_streaming(1024).await.unwrap().unwrap() {
+        StreamedFrame::Frame(frame) => assert_eq!(Frame::Bulk(Bytes::from_static(b"foo")), frame),
+        StreamedFrame::Bulk(_) => panic!("short bulk should not be streamed"),
+    }
+
+    let StreamedFrame::Bulk(mut reader) = conn.read_frame_streaming(1024).await.unwrap().unwrap() else {
+        panic!("long bulk should be streamed");
+    };
+    assert_eq!(payload.len(), reader.remaining());
+    let mut received = vec![];
+    let mut chunks = 0;
+    let mut chunk = [0; 4096];
+    loop {
+        let n = reader.read(&mut chunk).await.unwrap();
+        if n == 0 {
+            break;
+        }
+        received.extend_from_slice(&chunk[..n]);
+        chunks += 1;
+    }
+    assert_eq!(payload, received);
+    assert!(chunks > 1);
+
+    assert_eq!(Frame::Simple("OK".to_string()), conn.read_frame().await.unwrap().unwrap());
+}
+
+/// `peer_addr` 返回底层套接字对等方的地址。
+#[tokio::test]
+async fn peer_addr() {
+    let (client, server) = socket_pair().await;
+    let client_addr = client.local_addr().unwrap();
+    let server_addr = server.local_addr().unwrap();
+
+    assert_eq!(server_addr, Connection::new(client).peer_addr().unwrap());
+    assert_eq!(client_addr, Connection::new(server).peer_addr().unwrap());
+}
+
+/// 建立一对相互连接的 `Connection`。
+async fn connection_pair() -> (Connection, Connection) {
+    let (client, server) = socket_pair().await;
+
+    (Connection::new(client), Connection::new(server))
+}
+
+/// 建立一对相互连接的 `TcpStream`。
+async fn socket_pair() -> (TcpStream, TcpStream) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let (client, server) = tokio::join!(TcpStream::connect(addr), listener.accept());
+
+    (client.unwrap(), server.unwrap().0)
+}
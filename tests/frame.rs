@@ -0,0 +1,68 @@
+use mini_redis::Frame;
+
+use bytes::Bytes;
+use std::io::Cursor;
+
+/// `*-1\r\n` 被解析为空数组，并且只消费这 5 个字节。
+#[test]
+fn parse_null_array() {
+    let src = b"*-1\r\n:1\r\n";
+
+    let mut buf = Cursor::new(&src[..]);
+    Frame::check(&mut buf).unwrap();
+    assert_eq!(5, buf.position());
+
+    buf.set_position(0);
+    assert_eq!(Frame::NullArray, Frame::from(&mut buf));
+    assert_eq!(Frame::Integer(1), Frame::from(&mut buf));
+}
+
+/// 空数组可以作为其他数组的元素出现。
+#[test]
+fn parse_null_array_nested() {
+    let src = b"*2\r\n*-1\r\n$-1\r\n";
+
+    let mut buf = Cursor::new(&src[..]);
+    Frame::check(&mut buf).unwrap();
+
+    buf.set_position(0);
+    assert_eq!(Frame::Array(vec![Frame::NullArray, Frame::Null]), Frame::from(&mut buf));
+}
+
+/// 格式错误的 RESP3 浮点数和布尔值在检查时被拒绝，而不是在解析时 panic。
+#[test]
+fn reject_invalid_resp3_scalars() {
+    for src in [&b",abc\r\n"[..], b"#x\r\n", b"%1\r\n#t\r\n,nope\r\n"] {
+        let mut buf = Cursor::new(src);
+        assert!(Frame::check(&mut buf).is_err());
+    }
+
+    // 不完整的 map 需要更多数据
+    let mut buf = Cursor::new(&b"%1\r\n+key\r\n"[..]);
+    assert!(matches!(Frame::check(&mut buf), Err(mini_redis::FrameError::Incomplete)));
+}
+
+/// RESP3 推送 `>` 与数组一样逐个解析元素，但被解析为 `Push` 而不是 `Array`。
+#[test]
+fn parse_push() {
+    let src = b">3\r\n$7\r\nmessage\r\n$5\r\nhello\r\n$5\r\nworld\r\n:1\r\n";
+
+    let mut buf = Cursor::new(&src[..]);
+    Frame::check(&mut buf).unwrap();
+    assert_eq!(src.len() - 4, buf.position() as usize);
+
+    buf.set_position(0);
+    assert_eq!(
+        Frame::Push(vec![
+            Frame::Bulk(Bytes::from_static(b"message")),
+            Frame::Bulk(Bytes::from_static(b"hello")),
+            Frame::Bulk(Bytes::from_static(b"world")),
+        ]),
+        Frame::from(&mut buf)
+    );
+    assert_eq!(Frame::Integer(1), Frame::from(&mut buf));
+
+    // 不完整的推送需要更多数据
+    let mut buf = Cursor::new(&b">2\r\n+message\r\n"[..]);
+    assert!(matches!(Frame::check(&mut buf), Err(mini_redis::FrameError::Incomplete)));
+}
@@ -0,0 +1,45 @@
+use mini_redis::{clients::Pool, server};
+use std::net::SocketAddr;
+use tokio::net::TcpListener;
+use tokio::task::JoinHandle;
+
+/// 许多任务通过同一个连接池并发地执行 set 和 get。
+#[tokio::test]
+async fn pool_concurrent_get_set() {
+    let (addr, _) = start_server().await;
+
+    let pool = Pool::connect(addr, 4).await.unwrap();
+
+    let mut tasks = vec![];
+    for i in 0..64 {
+        let pool = pool.clone();
+        tasks.push(tokio::spawn(async move {
+            let key = format!("key{}", i);
+            let value = format!("value{}", i);
+
+            for _ in 0..10 {
+                let mut client = pool.get().await.unwrap();
+                client.set(&key, value.clone().into()).await.unwrap();
+                drop(client);
+
+                let mut client = pool.get().await.unwrap();
+                let got = client.get(&key).await.unwrap().unwrap();
+                assert_eq!(value.as_bytes(), &got[..]);
+            }
+        }));
+    }
+
+    for task in tasks {
+        task.await.unwrap();
+    }
+}
+
+/// 启动服务器
+async fn start_server() -> (SocketAddr, JoinHandle<()>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let handle = tokio::spawn(async move { server::run(listener, tokio::signal::ctrl_c()).await });
+
+    (addr, handle)
+}
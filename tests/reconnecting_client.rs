@@ -0,0 +1,50 @@
+use mini_redis::{clients::ReconnectingClient, server};
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::net::{TcpListener, ToSocketAddrs};
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+
+/// 服务器重启后，幂等命令透明地重连并重试；`PUBLISH` 返回错误，但下一次调用会重连。
+#[tokio::test]
+async fn reconnect_after_server_restart() {
+    let (addr, server) = start_server("127.0.0.1:0").await;
+
+    let mut client = ReconnectingClient::connect(addr).await.unwrap();
+    client.set_initial_backoff(Duration::from_millis(10));
+    client.set("hello", "world".into()).await.unwrap();
+
+    // 重启服务器。新服务器的数据库是空的。
+    let (addr, server) = restart_server(addr, server).await;
+
+    assert!(client.get("hello").await.unwrap().is_none());
+
+    // 再次重启。`PUBLISH` 不会被重试。
+    let (_, _server) = restart_server(addr, server).await;
+
+    assert!(client.publish("foo", "bar".into()).await.is_err());
+    assert_eq!(0, client.publish("foo", "bar".into()).await.unwrap());
+}
+
+/// 用于关闭服务器的发送器，以及服务器任务的句柄。
+type ServerHandle = (oneshot::Sender<()>, JoinHandle<()>);
+
+/// 关闭 `addr` 上的服务器并在同一地址上启动一个新服务器。
+async fn restart_server(addr: SocketAddr, server: ServerHandle) -> (SocketAddr, ServerHandle) {
+    let (tx, handle) = server;
+    tx.send(()).unwrap();
+    handle.await.unwrap();
+
+    start_server(addr).await
+}
+
+/// 启动服务器，返回它的地址以及用于关闭它的句柄。
+async fn start_server(addr: impl ToSocketAddrs) -> (SocketAddr, ServerHandle) {
+    let listener = TcpListener::bind(addr).await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let (tx, rx) = oneshot::channel::<()>();
+    let handle = tokio::spawn(async move { server::run(listener, rx).await });
+
+    (addr, (tx, handle))
+}
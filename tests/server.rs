@@ -1,8 +1,14 @@
-use mini_redis::server;
+use mini_redis::server::{self, Metrics, OverloadConfig, ServerConfig};
+use mini_redis::Frame;
 
+use std::collections::HashMap;
+use std::io::Cursor;
 use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::oneshot;
 use tokio::time::{self, Duration};
 
 /// A basic "hello world" style test. A server instance is started in a
@@ -331,6 +337,210 @@ async fn manage_subscription() {
     );
 }
 
+// Unsubscribing from the last channel returns the connection to normal mode,
+// where further unsubscribes are only acknowledged.
+#[tokio::test]
+async fn unsubscribe_all_leaves_subscribe_mode() {
+    let addr = start_server().await;
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+
+    assert_reply(
+        &mut stream,
+        b"*2\r\n$9\r\nSUBSCRIBE\r\n$5\r\nhello\r\n",
+        b"*3\r\n$9\r\nsubscribe\r\n$5\r\nhello\r\n:1\r\n",
+    )
+    .await;
+    assert_reply(
+        &mut stream,
+        b"*1\r\n$11\r\nUNSUBSCRIBE\r\n",
+        b"*3\r\n$11\r\nunsubscribe\r\n$5\r\nhello\r\n:0\r\n",
+    )
+    .await;
+
+    assert_reply(&mut stream, b"*2\r\n$3\r\nGET\r\n$5\r\nhello\r\n", b"$-1\r\n").await;
+    assert_reply(
+        &mut stream,
+        b"*2\r\n$11\r\nUNSUBSCRIBE\r\n$5\r\nhello\r\n",
+        b"*3\r\n$11\r\nunsubscribe\r\n$5\r\nhello\r\n:0\r\n",
+    )
+    .await;
+    assert_reply(&mut stream, b"*1\r\n$4\r\nPING\r\n", b"+PONG\r\n").await;
+}
+
+// A subscribed client may PING; the reply uses the subscribe-mode array format
+// and the subscription stays active afterwards.
+#[tokio::test]
+async fn ping_while_subscribed() {
+    let addr = start_server().await;
+
+    let mut publisher = TcpStream::connect(addr).await.unwrap();
+    let mut sub = TcpStream::connect(addr).await.unwrap();
+    assert_reply(
+        &mut sub,
+        b"*2\r\n$9\r\nSUBSCRIBE\r\n$5\r\nhello\r\n",
+        b"*3\r\n$9\r\nsubscribe\r\n$5\r\nhello\r\n:1\r\n",
+    )
+    .await;
+
+    assert_reply(&mut sub, b"*1\r\n$4\r\nPING\r\n", b"*2\r\n$4\r\npong\r\n$0\r\n\r\n").await;
+    assert_reply(&mut sub, b"*2\r\n$4\r\nPING\r\n$2\r\nhi\r\n", b"*2\r\n$4\r\npong\r\n$2\r\nhi\r\n").await;
+
+    // Still subscribed
+    assert_reply(&mut publisher, b"*3\r\n$7\r\nPUBLISH\r\n$5\r\nhello\r\n$5\r\nworld\r\n", b":1\r\n").await;
+    let mut response = [0; 39];
+    sub.read_exact(&mut response).await.unwrap();
+    assert_eq!(&b"*3\r\n$7\r\nmessage\r\n$5\r\nhello\r\n$5\r\nworld\r\n"[..], &response[..]);
+}
+
+// SUBSCRIBE and PSUBSCRIBE without a channel reply with an arity error instead
+// of closing the connection, both before and after entering subscribe mode.
+#[tokio::test]
+async fn subscribe_without_channels() {
+    let addr = start_server().await;
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+
+    assert_reply(
+        &mut stream,
+        b"*1\r\n$9\r\nsubscribe\r\n",
+        b"-ERR wrong number of arguments for 'subscribe' command\r\n",
+    )
+    .await;
+    assert_reply(
+        &mut stream,
+        b"*1\r\n$10\r\npsubscribe\r\n",
+        b"-ERR wrong number of arguments for 'psubscribe' command\r\n",
+    )
+    .await;
+    // Not subscribed, so PING gets the normal reply
+    assert_reply(&mut stream, b"*1\r\n$4\r\nPING\r\n", b"+PONG\r\n").await;
+
+    assert_reply(
+        &mut stream,
+        b"*2\r\n$9\r\nSUBSCRIBE\r\n$5\r\nhello\r\n",
+        b"*3\r\n$9\r\nsubscribe\r\n$5\r\nhello\r\n:1\r\n",
+    )
+    .await;
+    assert_reply(
+        &mut stream,
+        b"*1\r\n$9\r\nsubscribe\r\n",
+        b"-ERR wrong number of arguments for 'subscribe' command\r\n",
+    )
+    .await;
+    assert_reply(&mut stream, b"*1\r\n$4\r\nPING\r\n", b"*2\r\n$4\r\npong\r\n$0\r\n\r\n").await;
+}
+
+// With a keepalive interval configured, an otherwise silent subscription
+// receives periodic pings, and messages are still delivered whole.
+#[tokio::test]
+async fn keepalive_pings_idle_subscribers() {
+    let addr = start_server_with_config(ServerConfig {
+        keepalive_interval: Some(Duration::from_millis(50)),
+        ..Default::default()
+    })
+    .await;
+
+    let mut publisher = TcpStream::connect(addr).await.unwrap();
+    let mut sub = TcpStream::connect(addr).await.unwrap();
+    assert_reply(
+        &mut sub,
+        b"*2\r\n$9\r\nSUBSCRIBE\r\n$5\r\nhello\r\n",
+        b"*3\r\n$9\r\nsubscribe\r\n$5\r\nhello\r\n:1\r\n",
+    )
+    .await;
+
+    // Two intervals pass without any messages
+    for _ in 0..2 {
+        let mut response = [0; 14];
+        time::timeout(Duration::from_secs(1), sub.read_exact(&mut response)).await.unwrap().unwrap();
+        assert_eq!(b"*1\r\n$4\r\nping\r\n", &response);
+    }
+
+    assert_reply(&mut publisher, b"*3\r\n$7\r\nPUBLISH\r\n$5\r\nhello\r\n$5\r\nworld\r\n", b":1\r\n").await;
+    let mut response = [0; 39];
+    sub.read_exact(&mut response).await.unwrap();
+    assert_eq!(b"*3\r\n$7\r\nmessage\r\n$5\r\nhello\r\n$5\r\nworld\r\n", &response);
+}
+
+// Commands with too few or too many arguments get an arity error and the
+// connection stays usable.
+#[tokio::test]
+async fn wrong_number_of_arguments() {
+    let addr = start_server().await;
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+
+    assert_reply(
+        &mut stream,
+        b"*1\r\n$3\r\nGET\r\n",
+        b"-ERR wrong number of arguments for 'get' command\r\n",
+    )
+    .await;
+    assert_reply(
+        &mut stream,
+        b"*2\r\n$3\r\nSET\r\n$5\r\nhello\r\n",
+        b"-ERR wrong number of arguments for 'set' command\r\n",
+    )
+    .await;
+    assert_reply(
+        &mut stream,
+        b"*1\r\n$3\r\nDEL\r\n",
+        b"-ERR wrong number of arguments for 'del' command\r\n",
+    )
+    .await;
+    assert_reply(
+        &mut stream,
+        b"*3\r\n$3\r\nGET\r\n$5\r\nhello\r\n$5\r\nworld\r\n",
+        b"-ERR wrong number of arguments for 'get' command\r\n",
+    )
+    .await;
+
+    assert_reply(&mut stream, b"*3\r\n$3\r\nSET\r\n$5\r\nhello\r\n$5\r\nworld\r\n", b"+OK\r\n").await;
+    assert_reply(&mut stream, b"*2\r\n$3\r\nGET\r\n$5\r\nhello\r\n", b"$5\r\nworld\r\n").await;
+}
+
+// Requests typed by hand, e.g. over telnet, are accepted as inline commands
+// without RESP framing.
+#[tokio::test]
+async fn inline_commands() {
+    let addr = start_server().await;
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+
+    assert_reply(&mut stream, b"PING\r\n", b"+PONG\r\n").await;
+    assert_reply(&mut stream, b"SET hello world\r\n", b"+OK\r\n").await;
+    assert_reply(&mut stream, b"\r\nGET hello\n", b"$5\r\nworld\r\n").await;
+}
+
+// After HELLO 3, subscription confirmations and messages are sent as RESP3
+// push frames instead of arrays.
+#[tokio::test]
+async fn pubsub_uses_push_frames_on_resp3() {
+    let addr = start_server().await;
+
+    let mut publisher = TcpStream::connect(addr).await.unwrap();
+    let mut sub = TcpStream::connect(addr).await.unwrap();
+
+    // Switch to RESP3 and skip the HELLO reply, a map of server properties
+    sub.write_all(b"*2\r\n$5\r\nHELLO\r\n$1\r\n3\r\n").await.unwrap();
+    let mut buf = vec![];
+    while Frame::check(&mut Cursor::new(&buf[..])).is_err() {
+        let mut byte = [0; 1];
+        sub.read_exact(&mut byte).await.unwrap();
+        buf.push(byte[0]);
+    }
+    assert_eq!(b'%', buf[0]);
+
+    assert_reply(
+        &mut sub,
+        b"*2\r\n$9\r\nSUBSCRIBE\r\n$5\r\nhello\r\n",
+        b">3\r\n$9\r\nsubscribe\r\n$5\r\nhello\r\n:1\r\n",
+    )
+    .await;
+
+    assert_reply(&mut publisher, b"*3\r\n$7\r\nPUBLISH\r\n$5\r\nhello\r\n$5\r\nworld\r\n", b":1\r\n").await;
+    let mut response = [0; 39];
+    sub.read_exact(&mut response).await.unwrap();
+    assert_eq!(&b">3\r\n$7\r\nmessage\r\n$5\r\nhello\r\n$5\r\nworld\r\n"[..], &response[..]);
+}
+
 // In this case we test that server Responds with an Error message if a client
 // sends an unknown command
 #[tokio::test]
@@ -397,6 +607,967 @@ async fn send_error_get_set_after_subscribe() {
     assert_eq!(b"-ERR unknown command \'get\'\r\n", &response);
 }
 
+// A bulk length beyond the frame size limit closes the connection
+// immediately instead of buffering until the peer sends the whole value.
+#[tokio::test]
+async fn oversized_frame_closes_connection() {
+    let addr = start_server().await;
+
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+
+    stream
+        .write_all(b"*3\r\n$3\r\nSET\r\n$5\r\nhello\r\n$1000000000000\r\n")
+        .await
+        .unwrap();
+
+    // The server drops the connection without replying
+    let mut response = [0; 1];
+    let n = time::timeout(Duration::from_secs(1), stream.read(&mut response))
+        .await
+        .unwrap()
+        .unwrap_or(0);
+    assert_eq!(0, n);
+}
+
+// Several commands written in one go are all answered, in request order.
+#[tokio::test]
+async fn pipelined_commands() {
+    let addr = start_server().await;
+
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+
+    stream
+        .write_all(
+            b"*2\r\n$3\r\nGET\r\n$5\r\nhello\r\n\
+              *3\r\n$3\r\nSET\r\n$5\r\nhello\r\n$5\r\nworld\r\n\
+              *2\r\n$3\r\nGET\r\n$5\r\nhello\r\n",
+        )
+        .await
+        .unwrap();
+
+    let mut response = [0; 21];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(b"$-1\r\n+OK\r\n$5\r\nworld\r\n", &response);
+}
+
+// `SET` honours the NX, XX and GET options in any order.
+#[tokio::test]
+async fn set_nx_xx_get() {
+    let addr = start_server().await;
+
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+
+    // XX on a missing key does not write
+    stream
+        .write_all(b"*4\r\n$3\r\nSET\r\n$5\r\nhello\r\n$5\r\nworld\r\n$2\r\nXX\r\n")
+        .await
+        .unwrap();
+    let mut response = [0; 5];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(b"$-1\r\n", &response);
+
+    // NX on a missing key writes
+    stream
+        .write_all(b"*4\r\n$3\r\nSET\r\n$5\r\nhello\r\n$5\r\nworld\r\n$2\r\nnx\r\n")
+        .await
+        .unwrap();
+    let mut response = [0; 5];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(b"+OK\r\n", &response);
+
+    // NX on an existing key does not write
+    stream
+        .write_all(b"*4\r\n$3\r\nSET\r\n$5\r\nhello\r\n$5\r\nother\r\n$2\r\nNX\r\n")
+        .await
+        .unwrap();
+    let mut response = [0; 5];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(b"$-1\r\n", &response);
+
+    // GET before XX returns the previous value and writes the new one
+    stream
+        .write_all(b"*5\r\n$3\r\nSET\r\n$5\r\nhello\r\n$5\r\njazzy\r\n$3\r\nGET\r\n$2\r\nXX\r\n")
+        .await
+        .unwrap();
+    let mut response = [0; 11];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(b"$5\r\nworld\r\n", &response);
+
+    stream
+        .write_all(b"*2\r\n$3\r\nGET\r\n$5\r\nhello\r\n")
+        .await
+        .unwrap();
+    let mut response = [0; 11];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(b"$5\r\njazzy\r\n", &response);
+}
+
+// Unknown, repeated or conflicting `SET` options get a syntax error and the
+// connection stays usable.
+#[tokio::test]
+async fn set_unknown_option() {
+    let addr = start_server().await;
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+
+    assert_reply(
+        &mut stream,
+        b"*4\r\n$3\r\nSET\r\n$1\r\nk\r\n$1\r\nv\r\n$3\r\nFOO\r\n",
+        b"-ERR syntax error\r\n",
+    )
+    .await;
+    assert_reply(
+        &mut stream,
+        b"*5\r\n$3\r\nSET\r\n$1\r\nk\r\n$1\r\nv\r\n$2\r\nNX\r\n$2\r\nXX\r\n",
+        b"-ERR syntax error\r\n",
+    )
+    .await;
+    assert_reply(
+        &mut stream,
+        b"*6\r\n$3\r\nSET\r\n$1\r\nk\r\n$1\r\nv\r\n$7\r\nKEEPTTL\r\n$2\r\nPX\r\n$3\r\n100\r\n",
+        b"-ERR syntax error\r\n",
+    )
+    .await;
+    assert_reply(&mut stream, b"*2\r\n$3\r\nGET\r\n$1\r\nk\r\n", b"$-1\r\n").await;
+
+    assert_reply(&mut stream, b"*3\r\n$3\r\nSET\r\n$1\r\nk\r\n$1\r\nv\r\n", b"+OK\r\n").await;
+    assert_reply(&mut stream, b"*2\r\n$3\r\nGET\r\n$1\r\nk\r\n", b"$1\r\nv\r\n").await;
+}
+
+// Zero, negative and non-integer expire times are rejected without closing the
+// connection, and the key is not set.
+#[tokio::test]
+async fn set_invalid_expire_time() {
+    let addr = start_server().await;
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+
+    for (option, time) in [("EX", "-1"), ("EX", "0"), ("PX", "0"), ("EXAT", "-5"), ("PXAT", "0")] {
+        let request = format!(
+            "*5\r\n$3\r\nSET\r\n$1\r\nk\r\n$1\r\nv\r\n${}\r\n{}\r\n${}\r\n{}\r\n",
+            option.len(),
+            option,
+            time.len(),
+            time
+        );
+        assert_reply(&mut stream, request.as_bytes(), b"-ERR invalid expire time in 'set' command\r\n").await;
+    }
+    assert_reply(
+        &mut stream,
+        b"*5\r\n$3\r\nSET\r\n$1\r\nk\r\n$1\r\nv\r\n$2\r\nEX\r\n$3\r\nabc\r\n",
+        b"-ERR value is not an integer or out of range\r\n",
+    )
+    .await;
+    assert_reply(&mut stream, b"*2\r\n$3\r\nGET\r\n$1\r\nk\r\n", b"$-1\r\n").await;
+
+    assert_reply(
+        &mut stream,
+        b"*5\r\n$3\r\nSET\r\n$1\r\nk\r\n$1\r\nv\r\n$2\r\nEX\r\n$2\r\n10\r\n",
+        b"+OK\r\n",
+    )
+    .await;
+    assert_reply(&mut stream, b"*2\r\n$3\r\nGET\r\n$1\r\nk\r\n", b"$1\r\nv\r\n").await;
+}
+
+// Invalid argument values are answered with an error and the connection stays
+// open, like a wrong number of arguments.
+#[tokio::test]
+async fn invalid_arguments_keep_connection() {
+    let addr = start_server().await;
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+
+    let cases: &[(&[&str], &str)] = &[
+        (&["SCAN", "0", "COUNT", "0"], "ERR syntax error"),
+        (&["SCAN", "abc"], "ERR invalid cursor"),
+        (&["BLPOP", "l", "-1"], "ERR timeout is negative"),
+        (&["LPOP", "l", "-1"], "ERR value is out of range, must be positive"),
+        (&["EXPIREAT", "k", "abc"], "ERR value is not an integer or out of range"),
+        (&["GETEX", "k", "EX", "-1"], "ERR invalid expire time in 'getex' command"),
+        (&["SELECT", "abc"], "ERR value is not an integer or out of range"),
+        (&["MOVE", "k", "abc"], "ERR value is not an integer or out of range"),
+        (&["COMMAND", "FOO"], "ERR unknown subcommand 'FOO'. Try COMMAND HELP."),
+        (&["CLIENT", "FOO"], "ERR unknown subcommand 'FOO'. Try CLIENT HELP."),
+        (&["CONFIG", "FOO"], "ERR unknown subcommand 'FOO'. Try CONFIG HELP."),
+        (&["OBJECT", "FOO", "k"], "ERR unknown subcommand 'FOO'. Try OBJECT HELP."),
+        (&["DEBUG", "FOO"], "ERR unknown subcommand 'FOO'. Try DEBUG HELP."),
+    ];
+    for (args, error) in cases {
+        let mut request = format!("*{}\r\n", args.len());
+        for arg in args.iter() {
+            request.push_str(&format!("${}\r\n{}\r\n", arg.len(), arg));
+        }
+        assert_reply(&mut stream, request.as_bytes(), format!("-{}\r\n", error).as_bytes()).await;
+    }
+
+    assert_reply(&mut stream, b"*1\r\n$4\r\nPING\r\n", b"+PONG\r\n").await;
+}
+
+// `COMMAND GETKEYS` reports which arguments of a command are keys.
+#[tokio::test]
+async fn command_getkeys() {
+    let addr = start_server().await;
+
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+
+    stream
+        .write_all(b"*5\r\n$7\r\nCOMMAND\r\n$7\r\nGETKEYS\r\n$3\r\nset\r\n$1\r\nk\r\n$1\r\nv\r\n")
+        .await
+        .unwrap();
+
+    let mut response = [0; 11];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(b"*1\r\n$1\r\nk\r\n", &response);
+
+    // Pub/sub commands have no key arguments
+    stream
+        .write_all(b"*5\r\n$7\r\nCOMMAND\r\n$7\r\nGETKEYS\r\n$7\r\nPUBLISH\r\n$3\r\nfoo\r\n$3\r\nbar\r\n")
+        .await
+        .unwrap();
+
+    let mut response = [0; 39];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(b"-ERR The command has no key arguments\r\n", &response);
+}
+
+/// Shutting down with only idle connections drains them all without forcing.
+#[tokio::test]
+async fn shutdown_report_idle_connections() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+    let server = tokio::spawn(async move { server::run_reporting(listener, shutdown_rx).await });
+
+    // Make sure the connection is being handled before shutting down
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    stream.write_all(b"*1\r\n$4\r\nPING\r\n").await.unwrap();
+
+    let mut response = [0; 7];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(b"+PONG\r\n", &response);
+
+    shutdown_tx.send(()).unwrap();
+    let report = server.await.unwrap();
+
+    assert!(!report.forced);
+    assert_eq!(1, report.connections_drained);
+    assert!(report.uptime > Duration::ZERO);
+
+    // The server closed the connection
+    assert_eq!(0, stream.read(&mut response).await.unwrap());
+}
+
+/// A connection that never reads its responses keeps its handler blocked on
+/// writing. With a shutdown timeout the server gives up on it and reports the
+/// shutdown as forced instead of hanging.
+#[tokio::test]
+async fn shutdown_timeout_forces_stuck_connection() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+    let config = ServerConfig {
+        shutdown_timeout: Some(Duration::from_millis(200)),
+        ..Default::default()
+    };
+    let server = tokio::spawn(async move { server::run_with_config(listener, shutdown_rx, config).await });
+
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    let value = vec![b'x'; 1024 * 1024];
+    let mut set = b"*3\r\n$3\r\nSET\r\n$3\r\nbig\r\n$1048576\r\n".to_vec();
+    set.extend_from_slice(&value);
+    set.extend_from_slice(b"\r\n");
+    stream.write_all(&set).await.unwrap();
+
+    let mut response = [0; 5];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(b"+OK\r\n", &response);
+
+    // Request far more data than the socket buffers can hold, and never read it
+    for _ in 0..64 {
+        stream.write_all(b"*2\r\n$3\r\nGET\r\n$3\r\nbig\r\n").await.unwrap();
+    }
+    time::sleep(Duration::from_millis(100)).await;
+
+    shutdown_tx.send(()).unwrap();
+    let report = time::timeout(Duration::from_secs(5), server).await.unwrap().unwrap();
+
+    assert!(report.forced);
+    assert_eq!(1, report.connections_drained);
+}
+
+/// Scripting commands are recognized and rejected with a specific error, and
+/// the connection stays usable.
+#[tokio::test]
+async fn eval_not_supported() {
+    let addr = start_server().await;
+
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+
+    stream
+        .write_all(b"*3\r\n$4\r\nEVAL\r\n$8\r\nreturn 1\r\n$1\r\n0\r\n")
+        .await
+        .unwrap();
+
+    let mut response = [0; 47];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(b"-ERR scripting is not supported by mini-redis\r\n", &response);
+
+    // The connection is still open
+    stream.write_all(b"*1\r\n$4\r\nPING\r\n").await.unwrap();
+
+    let mut response = [0; 7];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(b"+PONG\r\n", &response);
+}
+
+/// Bulk loading grows the key space unless it was preallocated, either at
+/// startup or with `DEBUG RESERVE`.
+#[tokio::test]
+async fn preallocate_avoids_growth() {
+    const KEYS: usize = 10_000;
+
+    // Without preallocation the key space grows while loading
+    let addr = start_server_with_config(ServerConfig::default()).await;
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+
+    let before = debug_capacity(&mut stream).await;
+    load_keys(&mut stream, KEYS).await;
+    let after = debug_capacity(&mut stream).await;
+    assert!(before < KEYS);
+    assert!(after >= KEYS);
+
+    // Preallocated at startup, the capacity does not change
+    let addr = start_server_with_config(ServerConfig {
+        preallocate: KEYS,
+        ..Default::default()
+    }).await;
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+
+    let before = debug_capacity(&mut stream).await;
+    load_keys(&mut stream, KEYS).await;
+    assert!(before >= KEYS);
+    assert_eq!(before, debug_capacity(&mut stream).await);
+
+    // Reserved at runtime
+    let addr = start_server().await;
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+
+    stream
+        .write_all(b"*3\r\n$5\r\nDEBUG\r\n$7\r\nRESERVE\r\n$5\r\n10000\r\n")
+        .await
+        .unwrap();
+
+    let mut response = [0; 5];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(b"+OK\r\n", &response);
+    assert!(debug_capacity(&mut stream).await >= KEYS);
+}
+
+/// With debug hooks enabled, `DEBUG SLEEP` delays the reply and
+/// `DEBUG SET-ACTIVE-EXPIRE 0` stops the background task from purging expired
+/// keys until it is turned back on.
+#[tokio::test]
+async fn debug_test_hooks() {
+    let addr = start_server_with_config(ServerConfig {
+        debug_hooks: true,
+        notify_expired: true,
+        ..Default::default()
+    })
+    .await;
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+
+    // Expirations are announced on the expired channel when purged
+    let mut subscriber = TcpStream::connect(addr).await.unwrap();
+    assert_reply(
+        &mut subscriber,
+        b"*2\r\n$9\r\nSUBSCRIBE\r\n$20\r\n__keyevent__:expired\r\n",
+        b"*3\r\n$9\r\nsubscribe\r\n$20\r\n__keyevent__:expired\r\n:1\r\n",
+    )
+    .await;
+
+    assert_reply(&mut stream, b"*3\r\n$5\r\nDEBUG\r\n$17\r\nSET-ACTIVE-EXPIRE\r\n$1\r\n0\r\n", b"+OK\r\n").await;
+    assert_reply(
+        &mut stream,
+        b"*5\r\n$3\r\nSET\r\n$5\r\nhello\r\n$5\r\nworld\r\n$2\r\nPX\r\n$2\r\n10\r\n",
+        b"+OK\r\n",
+    )
+    .await;
+
+    let start = time::Instant::now();
+    assert_reply(&mut stream, b"*3\r\n$5\r\nDEBUG\r\n$5\r\nSLEEP\r\n$3\r\n100\r\n", b"+OK\r\n").await;
+    assert!(start.elapsed() >= Duration::from_millis(100));
+
+    // The key expired while active expiry was off, so nothing was purged
+    let mut response = [0; 1];
+    let res = time::timeout(Duration::from_millis(100), subscriber.read(&mut response)).await;
+    assert!(res.is_err());
+
+    // Turning it back on purges the key right away
+    assert_reply(&mut stream, b"*3\r\n$5\r\nDEBUG\r\n$17\r\nSET-ACTIVE-EXPIRE\r\n$1\r\n1\r\n", b"+OK\r\n").await;
+    let expected = b"*3\r\n$7\r\nmessage\r\n$20\r\n__keyevent__:expired\r\n$5\r\nhello\r\n";
+    let mut response = [0; 55];
+    time::timeout(Duration::from_secs(1), subscriber.read_exact(&mut response)).await.unwrap().unwrap();
+    assert_eq!(&expected[..], &response[..]);
+}
+
+/// `GET` never returns a key past its expiration, even when the background
+/// task has not purged it yet. The key is removed on access, exactly once.
+#[tokio::test]
+async fn get_expires_keys_lazily() {
+    let addr = start_server_with_config(ServerConfig {
+        debug_hooks: true,
+        notify_expired: true,
+        ..Default::default()
+    })
+    .await;
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+
+    let mut subscriber = TcpStream::connect(addr).await.unwrap();
+    assert_reply(
+        &mut subscriber,
+        b"*2\r\n$9\r\nSUBSCRIBE\r\n$20\r\n__keyevent__:expired\r\n",
+        b"*3\r\n$9\r\nsubscribe\r\n$20\r\n__keyevent__:expired\r\n:1\r\n",
+    )
+    .await;
+
+    // Keep the background task from purging the key
+    assert_reply(&mut stream, b"*3\r\n$5\r\nDEBUG\r\n$17\r\nSET-ACTIVE-EXPIRE\r\n$1\r\n0\r\n", b"+OK\r\n").await;
+    assert_reply(
+        &mut stream,
+        b"*5\r\n$3\r\nSET\r\n$5\r\nhello\r\n$5\r\nworld\r\n$2\r\nPX\r\n$2\r\n10\r\n",
+        b"+OK\r\n",
+    )
+    .await;
+    time::sleep(Duration::from_millis(50)).await;
+
+    assert_reply(&mut stream, b"*2\r\n$3\r\nGET\r\n$5\r\nhello\r\n", b"$-1\r\n").await;
+
+    // Removing the key on access announces the expiration
+    let expected = b"*3\r\n$7\r\nmessage\r\n$20\r\n__keyevent__:expired\r\n$5\r\nhello\r\n";
+    let mut response = [0; 55];
+    time::timeout(Duration::from_secs(1), subscriber.read_exact(&mut response)).await.unwrap().unwrap();
+    assert_eq!(&expected[..], &response[..]);
+
+    // The pending expiration was dropped with the key, so the background task
+    // does not announce it a second time
+    assert_reply(&mut stream, b"*3\r\n$5\r\nDEBUG\r\n$17\r\nSET-ACTIVE-EXPIRE\r\n$1\r\n1\r\n", b"+OK\r\n").await;
+    let mut response = [0; 1];
+    let res = time::timeout(Duration::from_millis(100), subscriber.read(&mut response)).await;
+    assert!(res.is_err());
+}
+
+/// `SET NX` and `SET XX` treat a key past its expiration as missing, even when
+/// the background task has not purged it yet.
+#[tokio::test]
+async fn set_condition_ignores_expired_keys() {
+    let addr = start_server_with_config(ServerConfig {
+        debug_hooks: true,
+        ..Default::default()
+    })
+    .await;
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+
+    // Keep the background task from purging the keys
+    assert_reply(&mut stream, b"*3\r\n$5\r\nDEBUG\r\n$17\r\nSET-ACTIVE-EXPIRE\r\n$1\r\n0\r\n", b"+OK\r\n").await;
+    for key in [b"a", b"b"] {
+        let mut request = b"*5\r\n$3\r\nSET\r\n$1\r\n".to_vec();
+        request.extend_from_slice(key);
+        request.extend_from_slice(b"\r\n$3\r\nold\r\n$2\r\nPX\r\n$2\r\n10\r\n");
+        assert_reply(&mut stream, &request, b"+OK\r\n").await;
+    }
+    time::sleep(Duration::from_millis(50)).await;
+
+    // XX does not write to the expired key
+    assert_reply(&mut stream, b"*4\r\n$3\r\nSET\r\n$1\r\na\r\n$3\r\nnew\r\n$2\r\nXX\r\n", b"$-1\r\n").await;
+    assert_reply(&mut stream, b"*2\r\n$3\r\nGET\r\n$1\r\na\r\n", b"$-1\r\n").await;
+
+    // NX writes over it, and the new value does not inherit the old expiration
+    assert_reply(&mut stream, b"*4\r\n$3\r\nSET\r\n$1\r\nb\r\n$3\r\nnew\r\n$2\r\nNX\r\n", b"+OK\r\n").await;
+    assert_reply(&mut stream, b"*3\r\n$5\r\nDEBUG\r\n$17\r\nSET-ACTIVE-EXPIRE\r\n$1\r\n1\r\n", b"+OK\r\n").await;
+    time::sleep(Duration::from_millis(50)).await;
+    assert_reply(&mut stream, b"*2\r\n$3\r\nGET\r\n$1\r\nb\r\n", b"$3\r\nnew\r\n").await;
+}
+
+/// `TOUCH` does not count a key past its expiration, even when the background
+/// task has not purged it yet.
+#[tokio::test]
+async fn touch_ignores_expired_keys() {
+    let addr = start_server_with_config(ServerConfig {
+        debug_hooks: true,
+        ..Default::default()
+    })
+    .await;
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+
+    // Keep the background task from purging the key
+    assert_reply(&mut stream, b"*3\r\n$5\r\nDEBUG\r\n$17\r\nSET-ACTIVE-EXPIRE\r\n$1\r\n0\r\n", b"+OK\r\n").await;
+    assert_reply(
+        &mut stream,
+        b"*5\r\n$3\r\nSET\r\n$1\r\na\r\n$3\r\nold\r\n$2\r\nPX\r\n$2\r\n10\r\n",
+        b"+OK\r\n",
+    )
+    .await;
+    assert_reply(&mut stream, b"*3\r\n$3\r\nSET\r\n$1\r\nb\r\n$3\r\nnew\r\n", b"+OK\r\n").await;
+    time::sleep(Duration::from_millis(50)).await;
+
+    assert_reply(&mut stream, b"*3\r\n$5\r\nTOUCH\r\n$1\r\na\r\n$1\r\nb\r\n", b":1\r\n").await;
+}
+
+/// `COPY` treats keys past their expiration as missing: an expired source is
+/// not copied, and an expired destination does not block the copy.
+#[tokio::test]
+async fn copy_ignores_expired_keys() {
+    let addr = start_server_with_config(ServerConfig {
+        debug_hooks: true,
+        ..Default::default()
+    })
+    .await;
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+
+    // Keep the background task from purging the keys
+    assert_reply(&mut stream, b"*3\r\n$5\r\nDEBUG\r\n$17\r\nSET-ACTIVE-EXPIRE\r\n$1\r\n0\r\n", b"+OK\r\n").await;
+    for key in [b"expired", b"old-dst"] {
+        let mut request = b"*5\r\n$3\r\nSET\r\n$7\r\n".to_vec();
+        request.extend_from_slice(key);
+        request.extend_from_slice(b"\r\n$3\r\nold\r\n$2\r\nPX\r\n$2\r\n10\r\n");
+        assert_reply(&mut stream, &request, b"+OK\r\n").await;
+    }
+    assert_reply(&mut stream, b"*3\r\n$3\r\nSET\r\n$3\r\nsrc\r\n$3\r\nnew\r\n", b"+OK\r\n").await;
+    time::sleep(Duration::from_millis(50)).await;
+
+    assert_reply(&mut stream, b"*3\r\n$4\r\nCOPY\r\n$7\r\nexpired\r\n$4\r\ncopy\r\n", b":0\r\n").await;
+    assert_reply(&mut stream, b"*2\r\n$3\r\nGET\r\n$4\r\ncopy\r\n", b"$-1\r\n").await;
+
+    // The destination does not keep the old expiration either
+    assert_reply(&mut stream, b"*3\r\n$4\r\nCOPY\r\n$3\r\nsrc\r\n$7\r\nold-dst\r\n", b":1\r\n").await;
+    assert_reply(&mut stream, b"*3\r\n$5\r\nDEBUG\r\n$17\r\nSET-ACTIVE-EXPIRE\r\n$1\r\n1\r\n", b"+OK\r\n").await;
+    time::sleep(Duration::from_millis(50)).await;
+    assert_reply(&mut stream, b"*2\r\n$3\r\nGET\r\n$7\r\nold-dst\r\n", b"$3\r\nnew\r\n").await;
+}
+
+/// `RENAME` and `RENAMENX` treat keys past their expiration as missing: an
+/// expired source cannot be renamed, and an expired destination does not block
+/// `RENAMENX`.
+#[tokio::test]
+async fn rename_ignores_expired_keys() {
+    let addr = start_server_with_config(ServerConfig {
+        debug_hooks: true,
+        ..Default::default()
+    })
+    .await;
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+
+    // Keep the background task from purging the keys
+    assert_reply(&mut stream, b"*3\r\n$5\r\nDEBUG\r\n$17\r\nSET-ACTIVE-EXPIRE\r\n$1\r\n0\r\n", b"+OK\r\n").await;
+    for key in [b"expired", b"old-dst"] {
+        let mut request = b"*5\r\n$3\r\nSET\r\n$7\r\n".to_vec();
+        request.extend_from_slice(key);
+        request.extend_from_slice(b"\r\n$3\r\nold\r\n$2\r\nPX\r\n$2\r\n10\r\n");
+        assert_reply(&mut stream, &request, b"+OK\r\n").await;
+    }
+    assert_reply(&mut stream, b"*3\r\n$3\r\nSET\r\n$3\r\nsrc\r\n$3\r\nnew\r\n", b"+OK\r\n").await;
+    time::sleep(Duration::from_millis(50)).await;
+
+    assert_reply(
+        &mut stream,
+        b"*3\r\n$6\r\nRENAME\r\n$7\r\nexpired\r\n$7\r\nrenamed\r\n",
+        b"-ERR no such key\r\n",
+    )
+    .await;
+    assert_reply(&mut stream, b"*2\r\n$3\r\nGET\r\n$7\r\nrenamed\r\n", b"$-1\r\n").await;
+
+    // The destination does not keep the old expiration either
+    assert_reply(&mut stream, b"*3\r\n$8\r\nRENAMENX\r\n$3\r\nsrc\r\n$7\r\nold-dst\r\n", b":1\r\n").await;
+    assert_reply(&mut stream, b"*3\r\n$5\r\nDEBUG\r\n$17\r\nSET-ACTIVE-EXPIRE\r\n$1\r\n1\r\n", b"+OK\r\n").await;
+    time::sleep(Duration::from_millis(50)).await;
+    assert_reply(&mut stream, b"*2\r\n$3\r\nGET\r\n$7\r\nold-dst\r\n", b"$3\r\nnew\r\n").await;
+}
+
+/// The test-only `DEBUG` subcommands are refused unless enabled in the
+/// server config.
+#[tokio::test]
+async fn debug_test_hooks_disabled_by_default() {
+    let addr = start_server().await;
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+
+    assert_reply(
+        &mut stream,
+        b"*3\r\n$5\r\nDEBUG\r\n$5\r\nSLEEP\r\n$1\r\n0\r\n",
+        b"-ERR DEBUG test hooks are disabled; enable `debug_hooks` in the server config\r\n",
+    )
+    .await;
+}
+
+/// Past the overload threshold, commands on every connection are rejected
+/// with `-BUSY` until the load drops again.
+#[tokio::test]
+async fn overload_busy_reply() {
+    let addr = start_server_with_config(ServerConfig {
+        overload: Some(OverloadConfig {
+            threshold: 2,
+            idle_timeout: Duration::from_secs(60),
+        }),
+        ..Default::default()
+    })
+    .await;
+
+    let mut first = TcpStream::connect(addr).await.unwrap();
+    let mut second = TcpStream::connect(addr).await.unwrap();
+    assert_ping(&mut first, b"+PONG\r\n").await;
+    assert_ping(&mut second, b"+PONG\r\n").await;
+
+    // A third connection pushes the server past the threshold
+    let mut third = TcpStream::connect(addr).await.unwrap();
+    assert_ping(&mut third, b"-BUSY server is overloaded, try again later\r\n").await;
+    assert_ping(&mut first, b"-BUSY server is overloaded, try again later\r\n").await;
+
+    // Once it disconnects the load recovers
+    drop(third);
+    time::sleep(Duration::from_millis(50)).await;
+    assert_ping(&mut first, b"+PONG\r\n").await;
+}
+
+/// While overloaded, idle connections are closed.
+#[tokio::test]
+async fn overload_closes_idle_connections() {
+    let addr = start_server_with_config(ServerConfig {
+        overload: Some(OverloadConfig {
+            threshold: 1,
+            idle_timeout: Duration::from_millis(50),
+        }),
+        ..Default::default()
+    })
+    .await;
+
+    let mut first = TcpStream::connect(addr).await.unwrap();
+    let _second = TcpStream::connect(addr).await.unwrap();
+
+    // The idle connection is closed by the server
+    let mut buf = [0; 1];
+    let n = time::timeout(Duration::from_secs(5), first.read(&mut buf)).await.unwrap().unwrap();
+    assert_eq!(0, n);
+}
+
+/// Sends `PING` and asserts the exact reply.
+async fn assert_ping(stream: &mut TcpStream, expected: &[u8]) {
+    stream.write_all(b"*1\r\n$4\r\nPING\r\n").await.unwrap();
+
+    let mut response = vec![0; expected.len()];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(expected, &response[..]);
+}
+
+/// With `max_connections = 1`, a second connection is not served until the
+/// first one is dropped.
+#[tokio::test]
+async fn max_connections_limit() {
+    let addr = start_server_with_config(ServerConfig {
+        max_connections: 1,
+        ..Default::default()
+    })
+    .await;
+
+    let mut first = TcpStream::connect(addr).await.unwrap();
+    assert_ping(&mut first, b"+PONG\r\n").await;
+
+    // The second connection is established by the OS but not accepted yet
+    let mut second = TcpStream::connect(addr).await.unwrap();
+    second.write_all(b"*1\r\n$4\r\nPING\r\n").await.unwrap();
+
+    let mut response = [0; 7];
+    let res = time::timeout(Duration::from_millis(100), second.read_exact(&mut response)).await;
+    assert!(res.is_err());
+
+    // Dropping the first connection frees the permit
+    drop(first);
+    time::timeout(Duration::from_secs(5), second.read_exact(&mut response))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(b"+PONG\r\n", &response);
+}
+
+/// Commands queued after `MULTI` are only applied by `EXEC`, which replies
+/// with an array of their replies.
+#[tokio::test]
+async fn multi_exec() {
+    let addr = start_server().await;
+
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    let mut other = TcpStream::connect(addr).await.unwrap();
+
+    assert_reply(&mut stream, b"*1\r\n$5\r\nMULTI\r\n", b"+OK\r\n").await;
+    assert_reply(
+        &mut stream,
+        b"*3\r\n$3\r\nSET\r\n$5\r\nhello\r\n$5\r\nworld\r\n",
+        b"+QUEUED\r\n",
+    )
+    .await;
+    assert_reply(&mut stream, b"*2\r\n$3\r\nGET\r\n$5\r\nhello\r\n", b"+QUEUED\r\n").await;
+
+    // Nothing has been applied yet
+    assert_reply(&mut other, b"*2\r\n$3\r\nGET\r\n$5\r\nhello\r\n", b"$-1\r\n").await;
+
+    assert_reply(&mut stream, b"*1\r\n$4\r\nEXEC\r\n", b"*2\r\n+OK\r\n$5\r\nworld\r\n").await;
+    assert_reply(&mut other, b"*2\r\n$3\r\nGET\r\n$5\r\nhello\r\n", b"$5\r\nworld\r\n").await;
+
+    // The connection is back to normal mode
+    assert_reply(&mut stream, b"*1\r\n$4\r\nEXEC\r\n", b"-ERR EXEC without MULTI\r\n").await;
+}
+
+/// An error while queuing aborts the transaction, and `DISCARD` drops the
+/// queued commands.
+#[tokio::test]
+async fn multi_abort_and_discard() {
+    let addr = start_server().await;
+
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+
+    assert_reply(&mut stream, b"*1\r\n$5\r\nMULTI\r\n", b"+OK\r\n").await;
+    assert_reply(
+        &mut stream,
+        b"*3\r\n$3\r\nSET\r\n$5\r\nhello\r\n$5\r\nworld\r\n",
+        b"+QUEUED\r\n",
+    )
+    .await;
+    // A malformed command does not close the connection inside a transaction
+    stream
+        .write_all(b"*3\r\n$3\r\nGET\r\n$5\r\nhello\r\n$5\r\nworld\r\n")
+        .await
+        .unwrap();
+    let mut response = vec![];
+    while !response.ends_with(b"\r\n") {
+        response.push(stream.read_u8().await.unwrap());
+    }
+    assert!(response.starts_with(b"-ERR "));
+
+    assert_reply(
+        &mut stream,
+        b"*1\r\n$4\r\nEXEC\r\n",
+        b"-EXECABORT Transaction discarded because of previous errors.\r\n",
+    )
+    .await;
+    assert_reply(&mut stream, b"*2\r\n$3\r\nGET\r\n$5\r\nhello\r\n", b"$-1\r\n").await;
+
+    assert_reply(&mut stream, b"*1\r\n$5\r\nMULTI\r\n", b"+OK\r\n").await;
+    assert_reply(&mut stream, b"*1\r\n$3\r\nFOO\r\n", b"-ERR unknown command 'foo'\r\n").await;
+    assert_reply(&mut stream, b"*1\r\n$7\r\nDISCARD\r\n", b"+OK\r\n").await;
+    assert_reply(&mut stream, b"*1\r\n$7\r\nDISCARD\r\n", b"-ERR DISCARD without MULTI\r\n").await;
+}
+
+/// `QUIT` is answered with `OK`, then the server closes the connection
+/// without running the commands pipelined after it.
+#[tokio::test]
+async fn quit_closes_connection() {
+    let addr = start_server().await;
+
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+
+    stream
+        .write_all(b"*1\r\n$4\r\nQUIT\r\n*1\r\n$4\r\nPING\r\n")
+        .await
+        .unwrap();
+
+    let mut response = [0; 5];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(b"+OK\r\n", &response);
+
+    let mut buf = [0; 1];
+    let n = time::timeout(Duration::from_secs(5), stream.read(&mut buf)).await.unwrap().unwrap();
+    assert_eq!(0, n);
+
+    // `QUIT` is also accepted in subscribe mode
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    assert_reply(
+        &mut stream,
+        b"*2\r\n$9\r\nSUBSCRIBE\r\n$5\r\nhello\r\n",
+        b"*3\r\n$9\r\nsubscribe\r\n$5\r\nhello\r\n:1\r\n",
+    )
+    .await;
+    assert_reply(&mut stream, b"*1\r\n$4\r\nQUIT\r\n", b"+OK\r\n").await;
+
+    let n = time::timeout(Duration::from_secs(5), stream.read(&mut buf)).await.unwrap().unwrap();
+    assert_eq!(0, n);
+}
+
+/// Sends `request` and asserts the exact reply.
+/// Records every metrics event so tests can inspect them.
+#[derive(Debug, Default)]
+struct CountingMetrics {
+    opened: AtomicU64,
+    closed: AtomicU64,
+    bytes_read: AtomicU64,
+    bytes_written: AtomicU64,
+    commands: Mutex<HashMap<String, u64>>,
+}
+
+impl Metrics for CountingMetrics {
+    fn connection_opened(&self, _addr: SocketAddr) {
+        self.opened.fetch_add(1, Ordering::SeqCst);
+    }
+
+    fn connection_closed(&self, _addr: SocketAddr) {
+        self.closed.fetch_add(1, Ordering::SeqCst);
+    }
+
+    fn command(&self, name: &str) {
+        *self.commands.lock().unwrap().entry(name.to_string()).or_default() += 1;
+    }
+
+    fn bytes_read(&self, n: u64) {
+        self.bytes_read.fetch_add(n, Ordering::SeqCst);
+    }
+
+    fn bytes_written(&self, n: u64) {
+        self.bytes_written.fetch_add(n, Ordering::SeqCst);
+    }
+}
+
+/// A metrics sink configured on the server observes connections, commands and
+/// traffic.
+#[tokio::test]
+async fn metrics_sink_receives_events() {
+    let metrics = Arc::new(CountingMetrics::default());
+    let addr = start_server_with_config(ServerConfig {
+        metrics: metrics.clone(),
+        ..Default::default()
+    })
+    .await;
+
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+
+    let requests: [(&[u8], &[u8]); 3] = [
+        (b"*1\r\n$4\r\nPING\r\n", b"+PONG\r\n"),
+        (b"*3\r\n$3\r\nSET\r\n$5\r\nhello\r\n$5\r\nworld\r\n", b"+OK\r\n"),
+        (b"*2\r\n$3\r\nGET\r\n$5\r\nhello\r\n", b"$5\r\nworld\r\n"),
+    ];
+    for (request, expected) in requests {
+        assert_reply(&mut stream, request, expected).await;
+    }
+
+    assert_eq!(1, metrics.opened.load(Ordering::SeqCst));
+    {
+        let commands = metrics.commands.lock().unwrap();
+        assert_eq!(Some(&1), commands.get("ping"));
+        assert_eq!(Some(&1), commands.get("set"));
+        assert_eq!(Some(&1), commands.get("get"));
+    }
+
+    // Traffic is reported once the connection closes at the latest
+    drop(stream);
+    time::timeout(Duration::from_secs(1), async {
+        while metrics.closed.load(Ordering::SeqCst) == 0 {
+            time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .unwrap();
+
+    let read: usize = requests.iter().map(|(request, _)| request.len()).sum();
+    let written: usize = requests.iter().map(|(_, response)| response.len()).sum();
+    assert_eq!(read as u64, metrics.bytes_read.load(Ordering::SeqCst));
+    assert_eq!(written as u64, metrics.bytes_written.load(Ordering::SeqCst));
+}
+
+/// OBJECT REFCOUNT reports 1 for existing keys and an error for missing ones,
+/// without closing the connection.
+#[tokio::test]
+async fn object_refcount() {
+    let addr = start_server().await;
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+
+    assert_reply(
+        &mut stream,
+        b"*3\r\n$6\r\nOBJECT\r\n$8\r\nREFCOUNT\r\n$5\r\nhello\r\n",
+        b"-ERR no such key\r\n",
+    )
+    .await;
+    assert_reply(&mut stream, b"*3\r\n$3\r\nSET\r\n$5\r\nhello\r\n$5\r\nworld\r\n", b"+OK\r\n").await;
+    assert_reply(&mut stream, b"*3\r\n$6\r\nOBJECT\r\n$8\r\nREFCOUNT\r\n$5\r\nhello\r\n", b":1\r\n").await;
+}
+
+/// With `sorted_replies` enabled HGETALL returns fields and SMEMBERS returns
+/// members sorted by their bytes, regardless of insertion order.
+#[tokio::test]
+async fn sorted_replies() {
+    let addr = start_server_with_config(ServerConfig {
+        sorted_replies: true,
+        ..Default::default()
+    })
+    .await;
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+
+    let fields = ["e", "b", "d", "a", "c"];
+    let mut request = format!("*{}\r\n$4\r\nHSET\r\n$4\r\nhash\r\n", 2 + fields.len() * 2);
+    for field in fields {
+        request.push_str(&format!("$1\r\n{field}\r\n$1\r\n{}\r\n", field.to_uppercase()));
+    }
+    assert_reply(&mut stream, request.as_bytes(), b":5\r\n").await;
+    assert_reply(
+        &mut stream,
+        b"*2\r\n$7\r\nHGETALL\r\n$4\r\nhash\r\n",
+        b"*10\r\n$1\r\na\r\n$1\r\nA\r\n$1\r\nb\r\n$1\r\nB\r\n$1\r\nc\r\n$1\r\nC\r\n\
+          $1\r\nd\r\n$1\r\nD\r\n$1\r\ne\r\n$1\r\nE\r\n",
+    )
+    .await;
+
+    assert_reply(
+        &mut stream,
+        b"*5\r\n$4\r\nSADD\r\n$3\r\nset\r\n$1\r\nz\r\n$1\r\nx\r\n$1\r\ny\r\n",
+        b":3\r\n",
+    )
+    .await;
+    assert_reply(
+        &mut stream,
+        b"*2\r\n$8\r\nSMEMBERS\r\n$3\r\nset\r\n",
+        b"*3\r\n$1\r\nx\r\n$1\r\ny\r\n$1\r\nz\r\n",
+    )
+    .await;
+}
+
+async fn assert_reply(stream: &mut TcpStream, request: &[u8], expected: &[u8]) {
+    stream.write_all(request).await.unwrap();
+
+    let mut response = vec![0; expected.len()];
+    stream.read_exact(&mut response).await.unwrap();
+    assert_eq!(expected, &response[..], "{}", String::from_utf8_lossy(&response));
+}
+
+/// Sets `count` keys in a single pipelined write and reads all the replies.
+async fn load_keys(stream: &mut TcpStream, count: usize) {
+    let mut request = vec![];
+    for i in 0..count {
+        let key = format!("key{}", i);
+        request.extend_from_slice(format!("*3\r\n$3\r\nSET\r\n${}\r\n{}\r\n$1\r\nv\r\n", key.len(), key).as_bytes());
+    }
+    stream.write_all(&request).await.unwrap();
+
+    let mut response = vec![0; count * 5];
+    stream.read_exact(&mut response).await.unwrap();
+    assert!(response.chunks(5).all(|reply| reply == b"+OK\r\n"));
+}
+
+/// Returns the key space capacity reported by `DEBUG CAPACITY`.
+async fn debug_capacity(stream: &mut TcpStream) -> usize {
+    stream.write_all(b"*2\r\n$5\r\nDEBUG\r\n$8\r\nCAPACITY\r\n").await.unwrap();
+
+    // Read an integer reply, `:<n>\r\n`
+    let mut response = vec![];
+    while !response.ends_with(b"\r\n") {
+        response.push(stream.read_u8().await.unwrap());
+    }
+    assert_eq!(b':', response[0]);
+    std::str::from_utf8(&response[1..response.len() - 2]).unwrap().parse().unwrap()
+}
+
+async fn start_server_with_config(config: ServerConfig) -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move { server::run_with_config(listener, tokio::signal::ctrl_c(), config).await });
+
+    addr
+}
+
 async fn start_server() -> SocketAddr {
     let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
     let addr = listener.local_addr().unwrap();
@@ -0,0 +1,45 @@
+//! 集成测试共享的辅助工具。
+
+use std::cmp;
+use std::io;
+use tokio::io::{AsyncWriteExt, DuplexStream};
+use tokio::time::{self, Duration};
+
+/// 以固定大小的块、带延迟地向对端释放字节的流。
+///
+/// 用于模拟慢速对端：帧被拆分到多次 `read_buf` 中到达，`Frame::check` 会多次返回 `Incomplete`，
+/// 迫使 `read_frame` 的循环等待更多数据。
+pub struct SlowStream {
+    /// 内存管道的写入端。
+    inner: DuplexStream,
+    /// 每次释放的最大字节数。
+    chunk: usize,
+    /// 两次释放之间的延迟。
+    delay: Duration,
+}
+
+impl SlowStream {
+    /// 创建一对相连的流，每隔 `delay` 向返回的读取端释放最多 `chunk` 字节。
+    ///
+    /// 管道的缓冲区只有 `chunk` 字节，读取端每次最多读到一块。
+    pub fn pair(chunk: usize, delay: Duration) -> (Self, DuplexStream) {
+        assert!(chunk > 0, "chunk size must be positive");
+
+        let (inner, peer) = tokio::io::duplex(chunk);
+        (Self { inner, chunk, delay }, peer)
+    }
+
+    /// 按块写入 `src` 中的所有字节，每块之后刷新并等待。
+    pub async fn write_all(&mut self, mut src: &[u8]) -> io::Result<()> {
+        while !src.is_empty() {
+            let n = cmp::min(self.chunk, src.len());
+            self.inner.write_all(&src[..n]).await?;
+            self.inner.flush().await?;
+            src = &src[n..];
+
+            time::sleep(self.delay).await;
+        }
+
+        Ok(())
+    }
+}